@@ -1,27 +1,339 @@
+use crate::core::ArchiveFormat;
+use regex::Regex;
+use serde::Serialize;
 use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// Environment variable with semicolon-separated exclusion patterns applied to every run
+pub const DEFAULT_EXCLUDES_ENV: &str = "ARCHTREE_DEFAULT_EXCLUDES";
+
+/// Whether exclusion patterns match case-sensitively by default: like the platform's usual
+/// filesystem, so Linux tells `README` from `readme` while Windows and macOS don't
+pub const PLATFORM_CASE_SENSITIVE: bool = !cfg!(any(windows, target_os = "macos"));
 
 /// Configuration for the backup tool
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Config {
     /// Path where the archive will be created
     pub output_path: String,
+    /// Format of the archive, from `--format` or else the output extension
+    pub format: ArchiveFormat,
     /// Whether to show progress during operations
     pub show_progress: bool,
     /// Path to the 7-Zip executable (if not in PATH)
     pub seven_zip_path: Option<String>,
+    /// File the raw output of every 7-Zip invocation is appended to
+    pub seven_zip_log: Option<String>,
+    /// Whether summaries are rendered as an aligned table instead of a list
+    pub table_summary: bool,
+    /// Whether summary lines are printed without their leading emoji
+    pub no_emoji: bool,
+    /// Whether hard links to an already added file are skipped
+    pub dedupe_hardlinks: bool,
+    /// Whether file headers are sampled to detect already-compressed content
+    pub analyze_content: bool,
+    /// Upper bound for 7-Zip's estimated memory use in bytes
+    pub max_memory: Option<u64>,
+    /// Baseline exclusion patterns applied in addition to those from the input
+    pub default_excludes: Vec<String>,
+    /// Whether hidden files and directories found while walking inputs are skipped
+    pub skip_hidden: bool,
+    /// Whether explicitly named hidden inputs are kept when hidden files are skipped
+    pub include_hidden_roots: bool,
+    /// Whether `.gitignore` files found while walking inputs exclude the paths they match
+    pub respect_gitignore: bool,
+    /// Whether paths are canonicalized so aliases of the same file are added once
+    pub canonicalize: bool,
+    /// Whether symlinks to directories are descended into while walking inputs
+    pub follow_symlinks: bool,
+    /// Levels walked below input directories without a `depth:` prefix; `None` walks all
+    pub max_depth: Option<usize>,
+    /// Files found in input directories smaller than this many bytes are excluded
+    pub min_file_size: Option<u64>,
+    /// Files found in input directories larger than this many bytes are excluded
+    pub max_file_size: Option<u64>,
+    /// Threads walking each input directory; above 1 its files come sorted by path
+    pub walk_jobs: usize,
+    /// Whether input directories are handed to 7-Zip whole instead of file by file
+    pub whole_directories: bool,
+    /// Content types (categories or MIME types) excluded by sniffing file headers
+    pub exclude_types: Vec<String>,
+    /// Wildcard patterns files found in input directories must match to be added
+    pub include_patterns: Vec<String>,
+    /// Regular expressions excluding the paths they match, alongside `!` wildcard patterns
+    pub exclude_regexes: Vec<String>,
+    /// Whether exclusion patterns match paths case-sensitively
+    pub case_sensitive: bool,
+    /// How progress is rendered while input paths are processed
+    pub progress_style: ProgressStyle,
+    /// Whether candidate files are counted first, so the progress counter shows a total
+    pub precount: bool,
+    /// Number of slowest input directories to report after processing; `None` disables timing
+    pub profile: Option<usize>,
+    /// Only files last accessed longer ago than this are archived
+    pub accessed_before: Option<Duration>,
+    /// Only files found in input directories modified within this time are archived
+    pub modified_within: Option<Duration>,
+    /// Whether archives are built with settings that make their bytes reproducible
+    pub reproducible: bool,
+    /// Maximum number of paths handed to a single 7-Zip invocation
+    pub chunk_size: Option<usize>,
+    /// Files smaller than this many bytes are stored without compression
+    pub store_below: Option<u64>,
+    /// Files larger than this many bytes are stored without compression
+    pub store_above: Option<u64>,
+    /// Whether the path of every added file is printed to stdout
+    pub print_added: bool,
+    /// Whether an empty file set after expansion and exclusions is an error
+    pub fail_on_empty: bool,
+    /// Whether files that vanish between listing and archiving are an error instead of skipped
+    pub fail_on_vanished: bool,
+    /// Whether warnings about restore-time data loss are treated as errors
+    pub strict: bool,
+    /// Compression ratio for an estimate-only run; when set no archive is created
+    pub estimate_ratio: Option<f64>,
+    /// Whether input paths are only processed and summarized, without running 7-Zip
+    pub dry_run: bool,
+    /// Whether archives are written to a temporary file and renamed into place on success
+    pub atomic_output: bool,
+    /// 7-Zip compression level (0-9); 7-Zip's default when `None`
+    pub compression_level: Option<u8>,
+    /// Number of compression threads 7-Zip uses; 7-Zip picks one per core when `None`
+    pub threads: Option<u32>,
+    /// Archives are split into volumes of this many bytes (`<output>.001`, `.002`, ...)
+    pub volume_size: Option<u64>,
+    /// Chain incrementals adding more than this many bytes are aborted
+    pub max_incremental_size: Option<u64>,
+    /// Whether archiving is skipped when the source tree's structure hash matches the stored one
+    pub skip_if_unchanged: bool,
+    /// Bytes that must be free on the output volume; the input size when `None`
+    pub min_free_space: Option<u64>,
+    /// Password archives are encrypted with and listed with; never serialized
+    #[serde(skip)]
+    pub password: Option<Password>,
+    /// Prepared list file handed to 7-Zip as-is instead of processing the input paths
+    pub raw_list: Option<String>,
+    /// Whether a `tree.txt` index of the archived files is added to the archive
+    pub embed_index: bool,
+    /// Switches replacing the default encoding switch of `7z l`
+    pub list_args: Option<Vec<String>>,
+    /// Locale `7z l` runs under instead of `en_US.UTF-8`
+    pub list_locale: Option<String>,
+    /// Whether listed modification times without a zone are UTC instead of local time
+    pub assume_utc: bool,
+    /// Regular expressions for archive entries left out of verification
+    pub ignore_entries: Vec<String>,
+    /// Wildcard patterns of files that may be missing from an archive without failing verification
+    pub allow_missing: Vec<String>,
+    /// File caching the processed input paths between verifications, see
+    /// [`crate::services::BackupService::get_input_paths`]
+    pub expand_cache: Option<String>,
 }
 
 impl Config {
     pub fn builder() -> ConfigBuilder {
         ConfigBuilder::default()
     }
+
+    /// Settings that decide which files the input paths expand to, so an expansion
+    /// cached with other settings isn't reused
+    pub fn selection_settings(&self) -> String {
+        format!(
+            "default_excludes={:?} skip_hidden={} include_hidden_roots={} \
+             respect_gitignore={} canonicalize={} follow_symlinks={} max_depth={:?} \
+             min_file_size={:?} max_file_size={:?} whole_directories={} dedupe_hardlinks={} \
+             exclude_types={:?} include_patterns={:?} exclude_regexes={:?} case_sensitive={}",
+            self.default_excludes,
+            self.skip_hidden,
+            self.include_hidden_roots,
+            self.respect_gitignore,
+            self.canonicalize,
+            self.follow_symlinks,
+            self.max_depth,
+            self.min_file_size,
+            self.max_file_size,
+            self.whole_directories,
+            self.dedupe_hardlinks,
+            self.exclude_types,
+            self.include_patterns,
+            self.exclude_regexes,
+            self.case_sensitive,
+        )
+    }
+
+    /// Settings that change the bytes of an archive built from the same files.
+    ///
+    /// Folded into the structure hash, so changing one of them rebuilds an archive that
+    /// `--skip-if-unchanged` would otherwise keep. The password only ever ends up hashed.
+    pub fn archive_settings(&self) -> String {
+        format!(
+            "format={:?} level={:?} store_below={:?} store_above={:?} reproducible={} \
+             max_memory={:?} volume_size={:?} embed_index={} password={:?}",
+            self.format,
+            self.compression_level,
+            self.store_below,
+            self.store_above,
+            self.reproducible,
+            self.max_memory,
+            self.volume_size,
+            self.embed_index,
+            self.password.as_ref().map(Password::expose),
+        )
+    }
+
+    /// Reject options the archive format can't honor.
+    ///
+    /// Checked once the format is final, as a chain member can change it after building.
+    pub fn check_format_support(&self) -> crate::core::Result<()> {
+        if self.password.is_some() && !self.format.supports_encryption() {
+            return Err(crate::core::ArchtreeError::config(format!(
+                "--password can't be used with {} archives, which can't be encrypted",
+                self.format
+            )));
+        }
+        if self.format.is_compressed() {
+            return Ok(());
+        }
+
+        let unsupported = [
+            ("--level", self.compression_level.is_some()),
+            ("--threads", self.threads.is_some()),
+            ("--store-below", self.store_below.is_some()),
+            ("--store-above", self.store_above.is_some()),
+        ];
+        match unsupported.iter().find(|(_, set)| *set) {
+            Some((option, _)) => Err(crate::core::ArchtreeError::config(format!(
+                "{} can't be used with {} archives, which are not compressed",
+                option, self.format
+            ))),
+            None => Ok(()),
+        }
+    }
 }
 
-#[derive(Default)]
 pub struct ConfigBuilder {
     output_path: Option<String>,
+    format: Option<ArchiveFormat>,
     show_progress: bool,
     seven_zip_path: Option<String>,
+    seven_zip_log: Option<String>,
+    table_summary: bool,
+    no_emoji: bool,
+    dedupe_hardlinks: bool,
+    analyze_content: bool,
+    max_memory: Option<u64>,
+    default_excludes: Vec<String>,
+    skip_hidden: bool,
+    include_hidden_roots: bool,
+    respect_gitignore: bool,
+    canonicalize: bool,
+    follow_symlinks: bool,
+    max_depth: Option<usize>,
+    min_file_size: Option<u64>,
+    max_file_size: Option<u64>,
+    walk_jobs: usize,
+    whole_directories: bool,
+    exclude_types: Vec<String>,
+    include_patterns: Vec<String>,
+    exclude_regexes: Vec<String>,
+    case_sensitive: bool,
+    progress_style: ProgressStyle,
+    precount: bool,
+    profile: Option<usize>,
+    accessed_before: Option<Duration>,
+    modified_within: Option<Duration>,
+    reproducible: bool,
+    chunk_size: Option<usize>,
+    store_below: Option<u64>,
+    store_above: Option<u64>,
+    print_added: bool,
+    fail_on_empty: bool,
+    fail_on_vanished: bool,
+    strict: bool,
+    estimate_ratio: Option<f64>,
+    dry_run: bool,
+    atomic_output: bool,
+    compression_level: Option<u8>,
+    threads: Option<u32>,
+    volume_size: Option<u64>,
+    max_incremental_size: Option<u64>,
+    skip_if_unchanged: bool,
+    min_free_space: Option<u64>,
+    password: Option<Password>,
+    raw_list: Option<String>,
+    embed_index: bool,
+    list_args: Option<Vec<String>>,
+    list_locale: Option<String>,
+    assume_utc: bool,
+    ignore_entries: Vec<String>,
+    allow_missing: Vec<String>,
+    expand_cache: Option<String>,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self {
+            output_path: None,
+            format: None,
+            show_progress: false,
+            seven_zip_path: None,
+            seven_zip_log: None,
+            table_summary: false,
+            no_emoji: false,
+            dedupe_hardlinks: false,
+            analyze_content: false,
+            max_memory: None,
+            default_excludes: Vec::new(),
+            skip_hidden: false,
+            include_hidden_roots: true,
+            respect_gitignore: false,
+            canonicalize: false,
+            follow_symlinks: false,
+            max_depth: None,
+            min_file_size: None,
+            max_file_size: None,
+            walk_jobs: 1,
+            whole_directories: false,
+            exclude_types: Vec::new(),
+            include_patterns: Vec::new(),
+            exclude_regexes: Vec::new(),
+            case_sensitive: PLATFORM_CASE_SENSITIVE,
+            progress_style: ProgressStyle::default(),
+            precount: true,
+            profile: None,
+            accessed_before: None,
+            modified_within: None,
+            reproducible: false,
+            chunk_size: None,
+            store_below: None,
+            store_above: None,
+            print_added: false,
+            fail_on_empty: false,
+            fail_on_vanished: false,
+            strict: false,
+            estimate_ratio: None,
+            dry_run: false,
+            atomic_output: false,
+            compression_level: None,
+            threads: None,
+            volume_size: None,
+            max_incremental_size: None,
+            skip_if_unchanged: false,
+            min_free_space: None,
+            password: None,
+            raw_list: None,
+            embed_index: false,
+            list_args: None,
+            list_locale: None,
+            assume_utc: false,
+            ignore_entries: Vec::new(),
+            allow_missing: Vec::new(),
+            expand_cache: None,
+        }
+    }
 }
 
 impl ConfigBuilder {
@@ -29,36 +341,329 @@ impl ConfigBuilder {
         Self::default()
     }
 
-    pub fn output_path(mut self, path: Option<&str>, try_env: bool) -> Self {
-        if let Some(p) = path {
-            if !p.trim().is_empty() {
-                self.output_path = Some(p.to_string());
-                return self;
-            }
+    pub fn output_path(mut self, path: Option<&str>, _try_env: bool) -> Self {
+        if let Some(p) = path
+            && !p.trim().is_empty()
+        {
+            self.output_path = Some(p.to_string());
         }
         self
     }
 
+    pub fn seven_zip_log(mut self, path: Option<&str>) -> Self {
+        self.seven_zip_log = path.map(str::to_string);
+        self
+    }
+
     pub fn show_progress(mut self, show: bool) -> Self {
         self.show_progress = show;
         self
     }
 
+    pub fn table_summary(mut self, table: bool) -> Self {
+        self.table_summary = table;
+        self
+    }
+
+    pub fn no_emoji(mut self, no_emoji: bool) -> Self {
+        self.no_emoji = no_emoji;
+        self
+    }
+
+    pub fn dedupe_hardlinks(mut self, dedupe: bool) -> Self {
+        self.dedupe_hardlinks = dedupe;
+        self
+    }
+
+    pub fn analyze_content(mut self, analyze: bool) -> Self {
+        self.analyze_content = analyze;
+        self
+    }
+
+    pub fn max_memory(mut self, bytes: Option<u64>) -> Self {
+        self.max_memory = bytes;
+        self
+    }
+
+    /// Baseline exclusion patterns separated by `;`, from `patterns` or else from
+    /// [`DEFAULT_EXCLUDES_ENV`]
+    pub fn default_excludes(mut self, patterns: Option<&str>, try_env: bool) -> Self {
+        let from_env = if try_env {
+            env::var(DEFAULT_EXCLUDES_ENV).ok()
+        } else {
+            None
+        };
+        if let Some(value) = patterns.map(str::to_string).or(from_env) {
+            self.default_excludes = value
+                .split(';')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        self
+    }
+
+    pub fn skip_hidden(mut self, skip: bool) -> Self {
+        self.skip_hidden = skip;
+        self
+    }
+
+    pub fn include_hidden_roots(mut self, include: bool) -> Self {
+        self.include_hidden_roots = include;
+        self
+    }
+
+    pub fn respect_gitignore(mut self, respect: bool) -> Self {
+        self.respect_gitignore = respect;
+        self
+    }
+
+    pub fn canonicalize(mut self, canonicalize: bool) -> Self {
+        self.canonicalize = canonicalize;
+        self
+    }
+
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    pub fn max_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    pub fn min_file_size(mut self, size: Option<u64>) -> Self {
+        self.min_file_size = size;
+        self
+    }
+
+    pub fn max_file_size(mut self, size: Option<u64>) -> Self {
+        self.max_file_size = size;
+        self
+    }
+
+    /// Walk each input directory with `jobs` threads
+    pub fn walk_jobs(mut self, jobs: usize) -> Self {
+        self.walk_jobs = jobs;
+        self
+    }
+
+    pub fn whole_directories(mut self, whole: bool) -> Self {
+        self.whole_directories = whole;
+        self
+    }
+
+    pub fn exclude_types(mut self, types: Vec<String>) -> Self {
+        self.exclude_types = types;
+        self
+    }
+
+    /// Only archive files in input directories matching at least one of these patterns
+    pub fn include_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.include_patterns = patterns;
+        self
+    }
+
+    /// Also exclude paths matching any of these regular expressions
+    pub fn exclude_regexes(mut self, patterns: Vec<String>) -> Self {
+        self.exclude_regexes = patterns;
+        self
+    }
+
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    pub fn progress_style(mut self, style: ProgressStyle) -> Self {
+        self.progress_style = style;
+        self
+    }
+
+    /// Count candidate files before processing them, for the counter's total
+    pub fn precount(mut self, precount: bool) -> Self {
+        self.precount = precount;
+        self
+    }
+
+    /// Skip files accessed within `age` of now
+    pub fn accessed_before(mut self, age: Option<Duration>) -> Self {
+        self.accessed_before = age;
+        self
+    }
+
+    pub fn modified_within(mut self, age: Option<Duration>) -> Self {
+        self.modified_within = age;
+        self
+    }
+
+    /// Time each input directory and report the `count` slowest
+    pub fn profile(mut self, count: Option<usize>) -> Self {
+        self.profile = count;
+        self
+    }
+
+    pub fn reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    pub fn chunk_size(mut self, size: Option<usize>) -> Self {
+        self.chunk_size = size;
+        self
+    }
+
+    pub fn store_below(mut self, size: Option<u64>) -> Self {
+        self.store_below = size;
+        self
+    }
+
+    pub fn store_above(mut self, size: Option<u64>) -> Self {
+        self.store_above = size;
+        self
+    }
+
+    pub fn print_added(mut self, print: bool) -> Self {
+        self.print_added = print;
+        self
+    }
+
+    /// Explicit archive format; without one it is inferred from the output extension
+    pub fn format(mut self, format: Option<ArchiveFormat>) -> Self {
+        self.format = format;
+        self
+    }
+
     pub fn seven_zip_path(mut self, path: Option<&str>, try_env: bool) -> Self {
-        if let Some(p) = path {
-            if !p.trim().is_empty() {
-                self.seven_zip_path = Some(p.to_string());
-                return self;
-            }
+        if let Some(p) = path
+            && !p.trim().is_empty()
+        {
+            self.seven_zip_path = Some(p.to_string());
+            return self;
         }
-        if try_env {
-            if let Ok(env_path) = env::var("SEVEN_ZIP_PATH") {
-                self.seven_zip_path = Some(env_path.trim().to_string());
-            }
+        if try_env && let Ok(env_path) = env::var("SEVEN_ZIP_PATH") {
+            self.seven_zip_path = Some(env_path.trim().to_string());
         }
         self
     }
 
+    pub fn fail_on_empty(mut self, fail: bool) -> Self {
+        self.fail_on_empty = fail;
+        self
+    }
+
+    pub fn fail_on_vanished(mut self, fail: bool) -> Self {
+        self.fail_on_vanished = fail;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Only estimate the archive size, assuming compressible data shrinks to `ratio` of its size
+    pub fn estimate_ratio(mut self, ratio: Option<f64>) -> Self {
+        self.estimate_ratio = ratio;
+        self
+    }
+
+    /// Only process the input paths and print the summary; no archive is created
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn atomic_output(mut self, atomic: bool) -> Self {
+        self.atomic_output = atomic;
+        self
+    }
+
+    /// Compression level from 0 (store) to 9 (ultra), passed to 7-Zip as `-mx<level>`
+    pub fn compression_level(mut self, level: Option<u8>) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    pub fn threads(mut self, threads: Option<u32>) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    pub fn volume_size(mut self, bytes: Option<u64>) -> Self {
+        self.volume_size = bytes;
+        self
+    }
+
+    /// Abort a chain incremental whose new and modified files add up to more than `bytes`
+    pub fn max_incremental_size(mut self, bytes: Option<u64>) -> Self {
+        self.max_incremental_size = bytes;
+        self
+    }
+
+    pub fn skip_if_unchanged(mut self, skip: bool) -> Self {
+        self.skip_if_unchanged = skip;
+        self
+    }
+
+    /// Require `bytes` free on the output volume instead of the total input size
+    pub fn min_free_space(mut self, bytes: Option<u64>) -> Self {
+        self.min_free_space = bytes;
+        self
+    }
+
+    pub fn password(mut self, password: Option<Password>) -> Self {
+        self.password = password;
+        self
+    }
+
+    /// Archive the paths of a prepared 7-Zip list file without processing them
+    pub fn raw_list(mut self, path: Option<&str>) -> Self {
+        self.raw_list = path.map(String::from);
+        self
+    }
+
+    pub fn embed_index(mut self, embed: bool) -> Self {
+        self.embed_index = embed;
+        self
+    }
+
+    /// Whitespace-separated switches for `7z l`, e.g. `"-scsWIN -sccWIN"`
+    pub fn list_args(mut self, args: Option<&str>) -> Self {
+        self.list_args = args.map(|args| args.split_whitespace().map(String::from).collect());
+        self
+    }
+
+    pub fn list_locale(mut self, locale: Option<&str>) -> Self {
+        self.list_locale = locale.map(String::from);
+        self
+    }
+
+    /// Read listed modification times without a zone as UTC
+    pub fn assume_utc(mut self, assume_utc: bool) -> Self {
+        self.assume_utc = assume_utc;
+        self
+    }
+
+    /// Regular expressions matched anywhere in an entry's path, e.g. `(^|/)Thumbs\.db$`
+    pub fn ignore_entries(mut self, patterns: Vec<String>) -> Self {
+        self.ignore_entries = patterns;
+        self
+    }
+
+    /// Wildcard patterns such as `*.lock` for transient files that may be missing
+    pub fn allow_missing(mut self, patterns: Vec<String>) -> Self {
+        self.allow_missing = patterns;
+        self
+    }
+
+    pub fn expand_cache(mut self, path: Option<&str>) -> Self {
+        self.expand_cache = path.map(String::from);
+        self
+    }
+
     pub fn build(self) -> Result<Config, anyhow::Error> {
         let output_path = self
             .output_path
@@ -68,14 +673,252 @@ impl ConfigBuilder {
         if output_path.is_empty() {
             anyhow::bail!("Output path cannot be empty");
         }
+        if output_path == "-" {
+            // 7-Zip seeks back to write the archive headers, so it needs a real file
+            anyhow::bail!(
+                "Writing the archive to standard output (--output -) isn't supported; \
+                 write it to a file and stream that file instead"
+            );
+        }
+        if let Some(ratio) = self.estimate_ratio
+            && !(ratio > 0.0 && ratio <= 1.0)
+        {
+            anyhow::bail!(
+                "Estimate ratio must be greater than 0 and at most 1, got {}",
+                ratio
+            );
+        }
+        if let Some(level) = self.compression_level
+            && level > 9
+        {
+            anyhow::bail!("Compression level must be between 0 and 9, got {}", level);
+        }
+        if self.threads == Some(0) {
+            anyhow::bail!("Thread count must be at least 1");
+        }
+        if self.walk_jobs == 0 {
+            anyhow::bail!("Walk job count must be at least 1");
+        }
+        if self.skip_if_unchanged && self.whole_directories {
+            // Only the directories themselves would be hashed, missing edits inside them
+            anyhow::bail!("Skipping unchanged trees can't be combined with whole directories");
+        }
+        if let (Some(min), Some(max)) = (self.min_file_size, self.max_file_size)
+            && min > max
+        {
+            anyhow::bail!(
+                "Minimum file size ({} bytes) is larger than the maximum ({} bytes)",
+                min,
+                max
+            );
+        }
+        for pattern in &self.ignore_entries {
+            if let Err(e) = Regex::new(pattern) {
+                anyhow::bail!("Invalid ignore-entry pattern '{}': {}", pattern, e);
+            }
+        }
+        let format = self
+            .format
+            .or_else(|| ArchiveFormat::from_extension(&output_path))
+            .unwrap_or_default();
         Ok(Config {
             output_path,
+            format,
             show_progress: self.show_progress,
             seven_zip_path: self.seven_zip_path,
+            seven_zip_log: self.seven_zip_log,
+            table_summary: self.table_summary,
+            no_emoji: self.no_emoji,
+            dedupe_hardlinks: self.dedupe_hardlinks,
+            analyze_content: self.analyze_content,
+            max_memory: self.max_memory,
+            default_excludes: self.default_excludes,
+            skip_hidden: self.skip_hidden,
+            include_hidden_roots: self.include_hidden_roots,
+            respect_gitignore: self.respect_gitignore,
+            canonicalize: self.canonicalize,
+            follow_symlinks: self.follow_symlinks,
+            max_depth: self.max_depth,
+            min_file_size: self.min_file_size,
+            max_file_size: self.max_file_size,
+            walk_jobs: self.walk_jobs,
+            whole_directories: self.whole_directories,
+            exclude_types: self.exclude_types,
+            include_patterns: self.include_patterns,
+            exclude_regexes: self.exclude_regexes,
+            case_sensitive: self.case_sensitive,
+            progress_style: self.progress_style,
+            precount: self.precount,
+            profile: self.profile,
+            accessed_before: self.accessed_before,
+            modified_within: self.modified_within,
+            reproducible: self.reproducible,
+            chunk_size: self.chunk_size,
+            store_below: self.store_below,
+            store_above: self.store_above,
+            print_added: self.print_added,
+            fail_on_empty: self.fail_on_empty,
+            fail_on_vanished: self.fail_on_vanished,
+            strict: self.strict,
+            estimate_ratio: self.estimate_ratio,
+            dry_run: self.dry_run,
+            atomic_output: self.atomic_output,
+            compression_level: self.compression_level,
+            threads: self.threads,
+            volume_size: self.volume_size,
+            max_incremental_size: self.max_incremental_size,
+            skip_if_unchanged: self.skip_if_unchanged,
+            min_free_space: self.min_free_space,
+            password: self.password,
+            raw_list: self.raw_list,
+            embed_index: self.embed_index,
+            list_args: self.list_args,
+            list_locale: self.list_locale,
+            assume_utc: self.assume_utc,
+            ignore_entries: self.ignore_entries,
+            allow_missing: self.allow_missing,
+            expand_cache: self.expand_cache,
         })
     }
 }
 
+/// How progress is rendered while input paths are processed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressStyle {
+    /// One line per excluded, skipped or invalid path
+    #[default]
+    Line,
+    /// A single line counting the processed paths, updated in place
+    Counter,
+    /// Nothing until the processing summary
+    None,
+}
+
+impl FromStr for ProgressStyle {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "line" => Ok(Self::Line),
+            "counter" => Ok(Self::Counter),
+            "none" => Ok(Self::None),
+            _ => Err(format!(
+                "Unknown progress style '{}' (expected line, counter or none)",
+                value
+            )),
+        }
+    }
+}
+
+/// Archive password, wiped from memory when dropped.
+///
+/// `Debug` never shows the password, so configs can be logged safely.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Password(Zeroizing<String>);
+
+impl Password {
+    pub fn new<S: Into<String>>(password: S) -> Self {
+        Self(Zeroizing::new(password.into()))
+    }
+
+    /// Read the password from the first line of `path`, trimmed of surrounding whitespace
+    /// such as the line ending an editor adds
+    pub fn from_file(path: &str) -> crate::core::Result<Self> {
+        use crate::core::ErrorContext;
+
+        let content = Zeroizing::new(
+            std::fs::read_to_string(path)
+                .context_io(format!("Failed to read password file: {}", path))?,
+        );
+        let password = content.lines().next().unwrap_or_default().trim();
+        if password.is_empty() {
+            return Err(crate::core::ArchtreeError::config(format!(
+                "Password file is empty: {}",
+                path
+            )));
+        }
+        Ok(Self::new(password))
+    }
+
+    /// The password itself, for handing to 7-Zip
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(***)")
+    }
+}
+
+/// Parse a human-readable size such as `512M`, `2G` or `1048576` into bytes.
+///
+/// Suffixes are binary (`K` = 1024) and case-insensitive; a trailing `B` or `iB` is allowed.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let trimmed = value.trim();
+    let upper = trimmed.to_ascii_uppercase();
+    let unit_start = upper
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(upper.len());
+    let (number, unit) = upper.split_at(unit_start);
+
+    let multiplier: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        "T" | "TB" | "TIB" => 1024 * 1024 * 1024 * 1024,
+        _ => return Err(format!("Invalid size unit in '{}'", trimmed)),
+    };
+
+    number
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid size: '{}'", trimmed))?
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("Size too large: '{}'", trimmed))
+}
+
+/// Parse a volume size such as `100m` or `4g` with [`parse_size`], rejecting zero
+pub fn parse_volume_size(value: &str) -> crate::core::Result<u64> {
+    let bytes = parse_size(value).map_err(crate::core::ArchtreeError::config)?;
+    if bytes == 0 {
+        return Err(crate::core::ArchtreeError::config(
+            "Volume size must be greater than 0",
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Parse a duration such as `30d`, `12h` or `90` (seconds) into a [`Duration`].
+///
+/// Units are `s`, `m`, `h`, `d` and `w`, case-insensitive; without a unit the number is seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let trimmed = value.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let unit_start = lower
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(lower.len());
+    let (number, unit) = lower.split_at(unit_start);
+
+    let multiplier: u64 = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("Invalid duration unit in '{}'", trimmed)),
+    };
+
+    number
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid duration: '{}'", trimmed))?
+        .checked_mul(multiplier)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Duration too large: '{}'", trimmed))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,6 +946,134 @@ mod tests {
         assert_eq!(config.seven_zip_path.unwrap(), "C:\\custom\\7z.exe");
     }
 
+    #[test]
+    fn test_compression_level_validated() {
+        let build_with = |level| {
+            Config::builder()
+                .output_path(Some("backup.7z"), false)
+                .compression_level(level)
+                .build()
+        };
+
+        assert_eq!(build_with(None).unwrap().compression_level, None);
+        assert_eq!(build_with(Some(0)).unwrap().compression_level, Some(0));
+        assert_eq!(build_with(Some(9)).unwrap().compression_level, Some(9));
+        let error = build_with(Some(10)).unwrap_err();
+        assert!(error.to_string().contains("between 0 and 9"));
+    }
+
+    #[test]
+    fn test_thread_count_validated() {
+        let build_with = |threads: Option<u32>| {
+            Config::builder()
+                .output_path(Some("backup.7z"), false)
+                .threads(threads)
+                .build()
+        };
+
+        assert_eq!(build_with(None).unwrap().threads, None);
+        assert_eq!(build_with(Some(16)).unwrap().threads, Some(16));
+        let error = build_with(Some(0)).unwrap_err();
+        assert!(error.to_string().contains("at least 1"));
+    }
+
+    #[test]
+    fn test_standard_output_rejected() {
+        let error = Config::builder()
+            .output_path(Some("-"), false)
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("--output -"));
+    }
+
+    #[test]
+    fn test_compression_options_rejected_for_tar() {
+        let config = Config::builder()
+            .output_path(Some("backup.tar"), false)
+            .compression_level(Some(9))
+            .build()
+            .unwrap();
+        let error = config.check_format_support().unwrap_err();
+        assert_eq!(error.category(), "config");
+        assert!(error.to_string().contains("--level can't be used with tar"));
+
+        let config = Config::builder()
+            .output_path(Some("backup.tar"), false)
+            .store_above(Some(1024))
+            .build()
+            .unwrap();
+        assert!(config.check_format_support().is_err());
+
+        let config = Config::builder()
+            .output_path(Some("backup.7z"), false)
+            .compression_level(Some(9))
+            .store_above(Some(1024))
+            .build()
+            .unwrap();
+        assert!(config.check_format_support().is_ok());
+    }
+
+    #[test]
+    fn test_password_is_never_shown() {
+        let config = Config::builder()
+            .output_path(Some("backup.7z"), false)
+            .password(Some(Password::new("hunter2")))
+            .build()
+            .unwrap();
+        assert!(config.check_format_support().is_ok());
+        assert!(!format!("{:?}", config).contains("hunter2"));
+        assert!(!serde_json::to_string(&config).unwrap().contains("hunter2"));
+
+        let config = Config::builder()
+            .output_path(Some("backup.tar"), false)
+            .password(Some(Password::new("hunter2")))
+            .build()
+            .unwrap();
+        let error = config.check_format_support().unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("--password can't be used with tar")
+        );
+        assert!(!error.to_string().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_password_from_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("password.txt");
+
+        std::fs::write(&path, "correct horse \r\nignored\n").unwrap();
+        let password = Password::from_file(&path.to_string_lossy()).unwrap();
+        assert_eq!(password.expose(), "correct horse");
+
+        std::fs::write(&path, "  \n").unwrap();
+        assert!(Password::from_file(&path.to_string_lossy()).is_err());
+    }
+
+    #[test]
+    fn test_format_inferred_from_output_extension() {
+        let format_of = |output: &str, format: Option<ArchiveFormat>| {
+            Config::builder()
+                .output_path(Some(output), false)
+                .format(format)
+                .build()
+                .unwrap()
+                .format
+        };
+
+        assert_eq!(format_of("backup.7z", None), ArchiveFormat::SevenZip);
+        assert_eq!(format_of("backup.zip", None), ArchiveFormat::Zip);
+        assert_eq!(format_of("backup.TAR", None), ArchiveFormat::Tar);
+        // Unknown extensions fall back to 7z
+        assert_eq!(format_of("backup.bak", None), ArchiveFormat::SevenZip);
+        // An explicit format wins over the extension
+        assert_eq!(
+            format_of("backup.zip", Some(ArchiveFormat::Tar)),
+            ArchiveFormat::Tar
+        );
+    }
+
     #[test]
     fn test_config_from_env() {
         // Set test environment variable
@@ -124,4 +1095,37 @@ mod tests {
             env::remove_var("SEVEN_ZIP_PATH");
         }
     }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1048576"), Ok(1048576));
+        assert_eq!(parse_size("512M"), Ok(512 * 1024 * 1024));
+        assert_eq!(parse_size("2gb"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("64KiB"), Ok(64 * 1024));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("12X").is_err());
+        assert!(parse_size("M").is_err());
+    }
+
+    #[test]
+    fn test_parse_volume_size() {
+        assert_eq!(parse_volume_size("100m").unwrap(), 100 * 1024 * 1024);
+        assert_eq!(parse_volume_size("4G").unwrap(), 4 * 1024 * 1024 * 1024);
+
+        let error = parse_volume_size("100q").unwrap_err();
+        assert_eq!(error.category(), "config");
+        assert!(error.to_string().contains("Invalid size unit"));
+        assert_eq!(parse_volume_size("0").unwrap_err().category(), "config");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(15 * 60)));
+        assert_eq!(parse_duration("30D"), Ok(Duration::from_secs(30 * 86400)));
+        assert_eq!(parse_duration("2w"), Ok(Duration::from_secs(14 * 86400)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("3y").is_err());
+        assert!(parse_duration("d").is_err());
+    }
 }
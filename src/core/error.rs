@@ -1,6 +1,12 @@
 use anyhow::Error as AnyhowError;
 use std::fmt;
 
+/// Process exit code used when a required external tool (7-Zip) is not available
+pub const EXIT_TOOL_NOT_AVAILABLE: u8 = 127;
+
+/// Process exit code used when an operation was cancelled, as after Ctrl-C
+pub const EXIT_CANCELLED: u8 = 130;
+
 /// Structured error types for the archtree application
 #[derive(Debug)]
 pub enum ArchtreeError {
@@ -32,6 +38,10 @@ pub enum ArchtreeError {
         message: String,
         source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
+    /// A required external tool is not installed or can't be run
+    ToolNotAvailable { tool: String },
+    /// The operation was cancelled before it finished
+    Cancelled { operation: String },
     /// Generic errors that don't fit other categories
     Other {
         message: String,
@@ -55,7 +65,11 @@ impl fmt::Display for ArchtreeError {
                     write!(f, "Path processing error: {}", message)
                 }
             }
-            ArchtreeError::Verification { message, archive_path, .. } => {
+            ArchtreeError::Verification {
+                message,
+                archive_path,
+                ..
+            } => {
                 if let Some(archive) = archive_path {
                     write!(f, "Verification error for '{}': {}", archive, message)
                 } else {
@@ -65,6 +79,16 @@ impl fmt::Display for ArchtreeError {
             ArchtreeError::ExternalTool { tool, message, .. } => {
                 write!(f, "External tool error ({}): {}", tool, message)
             }
+            ArchtreeError::ToolNotAvailable { tool } => {
+                write!(
+                    f,
+                    "External tool error ({}): {} is not available on this system",
+                    tool, tool
+                )
+            }
+            ArchtreeError::Cancelled { operation } => {
+                write!(f, "{} was cancelled", operation)
+            }
             ArchtreeError::Other { message, .. } => {
                 write!(f, "Error: {}", message)
             }
@@ -80,9 +104,10 @@ impl std::error::Error for ArchtreeError {
             | ArchtreeError::PathProcessing { source, .. }
             | ArchtreeError::Verification { source, .. }
             | ArchtreeError::ExternalTool { source, .. }
-            | ArchtreeError::Other { source, .. } => {
-                source.as_ref().map(|e| e.as_ref() as &(dyn std::error::Error + 'static))
-            }
+            | ArchtreeError::Other { source, .. } => source
+                .as_ref()
+                .map(|e| e.as_ref() as &(dyn std::error::Error + 'static)),
+            ArchtreeError::ToolNotAvailable { .. } | ArchtreeError::Cancelled { .. } => None,
         }
     }
 }
@@ -189,6 +214,107 @@ impl ArchtreeError {
             source: Some(Box::new(source)),
         }
     }
+
+    /// Create an error for an external tool that is not available
+    pub fn tool_not_available<T: Into<String>>(tool: T) -> Self {
+        Self::ToolNotAvailable { tool: tool.into() }
+    }
+
+    /// Create an error for an operation that was cancelled
+    pub fn cancelled<S: Into<String>>(operation: S) -> Self {
+        Self::Cancelled {
+            operation: operation.into(),
+        }
+    }
+
+    /// Create a generic error
+    pub fn other<S: Into<String>>(message: S) -> Self {
+        Self::Other {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Short machine-readable name of the error category
+    pub fn category(&self) -> &'static str {
+        match self {
+            ArchtreeError::Config { .. } => "config",
+            ArchtreeError::Io { .. } => "io",
+            ArchtreeError::PathProcessing { .. } => "path_processing",
+            ArchtreeError::Verification { .. } => "verification",
+            ArchtreeError::ExternalTool { .. } => "external_tool",
+            ArchtreeError::ToolNotAvailable { .. } => "tool_not_available",
+            ArchtreeError::Cancelled { .. } => "cancelled",
+            ArchtreeError::Other { .. } => "other",
+        }
+    }
+
+    /// Structured form of this error for machine consumption.
+    ///
+    /// Holds the category, the message without its category prefix, the path, tool or
+    /// archive the error is about (when known) and the messages of the source chain.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::Map::new();
+        json.insert("category".to_string(), self.category().into());
+
+        let message = match self {
+            ArchtreeError::Config { message, .. }
+            | ArchtreeError::Io { message, .. }
+            | ArchtreeError::PathProcessing { message, .. }
+            | ArchtreeError::Verification { message, .. }
+            | ArchtreeError::ExternalTool { message, .. }
+            | ArchtreeError::Other { message, .. } => message.clone(),
+            ArchtreeError::ToolNotAvailable { tool } => {
+                format!("{} is not available on this system", tool)
+            }
+            ArchtreeError::Cancelled { operation } => format!("{} was cancelled", operation),
+        };
+        json.insert("message".to_string(), message.into());
+
+        match self {
+            ArchtreeError::PathProcessing {
+                path: Some(path), ..
+            } => {
+                json.insert("path".to_string(), path.as_str().into());
+            }
+            ArchtreeError::Verification {
+                archive_path: Some(archive),
+                ..
+            } => {
+                json.insert("archive".to_string(), archive.as_str().into());
+            }
+            ArchtreeError::ExternalTool { tool, .. } | ArchtreeError::ToolNotAvailable { tool } => {
+                json.insert("tool".to_string(), tool.as_str().into());
+            }
+            _ => {}
+        }
+
+        let mut sources = Vec::new();
+        let mut current = std::error::Error::source(self);
+        while let Some(error) = current {
+            sources.push(serde_json::Value::from(error.to_string()));
+            current = error.source();
+        }
+        json.insert("sources".to_string(), sources.into());
+        json.insert("exit_code".to_string(), self.exit_code().into());
+
+        serde_json::Value::Object(json)
+    }
+
+    /// Process exit code for this error: 127 if a required tool is missing, 130 if the
+    /// operation was cancelled, 1 otherwise
+    pub fn exit_code(&self) -> u8 {
+        let mut current: Option<&(dyn std::error::Error + 'static)> = Some(self);
+        while let Some(error) = current {
+            match error.downcast_ref() {
+                Some(ArchtreeError::ToolNotAvailable { .. }) => return EXIT_TOOL_NOT_AVAILABLE,
+                Some(ArchtreeError::Cancelled { .. }) => return EXIT_CANCELLED,
+                _ => {}
+            }
+            current = error.source();
+        }
+        1
+    }
 }
 
 // Allow conversion from anyhow::Error for compatibility
@@ -224,7 +350,8 @@ pub trait ErrorContext<T> {
         message: S,
         archive: A,
     ) -> Result<T>;
-    fn context_external<T2: Into<String>, S: Into<String>>(self, tool: T2, message: S) -> Result<T>;
+    fn context_external<T2: Into<String>, S: Into<String>>(self, tool: T2, message: S)
+    -> Result<T>;
 }
 
 impl<T, E> ErrorContext<T> for std::result::Result<T, E>
@@ -251,7 +378,56 @@ where
         self.map_err(|_e| ArchtreeError::verification(message, Some(archive)))
     }
 
-    fn context_external<T2: Into<String>, S: Into<String>>(self, tool: T2, message: S) -> Result<T> {
+    fn context_external<T2: Into<String>, S: Into<String>>(
+        self,
+        tool: T2,
+        message: S,
+    ) -> Result<T> {
         self.map_err(|e| ArchtreeError::external_tool_with_source(tool, message, e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_for_missing_tool() {
+        assert_eq!(
+            ArchtreeError::tool_not_available("7-Zip").exit_code(),
+            EXIT_TOOL_NOT_AVAILABLE
+        );
+        assert_eq!(ArchtreeError::config("bad").exit_code(), 1);
+        assert_eq!(
+            ArchtreeError::cancelled("Verification").exit_code(),
+            EXIT_CANCELLED
+        );
+
+        // The category survives being wrapped as the source of another error
+        let wrapped: Result<()> = Err(ArchtreeError::tool_not_available("7-Zip"));
+        let wrapped = wrapped.context_io("Failed to create archive").unwrap_err();
+        assert_eq!(wrapped.exit_code(), EXIT_TOOL_NOT_AVAILABLE);
+    }
+
+    #[test]
+    fn test_verification_error_json() {
+        let error = ArchtreeError::verification("3 files are missing", Some("backup.7z"));
+        assert_eq!(
+            error.to_json(),
+            serde_json::json!({
+                "category": "verification",
+                "message": "3 files are missing",
+                "archive": "backup.7z",
+                "sources": [],
+                "exit_code": 1,
+            })
+        );
+
+        let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let error = ArchtreeError::io_with_source("Failed to read input paths", io_error);
+        let json = error.to_json();
+        assert_eq!(json["category"], "io");
+        assert_eq!(json["sources"], serde_json::json!(["no such file"]));
+        assert!(json.get("archive").is_none());
+    }
+}
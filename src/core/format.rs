@@ -0,0 +1,112 @@
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Archive format 7-Zip writes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    /// 7z archive (LZMA2); used when the output extension is not recognised
+    #[default]
+    #[serde(rename = "7z")]
+    SevenZip,
+    /// Zip archive (Deflate)
+    Zip,
+    /// Uncompressed tar archive
+    Tar,
+}
+
+impl ArchiveFormat {
+    /// Infer the format from the extension of an output path, ignoring case
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        extension.parse().ok()
+    }
+
+    /// Name of the format as accepted by `--format`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SevenZip => "7z",
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+        }
+    }
+
+    /// 7-Zip type switch (`-t`) selecting this format
+    pub fn type_switch(&self) -> String {
+        format!("-t{}", self.name())
+    }
+
+    /// Whether 7-Zip compresses data in this format
+    pub fn is_compressed(&self) -> bool {
+        !matches!(self, Self::Tar)
+    }
+
+    /// Whether 7-Zip can encrypt archives in this format
+    pub fn supports_encryption(&self) -> bool {
+        !matches!(self, Self::Tar)
+    }
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "7z" => Ok(Self::SevenZip),
+            "zip" => Ok(Self::Zip),
+            "tar" => Ok(Self::Tar),
+            _ => Err(format!(
+                "Unknown archive format '{}' (expected 7z, zip or tar)",
+                value
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(
+            ArchiveFormat::from_extension("backup.7z"),
+            Some(ArchiveFormat::SevenZip)
+        );
+        assert_eq!(
+            ArchiveFormat::from_extension("C:\\Backups\\docs.ZIP"),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(
+            ArchiveFormat::from_extension("/srv/backup.tar"),
+            Some(ArchiveFormat::Tar)
+        );
+        assert_eq!(ArchiveFormat::from_extension("backup.bak"), None);
+        assert_eq!(ArchiveFormat::from_extension("backup"), None);
+        assert_eq!(ArchiveFormat::Tar.type_switch(), "-ttar");
+    }
+
+    #[test]
+    fn test_type_switch_for_output_paths() {
+        let switch_for = |output: &str| {
+            ArchiveFormat::from_extension(output)
+                .unwrap_or_default()
+                .type_switch()
+        };
+
+        assert_eq!(switch_for("backup.zip"), "-tzip");
+        assert_eq!(switch_for("D:\\Backups\\docs.7z"), "-t7z");
+        assert_eq!(switch_for("/srv/backup.tar"), "-ttar");
+        // Without a known extension the 7z switch matches the archive 7-Zip writes
+        assert_eq!(switch_for("backup.bak"), "-t7z");
+        assert_eq!(switch_for("backup"), "-t7z");
+    }
+}
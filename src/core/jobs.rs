@@ -0,0 +1,190 @@
+use crate::core::{ArchtreeError, Config, ErrorContext, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A file holding named backup job definitions.
+///
+/// Jobs live under a `jobs` table keyed by name, e.g. in TOML:
+///
+/// ```toml
+/// [jobs.nightly]
+/// output = "nightly.7z"
+/// paths = ["/home/user/documents"]
+/// exclude = ["*.tmp"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobFile {
+    #[serde(default)]
+    pub jobs: BTreeMap<String, JobDefinition>,
+}
+
+/// A single backup job: the archive to create and the paths that go into it
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobDefinition {
+    /// Path where the archive will be created
+    pub output: String,
+    /// Input paths, in the same syntax as lines of an input file
+    pub paths: Vec<String>,
+    /// Exclusion patterns, applied as `!`-prefixed input lines
+    pub exclude: Vec<String>,
+    /// Path to the 7-Zip executable (if not in PATH)
+    pub seven_zip_path: Option<String>,
+    /// Suppress progress output
+    pub quiet: bool,
+    /// Verify the archive after creation
+    pub verify: bool,
+    /// Retry missing files after verification
+    pub retry: bool,
+    /// Render summaries as an aligned table
+    pub table: bool,
+    /// Archive hard-linked files only once
+    pub dedupe_hardlinks: bool,
+}
+
+impl JobFile {
+    /// Load a job file, picking the format from its extension (`.toml`, `.yaml` or `.yml`)
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .context_io(format!("Failed to read job file: {}", path.display()))?;
+
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("toml") => Self::from_toml_str(&content),
+            Some("yaml") | Some("yml") => Self::from_yaml_str(&content),
+            _ => Err(ArchtreeError::config(format!(
+                "Unsupported job file format: {} (expected .toml, .yaml or .yml)",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Parse job definitions from TOML
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        toml::from_str(content)
+            .map_err(|e| ArchtreeError::config_with_source("Failed to parse TOML job file", e))
+    }
+
+    /// Parse job definitions from YAML
+    pub fn from_yaml_str(content: &str) -> Result<Self> {
+        serde_yaml::from_str(content)
+            .map_err(|e| ArchtreeError::config_with_source("Failed to parse YAML job file", e))
+    }
+
+    /// Look up a job by name
+    pub fn job(&self, name: &str) -> Result<&JobDefinition> {
+        self.jobs.get(name).ok_or_else(|| {
+            let available: Vec<&str> = self.jobs.keys().map(String::as_str).collect();
+            ArchtreeError::config(format!(
+                "Job '{}' not found (available: {})",
+                name,
+                available.join(", ")
+            ))
+        })
+    }
+}
+
+impl JobDefinition {
+    /// Build the backup configuration for this job
+    pub fn to_config(&self) -> Result<Config> {
+        Config::builder()
+            .output_path(Some(&self.output), false)
+            .seven_zip_path(self.seven_zip_path.as_deref(), true)
+            .show_progress(!self.quiet)
+            .table_summary(self.table)
+            .dedupe_hardlinks(self.dedupe_hardlinks)
+            .default_excludes(None, true)
+            .build()
+            .map_err(|e| ArchtreeError::config(e.to_string()))
+    }
+
+    /// Input lines for this job: the paths followed by `!`-prefixed exclusions
+    pub fn input_paths(&self) -> Vec<String> {
+        self.paths
+            .iter()
+            .cloned()
+            .chain(self.exclude.iter().map(|pattern| format!("!{}", pattern)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_JOBS_TOML: &str = r#"
+[jobs.nightly]
+output = "nightly.7z"
+paths = ["/home/user/documents", "/home/user/projects"]
+exclude = ["*.tmp", "node_modules"]
+verify = true
+table = true
+
+[jobs.weekly]
+output = "weekly.7z"
+paths = ["/srv/data"]
+quiet = true
+"#;
+
+    #[test]
+    fn test_named_job_config_from_toml() {
+        let job_file = JobFile::from_toml_str(TWO_JOBS_TOML).unwrap();
+        assert_eq!(job_file.jobs.len(), 2);
+
+        let nightly = job_file.job("nightly").unwrap();
+        let config = nightly.to_config().unwrap();
+        assert_eq!(config.output_path, "nightly.7z");
+        assert!(config.show_progress);
+        assert!(config.table_summary);
+        assert!(nightly.verify);
+        assert!(!nightly.retry);
+        assert_eq!(
+            nightly.input_paths(),
+            vec![
+                "/home/user/documents",
+                "/home/user/projects",
+                "!*.tmp",
+                "!node_modules"
+            ]
+        );
+
+        let weekly = job_file.job("weekly").unwrap().to_config().unwrap();
+        assert_eq!(weekly.output_path, "weekly.7z");
+        assert!(!weekly.show_progress);
+
+        assert!(job_file.job("monthly").is_err());
+    }
+
+    #[test]
+    fn test_yaml_job_file() {
+        let yaml = "\
+jobs:
+  nightly:
+    output: nightly.7z
+    paths:
+      - /home/user/documents
+    exclude:
+      - \"*.log\"
+";
+        let job_file = JobFile::from_yaml_str(yaml).unwrap();
+        let nightly = job_file.job("nightly").unwrap();
+        assert_eq!(nightly.to_config().unwrap().output_path, "nightly.7z");
+        assert_eq!(
+            nightly.input_paths(),
+            vec!["/home/user/documents", "!*.log"]
+        );
+    }
+
+    #[test]
+    fn test_job_without_output_is_rejected() {
+        let job_file = JobFile::from_toml_str("[jobs.broken]\npaths = [\"/tmp\"]\n").unwrap();
+        assert!(job_file.job("broken").unwrap().to_config().is_err());
+    }
+}
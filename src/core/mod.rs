@@ -1,5 +1,9 @@
 pub mod config;
 pub mod error;
+pub mod format;
+pub mod jobs;
 
-pub use config::Config;
-pub use error::{ArchtreeError, ErrorContext, Result};
+pub use config::{Config, PLATFORM_CASE_SENSITIVE, Password, ProgressStyle};
+pub use error::{ArchtreeError, EXIT_CANCELLED, EXIT_TOOL_NOT_AVAILABLE, ErrorContext, Result};
+pub use format::ArchiveFormat;
+pub use jobs::{JobDefinition, JobFile};
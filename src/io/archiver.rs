@@ -1,7 +1,46 @@
-use crate::core::{ArchtreeError, ErrorContext, Result};
+use crate::core::{ArchiveFormat, ArchtreeError, ErrorContext, Password, Result};
+use crate::io::MemorySettings;
+use crate::io::log::append_invocation_log;
 use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::process::Command;
 
+/// Sequence number making the list files of concurrent 7-Zip calls distinct
+static LIST_FILE_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Path for a temporary 7-Zip list file no other call of this process uses at the same time
+fn temp_list_path(prefix: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "{}_{}_{}.txt",
+        prefix,
+        std::process::id(),
+        LIST_FILE_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Switches applied with `--reproducible`.
+///
+/// 7-Zip has no switch for a fixed entry timestamp, so this pins everything else that
+/// can vary between runs: the compression method and level, a single thread, UTF-8 names
+/// and no NTFS timestamp extra fields. The DOS modification time of each file is still
+/// stored, so identical inputs must also have identical modification times.
+pub const REPRODUCIBLE_ARGS: &[&str] = &[
+    "-mm=Deflate",
+    "-mx=5",
+    "-mmt=1",
+    "-mcu=on",
+    "-mtc=off",
+    "-mta=off",
+    "-mtm=off",
+];
+
+/// Switches applied with `--reproducible` to 7z archives.
+///
+/// Modification times are kept, as 7z has no DOS time to fall back on.
+pub const SEVEN_ZIP_REPRODUCIBLE_ARGS: &[&str] =
+    &["-m0=LZMA2", "-mx=5", "-mmt=1", "-mtc=off", "-mta=off"];
+
 /// Trait for archive creation strategies
 #[async_trait]
 pub trait Archiver: Send + Sync {
@@ -11,6 +50,47 @@ pub trait Archiver: Send + Sync {
     /// Add files to an existing archive
     async fn add_to_archive(&self, paths: &[String], archive_path: &str) -> Result<()>;
 
+    /// Create an archive from a list file with one path per line, without processing it.
+    ///
+    /// By default the list is read and its non-empty lines are archived as given.
+    async fn create_archive_from_list(&self, list_path: &str, output_path: &str) -> Result<()> {
+        let content = tokio::fs::read_to_string(list_path)
+            .await
+            .context_io(format!("Failed to read list file: {}", list_path))?;
+        let paths: Vec<String> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+        self.create_archive(&paths, output_path).await
+    }
+
+    /// Add one file to an existing archive under its file name, at the root of the archive
+    /// instead of under its full path.
+    ///
+    /// By default the file is added like [`Self::add_to_archive`] adds it.
+    async fn add_at_root(&self, path: &str, archive_path: &str) -> Result<()> {
+        self.add_to_archive(&[path.to_string()], archive_path).await
+    }
+
+    /// Create an archive from the given paths and add each of `root_files` at its root,
+    /// see [`Self::add_at_root`].
+    ///
+    /// By default the archive is created first and the files are then added one by one.
+    async fn create_archive_with_root_files(
+        &self,
+        paths: &[String],
+        root_files: &[String],
+        output_path: &str,
+    ) -> Result<()> {
+        self.create_archive(paths, output_path).await?;
+        for path in root_files {
+            self.add_at_root(path, output_path).await?;
+        }
+        Ok(())
+    }
+
     /// Check if the archiver is available on the system
     async fn is_available(&self) -> bool;
 
@@ -22,20 +102,210 @@ pub trait Archiver: Send + Sync {
 #[derive(Clone)]
 pub struct SevenZipArchiver {
     executable_path: String,
+    memory: Option<MemorySettings>,
+    reproducible: bool,
+    store: bool,
+    format: ArchiveFormat,
+    /// Compression level passed as `-mx<level>`; 7-Zip's default when `None`
+    compression_level: Option<u8>,
+    /// Compression threads passed as `-mmt<threads>`; `-mmt=on` when `None`
+    threads: Option<u32>,
+    /// Size in bytes of the volumes a new archive is split into
+    volume_size: Option<u64>,
+    /// Password the archive is encrypted with, file names included for 7z archives
+    password: Option<Password>,
+    /// File the raw output of every 7-Zip invocation is appended to
+    log_path: Option<PathBuf>,
 }
 
 impl SevenZipArchiver {
     pub fn new() -> Self {
         Self {
             executable_path: "7z.exe".to_string(),
+            memory: None,
+            reproducible: false,
+            store: false,
+            format: ArchiveFormat::default(),
+            compression_level: None,
+            threads: None,
+            volume_size: None,
+            password: None,
+            log_path: None,
         }
     }
 
     pub fn with_path(executable_path: String) -> Self {
-        Self { executable_path }
+        Self {
+            executable_path,
+            memory: None,
+            reproducible: false,
+            store: false,
+            format: ArchiveFormat::default(),
+            compression_level: None,
+            threads: None,
+            volume_size: None,
+            password: None,
+            log_path: None,
+        }
+    }
+
+    /// Apply dictionary and thread settings that bound 7-Zip's memory use
+    pub fn with_memory_settings(mut self, memory: MemorySettings) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Create archives whose bytes only depend on the file contents and modification times
+    pub fn with_reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    /// Store files without compressing them (`-mm=Copy`)
+    pub fn with_store(mut self, store: bool) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Append the raw stdout and stderr of every 7-Zip invocation to this file
+    pub fn with_log<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.log_path = Some(path.into());
+        self
+    }
+
+    /// Compress at this level (0-9) instead of 7-Zip's default
+    pub fn with_compression_level(mut self, level: Option<u8>) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    /// Write archives in this format instead of 7z
+    pub fn with_format(mut self, format: ArchiveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Compress with this many threads instead of letting 7-Zip pick one per core
+    pub fn with_threads(mut self, threads: Option<u32>) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Split new archives into volumes of this many bytes (`-v`); 7-Zip names them
+    /// `<archive>.001`, `<archive>.002`, ... and can't update them afterwards
+    pub fn with_volume_size(mut self, bytes: Option<u64>) -> Self {
+        self.volume_size = bytes;
+        self
+    }
+
+    /// Encrypt the archive with this password (`-p`); 7z archives also get their
+    /// headers encrypted (`-mhe=on`) so file names stay hidden
+    pub fn with_password(mut self, password: Option<Password>) -> Self {
+        self.password = password;
+        self
+    }
+
+    /// Content of the list file handed to 7-Zip; sorted when building reproducibly.
+    ///
+    /// Fails for paths a list file can't hold, see [`check_list_paths`].
+    fn list_file_content(&self, paths: &[String]) -> Result<String> {
+        check_list_paths(paths)?;
+        Ok(if self.reproducible {
+            let mut sorted = paths.to_vec();
+            sorted.sort();
+            sorted.join("\r\n")
+        } else {
+            paths.join("\r\n") // Use Windows line endings
+        })
+    }
+
+    /// Build a 7-Zip add (`a`) or update (`u`) command reading its inputs from a list file
+    fn archive_command(&self, command: &str, archive_path: &str, list_path: &Path) -> Command {
+        let mut cmd = self.method_command(command, true);
+        cmd.arg(archive_path) // Archive path
+            .arg(format!("@{}", list_path.display())); // Input file list
+        // .env("LANG", "en_US.UTF-8") // Force English output
+        // .env("LC_ALL", "en_US.UTF-8"); // Override locale settings
+        cmd
+    }
+
+    /// Build a 7-Zip command with every switch but the archive and its inputs; entries
+    /// keep their full path with `full_paths`, otherwise only their file name
+    fn method_command(&self, command: &str, full_paths: bool) -> Command {
+        let mut cmd = Command::new(&self.executable_path);
+        cmd.arg(command);
+        if full_paths {
+            cmd.arg("-spf"); // Use full paths
+        }
+        cmd.arg("-sccUTF-8"); // Force UTF-8 output
+        cmd.arg(self.format.type_switch());
+
+        // Volumes are only written when an archive is created
+        if let Some(bytes) = self.volume_size
+            && command == "a"
+        {
+            cmd.arg(volume_switch(bytes));
+        }
+
+        if let Some(password) = &self.password {
+            cmd.arg(format!("-p{}", password.expose()));
+            if self.format == ArchiveFormat::SevenZip {
+                cmd.arg("-mhe=on");
+            }
+        }
+
+        // Tar doesn't compress, so it takes no method switches
+        if self.format.is_compressed() {
+            // First, so the memory cap and reproducible settings can lower it
+            cmd.arg(match self.threads {
+                Some(threads) => format!("-mmt{}", threads),
+                None => "-mmt=on".to_string(),
+            });
+            if let Some(memory) = &self.memory {
+                cmd.args(memory.to_args());
+            }
+            if self.reproducible {
+                // Later switches win, so these override a thread count from the memory cap
+                cmd.args(match self.format {
+                    ArchiveFormat::SevenZip => SEVEN_ZIP_REPRODUCIBLE_ARGS,
+                    _ => REPRODUCIBLE_ARGS,
+                });
+            }
+            if let Some(level) = self.compression_level {
+                // After the reproducible switches so it overrides their level
+                cmd.arg(format!("-mx{}", level));
+            }
+            if self.store {
+                // After the reproducible switches so it overrides their method
+                cmd.arg(match self.format {
+                    ArchiveFormat::SevenZip => "-m0=Copy",
+                    _ => "-mm=Copy",
+                });
+            }
+        }
+        cmd
     }
 }
 
+/// Fail if a path contains a line break.
+///
+/// 7-Zip reads its list file one path per line, so such a path would be split into other
+/// paths and its file left out of the archive.
+pub fn check_list_paths(paths: &[String]) -> Result<()> {
+    match paths.iter().find(|path| path.contains(['\n', '\r'])) {
+        Some(path) => Err(ArchtreeError::config(format!(
+            "Can't archive {:?}: 7-Zip reads one path per line, so names with line breaks can't be archived. Exclude or rename it",
+            path
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// 7-Zip switch splitting an archive into volumes of `bytes` bytes
+fn volume_switch(bytes: u64) -> String {
+    format!("-v{}b", bytes)
+}
+
 impl Default for SevenZipArchiver {
     fn default() -> Self {
         Self::new()
@@ -46,33 +316,25 @@ impl Default for SevenZipArchiver {
 impl Archiver for SevenZipArchiver {
     async fn create_archive(&self, paths: &[String], output_path: &str) -> Result<()> {
         // Create a temporary file list for 7-Zip with explicit path
-        let temp_dir = std::env::temp_dir();
-        let temp_list_path = temp_dir.join(format!("7zip_list_{}.txt", std::process::id()));
+        let temp_list_path = temp_list_path("7zip_list");
 
         // Write all paths to the temporary file with UTF-8 encoding
-        let list_content = paths.join("\r\n"); // Use Windows line endings
+        let list_content = self.list_file_content(paths)?;
         tokio::fs::write(&temp_list_path, list_content.as_bytes())
             .await
             .context_io("Failed to write path list to temporary file")?;
 
         // Build 7-Zip command
-        let mut cmd = Command::new(&self.executable_path);
-        cmd.args([
-            "a",                                       // Add to archive
-            "-spf",                                    // Use full paths
-            "-sccUTF-8",                               // Force UTF-8 output
-            "-tzip",                                   // 7z format
-            output_path,                               // Output archive path
-            &format!("@{}", temp_list_path.display()), // Input file list
-        ]);
-        // .env("LANG", "en_US.UTF-8") // Force English output
-        // .env("LC_ALL", "en_US.UTF-8"); // Override locale settings
+        let mut cmd = self.archive_command("a", output_path, &temp_list_path);
 
         // Execute the command
         let output = cmd
             .output()
             .await
             .context_external("7z", "Failed to execute 7z command")?;
+        if let Some(log_path) = &self.log_path {
+            append_invocation_log(log_path, &cmd, &output).await?;
+        }
 
         // Clean up the temporary file
         let _ = tokio::fs::remove_file(&temp_list_path).await;
@@ -98,33 +360,25 @@ impl Archiver for SevenZipArchiver {
             .to_string();
 
         // Create a temporary file list for 7-Zip with explicit path
-        let temp_dir = std::env::temp_dir();
-        let temp_list_path = temp_dir.join(format!("7zip_add_list_{}.txt", std::process::id()));
+        let temp_list_path = temp_list_path("7zip_add_list");
 
         // Write all paths to the temporary file with UTF-8 encoding
-        let list_content = paths.join("\r\n"); // Use Windows line endings
+        let list_content = self.list_file_content(paths)?;
         tokio::fs::write(&temp_list_path, list_content.as_bytes())
             .await
             .context_io("Failed to write path list to temporary file")?;
 
         // Build 7-Zip command (use 'u' for update instead of 'a' for add)
-        let mut cmd = Command::new(&self.executable_path);
-        cmd.args([
-            "u",                                       // Update archive (add if not exists)
-            "-spf",                                    // Use full paths
-            "-sccUTF-8",                               // Force UTF-8 output
-            "-tzip",                                   // 7z format
-            &archive_path,                             // Archive path
-            &format!("@{}", temp_list_path.display()), // Input file list
-        ]);
-        // .env("LANG", "en_US.UTF-8") // Force English output
-        // .env("LC_ALL", "en_US.UTF-8"); // Override locale settings
+        let mut cmd = self.archive_command("u", &archive_path, &temp_list_path);
 
         // Execute the command
         let output = cmd
             .output()
             .await
             .context_io("Failed to execute 7z update command")?;
+        if let Some(log_path) = &self.log_path {
+            append_invocation_log(log_path, &cmd, &output).await?;
+        }
 
         // Clean up the temporary file
         let _ = tokio::fs::remove_file(&temp_list_path).await;
@@ -144,6 +398,59 @@ impl Archiver for SevenZipArchiver {
         Ok(())
     }
 
+    async fn create_archive_from_list(&self, list_path: &str, output_path: &str) -> Result<()> {
+        // 7-Zip reads the list itself, so its paths and encoding are used unchanged
+        let mut cmd = self.archive_command("a", output_path, Path::new(list_path));
+
+        let output = cmd
+            .output()
+            .await
+            .context_external("7z", "Failed to execute 7z command")?;
+        if let Some(log_path) = &self.log_path {
+            append_invocation_log(log_path, &cmd, &output).await?;
+        }
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            return Err(ArchtreeError::external_tool(
+                "7z",
+                format!("7z command failed:\nStderr: {}\nStdout: {}", stderr, stdout),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn add_at_root(&self, path: &str, archive_path: &str) -> Result<()> {
+        // Without -spf 7-Zip stores a file given by an absolute path under its name only;
+        // an update adds it without rewriting volumes
+        let mut cmd = self.method_command("u", false);
+        cmd.arg(archive_path).arg(path);
+
+        let output = cmd
+            .output()
+            .await
+            .context_external("7z", "Failed to execute 7z update command")?;
+        if let Some(log_path) = &self.log_path {
+            append_invocation_log(log_path, &cmd, &output).await?;
+        }
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            return Err(ArchtreeError::external_tool(
+                "7z",
+                format!(
+                    "7z update command failed:\nStderr: {}\nStdout: {}",
+                    stderr, stdout
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
     async fn is_available(&self) -> bool {
         Command::new(&self.executable_path)
             .arg("--help")
@@ -158,6 +465,353 @@ impl Archiver for SevenZipArchiver {
     }
 }
 
+/// Archiver that hands paths to another archiver in chunks of at most `chunk_size`.
+///
+/// `create_archive` creates the archive from the first chunk and adds the remaining
+/// chunks to it, so a huge input set still yields a single archive.
+#[derive(Clone)]
+pub struct ChunkedArchiver<A> {
+    inner: A,
+    chunk_size: Option<usize>,
+    show_progress: bool,
+}
+
+impl<A: Archiver> ChunkedArchiver<A> {
+    /// Wrap an archiver; `None` (or 0) passes every call through unchanged
+    pub fn new(inner: A, chunk_size: Option<usize>) -> Self {
+        Self {
+            inner,
+            chunk_size: chunk_size.filter(|&size| size > 0),
+            show_progress: false,
+        }
+    }
+
+    /// Report each chunk as it is processed
+    pub fn with_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+
+    fn chunks<'a>(&self, paths: &'a [String]) -> Vec<&'a [String]> {
+        match self.chunk_size {
+            Some(size) if paths.len() > size => paths.chunks(size).collect(),
+            _ => vec![paths],
+        }
+    }
+
+    async fn add_chunks(
+        &self,
+        chunks: &[&[String]],
+        first: usize,
+        archive_path: &str,
+    ) -> Result<()> {
+        for (index, chunk) in chunks.iter().enumerate().skip(first) {
+            self.report_chunk(index, chunks.len(), chunk.len());
+            self.inner.add_to_archive(chunk, archive_path).await?;
+        }
+        Ok(())
+    }
+
+    fn report_chunk(&self, index: usize, total: usize, files: usize) {
+        if self.show_progress && total > 1 {
+            eprintln!("  📦 Chunk {}/{}: {} files", index + 1, total, files);
+        }
+    }
+}
+
+#[async_trait]
+impl<A: Archiver> Archiver for ChunkedArchiver<A> {
+    async fn create_archive(&self, paths: &[String], output_path: &str) -> Result<()> {
+        let chunks = self.chunks(paths);
+        self.report_chunk(0, chunks.len(), chunks[0].len());
+        self.inner.create_archive(chunks[0], output_path).await?;
+        self.add_chunks(&chunks, 1, output_path).await?;
+
+        if self.show_progress && chunks.len() > 1 {
+            eprintln!(
+                "  📦 Archived {} files in {} chunks",
+                paths.len(),
+                chunks.len()
+            );
+        }
+        Ok(())
+    }
+
+    async fn add_to_archive(&self, paths: &[String], archive_path: &str) -> Result<()> {
+        self.add_chunks(&self.chunks(paths), 0, archive_path).await
+    }
+
+    async fn create_archive_from_list(&self, list_path: &str, output_path: &str) -> Result<()> {
+        // A prepared list is handed over whole
+        self.inner
+            .create_archive_from_list(list_path, output_path)
+            .await
+    }
+
+    async fn add_at_root(&self, path: &str, archive_path: &str) -> Result<()> {
+        self.inner.add_at_root(path, archive_path).await
+    }
+
+    async fn is_available(&self) -> bool {
+        self.inner.is_available().await
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+/// Size limits outside of which files are stored without compression
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StoreThresholds {
+    /// Files smaller than this many bytes are stored
+    pub below: Option<u64>,
+    /// Files larger than this many bytes are stored
+    pub above: Option<u64>,
+}
+
+impl StoreThresholds {
+    /// Check if a file of `size` bytes should be stored rather than compressed
+    pub fn should_store(&self, size: u64) -> bool {
+        self.below.is_some_and(|below| size < below) || self.above.is_some_and(|above| size > above)
+    }
+
+    /// Split paths into (store, compress) groups by file size, keeping their order.
+    ///
+    /// Files whose size can't be read are compressed.
+    pub fn partition(&self, paths: &[String]) -> (Vec<String>, Vec<String>) {
+        if self.below.is_none() && self.above.is_none() {
+            return (Vec::new(), paths.to_vec());
+        }
+
+        paths.iter().cloned().partition(|path| {
+            std::fs::metadata(path).is_ok_and(|metadata| self.should_store(metadata.len()))
+        })
+    }
+}
+
+/// Archiver that stores files outside the size thresholds and compresses the rest.
+///
+/// 7-Zip applies one method per invocation, so this takes two: the store group is
+/// written first and the compress group is then added to the same archive.
+#[derive(Clone)]
+pub struct StoreRoutingArchiver<A> {
+    compress: A,
+    store: A,
+    thresholds: StoreThresholds,
+    show_progress: bool,
+}
+
+impl<A: Archiver> StoreRoutingArchiver<A> {
+    /// Route files to `store` or `compress`; without thresholds everything is compressed
+    pub fn new(compress: A, store: A, thresholds: StoreThresholds) -> Self {
+        Self {
+            compress,
+            store,
+            thresholds,
+            show_progress: false,
+        }
+    }
+
+    /// Report the size of both groups
+    pub fn with_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+
+    fn partition(&self, paths: &[String]) -> (Vec<String>, Vec<String>) {
+        let (store, compress) = self.thresholds.partition(paths);
+        if self.show_progress && !store.is_empty() {
+            eprintln!(
+                "  🗃️  Storing {} files uncompressed, compressing {}",
+                store.len(),
+                compress.len()
+            );
+        }
+        (store, compress)
+    }
+}
+
+#[async_trait]
+impl<A: Archiver> Archiver for StoreRoutingArchiver<A> {
+    async fn create_archive(&self, paths: &[String], output_path: &str) -> Result<()> {
+        let (store, compress) = self.partition(paths);
+        if store.is_empty() {
+            return self.compress.create_archive(&compress, output_path).await;
+        }
+
+        self.store.create_archive(&store, output_path).await?;
+        if !compress.is_empty() {
+            self.compress.add_to_archive(&compress, output_path).await?;
+        }
+        Ok(())
+    }
+
+    async fn add_to_archive(&self, paths: &[String], archive_path: &str) -> Result<()> {
+        let (store, compress) = self.partition(paths);
+        if !store.is_empty() {
+            self.store.add_to_archive(&store, archive_path).await?;
+        }
+        if !compress.is_empty() {
+            self.compress
+                .add_to_archive(&compress, archive_path)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn create_archive_from_list(&self, list_path: &str, output_path: &str) -> Result<()> {
+        // File sizes of a prepared list are unknown, so everything is compressed
+        self.compress
+            .create_archive_from_list(list_path, output_path)
+            .await
+    }
+
+    async fn add_at_root(&self, path: &str, archive_path: &str) -> Result<()> {
+        self.compress.add_at_root(path, archive_path).await
+    }
+
+    async fn is_available(&self) -> bool {
+        self.compress.is_available().await
+    }
+
+    fn name(&self) -> &'static str {
+        self.compress.name()
+    }
+}
+
+/// Suffix of the temporary archive an atomic create writes to
+pub const ATOMIC_TEMP_SUFFIX: &str = ".tmp";
+
+/// Inputs of an archive created by [`AtomicArchiver`]
+#[derive(Clone, Copy)]
+enum ArchiveSource<'a> {
+    Paths(&'a [String]),
+    WithRootFiles(&'a [String], &'a [String]),
+    ListFile(&'a str),
+}
+
+impl ArchiveSource<'_> {
+    async fn create_with<A: Archiver>(self, archiver: &A, output_path: &str) -> Result<()> {
+        match self {
+            Self::Paths(paths) => archiver.create_archive(paths, output_path).await,
+            Self::WithRootFiles(paths, root_files) => {
+                archiver
+                    .create_archive_with_root_files(paths, root_files, output_path)
+                    .await
+            }
+            Self::ListFile(list_path) => {
+                archiver
+                    .create_archive_from_list(list_path, output_path)
+                    .await
+            }
+        }
+    }
+}
+
+/// Archiver that creates archives under a temporary name and renames them into place.
+///
+/// An interrupted or failed create therefore never leaves a partial archive at the
+/// output path; the temporary file is removed on failure. Files added at the root along
+/// with the create are added before the rename. An existing archive is copied
+/// to the temporary name first, so adding to it behaves as without this wrapper.
+#[derive(Clone)]
+pub struct AtomicArchiver<A> {
+    inner: A,
+    enabled: bool,
+}
+
+impl<A: Archiver> AtomicArchiver<A> {
+    /// Wrap an archiver; when `enabled` is false every call is passed through unchanged
+    pub fn new(inner: A, enabled: bool) -> Self {
+        Self { inner, enabled }
+    }
+
+    /// Temporary path an archive is written to before being renamed to `output_path`
+    pub fn temp_path(output_path: &str) -> String {
+        format!("{}{}", output_path, ATOMIC_TEMP_SUFFIX)
+    }
+
+    async fn create_atomically(&self, source: ArchiveSource<'_>, output_path: &str) -> Result<()> {
+        if !self.enabled {
+            return source.create_with(&self.inner, output_path).await;
+        }
+
+        let temp = Self::temp_path(output_path);
+        let result = self.create_at_temp(source, output_path, &temp).await;
+        if result.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
+        }
+        result
+    }
+
+    async fn create_at_temp(
+        &self,
+        source: ArchiveSource<'_>,
+        output_path: &str,
+        temp: &str,
+    ) -> Result<()> {
+        // A leftover from an earlier interrupted run would otherwise be added to
+        let _ = tokio::fs::remove_file(temp).await;
+        if tokio::fs::try_exists(output_path).await.unwrap_or(false) {
+            tokio::fs::copy(output_path, temp)
+                .await
+                .context_io(format!(
+                    "Failed to copy existing archive to temporary file: {}",
+                    temp
+                ))?;
+        }
+
+        source.create_with(&self.inner, temp).await?;
+        tokio::fs::rename(temp, output_path)
+            .await
+            .context_io(format!(
+                "Failed to move temporary archive into place: {}",
+                output_path
+            ))
+    }
+}
+
+#[async_trait]
+impl<A: Archiver> Archiver for AtomicArchiver<A> {
+    async fn create_archive(&self, paths: &[String], output_path: &str) -> Result<()> {
+        self.create_atomically(ArchiveSource::Paths(paths), output_path)
+            .await
+    }
+
+    async fn add_to_archive(&self, paths: &[String], archive_path: &str) -> Result<()> {
+        // 7-Zip already writes updates to a temporary file before replacing the archive
+        self.inner.add_to_archive(paths, archive_path).await
+    }
+
+    async fn create_archive_from_list(&self, list_path: &str, output_path: &str) -> Result<()> {
+        self.create_atomically(ArchiveSource::ListFile(list_path), output_path)
+            .await
+    }
+
+    async fn add_at_root(&self, path: &str, archive_path: &str) -> Result<()> {
+        self.inner.add_at_root(path, archive_path).await
+    }
+
+    async fn create_archive_with_root_files(
+        &self,
+        paths: &[String],
+        root_files: &[String],
+        output_path: &str,
+    ) -> Result<()> {
+        self.create_atomically(ArchiveSource::WithRootFiles(paths, root_files), output_path)
+            .await
+    }
+
+    async fn is_available(&self) -> bool {
+        self.inner.is_available().await
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,6 +833,641 @@ mod tests {
         assert_eq!(archiver.name(), "7-Zip");
     }
 
+    #[test]
+    fn test_reproducible_args_applied() {
+        let archiver = SevenZipArchiver::with_path("7z".to_string())
+            .with_format(ArchiveFormat::Zip)
+            .with_reproducible(true);
+        let cmd = archiver.archive_command("a", "out.zip", Path::new("list.txt"));
+        let args: Vec<String> = cmd
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+
+        for switch in REPRODUCIBLE_ARGS {
+            assert!(args.iter().any(|arg| arg == switch), "missing {}", switch);
+        }
+        assert_eq!(args[0], "a");
+        assert_eq!(&args[args.len() - 2..], ["out.zip", "@list.txt"]);
+
+        let plain = SevenZipArchiver::with_path("7z".to_string());
+        let cmd = plain.archive_command("a", "out.zip", Path::new("list.txt"));
+        assert!(!cmd.as_std().get_args().any(|arg| arg == "-mtc=off"));
+    }
+
+    type Call = (&'static str, Vec<String>);
+
+    /// Archiver recording each call as (operation, paths)
+    #[derive(Clone, Default)]
+    struct RecordingArchiver {
+        calls: std::sync::Arc<std::sync::Mutex<Vec<Call>>>,
+    }
+
+    #[async_trait]
+    impl Archiver for RecordingArchiver {
+        async fn create_archive(&self, paths: &[String], _output_path: &str) -> Result<()> {
+            self.calls.lock().unwrap().push(("create", paths.to_vec()));
+            Ok(())
+        }
+
+        async fn add_to_archive(&self, paths: &[String], _archive_path: &str) -> Result<()> {
+            self.calls.lock().unwrap().push(("update", paths.to_vec()));
+            Ok(())
+        }
+
+        async fn is_available(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &'static str {
+            "Recording"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunked_create_then_update() {
+        let inner = RecordingArchiver::default();
+        let archiver = ChunkedArchiver::new(inner.clone(), Some(2));
+        let paths: Vec<String> = (1..=5).map(|i| format!("file{}.txt", i)).collect();
+
+        archiver.create_archive(&paths, "out.zip").await.unwrap();
+
+        let calls = inner.calls.lock().unwrap().clone();
+        assert_eq!(
+            calls,
+            vec![
+                (
+                    "create",
+                    vec!["file1.txt".to_string(), "file2.txt".to_string()]
+                ),
+                (
+                    "update",
+                    vec!["file3.txt".to_string(), "file4.txt".to_string()]
+                ),
+                ("update", vec!["file5.txt".to_string()]),
+            ]
+        );
+
+        // Without a chunk size every call is passed through as is
+        let inner = RecordingArchiver::default();
+        let archiver = ChunkedArchiver::new(inner.clone(), None);
+        archiver.add_to_archive(&paths, "out.zip").await.unwrap();
+        assert_eq!(*inner.calls.lock().unwrap(), vec![("update", paths)]);
+    }
+
+    #[test]
+    fn test_store_thresholds() {
+        let thresholds = StoreThresholds {
+            below: Some(100),
+            above: Some(1000),
+        };
+        assert!(thresholds.should_store(99));
+        assert!(!thresholds.should_store(100));
+        assert!(!thresholds.should_store(1000));
+        assert!(thresholds.should_store(1001));
+
+        assert!(!StoreThresholds::default().should_store(0));
+        let above_only = StoreThresholds {
+            below: None,
+            above: Some(10),
+        };
+        assert!(!above_only.should_store(0));
+        assert!(above_only.should_store(11));
+    }
+
+    #[tokio::test]
+    async fn test_store_group_created_then_compress_group_added() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut paths = Vec::new();
+        for (name, size) in [("tiny.txt", 10), ("medium.txt", 500), ("huge.bin", 5000)] {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, vec![b'x'; size]).unwrap();
+            paths.push(path.to_string_lossy().to_string());
+        }
+        let [tiny, medium, huge] = [&paths[0], &paths[1], &paths[2]];
+
+        let thresholds = StoreThresholds {
+            below: Some(100),
+            above: Some(1000),
+        };
+        assert_eq!(
+            thresholds.partition(&paths),
+            (vec![tiny.clone(), huge.clone()], vec![medium.clone()])
+        );
+
+        let compress = RecordingArchiver::default();
+        let store = RecordingArchiver::default();
+        let archiver = StoreRoutingArchiver::new(compress.clone(), store.clone(), thresholds);
+        archiver.create_archive(&paths, "out.zip").await.unwrap();
+
+        assert_eq!(
+            *store.calls.lock().unwrap(),
+            vec![("create", vec![tiny.clone(), huge.clone()])]
+        );
+        assert_eq!(
+            *compress.calls.lock().unwrap(),
+            vec![("update", vec![medium.clone()])]
+        );
+
+        // Without thresholds every file is compressed in a single call
+        let compress = RecordingArchiver::default();
+        let store = RecordingArchiver::default();
+        let archiver =
+            StoreRoutingArchiver::new(compress.clone(), store.clone(), StoreThresholds::default());
+        archiver.create_archive(&paths, "out.zip").await.unwrap();
+        assert!(store.calls.lock().unwrap().is_empty());
+        assert_eq!(*compress.calls.lock().unwrap(), vec![("create", paths)]);
+    }
+
+    /// Archiver that writes part of an archive and then fails, like an interrupted 7-Zip run
+    struct InterruptedArchiver {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Archiver for InterruptedArchiver {
+        async fn create_archive(&self, _paths: &[String], output_path: &str) -> Result<()> {
+            fs::write(output_path, b"PK\x03\x04partial").unwrap();
+            if self.fail {
+                return Err(ArchtreeError::external_tool("7z", "interrupted"));
+            }
+            Ok(())
+        }
+
+        async fn add_to_archive(&self, paths: &[String], archive_path: &str) -> Result<()> {
+            self.create_archive(paths, archive_path).await
+        }
+
+        async fn is_available(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &'static str {
+            "Interrupted"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_atomic_create_leaves_nothing_on_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("backup.zip");
+        let output = output.to_string_lossy().to_string();
+        let temp = AtomicArchiver::<InterruptedArchiver>::temp_path(&output);
+        let paths = vec!["file.txt".to_string()];
+
+        let archiver = AtomicArchiver::new(InterruptedArchiver { fail: true }, true);
+        assert!(archiver.create_archive(&paths, &output).await.is_err());
+        assert!(!Path::new(&output).exists());
+        assert!(!Path::new(&temp).exists());
+
+        let archiver = AtomicArchiver::new(InterruptedArchiver { fail: false }, true);
+        archiver.create_archive(&paths, &output).await.unwrap();
+        assert!(Path::new(&output).exists());
+        assert!(!Path::new(&temp).exists());
+
+        // Without atomic output the partial archive stays behind
+        fs::remove_file(&output).unwrap();
+        let archiver = AtomicArchiver::new(InterruptedArchiver { fail: true }, false);
+        assert!(archiver.create_archive(&paths, &output).await.is_err());
+        assert!(Path::new(&output).exists());
+    }
+
+    /// Archiver that creates archives but fails to add to them
+    struct FailingAddArchiver;
+
+    #[async_trait]
+    impl Archiver for FailingAddArchiver {
+        async fn create_archive(&self, _paths: &[String], output_path: &str) -> Result<()> {
+            fs::write(output_path, b"PK\x03\x04").unwrap();
+            Ok(())
+        }
+
+        async fn add_to_archive(&self, _paths: &[String], _archive_path: &str) -> Result<()> {
+            Err(ArchtreeError::external_tool("7z", "interrupted"))
+        }
+
+        async fn is_available(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &'static str {
+            "FailingAdd"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_atomic_create_adds_root_files_before_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("backup.zip");
+        let output = output.to_string_lossy().to_string();
+        let temp = AtomicArchiver::<FailingAddArchiver>::temp_path(&output);
+        let paths = vec!["file.txt".to_string()];
+        let root_files = vec!["tree.txt".to_string()];
+
+        // The create succeeds but adding the root file fails: nothing is moved into place
+        let archiver = AtomicArchiver::new(FailingAddArchiver, true);
+        assert!(
+            archiver
+                .create_archive_with_root_files(&paths, &root_files, &output)
+                .await
+                .is_err()
+        );
+        assert!(!Path::new(&output).exists());
+        assert!(!Path::new(&temp).exists());
+    }
+
+    #[test]
+    fn test_store_switch_overrides_reproducible_method() {
+        let archiver = SevenZipArchiver::with_path("7z".to_string())
+            .with_format(ArchiveFormat::Zip)
+            .with_reproducible(true)
+            .with_store(true);
+        let cmd = archiver.archive_command("a", "out.zip", Path::new("list.txt"));
+        let args: Vec<String> = cmd
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        let deflate = args.iter().position(|arg| arg == "-mm=Deflate").unwrap();
+        let copy = args.iter().position(|arg| arg == "-mm=Copy").unwrap();
+        assert!(copy > deflate);
+    }
+
+    #[test]
+    fn test_thread_switch() {
+        let args_of = |archiver: SevenZipArchiver, command: &str| -> Vec<String> {
+            archiver
+                .archive_command(command, "out.7z", Path::new("list.txt"))
+                .as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect()
+        };
+
+        assert!(args_of(SevenZipArchiver::new(), "a").contains(&"-mmt=on".to_string()));
+        // Creating and updating an archive use the same thread count
+        for command in ["a", "u"] {
+            let args = args_of(SevenZipArchiver::new().with_threads(Some(16)), command);
+            assert!(args.contains(&"-mmt16".to_string()));
+            assert!(!args.contains(&"-mmt=on".to_string()));
+        }
+
+        // Reproducible archives stay single-threaded
+        let args = args_of(
+            SevenZipArchiver::new()
+                .with_threads(Some(16))
+                .with_reproducible(true),
+            "a",
+        );
+        let requested = args.iter().position(|arg| arg == "-mmt16").unwrap();
+        let single = args.iter().position(|arg| arg == "-mmt=1").unwrap();
+        assert!(single > requested);
+    }
+
+    #[test]
+    fn test_volume_switch_from_parsed_size() {
+        let bytes = crate::core::config::parse_volume_size("100m").unwrap();
+        let archiver = SevenZipArchiver::new().with_volume_size(Some(bytes));
+        let args_of = |command: &str| -> Vec<String> {
+            archiver
+                .archive_command(command, "out.7z", Path::new("list.txt"))
+                .as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect()
+        };
+
+        assert!(args_of("a").contains(&"-v104857600b".to_string()));
+        // 7-Zip can't update split archives, so updates never ask for volumes
+        assert!(!args_of("u").iter().any(|arg| arg.starts_with("-v")));
+    }
+
+    #[test]
+    fn test_password_switches() {
+        let args_of = |archiver: SevenZipArchiver| -> Vec<String> {
+            archiver
+                .archive_command("a", "out", Path::new("list.txt"))
+                .as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect()
+        };
+        let password = Some(Password::new("s3cret"));
+
+        let seven_zip = args_of(SevenZipArchiver::new().with_password(password.clone()));
+        assert!(seven_zip.contains(&"-ps3cret".to_string()));
+        assert!(seven_zip.contains(&"-mhe=on".to_string()));
+
+        // Zip can't encrypt its headers, only the file data
+        let zip = args_of(
+            SevenZipArchiver::new()
+                .with_format(ArchiveFormat::Zip)
+                .with_password(password),
+        );
+        assert!(zip.contains(&"-ps3cret".to_string()));
+        assert!(!zip.contains(&"-mhe=on".to_string()));
+
+        let plain = args_of(SevenZipArchiver::new());
+        assert!(!plain.iter().any(|arg| arg.starts_with("-p")));
+    }
+
+    #[test]
+    fn test_format_switches() {
+        let args_of = |archiver: SevenZipArchiver| -> Vec<String> {
+            archiver
+                .archive_command("a", "out", Path::new("list.txt"))
+                .as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect()
+        };
+
+        let zip = args_of(
+            SevenZipArchiver::new()
+                .with_format(ArchiveFormat::Zip)
+                .with_store(true),
+        );
+        assert!(zip.contains(&"-tzip".to_string()));
+        assert!(zip.contains(&"-mm=Copy".to_string()));
+
+        let seven_zip = args_of(
+            SevenZipArchiver::new()
+                .with_format(ArchiveFormat::SevenZip)
+                .with_store(true),
+        );
+        assert!(seven_zip.contains(&"-t7z".to_string()));
+        assert!(seven_zip.contains(&"-m0=Copy".to_string()));
+
+        let tar = args_of(
+            SevenZipArchiver::new()
+                .with_format(ArchiveFormat::Tar)
+                .with_reproducible(true)
+                .with_store(true),
+        );
+        assert!(tar.contains(&"-ttar".to_string()));
+        assert!(!tar.iter().any(|arg| arg.starts_with("-m")));
+    }
+
+    #[test]
+    fn test_compression_level_switch() {
+        let args_of = |archiver: SevenZipArchiver| -> Vec<String> {
+            archiver
+                .archive_command("a", "out", Path::new("list.txt"))
+                .as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect()
+        };
+
+        // 7-Zip's own default level unless one is asked for
+        let default = args_of(SevenZipArchiver::new());
+        assert!(!default.iter().any(|arg| arg.starts_with("-mx")));
+
+        let fast = args_of(SevenZipArchiver::new().with_compression_level(Some(1)));
+        assert!(fast.contains(&"-mx1".to_string()));
+
+        // The explicit level comes after the reproducible -mx=5, so it wins
+        let reproducible = args_of(
+            SevenZipArchiver::new()
+                .with_reproducible(true)
+                .with_compression_level(Some(9)),
+        );
+        let fixed = reproducible.iter().position(|arg| arg == "-mx=5").unwrap();
+        let level = reproducible.iter().position(|arg| arg == "-mx9").unwrap();
+        assert!(level > fixed);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_seven_zip_log_records_raw_output() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        // Stand-in for 7-Zip that prints to both streams
+        let runner = temp_dir.path().join("fake7z");
+        fs::write(
+            &runner,
+            "#!/bin/sh\necho \"Everything is Ok\"\necho \"WARNING: skipped\" >&2\n",
+        )
+        .unwrap();
+        fs::set_permissions(&runner, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let log = temp_dir.path().join("7z.log");
+        let archiver =
+            SevenZipArchiver::with_path(runner.to_string_lossy().to_string()).with_log(&log);
+        let output = temp_dir.path().join("out.zip");
+        archiver
+            .create_archive(&["file.txt".to_string()], &output.to_string_lossy())
+            .await
+            .unwrap();
+
+        let content = fs::read_to_string(&log).unwrap();
+        assert!(content.starts_with("===="));
+        assert!(content.contains(" a -spf "));
+        assert!(content.contains("--- stdout ---\nEverything is Ok\n"));
+        assert!(content.contains("--- stderr ---\nWARNING: skipped\n"));
+
+        // A second invocation is appended under its own header
+        archiver
+            .create_archive(&["file.txt".to_string()], &output.to_string_lossy())
+            .await
+            .unwrap();
+        let content = fs::read_to_string(&log).unwrap();
+        assert_eq!(content.matches("====").count(), 4);
+
+        // The password is logged as a placeholder
+        let archiver = archiver.with_password(Some(Password::new("s3cret")));
+        archiver
+            .create_archive(&["file.txt".to_string()], &output.to_string_lossy())
+            .await
+            .unwrap();
+        let content = fs::read_to_string(&log).unwrap();
+        assert!(content.contains(" -p*** "));
+        assert!(!content.contains("s3cret"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_raw_list_is_passed_to_seven_zip() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        // Stand-in for 7-Zip that records its arguments
+        let args_file = temp_dir.path().join("args.txt");
+        let runner = temp_dir.path().join("fake7z");
+        fs::write(
+            &runner,
+            format!("#!/bin/sh\necho \"$@\" > '{}'\n", args_file.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&runner, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let list = temp_dir.path().join("prepared.txt");
+        fs::write(&list, "/data/a.txt\r\n/data/b.txt").unwrap();
+        let archiver = SevenZipArchiver::with_path(runner.to_string_lossy().to_string());
+        archiver
+            .create_archive_from_list(&list.to_string_lossy(), "out.zip")
+            .await
+            .unwrap();
+
+        let args = fs::read_to_string(&args_file).unwrap();
+        assert!(
+            args.trim_end()
+                .ends_with(&format!("out.zip @{}", list.display()))
+        );
+        // The list itself is left as it was
+        assert_eq!(
+            fs::read_to_string(&list).unwrap(),
+            "/data/a.txt\r\n/data/b.txt"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_add_at_root_leaves_out_full_paths_and_volumes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        // Stand-in for 7-Zip that records its arguments
+        let args_file = temp_dir.path().join("args.txt");
+        let runner = temp_dir.path().join("fake7z");
+        fs::write(
+            &runner,
+            format!("#!/bin/sh\necho \"$@\" > '{}'\n", args_file.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&runner, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let archiver = SevenZipArchiver::with_path(runner.to_string_lossy().to_string())
+            .with_reproducible(true)
+            .with_volume_size(Some(1024));
+        archiver
+            .add_at_root("/tmp/index/tree.txt", "out.7z")
+            .await
+            .unwrap();
+
+        let args = fs::read_to_string(&args_file).unwrap();
+        let args: Vec<&str> = args.split_whitespace().collect();
+        assert_eq!(args[0], "u");
+        assert!(!args.contains(&"-spf"));
+        assert!(!args.iter().any(|arg| arg.starts_with("-v")));
+        assert!(args.contains(&"-mtc=off"));
+        assert_eq!(&args[args.len() - 2..], ["out.7z", "/tmp/index/tree.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_concurrent_archives_use_separate_list_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        // Stand-in for 7-Zip that waits, so both lists exist at the same time, and then
+        // copies the list it was given into the archive
+        let runner = temp_dir.path().join("fake7z");
+        fs::write(
+            &runner,
+            "#!/bin/sh\nfor arg; do archive=\"$list\"; list=\"$arg\"; done\nsleep 0.2\ncat \"${list#@}\" > \"$archive\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&runner, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let archiver = SevenZipArchiver::with_path(runner.to_string_lossy().to_string());
+        let first = temp_dir
+            .path()
+            .join("first.7z")
+            .to_string_lossy()
+            .to_string();
+        let second = temp_dir
+            .path()
+            .join("second.7z")
+            .to_string_lossy()
+            .to_string();
+        let first_paths = vec!["/data/a.txt".to_string()];
+        let second_paths = vec!["/data/b.txt".to_string(), "/data/c.txt".to_string()];
+
+        let (first_result, second_result) = tokio::join!(
+            archiver.create_archive(&first_paths, &first),
+            archiver.create_archive(&second_paths, &second)
+        );
+        first_result.unwrap();
+        second_result.unwrap();
+
+        assert_eq!(fs::read_to_string(&first).unwrap(), "/data/a.txt");
+        assert_eq!(
+            fs::read_to_string(&second).unwrap(),
+            "/data/b.txt\r\n/data/c.txt"
+        );
+        assert_ne!(temp_list_path("7zip_list"), temp_list_path("7zip_list"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_password_file_reaches_seven_zip_but_not_the_log() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        // Stand-in for 7-Zip that records its arguments
+        let args_file = temp_dir.path().join("args.txt");
+        let runner = temp_dir.path().join("fake7z");
+        fs::write(
+            &runner,
+            format!("#!/bin/sh\necho \"$@\" > '{}'\n", args_file.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&runner, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let password_file = temp_dir.path().join("password.txt");
+        fs::write(&password_file, "open sesame\n").unwrap();
+        let password = Password::from_file(&password_file.to_string_lossy()).unwrap();
+
+        let log = temp_dir.path().join("7z.log");
+        let archiver = SevenZipArchiver::with_path(runner.to_string_lossy().to_string())
+            .with_password(Some(password))
+            .with_log(&log);
+        let output = temp_dir.path().join("out.7z");
+        archiver
+            .create_archive(&["file.txt".to_string()], &output.to_string_lossy())
+            .await
+            .unwrap();
+        fs::write(&output, "").unwrap();
+        archiver
+            .add_to_archive(&["other.txt".to_string()], &output.to_string_lossy())
+            .await
+            .unwrap();
+
+        // The last invocation, the update, got the password as well
+        let args = fs::read_to_string(&args_file).unwrap();
+        assert!(args.starts_with("u "));
+        assert!(args.contains("-popen sesame -mhe=on"));
+
+        let log = fs::read_to_string(&log).unwrap();
+        assert_eq!(log.matches("-p***").count(), 2);
+        assert!(!log.contains("sesame"));
+    }
+
+    #[test]
+    fn test_reproducible_list_is_sorted() {
+        let paths = vec!["/b/2.txt".to_string(), "/a/1.txt".to_string()];
+
+        let archiver = SevenZipArchiver::new().with_reproducible(true);
+        assert_eq!(
+            archiver.list_file_content(&paths).unwrap(),
+            "/a/1.txt\r\n/b/2.txt"
+        );
+
+        let archiver = SevenZipArchiver::new();
+        assert_eq!(
+            archiver.list_file_content(&paths).unwrap(),
+            "/b/2.txt\r\n/a/1.txt"
+        );
+
+        // A line break would split the path in two
+        let broken = vec!["/a/line\nbreak.txt".to_string()];
+        let error = archiver.list_file_content(&broken).unwrap_err();
+        assert_eq!(error.category(), "config");
+    }
+
     #[tokio::test]
     async fn test_create_archive_with_mock_files() {
         let archiver = SevenZipArchiver::new();
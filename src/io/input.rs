@@ -1,6 +1,6 @@
-use crate::core::{Result, ErrorContext};
+use crate::core::{ArchtreeError, ErrorContext, Result};
 use async_trait::async_trait;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Read};
 
 /// Trait for reading input paths
 #[async_trait]
@@ -9,12 +9,48 @@ pub trait InputReader: Send + Sync {
     async fn read_paths(&self) -> Result<Vec<String>>;
 }
 
+/// Split NUL-delimited input like `find -print0` output into paths.
+///
+/// Unlike lines, the paths are not trimmed, since names may start or end with spaces
+/// and contain newlines; only empty entries are skipped.
+fn split_null_delimited(content: &str) -> Vec<String> {
+    content
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Path on one line of a path list, or `None` for blank lines and `#` comments.
+///
+/// Lines are trimmed; a leading `\\#` stands for a path starting with a literal `#`.
+fn parse_line(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return None;
+    }
+    Some(match trimmed.strip_prefix("\\#") {
+        Some(rest) => format!("#{}", rest),
+        None => trimmed.to_string(),
+    })
+}
+
 /// Reader that reads from standard input
-pub struct StdinReader;
+pub struct StdinReader {
+    null_delimited: bool,
+}
 
 impl StdinReader {
     pub fn new() -> Self {
-        Self
+        Self {
+            null_delimited: false,
+        }
+    }
+
+    /// Split the input on NUL characters instead of lines, for paths containing newlines
+    pub fn with_null_delimiter(mut self, null_delimited: bool) -> Self {
+        self.null_delimited = null_delimited;
+        self
     }
 }
 
@@ -28,14 +64,22 @@ impl Default for StdinReader {
 impl InputReader for StdinReader {
     async fn read_paths(&self) -> Result<Vec<String>> {
         let stdin = io::stdin();
+        if self.null_delimited {
+            let mut content = Vec::new();
+            stdin
+                .lock()
+                .read_to_end(&mut content)
+                .context_io("Failed to read stdin")?;
+            let content = String::from_utf8(content)
+                .map_err(|_| ArchtreeError::config("Input paths on stdin are not valid UTF-8"))?;
+            return Ok(split_null_delimited(&content));
+        }
+
         let mut paths = Vec::new();
 
         for line in stdin.lock().lines() {
             let line = line.context_io("Failed to read line from stdin")?;
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                paths.push(trimmed.to_string());
-            }
+            paths.extend(parse_line(&line));
         }
 
         Ok(paths)
@@ -45,14 +89,22 @@ impl InputReader for StdinReader {
 /// Reader that reads from a file
 pub struct FileReader {
     file_path: String,
+    null_delimited: bool,
 }
 
 impl FileReader {
     pub fn new(file_path: &str) -> Self {
         Self {
             file_path: file_path.to_string(),
+            null_delimited: false,
         }
     }
+
+    /// Split the file on NUL characters instead of lines, for paths containing newlines
+    pub fn with_null_delimiter(mut self, null_delimited: bool) -> Self {
+        self.null_delimited = null_delimited;
+        self
+    }
 }
 
 #[async_trait]
@@ -61,13 +113,32 @@ impl InputReader for FileReader {
         let content = tokio::fs::read_to_string(&self.file_path)
             .await
             .context_io(format!("Failed to read file: {}", self.file_path))?;
+        if self.null_delimited {
+            return Ok(split_null_delimited(&content));
+        }
 
-        let paths = content
-            .lines()
-            .map(|line| line.trim().to_string())
-            .filter(|line| !line.is_empty())
-            .collect();
+        Ok(content.lines().filter_map(parse_line).collect())
+    }
+}
+
+/// Reader concatenating the paths of several readers, in order
+pub struct ChainedReader {
+    readers: Vec<Box<dyn InputReader>>,
+}
+
+impl ChainedReader {
+    pub fn new(readers: Vec<Box<dyn InputReader>>) -> Self {
+        Self { readers }
+    }
+}
 
+#[async_trait]
+impl InputReader for ChainedReader {
+    async fn read_paths(&self) -> Result<Vec<String>> {
+        let mut paths = Vec::new();
+        for reader in &self.readers {
+            paths.extend(reader.read_paths().await?);
+        }
         Ok(paths)
     }
 }
@@ -101,7 +172,7 @@ mod tests {
         let mut temp_file = NamedTempFile::new().unwrap();
         writeln!(temp_file, "C:\\path\\one").unwrap();
         writeln!(temp_file, "C:\\path\\two").unwrap();
-        writeln!(temp_file, "").unwrap(); // Empty line should be filtered
+        writeln!(temp_file).unwrap(); // Empty line should be filtered
         writeln!(temp_file, "  C:\\path\\three  ").unwrap(); // Should be trimmed
 
         let reader = FileReader::new(&temp_file.path().to_string_lossy());
@@ -113,6 +184,64 @@ mod tests {
         assert_eq!(paths[2], "C:\\path\\three");
     }
 
+    #[tokio::test]
+    async fn test_file_reader_skips_comments() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "# backup configs").unwrap();
+        writeln!(temp_file, "/etc/nginx").unwrap();
+        writeln!(temp_file).unwrap();
+        writeln!(temp_file, "   # indented comment").unwrap();
+        writeln!(temp_file, "/home/me/notes#1.txt").unwrap();
+        writeln!(temp_file, "\\#hashtag dir").unwrap();
+        writeln!(temp_file, "  ").unwrap();
+        writeln!(temp_file, "/var/log").unwrap();
+
+        let reader = FileReader::new(&temp_file.path().to_string_lossy());
+        assert_eq!(
+            reader.read_paths().await.unwrap(),
+            vec![
+                "/etc/nginx",
+                "/home/me/notes#1.txt",
+                "#hashtag dir",
+                "/var/log"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_reader_with_null_delimiter() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"a\0b\0c").unwrap();
+
+        let reader = FileReader::new(&temp_file.path().to_string_lossy()).with_null_delimiter(true);
+        assert_eq!(reader.read_paths().await.unwrap(), vec!["a", "b", "c"]);
+
+        // Newlines and surrounding spaces belong to the names
+        assert_eq!(
+            split_null_delimited("line\nbreak.txt\0 spaced \0\0"),
+            vec!["line\nbreak.txt", " spaced "]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chained_reader_keeps_order_and_duplicates() {
+        let mut system = NamedTempFile::new().unwrap();
+        writeln!(system, "/etc\n/var/log").unwrap();
+        let mut projects = NamedTempFile::new().unwrap();
+        writeln!(projects, "/home/me/src\n/etc").unwrap();
+
+        let reader = ChainedReader::new(vec![
+            Box::new(FileReader::new(&system.path().to_string_lossy())),
+            Box::new(FileReader::new(&projects.path().to_string_lossy())),
+        ]);
+
+        // Duplicates are left for the path processor to drop
+        assert_eq!(
+            reader.read_paths().await.unwrap(),
+            vec!["/etc", "/var/log", "/home/me/src", "/etc"]
+        );
+    }
+
     #[tokio::test]
     async fn test_vec_reader() {
         let input_paths = vec![
@@ -0,0 +1,51 @@
+use crate::core::{ErrorContext, Result};
+use std::path::Path;
+use std::process::Output;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Append the raw stdout and stderr of a 7-Zip invocation to `log_path`.
+///
+/// Each invocation gets a header with the time, the command line and the exit status.
+/// A password switch is logged as `-p***`.
+pub async fn append_invocation_log(
+    log_path: &Path,
+    command: &Command,
+    output: &Output,
+) -> Result<()> {
+    let command = command.as_std();
+    let mut line = vec![command.get_program().to_string_lossy().to_string()];
+    line.extend(
+        command
+            .get_args()
+            .map(|arg| redact_password(&arg.to_string_lossy())),
+    );
+
+    let entry = format!(
+        "==== {} | {} | {} ====\n--- stdout ---\n{}\n--- stderr ---\n{}\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        line.join(" "),
+        output.status,
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+        String::from_utf8_lossy(&output.stderr).trim_end()
+    );
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .await
+        .context_io(format!("Failed to open 7-Zip log: {}", log_path.display()))?;
+    file.write_all(entry.as_bytes())
+        .await
+        .context_io(format!("Failed to write 7-Zip log: {}", log_path.display()))
+}
+
+/// Hide the value of a `-p<password>` switch
+fn redact_password(arg: &str) -> String {
+    if arg.starts_with("-p") && arg.len() > 2 {
+        "-p***".to_string()
+    } else {
+        arg.to_string()
+    }
+}
@@ -0,0 +1,121 @@
+/// One mebibyte in bytes
+const MIB: u64 = 1024 * 1024;
+
+/// Largest dictionary considered (the one 7-Zip uses for `-mx9`)
+const MAX_DICTIONARY: u64 = 64 * MIB;
+
+/// Smallest dictionary 7-Zip accepts for LZMA2
+const MIN_DICTIONARY: u64 = 64 * 1024;
+
+/// Dictionary 7-Zip uses at its default compression level (`-mx5`)
+pub const DEFAULT_DICTIONARY: u64 = 16 * MIB;
+
+/// Below this dictionary size, threads are traded away for a larger dictionary
+const PREFERRED_MIN_DICTIONARY: u64 = MIB;
+
+/// Dictionary and thread settings that keep 7-Zip's LZMA2 encoder under a memory cap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemorySettings {
+    /// Dictionary size in bytes (`-md`)
+    pub dictionary_size: u64,
+    /// Number of compression threads (`-mmt`)
+    pub threads: usize,
+}
+
+impl MemorySettings {
+    /// Derive the largest settings whose estimated memory use fits within `limit` bytes.
+    ///
+    /// The thread count is lowered before the dictionary drops under 1 MiB. Returns
+    /// `None` if even a single thread with the smallest dictionary doesn't fit.
+    pub fn for_limit(limit: u64, max_threads: usize) -> Option<Self> {
+        for threads in (2..=max_threads.max(1)).rev() {
+            if let Some(dictionary_size) = largest_dictionary_for(limit, threads)
+                && dictionary_size >= PREFERRED_MIN_DICTIONARY
+            {
+                return Some(Self {
+                    dictionary_size,
+                    threads,
+                });
+            }
+        }
+
+        // A single thread leaves the most room for the dictionary
+        largest_dictionary_for(limit, 1).map(|dictionary_size| Self {
+            dictionary_size,
+            threads: 1,
+        })
+    }
+
+    /// Estimated peak memory use of the encoder in bytes
+    pub fn estimated_memory(&self) -> u64 {
+        lzma2_compression_memory(self.dictionary_size, self.threads)
+    }
+
+    /// Whether the dictionary is smaller than the one of the default compression level
+    pub fn reduces_compression(&self) -> bool {
+        self.dictionary_size < DEFAULT_DICTIONARY
+    }
+
+    /// Command line switches for 7-Zip
+    pub fn to_args(&self) -> Vec<String> {
+        let dictionary = if self.dictionary_size.is_multiple_of(MIB) {
+            format!("{}m", self.dictionary_size / MIB)
+        } else {
+            format!("{}k", self.dictionary_size / 1024)
+        };
+        vec![
+            format!("-md={}", dictionary),
+            format!("-mmt={}", self.threads),
+        ]
+    }
+}
+
+/// Estimated memory used by 7-Zip's LZMA2 encoder with the BT4 match finder.
+///
+/// Each LZMA encoder needs about 11.5 bytes per dictionary byte for the window and
+/// hash chains, plus a 4x dictionary input block and about 6 MiB of fixed buffers.
+/// One encoder runs per two threads.
+pub fn lzma2_compression_memory(dictionary_size: u64, threads: usize) -> u64 {
+    let encoders = (threads.max(1) as u64).div_ceil(2);
+    let per_encoder = dictionary_size * 23 / 2 + dictionary_size * 4 + 6 * MIB;
+    encoders * per_encoder
+}
+
+fn largest_dictionary_for(limit: u64, threads: usize) -> Option<u64> {
+    let mut dictionary_size = MAX_DICTIONARY;
+    while dictionary_size >= MIN_DICTIONARY {
+        if lzma2_compression_memory(dictionary_size, threads) <= limit {
+            return Some(dictionary_size);
+        }
+        dictionary_size /= 2;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args_for_memory_cap() {
+        // 512 MiB fits two encoders (four threads) with a 16 MiB dictionary
+        let settings = MemorySettings::for_limit(512 * MIB, 4).unwrap();
+        assert_eq!(settings.to_args(), vec!["-md=16m", "-mmt=4"]);
+        assert!(settings.estimated_memory() <= 512 * MIB);
+        assert!(!settings.reduces_compression());
+
+        // 64 MiB on a single thread only leaves room for a 2 MiB dictionary
+        let settings = MemorySettings::for_limit(64 * MIB, 1).unwrap();
+        assert_eq!(settings.to_args(), vec!["-md=2m", "-mmt=1"]);
+        assert!(settings.reduces_compression());
+
+        // Generous caps are bounded by the largest dictionary
+        let settings = MemorySettings::for_limit(16 * 1024 * MIB, 2).unwrap();
+        assert_eq!(settings.to_args(), vec!["-md=64m", "-mmt=2"]);
+    }
+
+    #[test]
+    fn test_memory_cap_too_small() {
+        assert_eq!(MemorySettings::for_limit(MIB, 8), None);
+    }
+}
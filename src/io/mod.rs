@@ -1,5 +1,16 @@
 pub mod archiver;
 pub mod input;
+pub mod log;
+pub mod memory;
+pub mod space;
+pub mod table;
+pub mod volumes;
 
-pub use archiver::{Archiver, SevenZipArchiver};
-pub use input::{FileReader, InputReader, StdinReader, VecReader};
+pub use archiver::{
+    Archiver, AtomicArchiver, ChunkedArchiver, SevenZipArchiver, StoreRoutingArchiver,
+    StoreThresholds,
+};
+pub use input::{ChainedReader, FileReader, InputReader, StdinReader, VecReader};
+pub use memory::MemorySettings;
+pub use space::{FreeSpaceProvider, SystemFreeSpace};
+pub use table::{SummaryTable, without_emoji};
@@ -0,0 +1,62 @@
+use crate::core::{ArchtreeError, ErrorContext, Result};
+use std::path::{Path, PathBuf};
+
+/// Source of the free space on a volume, replaceable in tests
+pub trait FreeSpaceProvider: Send + Sync {
+    /// Bytes available to this user on the volume holding `path`
+    fn available_space(&self, path: &Path) -> std::io::Result<u64>;
+}
+
+/// Provider asking the operating system
+pub struct SystemFreeSpace;
+
+impl FreeSpaceProvider for SystemFreeSpace {
+    fn available_space(&self, path: &Path) -> std::io::Result<u64> {
+        fs4::available_space(path)
+    }
+}
+
+/// Closest existing directory the archive at `output_path` will be written to
+fn output_directory(output_path: &str) -> PathBuf {
+    let mut dir = Path::new(output_path)
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    while !dir.exists() {
+        match dir.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => dir = parent.to_path_buf(),
+            _ => return PathBuf::from("."),
+        }
+    }
+    dir
+}
+
+/// Fail unless the volume the archive is written to has at least `required` bytes available.
+///
+/// `reason` tells where the requirement comes from, for the error message.
+pub fn check_free_space(
+    provider: &dyn FreeSpaceProvider,
+    output_path: &str,
+    required: u64,
+    reason: &str,
+) -> Result<()> {
+    if required == 0 {
+        return Ok(());
+    }
+    let dir = output_directory(output_path);
+    let available = provider
+        .available_space(&dir)
+        .context_io(format!("Failed to read free space of {}", dir.display()))?;
+    if available < required {
+        return Err(ArchtreeError::io(format!(
+            "Not enough free space for {}: {} bytes available in {}, {} bytes needed ({})",
+            output_path,
+            available,
+            dir.display(),
+            required,
+            reason
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use std::io::{self, Write};
+
+/// Fixed-width summary table with left-aligned labels and right-aligned counts
+#[derive(Debug, Clone)]
+pub struct SummaryTable {
+    title: String,
+    rows: Vec<(String, usize)>,
+}
+
+impl SummaryTable {
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        Self {
+            title: title.into(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row to the table
+    pub fn row<S: Into<String>>(mut self, label: S, count: usize) -> Self {
+        self.rows.push((label.into(), count));
+        self
+    }
+
+    /// Render the table into the given writer
+    pub fn render(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let label_width = self
+            .rows
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let count_width = self
+            .rows
+            .iter()
+            .map(|(_, count)| count.to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        // Widen the label column if the title would not fit otherwise
+        let inner_width = label_width + count_width + 3;
+        let title_width = self.title.chars().count();
+        let label_width = if title_width > inner_width {
+            label_width + (title_width - inner_width)
+        } else {
+            label_width
+        };
+        let inner_width = label_width + count_width + 3;
+
+        let border = format!(
+            "+-{}-+-{}-+",
+            "-".repeat(label_width),
+            "-".repeat(count_width)
+        );
+
+        writeln!(writer, "{}", border)?;
+        writeln!(writer, "| {:<width$} |", self.title, width = inner_width)?;
+        writeln!(writer, "{}", border)?;
+        for (label, count) in &self.rows {
+            writeln!(
+                writer,
+                "| {:<label_width$} | {:>count_width$} |",
+                label,
+                count,
+                label_width = label_width,
+                count_width = count_width
+            )?;
+        }
+        writeln!(writer, "{}", border)
+    }
+
+    /// Render the table to standard error
+    pub fn print(&self) {
+        let _ = self.render(&mut io::stderr());
+    }
+}
+
+/// `line` without the emoji it starts with, for --no-emoji; its indentation is kept
+pub fn without_emoji(line: &str) -> String {
+    let text = line.trim_start();
+    let indent = &line[..line.len() - text.len()];
+    let rest = text.trim_start_matches(is_emoji);
+    if rest.len() == text.len() {
+        return line.to_string();
+    }
+    format!("{}{}", indent, rest.trim_start())
+}
+
+/// Whether `c` is one of the symbols the summaries start their lines with
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x2139 | 0x200D | 0xFE0F | 0x2190..=0x2BFF | 0x1F000..=0x1FAFF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_alignment() {
+        let table = SummaryTable::new("Summary")
+            .row("Added", 1234)
+            .row("Excluded", 5)
+            .row("Total", 1239);
+
+        let mut output = Vec::new();
+        table.render(&mut output).unwrap();
+        let rendered = String::from_utf8(output).unwrap();
+
+        let expected = "\
++----------+------+
+| Summary         |
++----------+------+
+| Added    | 1234 |
+| Excluded |    5 |
+| Total    | 1239 |
++----------+------+
+";
+        assert_eq!(rendered, expected);
+
+        // Every line has the same width
+        let widths: Vec<usize> = rendered.lines().map(|l| l.chars().count()).collect();
+        assert!(widths.iter().all(|&w| w == widths[0]));
+    }
+
+    #[test]
+    fn test_table_widens_for_long_title() {
+        let table = SummaryTable::new("Verification Results").row("Missing", 0);
+
+        let mut output = Vec::new();
+        table.render(&mut output).unwrap();
+        let rendered = String::from_utf8(output).unwrap();
+
+        let widths: Vec<usize> = rendered.lines().map(|l| l.chars().count()).collect();
+        assert!(widths.iter().all(|&w| w == widths[0]));
+        assert!(rendered.contains("| Verification Results |"));
+    }
+
+    #[test]
+    fn test_without_emoji() {
+        assert_eq!(
+            without_emoji("\n📊 Processing Summary:"),
+            "\nProcessing Summary:"
+        );
+        assert_eq!(without_emoji("  ✓ Added: 3 files"), "  Added: 3 files");
+        assert_eq!(
+            without_emoji("  ⚠️  Invalid: 1 paths"),
+            "  Invalid: 1 paths"
+        );
+        assert_eq!(
+            without_emoji("  ⏭️  Skipped: 2 files"),
+            "  Skipped: 2 files"
+        );
+        assert_eq!(without_emoji("ℹ️  Expected missing"), "Expected missing");
+        // Only a leading emoji goes; file names keep theirs and their accents
+        assert_eq!(without_emoji("    - café 🎉.txt"), "    - café 🎉.txt");
+    }
+}
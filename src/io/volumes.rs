@@ -0,0 +1,73 @@
+use std::path::Path;
+
+/// Path of the first volume 7-Zip writes for a split archive: `<archive>.001`
+pub fn first_volume(archive_path: &str) -> String {
+    format!("{}.001", archive_path)
+}
+
+/// Path to hand to 7-Zip for reading an archive that may have been split into volumes.
+///
+/// An existing file is used as it is, so `backup.7z.001` can be passed directly. When
+/// `backup.7z` doesn't exist but `backup.7z.001` does, the first volume is used and
+/// 7-Zip finds the others next to it.
+pub fn resolve_archive_path(archive_path: &str) -> String {
+    let first = first_volume(archive_path);
+    if !Path::new(archive_path).exists() && Path::new(&first).exists() {
+        first
+    } else {
+        archive_path.to_string()
+    }
+}
+
+/// Size of an archive in bytes, adding up `<archive>.001`, `.002`, ... if it was split.
+///
+/// `None` if neither the archive nor its first volume exists.
+pub fn archive_size(archive_path: &str) -> Option<u64> {
+    if let Ok(metadata) = std::fs::metadata(archive_path) {
+        return Some(metadata.len());
+    }
+
+    let mut total = None;
+    for number in 1.. {
+        let volume = format!("{}.{:03}", archive_path, number);
+        let Ok(metadata) = std::fs::metadata(&volume) else {
+            break;
+        };
+        *total.get_or_insert(0) += metadata.len();
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_split_archive_is_found_by_its_volumes() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir
+            .path()
+            .join("backup.7z")
+            .to_string_lossy()
+            .to_string();
+
+        assert_eq!(resolve_archive_path(&archive), archive);
+        assert_eq!(archive_size(&archive), None);
+
+        fs::write(first_volume(&archive), vec![0u8; 100]).unwrap();
+        fs::write(format!("{}.002", archive), vec![0u8; 30]).unwrap();
+        assert_eq!(resolve_archive_path(&archive), first_volume(&archive));
+        assert_eq!(
+            resolve_archive_path(&first_volume(&archive)),
+            first_volume(&archive)
+        );
+        assert_eq!(archive_size(&archive), Some(130));
+
+        // A whole archive wins over leftover volumes
+        fs::write(&archive, vec![0u8; 10]).unwrap();
+        assert_eq!(resolve_archive_path(&archive), archive);
+        assert_eq!(archive_size(&archive), Some(10));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod core;
+pub mod io;
+pub mod processing;
+pub mod services;
+#[cfg(test)]
+pub(crate) mod testing;
+pub mod verification;
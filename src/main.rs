@@ -1,15 +1,31 @@
-mod core;
-mod io;
-mod processing;
-mod services;
-mod verification;
-
+use archtree::core::config::{parse_duration, parse_size, parse_volume_size};
+use archtree::core::{
+    ArchiveFormat, ArchtreeError, Config, EXIT_CANCELLED, JobFile, PLATFORM_CASE_SENSITIVE,
+    Password, ProgressStyle, Result,
+};
+use archtree::io::memory::DEFAULT_DICTIONARY;
+use archtree::io::{
+    self, AtomicArchiver, ChunkedArchiver, FileReader, MemorySettings, SevenZipArchiver,
+    StdinReader, StoreRoutingArchiver, StoreThresholds,
+};
+use archtree::processing::content::DEFAULT_COMPRESSION_RATIO;
+use archtree::processing::validation::FileSystemValidator;
+use archtree::processing::{ContentType, PathProcessor};
+use archtree::services::chain::ChainListing;
+use archtree::services::{
+    BackupChain, BackupService, ConsoleBackupCallback, ResultLine, RunReport,
+};
+use archtree::verification::verifier::{ArchiveVerifier, ExpandedVerifier};
+use archtree::verification::{
+    self, ArchiveVerificationReport, ConsoleCallback, JsonCallback, ReportFormat,
+    VerificationAndRetryService, VerificationCallback, VerificationMode,
+};
+use chrono::Local;
 use clap::{Parser, Subcommand};
-use core::{ArchtreeError, Config, Result};
-use io::{FileReader, SevenZipArchiver, StdinReader};
-use processing::validation::FileSystemValidator;
-use services::BackupService;
-use verification::{ConsoleCallback, VerificationAndRetryService, VerificationMode};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 #[derive(Parser)]
 #[command(
@@ -18,6 +34,14 @@ use verification::{ConsoleCallback, VerificationAndRetryService, VerificationMod
     version = "0.2.1"
 )]
 struct Args {
+    /// Report errors as a JSON object on stderr instead of a message
+    #[arg(long = "json", global = true)]
+    json: bool,
+
+    /// Print one `ARCHTREE_RESULT key=value ...` line on stderr when the command ends
+    #[arg(long = "result-line", global = true)]
+    result_line: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -25,130 +49,1018 @@ struct Args {
 #[derive(Subcommand)]
 enum Commands {
     /// Create a backup archive from input paths
-    Backup {
-        /// Input file containing paths to backup (reads from stdin if not provided)
-        #[arg(short = 'f', long = "file")]
-        input_file: Option<String>,
-
-        /// Output archive path
-        #[arg(short = 'o', long = "output", required = true)]
-        output: String,
-
-        /// Path to 7-Zip executable
-        #[arg(long = "7zip-path")]
-        seven_zip_path: Option<String>,
-
-        /// Disable progress output
-        #[arg(short = 'q', long = "quiet")]
-        quiet: bool,
-
-        /// Verify archive contents after creation
-        #[arg(short = 'v', long = "verify")]
-        verify: bool,
-
-        /// Retry missing files (requires --verify)
-        #[arg(short = 'r', long = "retry")]
-        retry: bool,
-    },
+    Backup(BackupArgs),
     /// Verify an existing archive against input paths
-    Verify {
-        /// Archive file to verify
-        #[arg(short = 'a', long = "archive", required = true)]
-        archive: String,
-
-        /// Input file containing expected paths (reads from stdin if not provided)
-        #[arg(short = 'f', long = "file")]
-        input_file: Option<String>,
-
-        /// Path to 7-Zip executable
-        #[arg(long = "7zip-path")]
-        seven_zip_path: Option<String>,
-
-        /// Disable progress output
-        #[arg(short = 'q', long = "quiet")]
-        quiet: bool,
-
-        /// Retry missing files by updating the archive
-        #[arg(short = 'r', long = "retry")]
-        retry: bool,
-
-        /// Check if archived files are up to date with filesystem versions
-        #[arg(long = "check-freshness")]
-        check_freshness: bool,
-
-        /// Update outdated files in the archive (requires --check-freshness)
-        /// This will automatically replace files in the archive with newer versions from the filesystem
-        #[arg(long = "update-outdated")]
-        update_outdated: bool,
-    },
+    Verify(VerifyArgs),
+    /// Run a named backup job from a TOML or YAML job file
+    Run(RunArgs),
+    /// Show per-file compression statistics of an archive
+    Stats(StatsArgs),
+}
+
+#[derive(clap::Args, Default)]
+struct BackupArgs {
+    /// Input file containing paths to backup; repeat to combine several lists (reads from stdin if not provided)
+    #[arg(short = 'f', long = "file", action = clap::ArgAction::Append)]
+    input_file: Vec<String>,
+
+    /// Input paths are separated by NUL characters instead of newlines (like `find -print0`)
+    #[arg(short = '0', long = "null")]
+    null: bool,
+
+    /// Output archive path (defaults to `<dir>.7z` when the only input is a directory)
+    #[arg(short = 'o', long = "output", conflicts_with = "chain_dir")]
+    output: Option<String>,
+
+    /// Write to an incremental chain in this directory: full.7z first, then
+    /// inc-YYYYMMDD.7z archives holding only files changed since the chain's last member
+    #[arg(long = "chain-dir", value_name = "DIR", conflicts_with = "verify")]
+    chain_dir: Option<String>,
+
+    /// Archive the paths of a prepared 7-Zip list file as-is, skipping all path
+    /// processing (exclusions, directory walking, filters)
+    #[arg(
+        long = "raw-list",
+        value_name = "FILE",
+        requires = "output",
+        conflicts_with_all = ["input_file", "null", "chain_dir", "estimate", "compare_to_previous"]
+    )]
+    raw_list: Option<String>,
+
+    /// Archive format: 7z, zip or tar (default: from the output extension, else 7z)
+    #[arg(long = "format", value_name = "FORMAT")]
+    format: Option<ArchiveFormat>,
+
+    /// Path to 7-Zip executable
+    #[arg(long = "7zip-path")]
+    seven_zip_path: Option<String>,
+
+    /// Append the raw stdout and stderr of every 7-Zip invocation to this file
+    #[arg(long = "seven-zip-log", value_name = "PATH")]
+    seven_zip_log: Option<String>,
+
+    /// Disable progress output
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Verify archive contents after creation
+    #[arg(short = 'v', long = "verify")]
+    verify: bool,
+
+    /// Retry missing files (requires --verify)
+    #[arg(short = 'r', long = "retry")]
+    retry: bool,
+
+    /// Render summaries as an aligned table
+    #[arg(long = "table")]
+    table: bool,
+
+    /// Print summaries without the emoji that start their lines
+    #[arg(long = "no-emoji")]
+    no_emoji: bool,
+
+    /// How progress is shown while paths are processed: a line per excluded or skipped
+    /// path, a single counter updated in place, or none
+    #[arg(long = "progress-style", value_name = "STYLE", default_value = "line")]
+    progress_style: ProgressStyle,
+
+    /// Don't count the files first to show an added/total counter with the counter progress
+    /// style, which saves walking very large trees twice
+    #[arg(long = "no-precount")]
+    no_precount: bool,
+
+    /// Skip hidden (dot-prefixed) files and directories found inside input directories
+    #[arg(long = "skip-hidden")]
+    skip_hidden: bool,
+
+    /// Keep explicitly named hidden inputs when --skip-hidden is set (the default)
+    #[arg(
+        long = "include-hidden-roots",
+        overrides_with = "no_include_hidden_roots"
+    )]
+    include_hidden_roots: bool,
+
+    /// Skip explicitly named hidden inputs too when --skip-hidden is set
+    #[arg(
+        long = "no-include-hidden-roots",
+        overrides_with = "include_hidden_roots"
+    )]
+    no_include_hidden_roots: bool,
+
+    /// Skip files matched by .gitignore files found in input directories
+    #[arg(long = "respect-gitignore")]
+    respect_gitignore: bool,
+
+    /// Resolve `.`, `..` and symlinks so each file is archived once, under its canonical path
+    #[arg(long = "canonicalize")]
+    canonicalize: bool,
+
+    /// Descend into symlinks to directories found in input directories; without it they
+    /// are skipped, while symlinks to files are always archived
+    #[arg(long = "follow-symlinks")]
+    follow_symlinks: bool,
+
+    /// Only archive files at most N levels below each input directory (1 = files directly
+    /// inside); a `depth:N:` prefix on an input takes precedence
+    #[arg(long = "max-depth", value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Skip files in input directories smaller than SIZE (e.g. 1k)
+    #[arg(long = "min-file-size", value_name = "SIZE", value_parser = parse_size)]
+    min_file_size: Option<u64>,
+
+    /// Skip files in input directories larger than SIZE (e.g. 500m)
+    #[arg(long = "max-file-size", value_name = "SIZE", value_parser = parse_size)]
+    max_file_size: Option<u64>,
+
+    /// Walk each input directory with N threads, which speeds up huge trees; its files are
+    /// then archived sorted by path
+    #[arg(long = "jobs", value_name = "N", default_value_t = 1)]
+    jobs: usize,
+
+    /// Hand input directories to 7-Zip whole, keeping their exact structure and empty
+    /// subdirectories; exclusions and filters then only apply to the input paths themselves
+    #[arg(
+        long = "archive-dirs-whole",
+        conflicts_with_all = [
+            "skip_hidden", "respect_gitignore", "exclude_type", "include", "accessed_before", "modified_within",
+            "dedupe_hardlink", "analyze_content", "store_below", "store_above", "embed_index",
+            "estimate", "chain_dir", "follow_symlinks", "max_depth", "min_file_size",
+            "max_file_size", "jobs"
+        ]
+    )]
+    archive_dirs_whole: bool,
+
+    /// Skip files in input directories whose content is of this type, detected from their first bytes (category such as image or app, or a MIME type; repeatable)
+    #[arg(long = "exclude-type", value_name = "TYPE", value_parser = parse_content_type)]
+    exclude_type: Vec<String>,
+
+    /// Only archive files in input directories matching this wildcard pattern, e.g. `*.rs`
+    /// (repeatable; exclusions still apply on top)
+    #[arg(long = "include", value_name = "PATTERN")]
+    include: Vec<String>,
+
+    /// Exclude paths matching this regular expression, e.g. '\.(tmp|bak|swp)$'
+    /// (repeatable; combines with `!` wildcard exclusions)
+    #[arg(long = "exclude-regex", value_name = "PATTERN", action = clap::ArgAction::Append)]
+    exclude_regex: Vec<String>,
+
+    /// Deprecated: use `--case-sensitive-exclude true`
+    #[arg(long = "case-sensitive", conflicts_with = "case_sensitive_exclude")]
+    case_sensitive: bool,
+
+    /// Whether exclusion patterns match case-sensitively, overriding the platform default
+    /// (sensitive on Linux, insensitive on Windows and macOS)
+    #[arg(long = "case-sensitive-exclude", value_name = "BOOL")]
+    case_sensitive_exclude: Option<bool>,
+
+    /// Report the N input directories that took longest to scan (default: 5)
+    #[arg(long = "profile", value_name = "N", num_args = 0..=1, default_missing_value = "5")]
+    profile: Option<usize>,
+
+    /// Only archive files in input directories not accessed within this time (e.g. 30d, 12h)
+    #[arg(long = "accessed-before", value_name = "DURATION", value_parser = parse_duration)]
+    accessed_before: Option<Duration>,
+
+    /// Only archive files in input directories modified within this time (e.g. 7d, 12h)
+    #[arg(long = "modified-within", value_name = "DURATION", value_parser = parse_duration)]
+    modified_within: Option<Duration>,
+
+    /// Archive only one path per hard-linked file and list the others in a hardlinks.txt (Unix only)
+    #[arg(long = "dedupe-hardlink", conflicts_with = "volume_size")]
+    dedupe_hardlink: bool,
+
+    /// Write the final summary as JSON to this file
+    #[arg(long = "summary-json-file", value_name = "PATH")]
+    summary_json_file: Option<String>,
+
+    /// Sample file headers and report how much of the data is already compressed
+    #[arg(long = "analyze-content")]
+    analyze_content: bool,
+
+    /// Keep 7-Zip's estimated memory use under this size (e.g. 512M, 2G)
+    #[arg(long = "max-memory", value_name = "SIZE", value_parser = parse_size)]
+    max_memory: Option<u64>,
+
+    /// Use fixed 7-Zip settings and sorted entries so identical inputs give identical archives
+    #[arg(long = "reproducible")]
+    reproducible: bool,
+
+    /// Hand at most N paths to each 7-Zip invocation, adding later chunks to the same archive
+    #[arg(long = "chunk-size", value_name = "N")]
+    chunk_size: Option<usize>,
+
+    /// Store files smaller than SIZE without compression (e.g. 4K)
+    #[arg(long = "store-below", value_name = "SIZE", value_parser = parse_size)]
+    store_below: Option<u64>,
+
+    /// Store files larger than SIZE without compression (e.g. 1G)
+    #[arg(long = "store-above", value_name = "SIZE", value_parser = parse_size)]
+    store_above: Option<u64>,
+
+    /// Print the path of every archived file to stdout, one per line, once the archive is created
+    #[arg(long = "print-added")]
+    print_added: bool,
+
+    /// Fail if no files are left after expanding inputs and applying exclusions
+    #[arg(long = "fail-on-empty")]
+    fail_on_empty: bool,
+
+    /// Fail instead of skipping files that vanish between listing and archiving
+    #[arg(long = "fail-on-vanished")]
+    fail_on_vanished: bool,
+
+    /// Compression level from 0 (store) to 9 (ultra), passed to 7-Zip as -mx<LEVEL>;
+    /// 7-Zip's default when not given
+    #[arg(short = 'm', long = "level", value_name = "LEVEL")]
+    level: Option<u8>,
+
+    /// Number of threads 7-Zip compresses with, passed as -mmt<N> (default: one per core)
+    #[arg(long = "threads", value_name = "N")]
+    threads: Option<u32>,
+
+    /// Split the archive into volumes of SIZE (e.g. 100m, 4g), written as
+    /// `<output>.001`, `<output>.002`, ...
+    #[arg(
+        long = "volume-size",
+        value_name = "SIZE",
+        value_parser = parse_volume_size,
+        conflicts_with_all = ["retry", "chunk_size", "store_below", "store_above", "atomic", "chain_dir"]
+    )]
+    volume_size: Option<u64>,
+
+    /// Skip archiving when no file was added, removed, resized or modified since the last
+    /// backup to the same output, comparing a hash stored in `<output>.tree-hash`
+    #[arg(
+        long = "skip-if-unchanged",
+        conflicts_with_all = ["chain_dir", "estimate", "dry_run", "archive_dirs_whole"]
+    )]
+    skip_if_unchanged: bool,
+
+    /// Abort a --chain-dir incremental whose new and modified files exceed SIZE (e.g. 2g)
+    #[arg(
+        long = "max-incremental-size",
+        value_name = "SIZE",
+        value_parser = parse_size,
+        requires = "chain_dir"
+    )]
+    max_incremental_size: Option<u64>,
+
+    /// Abort before archiving unless SIZE is free on the output volume (e.g. 10g); without
+    /// it, having less free space than the input files add up to is only a warning
+    #[arg(long = "min-free-space", value_name = "SIZE", value_parser = parse_size)]
+    min_free_space: Option<u64>,
+
+    /// Encrypt the archive with this password, file names included for 7z archives.
+    /// Prefer --password-file: command lines end up in shell history and process lists
+    #[arg(
+        short = 'p',
+        long = "password",
+        value_name = "PASSWORD",
+        conflicts_with = "password_file"
+    )]
+    password: Option<String>,
+
+    /// Read the archive password from the first line of this file
+    #[arg(long = "password-file", value_name = "PATH")]
+    password_file: Option<String>,
+
+    /// Add a tree.txt index of the archived files and their sizes to the archive
+    #[arg(long = "embed-index", conflicts_with = "volume_size")]
+    embed_index: bool,
+
+    /// Write the archive to `<output>.tmp` and rename it into place only once it is complete
+    #[arg(long = "atomic")]
+    atomic: bool,
+
+    /// Only estimate the archive size from the input sizes and content, without archiving
+    #[arg(long = "estimate", conflicts_with_all = ["verify", "compare_to_previous", "summary_json_file"])]
+    estimate: bool,
+
+    /// Only process the input paths and print the summary, without running 7-Zip
+    #[arg(
+        long = "dry-run",
+        conflicts_with_all = ["verify", "chain_dir", "raw_list", "estimate", "compare_to_previous"]
+    )]
+    dry_run: bool,
+
+    /// Compression ratio assumed for data that isn't already compressed (0 < RATIO <= 1)
+    #[arg(long = "estimate-ratio", value_name = "RATIO", requires = "estimate", default_value_t = DEFAULT_COMPRESSION_RATIO)]
+    estimate_ratio: f64,
+
+    /// Fail instead of warning about paths that would collide when restored (case-only differences)
+    #[arg(long = "strict")]
+    strict: bool,
+
+    /// After the backup, print the files added, removed and resized since --previous
+    #[arg(long = "compare-to-previous", requires = "previous")]
+    compare_to_previous: bool,
+
+    /// Earlier archive to compare the new one with (see --compare-to-previous)
+    #[arg(
+        long = "previous",
+        value_name = "ARCHIVE",
+        requires = "compare_to_previous"
+    )]
+    previous: Option<String>,
+
+    /// Switches for `7z l` replacing the default `-sccUTF-8` (e.g. "-scsWIN -sccWIN")
+    #[arg(
+        long = "list-args",
+        value_name = "SWITCHES",
+        allow_hyphen_values = true
+    )]
+    list_args: Option<String>,
+
+    /// Locale for `7z l` instead of en_US.UTF-8
+    #[arg(long = "list-locale", value_name = "LOCALE")]
+    list_locale: Option<String>,
+}
+
+#[derive(clap::Args, Default)]
+struct VerifyArgs {
+    /// Archive file to verify (repeat to verify several archives against the same paths)
+    #[arg(
+        short = 'a',
+        long = "archive",
+        required_unless_present_any = ["archive_glob", "checksum_file"]
+    )]
+    archive: Vec<String>,
+
+    /// Check files on disk against an SFV or md5sum/sha*sum checksum file instead of an
+    /// archive; relative paths are resolved against the checksum file's directory
+    #[arg(
+        long = "checksum-file",
+        value_name = "PATH",
+        conflicts_with_all = ["archive", "archive_glob", "retry", "check_freshness", "reverse", "count_only"]
+    )]
+    checksum_file: Option<String>,
+
+    /// Verify the archives matching this file name pattern, newest first (e.g. backup-*.7z)
+    #[arg(long = "archive-glob", value_name = "PATTERN")]
+    archive_glob: Option<String>,
+
+    /// With --archive-glob, only verify the newest matching archive
+    #[arg(long = "latest", requires = "archive_glob")]
+    latest: bool,
+
+    /// Verify up to N archives at the same time
+    #[arg(long = "parallel", value_name = "N", default_value_t = 1)]
+    parallel: usize,
+
+    /// Input file containing expected paths; repeat to combine several lists (reads from stdin if not provided)
+    #[arg(short = 'f', long = "file", action = clap::ArgAction::Append)]
+    input_file: Vec<String>,
+
+    /// Expected paths are separated by NUL characters instead of newlines
+    #[arg(short = '0', long = "null")]
+    null: bool,
+
+    /// Path to 7-Zip executable
+    #[arg(long = "7zip-path")]
+    seven_zip_path: Option<String>,
+
+    /// Append the raw stdout and stderr of every 7-Zip invocation to this file
+    #[arg(long = "seven-zip-log", value_name = "PATH")]
+    seven_zip_log: Option<String>,
+
+    /// Password of encrypted archives, needed to list those with encrypted file names
+    #[arg(
+        short = 'p',
+        long = "password",
+        value_name = "PASSWORD",
+        conflicts_with = "password_file"
+    )]
+    password: Option<String>,
+
+    /// Read the archive password from the first line of this file
+    #[arg(long = "password-file", value_name = "PATH")]
+    password_file: Option<String>,
+
+    /// Disable progress output
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Retry missing files by updating the archive
+    #[arg(short = 'r', long = "retry")]
+    retry: bool,
+
+    /// Check if archived files are up to date with filesystem versions
+    #[arg(long = "check-freshness")]
+    check_freshness: bool,
+
+    /// Fail verification on a file that can't be read while checking it instead of listing
+    /// it as unverifiable or not compared (requires --check-freshness or --checksum)
+    #[arg(long = "strict-hash")]
+    strict_hash: bool,
+
+    /// Run 7-Zip's integrity test on each archive first, failing if any entry doesn't match
+    /// its stored CRC
+    #[arg(long = "test", conflicts_with = "checksum_file")]
+    test: bool,
+
+    /// Also compare each archived file's CRC32 with its file on disk, reporting files whose
+    /// content differs as corrupted (reads every file)
+    #[arg(
+        long = "checksum",
+        conflicts_with_all = ["checksum_file", "reverse", "count_only", "check_archive_age"]
+    )]
+    checksum: bool,
+
+    /// Update outdated files in the archive (requires --check-freshness)
+    /// This will automatically replace files in the archive with newer versions from the filesystem
+    #[arg(long = "update-outdated")]
+    update_outdated: bool,
+
+    /// Remove this many leading path components from archived and expected paths before comparing
+    #[arg(long = "strip-components", value_name = "N", default_value_t = 0)]
+    strip_components: usize,
+
+    /// Render summaries as an aligned table
+    #[arg(long = "table")]
+    table: bool,
+
+    /// Print summaries without the emoji that start their lines
+    #[arg(long = "no-emoji")]
+    no_emoji: bool,
+
+    /// Report format: text, or json to print each archive's result as one JSON object on
+    /// stdout while progress stays on stderr
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        default_value = "text",
+        conflicts_with_all = ["checksum_file", "check_freshness", "reverse", "count_only", "check_archive_age"]
+    )]
+    format: ReportFormat,
+
+    /// Record verification progress in this file and resume from it if it exists
+    #[arg(long = "checkpoint", value_name = "FILE")]
+    checkpoint: Option<String>,
+
+    /// Add missing files to the archive in chunks of at most N paths (with --retry)
+    #[arg(long = "chunk-size", value_name = "N")]
+    chunk_size: Option<usize>,
+
+    /// Write the files missing from the archive to this file, usable as backup input
+    #[arg(long = "write-missing", value_name = "PATH")]
+    write_missing: Option<String>,
+
+    /// Write the result as a JUnit XML report to this file, for CI test result views:
+    /// one test case per expected file, missing files as failures
+    #[arg(
+        long = "junit",
+        value_name = "PATH",
+        conflicts_with_all = ["checksum_file", "reverse", "count_only", "check_archive_age"]
+    )]
+    junit: Option<String>,
+
+    /// Only check whether any source file is newer than the archive file itself
+    #[arg(long = "check-archive-age")]
+    check_archive_age: bool,
+
+    /// Check that every file in the archive still exists on disk (no input paths needed)
+    #[arg(
+        long = "reverse",
+        conflicts_with_all = ["retry", "check_freshness", "check_archive_age", "count_only"]
+    )]
+    reverse: bool,
+
+    /// Only compare the number of files in the archive with the number of source files
+    #[arg(long = "count-only")]
+    count_only: bool,
+
+    /// Largest file count difference --count-only still reports as a pass
+    #[arg(
+        long = "count-tolerance",
+        value_name = "N",
+        default_value_t = 0,
+        requires = "count_only"
+    )]
+    count_tolerance: usize,
+
+    /// Skip hidden files found inside input directories, matching a backup made with --skip-hidden
+    #[arg(long = "skip-hidden")]
+    skip_hidden: bool,
+
+    /// Keep explicitly named hidden inputs when --skip-hidden is set (the default)
+    #[arg(
+        long = "include-hidden-roots",
+        overrides_with = "no_include_hidden_roots"
+    )]
+    include_hidden_roots: bool,
+
+    /// Skip explicitly named hidden inputs too when --skip-hidden is set
+    #[arg(
+        long = "no-include-hidden-roots",
+        overrides_with = "include_hidden_roots"
+    )]
+    no_include_hidden_roots: bool,
+
+    /// Skip files matched by .gitignore files, matching a backup made with --respect-gitignore
+    #[arg(long = "respect-gitignore")]
+    respect_gitignore: bool,
+
+    /// Expect canonical paths, matching a backup made with --canonicalize
+    #[arg(long = "canonicalize")]
+    canonicalize: bool,
+
+    /// Skip files of this content type, matching a backup made with --exclude-type
+    #[arg(long = "exclude-type", value_name = "TYPE", value_parser = parse_content_type)]
+    exclude_type: Vec<String>,
+
+    /// Only expect files in input directories matching this wildcard pattern, matching a
+    /// backup made with --include (repeatable)
+    #[arg(long = "include", value_name = "PATTERN")]
+    include: Vec<String>,
+
+    /// Deprecated: use `--case-sensitive-exclude true`
+    #[arg(long = "case-sensitive", conflicts_with = "case_sensitive_exclude")]
+    case_sensitive: bool,
+
+    /// Whether exclusion patterns match case-sensitively, as in a backup made with
+    /// --case-sensitive-exclude
+    #[arg(long = "case-sensitive-exclude", value_name = "BOOL")]
+    case_sensitive_exclude: Option<bool>,
+
+    /// Skip paths matching this regular expression, as in a backup made with --exclude-regex
+    #[arg(long = "exclude-regex", value_name = "PATTERN", action = clap::ArgAction::Append)]
+    exclude_regex: Vec<String>,
+
+    /// Leave out archived and expected files whose path matches this regular expression
+    /// (e.g. '(^|/)Thumbs\.db$'); repeat to ignore several patterns
+    #[arg(
+        long = "ignore-entry",
+        value_name = "PATTERN",
+        action = clap::ArgAction::Append,
+        conflicts_with = "checksum_file"
+    )]
+    ignore_entry: Vec<String>,
+
+    /// Accept missing files matching this wildcard pattern (e.g. '*.lock'): they are
+    /// reported as expected-missing and don't fail verification; repeatable
+    #[arg(
+        long = "allow-missing",
+        value_name = "PATTERN",
+        action = clap::ArgAction::Append,
+        conflicts_with = "checksum_file"
+    )]
+    allow_missing: Vec<String>,
+
+    /// Cache the expanded expected files in this file and reuse them while the source
+    /// directories are unchanged, speeding up repeated verifies of the same tree
+    #[arg(
+        long = "expand-cache",
+        value_name = "PATH",
+        conflicts_with = "checksum_file"
+    )]
+    expand_cache: Option<String>,
+
+    /// Expect only one path per hard-linked file, matching a backup made with --dedupe-hardlink
+    #[arg(long = "dedupe-hardlink")]
+    dedupe_hardlink: bool,
+
+    /// Descend into symlinked directories, matching a backup made with --follow-symlinks
+    #[arg(long = "follow-symlinks")]
+    follow_symlinks: bool,
+
+    /// Only expect files at most N levels below each input directory, matching a backup
+    /// made with --max-depth
+    #[arg(long = "max-depth", value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Skip files smaller than SIZE, matching a backup made with --min-file-size
+    #[arg(long = "min-file-size", value_name = "SIZE", value_parser = parse_size)]
+    min_file_size: Option<u64>,
+
+    /// Skip files larger than SIZE, matching a backup made with --max-file-size
+    #[arg(long = "max-file-size", value_name = "SIZE", value_parser = parse_size)]
+    max_file_size: Option<u64>,
+
+    /// Only expect files in input directories modified within this time, matching a backup
+    /// made with --modified-within (e.g. 7d, 12h)
+    #[arg(long = "modified-within", value_name = "DURATION", value_parser = parse_duration)]
+    modified_within: Option<Duration>,
+
+    /// Read archive modification times as UTC instead of local time, for archives listed by
+    /// a 7-Zip that shows UTC; times that name their zone are always read in it
+    #[arg(long = "assume-utc")]
+    assume_utc: bool,
+
+    /// Seconds archive and filesystem modification times may differ by and still count as
+    /// up to date (with --check-freshness); raise it for filesystems with coarse timestamps
+    #[arg(
+        long = "freshness-tolerance",
+        value_name = "SECONDS",
+        default_value_t = verification::verifier::FRESHNESS_TOLERANCE_SECONDS
+    )]
+    freshness_tolerance: u64,
+
+    /// Report archived files that are newer than the filesystem (requires --check-freshness)
+    #[arg(long = "report-reverse-freshness")]
+    report_reverse_freshness: bool,
+
+    /// Fail instead of passing trivially if no files are expected after exclusions
+    #[arg(long = "fail-on-empty")]
+    fail_on_empty: bool,
+
+    /// Switches for `7z l` replacing the default `-sccUTF-8` (e.g. "-scsWIN -sccWIN")
+    #[arg(
+        long = "list-args",
+        value_name = "SWITCHES",
+        allow_hyphen_values = true
+    )]
+    list_args: Option<String>,
+
+    /// Locale for `7z l` instead of en_US.UTF-8
+    #[arg(long = "list-locale", value_name = "LOCALE")]
+    list_locale: Option<String>,
+}
+
+#[derive(clap::Args, Default)]
+struct RunArgs {
+    /// Job definition file (.toml, .yaml or .yml)
+    #[arg(long = "jobs", required = true)]
+    jobs: String,
+
+    /// Name of the job to run
+    #[arg(long = "job", required = true)]
+    job: String,
+}
+
+#[derive(clap::Args, Default)]
+struct StatsArgs {
+    /// Archive file to inspect
+    #[arg(short = 'a', long = "archive", required = true)]
+    archive: String,
+
+    /// Path to 7-Zip executable
+    #[arg(long = "7zip-path")]
+    seven_zip_path: Option<String>,
+
+    /// Append the raw stdout and stderr of every 7-Zip invocation to this file
+    #[arg(long = "seven-zip-log", value_name = "PATH")]
+    seven_zip_log: Option<String>,
+
+    /// Switches for `7z l` replacing the default `-sccUTF-8` (e.g. "-scsWIN -sccWIN")
+    #[arg(
+        long = "list-args",
+        value_name = "SWITCHES",
+        allow_hyphen_values = true
+    )]
+    list_args: Option<String>,
+
+    /// Locale for `7z l` instead of en_US.UTF-8
+    #[arg(long = "list-locale", value_name = "LOCALE")]
+    list_locale: Option<String>,
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     let args = Args::parse();
 
-    match args.command {
-        Commands::Backup {
-            input_file,
-            output,
-            seven_zip_path,
-            quiet,
-            verify,
-            retry,
-        } => run_backup_command(input_file, output, seven_zip_path, quiet, verify, retry).await,
-        Commands::Verify {
-            archive,
-            input_file,
-            seven_zip_path,
-            quiet,
-            retry,
-            check_freshness,
-            update_outdated,
-        } => {
-            run_verify_command(
-                archive,
-                input_file,
-                seven_zip_path,
-                quiet,
-                retry,
-                check_freshness,
-                update_outdated,
-            )
-            .await
+    let (result, line) = match args.command {
+        Commands::Backup(backup_args) => {
+            let mut line = ResultLine::new("backup");
+            (run_backup_command(backup_args, &mut line).await, line)
+        }
+        Commands::Verify(verify_args) => {
+            let mut line = ResultLine::new("verify");
+            (run_verify_command(verify_args, &mut line).await, line)
         }
+        Commands::Run(run_args) => {
+            let mut line = ResultLine::new("run");
+            (run_job_command(run_args, &mut line).await, line)
+        }
+        Commands::Stats(stats_args) => (
+            run_stats_command(stats_args).await,
+            ResultLine::new("stats"),
+        ),
+    };
+
+    if args.result_line {
+        eprintln!("{}", line.render(&result));
     }
+    exit_code_for(result, args.json)
 }
 
-async fn run_backup_command(
-    input_file: Option<String>,
-    output: String,
-    seven_zip_path: Option<String>,
-    quiet: bool,
-    verify: bool,
-    retry: bool,
-) -> Result<()> {
-    // Build configuration
-    let config = Config::builder()
-        .output_path(Some(&output), false) // Don't try environment for explicit output
+/// Report an error and map it to the process exit code (127 when 7-Zip is missing)
+fn exit_code_for(result: Result<()>, json: bool) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            if json {
+                eprintln!("{}", e.to_json());
+            } else {
+                eprintln!("Error: {}", e);
+            }
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+async fn run_backup_command(args: BackupArgs, line: &mut ResultLine) -> Result<()> {
+    let BackupArgs {
+        input_file,
+        null,
+        output,
+        chain_dir,
+        raw_list,
+        format,
+        seven_zip_path,
+        seven_zip_log,
+        quiet,
+        verify,
+        retry,
+        table,
+        no_emoji,
+        progress_style,
+        no_precount,
+        dedupe_hardlink,
+        skip_hidden,
+        include_hidden_roots,
+        no_include_hidden_roots,
+        respect_gitignore,
+        canonicalize,
+        follow_symlinks,
+        max_depth,
+        min_file_size,
+        max_file_size,
+        jobs,
+        archive_dirs_whole,
+        exclude_type,
+        include,
+        exclude_regex,
+        case_sensitive,
+        case_sensitive_exclude,
+        profile,
+        accessed_before,
+        modified_within,
+        summary_json_file,
+        analyze_content,
+        max_memory,
+        reproducible,
+        chunk_size,
+        store_below,
+        store_above,
+        print_added,
+        fail_on_empty,
+        fail_on_vanished,
+        strict,
+        level,
+        threads,
+        volume_size,
+        max_incremental_size,
+        skip_if_unchanged,
+        min_free_space,
+        password,
+        password_file,
+        embed_index,
+        atomic,
+        estimate,
+        dry_run,
+        estimate_ratio,
+        compare_to_previous,
+        previous,
+        list_args,
+        list_locale,
+    } = args;
+
+    // Create reader based on input source
+    let mut reader = input_reader(&input_file, null);
+
+    // Without --output a single input directory names the archive
+    let output = match (output, &chain_dir) {
+        (None, None) => {
+            let input_paths = reader.read_paths().await?;
+            let derived = derive_output_path(&input_paths).ok_or_else(|| {
+                ArchtreeError::config(
+                    "--output is required unless the only input is a single directory",
+                )
+            })?;
+            if !quiet {
+                eprintln!("📛 Archive name derived from input directory: {}", derived);
+            }
+            reader = Box::new(io::VecReader::new(input_paths));
+            Some(derived)
+        }
+        (output, _) => output,
+    };
+
+    // Build configuration; a chain directory gets its archive name below
+    let mut config = Config::builder()
+        .output_path(output.as_deref().or(chain_dir.as_deref()), false) // Don't try environment for explicit output
+        .format(format)
         .seven_zip_path(seven_zip_path.as_deref(), true)
+        .seven_zip_log(seven_zip_log.as_deref())
         .show_progress(!quiet)
+        .table_summary(table)
+        .no_emoji(no_emoji)
+        .progress_style(progress_style)
+        .precount(!no_precount)
+        .dedupe_hardlinks(dedupe_hardlink)
+        .default_excludes(None, true)
+        .skip_hidden(skip_hidden)
+        .include_hidden_roots(include_hidden_roots || !no_include_hidden_roots)
+        .respect_gitignore(respect_gitignore)
+        .canonicalize(canonicalize)
+        .follow_symlinks(follow_symlinks)
+        .max_depth(max_depth)
+        .min_file_size(min_file_size)
+        .max_file_size(max_file_size)
+        .walk_jobs(jobs)
+        .whole_directories(archive_dirs_whole)
+        .exclude_types(exclude_type)
+        .include_patterns(include)
+        .exclude_regexes(exclude_regex)
+        .case_sensitive(exclusion_case_sensitivity(
+            case_sensitive,
+            case_sensitive_exclude,
+        ))
+        .profile(profile)
+        .accessed_before(accessed_before)
+        .modified_within(modified_within)
+        .analyze_content(analyze_content)
+        .max_memory(max_memory)
+        .reproducible(reproducible)
+        .chunk_size(chunk_size)
+        .store_below(store_below)
+        .store_above(store_above)
+        .print_added(print_added)
+        .fail_on_empty(fail_on_empty)
+        .fail_on_vanished(fail_on_vanished)
+        .strict(strict)
+        .compression_level(level)
+        .threads(threads)
+        .volume_size(volume_size)
+        .max_incremental_size(max_incremental_size)
+        .skip_if_unchanged(skip_if_unchanged)
+        .min_free_space(min_free_space)
+        .password(read_password(password, password_file.as_deref())?)
+        .embed_index(embed_index)
+        .atomic_output(atomic)
+        .raw_list(raw_list.as_deref())
+        .estimate_ratio(estimate.then_some(estimate_ratio))
+        .dry_run(dry_run)
+        .list_args(list_args.as_deref())
+        .list_locale(list_locale.as_deref())
         .build()?;
 
-    // Create archiver with custom path if specified
-    let archiver = match &config.seven_zip_path {
-        Some(path) => SevenZipArchiver::with_path(path.clone()),
-        None => SevenZipArchiver::new(),
+    let mut baseline = None;
+    if let Some(dir) = &chain_dir {
+        let verifier = verification::SevenZipVerifier::from_config(&config);
+        let member = BackupChain::new(dir)
+            .next_member(&verifier, Local::now().date_naive())
+            .await?;
+        if !quiet {
+            let kind = if member.baseline.is_some() {
+                "incremental"
+            } else {
+                "full"
+            };
+            eprintln!("🔗 Chain member ({}): {}", kind, member.archive_path);
+        }
+        config.output_path = member.archive_path;
+        config.format = format
+            .or_else(|| ArchiveFormat::from_extension(&config.output_path))
+            .unwrap_or_default();
+        baseline = member.baseline;
+    }
+
+    // Verification expands the input paths, which a raw list doesn't have
+    if raw_list.is_some() && verify {
+        eprintln!("⚠️  --verify is unavailable with --raw-list; the archive is not verified");
+    }
+
+    let steps = BackupSteps {
+        baseline,
+        verify: verify && raw_list.is_none(),
+        retry,
+        summary_json_file,
+        previous: previous.filter(|_| compare_to_previous),
     };
+    let report = execute_backup(config, reader, steps).await?;
+    line.record_report(&report);
+    Ok(())
+}
 
-    // Create reader based on input source
-    let reader: Box<dyn io::InputReader> = match &input_file {
-        Some(file_path) => Box::new(FileReader::new(file_path)),
-        None => Box::new(StdinReader::new()),
+/// Reader for the -f input files, concatenated in order, or stdin without any
+fn input_reader(input_files: &[String], null: bool) -> Box<dyn io::InputReader> {
+    let file_reader = |path: &String| -> Box<dyn io::InputReader> {
+        Box::new(FileReader::new(path).with_null_delimiter(null))
+    };
+    match input_files {
+        [] => Box::new(StdinReader::new().with_null_delimiter(null)),
+        [path] => file_reader(path),
+        paths => Box::new(io::ChainedReader::new(
+            paths.iter().map(file_reader).collect(),
+        )),
+    }
+}
+
+/// Password from --password or --password-file
+fn read_password(
+    password: Option<String>,
+    password_file: Option<&str>,
+) -> Result<Option<Password>> {
+    match (password, password_file) {
+        (Some(password), _) => Ok(Some(Password::new(password))),
+        (None, Some(path)) => Password::from_file(path).map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Archive path for input consisting of a single directory: `<dirname>.7z` next to the
+/// directory, so it doesn't depend on where archtree runs and isn't inside what it archives
+fn derive_output_path(input_paths: &[String]) -> Option<String> {
+    let (include_paths, _) = PathProcessor::extract_exclusion_patterns(input_paths);
+    let (include_paths, _) = PathProcessor::extract_include_patterns(&include_paths);
+    let [input] = include_paths.as_slice() else {
+        return None;
     };
 
+    let (_, path) = PathProcessor::parse_depth_prefix(input);
+    let directory = std::fs::canonicalize(path)
+        .ok()
+        .filter(|path| path.is_dir())?;
+    let name = directory.file_name()?.to_string_lossy();
+    let archive = directory.parent()?.join(format!("{}.7z", name));
+    Some(archive.to_string_lossy().to_string())
+}
+
+/// Steps of a backup run around creating the archive itself
+#[derive(Default)]
+struct BackupSteps {
+    /// Files already in the incremental chain; only changes are archived
+    baseline: Option<ChainListing>,
+    /// Verify the archive after creation
+    verify: bool,
+    /// Retry missing files after verification
+    retry: bool,
+    /// Write the final summary as JSON to this file
+    summary_json_file: Option<String>,
+    /// Print the changes relative to this archive after the backup
+    previous: Option<String>,
+}
+
+/// Create the archive and optionally verify it
+async fn execute_backup(
+    config: Config,
+    reader: Box<dyn io::InputReader>,
+    steps: BackupSteps,
+) -> Result<RunReport> {
+    let BackupSteps {
+        baseline,
+        verify,
+        retry,
+        summary_json_file,
+        previous,
+    } = steps;
+    let quiet = !config.show_progress;
+    config.check_format_support()?;
+
+    // Files outside the store thresholds go through a second, store-only 7-Zip invocation,
+    // and with --atomic the whole archive is built under a temporary name
+    let seven_zip = create_backup_archiver(&config)?;
+    let archiver = AtomicArchiver::new(
+        StoreRoutingArchiver::new(
+            ChunkedArchiver::new(seven_zip.clone(), config.chunk_size).with_progress(!quiet),
+            ChunkedArchiver::new(seven_zip.with_store(true), config.chunk_size)
+                .with_progress(!quiet),
+            StoreThresholds {
+                below: config.store_below,
+                above: config.store_above,
+            },
+        )
+        .with_progress(!quiet),
+        config.atomic_output,
+    );
+
     // Create and run backup service
-    let backup_service = BackupService::new(archiver, reader, config.clone());
-    backup_service.run().await?;
+    let mut backup_service = BackupService::new(archiver.clone(), reader, config.clone())
+        .with_callback(ConsoleBackupCallback::new(!quiet))
+        .with_archive_check(verification::SevenZipVerifier::from_config(&config));
+    if let Some(baseline) = baseline {
+        backup_service = backup_service.with_baseline(baseline);
+    }
+    let mut report = backup_service.run().await?;
+
+    if let Some(summary_path) = &summary_json_file {
+        backup_service.write_summary_json(summary_path).await?;
+    }
+    if report.unchanged {
+        return Ok(report);
+    }
+
+    // An incremental without changes leaves no archive to compare
+    if let Some(previous) = &previous
+        && std::path::Path::new(&config.output_path).exists()
+    {
+        let verifier = verification::SevenZipVerifier::from_config(&config);
+        let diff =
+            verification::diff::diff_archives(&verifier, previous, &config.output_path).await?;
+        diff.print_summary(previous, !quiet);
+    }
 
     // Handle verification if requested
     if verify {
@@ -156,36 +1068,24 @@ async fn run_backup_command(
             eprintln!("\n🔍 Verifying archive...");
         }
 
-        // Get the input paths that were processed
-        let input_paths = backup_service.get_input_paths().await?;
-
-        // Create new reader for verification (since we consumed the original)
-        let verify_reader: Box<dyn io::InputReader> = match &input_file {
-            Some(file_path) => Box::new(FileReader::new(file_path)),
-            None => {
-                // For stdin, we'll use the processed paths directly
-                Box::new(io::VecReader::new(input_paths))
-            }
-        };
-
-        // Create verification components
-        let verify_archiver = match &config.seven_zip_path {
-            Some(path) => SevenZipArchiver::with_path(path.clone()),
-            None => SevenZipArchiver::new(),
-        };
-
-        let verify_service =
-            BackupService::new(verify_archiver.clone(), verify_reader, config.clone());
-        let processed_paths = verify_service.get_input_paths().await?;
+        // The files the backup archived, cached by the service, so nothing is walked again
+        let mut processed_paths = backup_service.get_input_paths().await?;
+        if config.whole_directories {
+            // Whole directories are compared by the files 7-Zip found inside them
+            processed_paths = verification::verifier::expand_input_paths_with(
+                &processed_paths,
+                &verification::verifier::DirectoryEnumerator::new(config.follow_symlinks),
+            )
+            .await?;
+        }
 
         // Create verifier
-        let verifier = match &config.seven_zip_path {
-            Some(path) => verification::SevenZipVerifier::with_path(path.clone()),
-            None => verification::SevenZipVerifier::new(),
-        };
+        let verifier = ExpandedVerifier::new(verification::SevenZipVerifier::from_config(&config));
 
         // Create callback for progress reporting
-        let callback = ConsoleCallback::new(!quiet);
+        let callback = ConsoleCallback::new(!quiet)
+            .with_table(config.table_summary)
+            .with_no_emoji(config.no_emoji);
 
         // Create validator
         let validator = FileSystemValidator::new();
@@ -198,61 +1098,303 @@ async fn run_backup_command(
         };
 
         // Run verification
-        VerificationAndRetryService::verify(
-            &output,
+        let verification = VerificationAndRetryService::verify(
+            &config,
+            &config.output_path,
             &processed_paths,
-            &verify_archiver,
+            &archiver,
             &validator,
             &verifier,
             mode,
             callback,
+            &CancellationToken::new(),
         )
         .await?;
+        if let Some(verification) = verification.verification {
+            report = report.with_verification(verification);
+        }
     }
 
-    Ok(())
+    Ok(report)
 }
 
-async fn run_verify_command(
-    archive: String,
-    input_file: Option<String>,
-    seven_zip_path: Option<String>,
-    quiet: bool,
-    retry: bool,
-    check_freshness: bool,
-    update_outdated: bool,
-) -> Result<()> {
+/// Check that an --exclude-type value names a known category or a MIME type
+fn parse_content_type(value: &str) -> std::result::Result<String, String> {
+    value.parse::<ContentType>()?;
+    Ok(value.to_string())
+}
+
+/// Create the archiver for a backup, applying the memory cap if one is set
+fn create_backup_archiver(config: &Config) -> Result<SevenZipArchiver> {
+    // Create archiver with custom path if specified
+    let archiver = match &config.seven_zip_path {
+        Some(path) => SevenZipArchiver::with_path(path.clone()),
+        None => SevenZipArchiver::new(),
+    }
+    .with_reproducible(config.reproducible)
+    .with_compression_level(config.compression_level)
+    .with_threads(config.threads)
+    .with_format(config.format)
+    .with_volume_size(config.volume_size)
+    .with_password(config.password.clone());
+    let archiver = match &config.seven_zip_log {
+        Some(path) => archiver.with_log(path),
+        None => archiver,
+    };
+
+    let Some(max_memory) = config.max_memory else {
+        return Ok(archiver);
+    };
+
+    // The cap may lower an explicit --threads, but never raises it
+    let threads = match config.threads {
+        Some(threads) => threads as usize,
+        None => std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+    };
+    let memory = MemorySettings::for_limit(max_memory, threads).ok_or_else(|| {
+        ArchtreeError::config(format!(
+            "--max-memory {} bytes is too small for 7-Zip to compress with",
+            max_memory
+        ))
+    })?;
+
+    if memory.reduces_compression() {
+        eprintln!(
+            "⚠️  --max-memory limits the dictionary to {} KiB, below the {} MiB of the default compression level; expect a larger archive",
+            memory.dictionary_size / 1024,
+            DEFAULT_DICTIONARY / (1024 * 1024)
+        );
+    }
+    if config.show_progress {
+        eprintln!(
+            "🧠 Memory cap: using {} (estimated {} MiB)",
+            memory.to_args().join(" "),
+            memory.estimated_memory() / (1024 * 1024)
+        );
+    }
+
+    Ok(archiver.with_memory_settings(memory))
+}
+
+async fn run_verify_command(args: VerifyArgs, line: &mut ResultLine) -> Result<()> {
+    let VerifyArgs {
+        archive: mut archives,
+        checksum_file,
+        archive_glob,
+        latest,
+        parallel,
+        input_file,
+        null,
+        seven_zip_path,
+        seven_zip_log,
+        password,
+        password_file,
+        quiet,
+        retry,
+        check_freshness,
+        strict_hash,
+        test,
+        checksum,
+        update_outdated,
+        strip_components,
+        table,
+        no_emoji,
+        format,
+        checkpoint,
+        check_archive_age,
+        reverse,
+        count_only,
+        count_tolerance,
+        dedupe_hardlink,
+        follow_symlinks,
+        max_depth,
+        min_file_size,
+        max_file_size,
+        modified_within,
+        skip_hidden,
+        include_hidden_roots,
+        no_include_hidden_roots,
+        respect_gitignore,
+        canonicalize,
+        exclude_type,
+        include,
+        case_sensitive,
+        case_sensitive_exclude,
+        exclude_regex,
+        ignore_entry,
+        allow_missing,
+        expand_cache,
+        assume_utc,
+        freshness_tolerance,
+        report_reverse_freshness,
+        fail_on_empty,
+        list_args,
+        list_locale,
+        write_missing,
+        junit,
+        chunk_size,
+    } = args;
+
+    if let Some(checksum_file) = &checksum_file {
+        let result = verification::checksum::verify_checksum_file(checksum_file).await?;
+        report_checksum_verification(checksum_file, &result);
+        if !result.is_ok() {
+            return Err(ArchtreeError::verification(
+                format!(
+                    "{} of {} files don't match the checksum file",
+                    result.mismatched.len() + result.unreadable.len(),
+                    result.total()
+                ),
+                None::<String>,
+            ));
+        }
+        return Ok(());
+    }
+
+    if let Some(pattern) = &archive_glob {
+        let mut matches = verification::verifier::expand_archive_glob(pattern).await?;
+        if latest {
+            matches.truncate(1);
+        }
+        archives.extend(matches);
+    }
+
     // Build configuration
     let config = Config::builder()
-        .output_path(Some(&archive), false) // Use archive path as output for potential retry
+        .output_path(archives.first().map(String::as_str), false) // Use archive path as output for potential retry
         .seven_zip_path(seven_zip_path.as_deref(), true)
+        .seven_zip_log(seven_zip_log.as_deref())
+        .password(read_password(password, password_file.as_deref())?)
         .show_progress(!quiet)
+        .table_summary(table)
+        .no_emoji(no_emoji)
+        .dedupe_hardlinks(dedupe_hardlink)
+        .follow_symlinks(follow_symlinks)
+        .max_depth(max_depth)
+        .min_file_size(min_file_size)
+        .max_file_size(max_file_size)
+        .modified_within(modified_within)
+        .default_excludes(None, true)
+        .skip_hidden(skip_hidden)
+        .include_hidden_roots(include_hidden_roots || !no_include_hidden_roots)
+        .respect_gitignore(respect_gitignore)
+        .canonicalize(canonicalize)
+        .exclude_types(exclude_type)
+        .include_patterns(include)
+        .case_sensitive(exclusion_case_sensitivity(
+            case_sensitive,
+            case_sensitive_exclude,
+        ))
+        .exclude_regexes(exclude_regex)
+        .ignore_entries(ignore_entry)
+        .allow_missing(allow_missing)
+        .expand_cache(expand_cache.as_deref())
+        .chunk_size(chunk_size)
+        .fail_on_empty(fail_on_empty)
+        .list_args(list_args.as_deref())
+        .list_locale(list_locale.as_deref())
+        .assume_utc(assume_utc)
         .build()?;
 
+    // A corrupt archive fails before anything is compared with it
+    if test {
+        let verifier = verification::SevenZipVerifier::from_config(&config);
+        for archive in &archives {
+            test_archive_integrity(&verifier, archive, quiet).await?;
+        }
+    }
+
+    if reverse {
+        let verifier = verification::SevenZipVerifier::from_config(&config);
+        for archive in &archives {
+            let result = verification::verifier::verify_reverse(&verifier, archive).await?;
+            report_reverse_verification(archive, &result);
+        }
+        return Ok(());
+    }
+
     // Create reader based on input source
-    let reader: Box<dyn io::InputReader> = match &input_file {
-        Some(file_path) => Box::new(FileReader::new(file_path)),
-        None => Box::new(StdinReader::new()),
-    };
+    let reader = input_reader(&input_file, null);
 
-    // Create archiver for potential retry operations
-    let archiver = match &config.seven_zip_path {
+    // Create archiver for potential retry operations, updating in the archive's own format
+    let seven_zip = match &config.seven_zip_path {
         Some(path) => SevenZipArchiver::with_path(path.clone()),
         None => SevenZipArchiver::new(),
+    }
+    .with_format(config.format)
+    .with_password(config.password.clone());
+    let seven_zip = match &config.seven_zip_log {
+        Some(path) => seven_zip.with_log(path),
+        None => seven_zip,
     };
+    let archiver = ChunkedArchiver::new(seven_zip, config.chunk_size).with_progress(!quiet);
 
     // Get processed input paths using backup service logic
     let service = BackupService::new(archiver.clone(), reader, config.clone());
     let input_paths = service.get_input_paths().await?;
 
-    // Create verifier
-    let verifier = match &config.seven_zip_path {
-        Some(path) => verification::SevenZipVerifier::with_path(path.clone()),
-        None => verification::SevenZipVerifier::new(),
-    };
+    if check_archive_age {
+        for archive in &archives {
+            let result = verification::verifier::check_archive_age(archive, &input_paths).await?;
+            report_archive_age(archive, &result);
+        }
+        return Ok(());
+    }
+
+    if count_only {
+        let verifier = verification::SevenZipVerifier::from_config(&config);
+
+        let mut mismatches = 0;
+        for archive in &archives {
+            let result = verification::verifier::verify_file_count(
+                &verifier,
+                archive,
+                &input_paths,
+                count_tolerance,
+            )
+            .await?;
+            report_file_count(archive, &result);
+            if !result.is_match() {
+                mismatches += 1;
+            }
+        }
 
-    // Create callback for progress reporting
-    let callback = ConsoleCallback::new(!quiet);
+        if mismatches > 0 {
+            return Err(ArchtreeError::verification(
+                format!(
+                    "File count mismatch in {} of {} archives",
+                    mismatches,
+                    archives.len()
+                ),
+                None::<String>,
+            ));
+        }
+        return Ok(());
+    }
+
+    if checkpoint.is_some() && archives.len() > 1 {
+        return Err(ArchtreeError::config(
+            "--checkpoint can only be used when verifying a single archive",
+        ));
+    }
+    if write_missing.is_some() && archives.len() > 1 {
+        return Err(ArchtreeError::config(
+            "--write-missing can only be used when verifying a single archive",
+        ));
+    }
+
+    // Create verifier
+    let mut verifier = verification::SevenZipVerifier::from_config(&config)
+        .with_strip_components(strip_components)
+        .with_strict_hash(strict_hash)
+        .with_reverse_freshness(report_reverse_freshness)
+        .with_freshness_tolerance(freshness_tolerance)
+        .with_checksums(checksum);
+    if let Some(checkpoint_path) = checkpoint {
+        verifier = verifier.with_checkpoint(checkpoint_path);
+    }
 
     // Create validator
     let validator = FileSystemValidator::new();
@@ -263,6 +1405,11 @@ async fn run_verify_command(
             "--update-outdated requires --check-freshness to be enabled",
         ));
     }
+    if strict_hash && !check_freshness && !checksum {
+        return Err(ArchtreeError::config(
+            "--strict-hash requires --check-freshness or --checksum to be enabled",
+        ));
+    }
 
     // Determine verification mode
     let mode = if retry {
@@ -271,40 +1418,438 @@ async fn run_verify_command(
         VerificationMode::VerifyOnly
     };
 
-    if !quiet {
-        eprintln!("🔍 Verifying archive: {}", archive);
-    }
+    let cancellation = cancel_on_ctrl_c(quiet);
 
-    // Run verification with optional freshness checking
-    if check_freshness {
-        VerificationAndRetryService::verify_with_freshness(
-            &archive,
+    // Several archives are verified concurrently, each reporting in one block when done
+    if archives.len() > 1 {
+        if !quiet {
+            eprintln!(
+                "🔍 Verifying {} archives ({} at a time)",
+                archives.len(),
+                parallel.max(1)
+            );
+        }
+
+        let reports = VerificationAndRetryService::verify_archives(
+            &config,
+            &archives,
             &input_paths,
-            &archiver,
-            &validator,
-            &verifier,
+            Arc::new(archiver),
+            Arc::new(validator),
+            Arc::new(verifier),
             mode,
             check_freshness,
             update_outdated,
-            callback,
+            parallel,
+            |archive| verification_callback(archive, format, quiet, table, no_emoji, true),
+            &cancellation,
         )
-        .await?;
+        .await;
+        for result in reports
+            .iter()
+            .filter_map(ArchiveVerificationReport::verification)
+        {
+            line.record_verification(result);
+        }
+        if let Some(junit_path) = &junit {
+            write_junit(junit_path, &reports, quiet).await?;
+        }
+        return report_archive_verifications(&reports);
+    }
+
+    let mut reports = Vec::new();
+    for archive in &archives {
+        // Create callback for progress reporting
+        let callback = verification_callback(archive, format, quiet, table, no_emoji, false);
+
+        if !quiet {
+            eprintln!("🔍 Verifying archive: {}", archive);
+        }
+
+        // Run verification with optional freshness checking
+        let run = if check_freshness {
+            VerificationAndRetryService::verify_with_freshness(
+                &config,
+                archive,
+                &input_paths,
+                &archiver,
+                &validator,
+                &verifier,
+                mode,
+                check_freshness,
+                update_outdated,
+                callback,
+                &cancellation,
+            )
+            .await
+        } else {
+            VerificationAndRetryService::verify(
+                &config,
+                archive,
+                &input_paths,
+                &archiver,
+                &validator,
+                &verifier,
+                mode,
+                callback,
+                &cancellation,
+            )
+            .await
+        };
+        // A failed verification still goes into the JUnit report before it's returned
+        let run = match run {
+            Ok(run) => run,
+            Err(e) => {
+                reports.push(ArchiveVerificationReport {
+                    archive: archive.clone(),
+                    result: Err(e),
+                });
+                break;
+            }
+        };
+
+        if let Some(result) = &run.verification {
+            line.record_verification(result);
+            if let Some(missing_path) = &write_missing {
+                result.write_missing_files(missing_path).await?;
+                if !quiet {
+                    eprintln!(
+                        "📝 Wrote {} missing files to {}",
+                        result.missing_files.len(),
+                        missing_path
+                    );
+                }
+            }
+        }
+        reports.push(ArchiveVerificationReport {
+            archive: archive.clone(),
+            result: Ok(run),
+        });
+    }
+
+    if let Some(junit_path) = &junit {
+        write_junit(junit_path, &reports, quiet).await?;
+    }
+    match reports.pop() {
+        Some(ArchiveVerificationReport { result: Err(e), .. }) => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Run the integrity test of --test on `archive`, failing if it doesn't pass
+async fn test_archive_integrity(
+    verifier: &impl ArchiveVerifier,
+    archive: &str,
+    quiet: bool,
+) -> Result<()> {
+    if !quiet {
+        eprintln!("🧪 Testing archive integrity: {}", archive);
+    }
+    if !verifier.test_integrity(archive).await? {
+        return Err(ArchtreeError::verification(
+            format!(
+                "Archive failed its integrity test (7z t reported errors): {}",
+                archive
+            ),
+            Some(archive),
+        ));
+    }
+    if !quiet {
+        eprintln!("✅ Integrity test passed: {}", archive);
+    }
+    Ok(())
+}
+
+/// Whether exclusion patterns match case-sensitively: --case-sensitive-exclude, or the
+/// deprecated --case-sensitive, or else the platform default
+fn exclusion_case_sensitivity(case_sensitive: bool, case_sensitive_exclude: Option<bool>) -> bool {
+    if case_sensitive {
+        eprintln!("⚠️  --case-sensitive is deprecated; use --case-sensitive-exclude true");
+    }
+    case_sensitive_exclude.unwrap_or(case_sensitive || PLATFORM_CASE_SENSITIVE)
+}
+
+/// Write the JUnit XML report of --junit
+async fn write_junit(path: &str, reports: &[ArchiveVerificationReport], quiet: bool) -> Result<()> {
+    verification::junit::write_junit_report(path, reports).await?;
+    if !quiet {
+        eprintln!("📝 Wrote JUnit report to {}", path);
+    }
+    Ok(())
+}
+
+/// Progress callback of a verification; with --format json it also prints the result as JSON.
+///
+/// A `buffered` callback holds its output back until the archive is done, for archives
+/// verified at the same time.
+fn verification_callback(
+    archive: &str,
+    format: ReportFormat,
+    quiet: bool,
+    table: bool,
+    no_emoji: bool,
+    buffered: bool,
+) -> Box<dyn VerificationCallback> {
+    let console = ConsoleCallback::new(!quiet)
+        .with_table(table)
+        .with_no_emoji(no_emoji);
+    let console = if buffered {
+        console.buffered(archive)
     } else {
-        VerificationAndRetryService::verify(
-            &archive,
-            &input_paths,
-            &archiver,
-            &validator,
-            &verifier,
-            mode,
-            callback,
-        )
-        .await?;
+        console
+    };
+    match format {
+        ReportFormat::Text => Box::new(console),
+        ReportFormat::Json => Box::new(JsonCallback::new(archive, console)),
     }
+}
+
+/// Token cancelled by the first Ctrl-C; a second one exits immediately
+fn cancel_on_ctrl_c(quiet: bool) -> CancellationToken {
+    let cancellation = CancellationToken::new();
+    let trigger = cancellation.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            if !quiet {
+                eprintln!("\n⏹️  Cancelling verification (press Ctrl-C again to abort)...");
+            }
+            trigger.cancel();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(EXIT_CANCELLED.into());
+            }
+        }
+    });
+    cancellation
+}
+
+/// Print per-archive results and the aggregate of a multi-archive verification
+fn report_archive_verifications(reports: &[ArchiveVerificationReport]) -> Result<()> {
+    eprintln!("\n📚 Archive Results:");
 
+    let mut complete = 0;
+    let mut incomplete = 0;
+    let mut failed = 0;
+    for report in reports {
+        match (report.verification(), &report.result) {
+            (Some(result), _) if result.is_complete() => {
+                complete += 1;
+                eprintln!(
+                    "  ✅ {}: {}/{} files",
+                    report.archive, result.total_archived, result.total_expected
+                );
+            }
+            (Some(result), _) => {
+                incomplete += 1;
+                let corrupted = match result.corrupted_files.len() {
+                    0 => String::new(),
+                    count => format!(", {} corrupted", count),
+                };
+                eprintln!(
+                    "  ❌ {}: {}/{} files ({} missing{})",
+                    report.archive,
+                    result.total_archived,
+                    result.total_expected,
+                    result.missing_files.len(),
+                    corrupted
+                );
+            }
+            (None, Err(e)) => {
+                failed += 1;
+                eprintln!("  💥 {}: {}", report.archive, e);
+            }
+            // Every verification compares files, so there's always a result
+            (None, Ok(_)) => {}
+        }
+    }
+
+    eprintln!(
+        "📊 {} archives: {} complete, {} incomplete, {} failed",
+        reports.len(),
+        complete,
+        incomplete,
+        failed
+    );
+
+    // A cancelled run fails as cancelled rather than as unverifiable archives
+    let cancelled = reports
+        .iter()
+        .any(|report| matches!(report.result, Err(ArchtreeError::Cancelled { .. })));
+    if cancelled {
+        return Err(ArchtreeError::cancelled("Verification"));
+    }
+    if failed > 0 {
+        return Err(ArchtreeError::verification(
+            format!(
+                "{} of {} archives could not be verified",
+                failed,
+                reports.len()
+            ),
+            None::<String>,
+        ));
+    }
     Ok(())
 }
 
+async fn run_job_command(args: RunArgs, line: &mut ResultLine) -> Result<()> {
+    let job_file = JobFile::from_path(&args.jobs)?;
+    let job = job_file.job(&args.job)?;
+    let config = job.to_config()?;
+
+    if config.show_progress {
+        eprintln!("📋 Running job '{}' from {}", args.job, args.jobs);
+    }
+
+    let reader = Box::new(io::VecReader::new(job.input_paths()));
+    let steps = BackupSteps {
+        verify: job.verify,
+        retry: job.retry,
+        ..Default::default()
+    };
+    let report = execute_backup(config, reader, steps).await?;
+    line.record_report(&report);
+    Ok(())
+}
+
+async fn run_stats_command(args: StatsArgs) -> Result<()> {
+    let config = Config::builder()
+        .output_path(Some(&args.archive), false)
+        .seven_zip_path(args.seven_zip_path.as_deref(), true)
+        .seven_zip_log(args.seven_zip_log.as_deref())
+        .list_args(args.list_args.as_deref())
+        .list_locale(args.list_locale.as_deref())
+        .build()?;
+
+    let verifier = verification::SevenZipVerifier::from_config(&config);
+    let listing = verifier.list_archive(&args.archive).await?;
+    let files: Vec<_> = listing
+        .entries
+        .iter()
+        .filter(|entry| !entry.is_directory)
+        .collect();
+
+    println!("📈 Compression stats: {}", args.archive);
+    println!("{:>8} {:>14} {:>14}  Path", "Ratio", "Size", "Packed");
+    for entry in &files {
+        let ratio = entry
+            .compression_ratio()
+            .map(|ratio| format!("{:.1}%", ratio * 100.0))
+            .unwrap_or_else(|| "N/A".to_string());
+        let packed = entry
+            .packed_size
+            .map(|packed| packed.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:>8} {:>14} {:>14}  {}",
+            ratio, entry.size, packed, entry.path
+        );
+    }
+
+    let total_size: u64 = files.iter().map(|entry| entry.size).sum();
+    let total_packed: u64 = files.iter().filter_map(|entry| entry.packed_size).sum();
+    if total_size > 0 {
+        println!(
+            "📊 {} files: {} bytes packed into {} bytes ({:.1}%)",
+            files.len(),
+            total_size,
+            total_packed,
+            total_packed as f64 / total_size as f64 * 100.0
+        );
+    } else {
+        println!("📊 {} files, 0 bytes", files.len());
+    }
+
+    Ok(())
+}
+
+fn report_reverse_verification(
+    archive: &str,
+    result: &verification::verifier::ReverseVerificationResult,
+) {
+    if result.is_complete() {
+        eprintln!(
+            "✅ All {} archived files still exist on disk: {}",
+            result.total_checked, archive
+        );
+        return;
+    }
+
+    eprintln!(
+        "⚠️  {} of {} archived files no longer exist on disk: {}",
+        result.gone_from_disk.len(),
+        result.total_checked,
+        archive
+    );
+    for path in &result.gone_from_disk {
+        eprintln!("  🗑️  {}", path);
+    }
+}
+
+fn report_checksum_verification(
+    checksum_file: &str,
+    result: &verification::checksum::ChecksumVerificationResult,
+) {
+    for mismatch in &result.mismatched {
+        eprintln!(
+            "  ❌ {}: expected {}, got {}",
+            mismatch.path, mismatch.expected, mismatch.actual
+        );
+    }
+    for (path, error) in &result.unreadable {
+        eprintln!("  ⚠️  {}: {}", path, error);
+    }
+    if result.is_ok() {
+        eprintln!("✅ All {} files match {}", result.total(), checksum_file);
+    } else {
+        eprintln!(
+            "❌ {} of {} files match {} ({} differ, {} unreadable)",
+            result.matched.len(),
+            result.total(),
+            checksum_file,
+            result.mismatched.len(),
+            result.unreadable.len()
+        );
+    }
+}
+
+fn report_file_count(archive: &str, result: &verification::verifier::FileCountResult) {
+    if result.is_match() {
+        eprintln!(
+            "✅ Archive has {} files; source tree has {} files: {}",
+            result.archive_files, result.source_files, archive
+        );
+    } else {
+        eprintln!(
+            "❌ Archive has {} files; source tree has {} files ({:+}, tolerance {}): {}",
+            result.archive_files,
+            result.source_files,
+            result.delta(),
+            result.tolerance,
+            archive
+        );
+    }
+}
+
+fn report_archive_age(archive: &str, result: &verification::verifier::ArchiveAgeResult) {
+    if !result.is_stale() {
+        eprintln!(
+            "✅ Archive is newer than all {} source files: {}",
+            result.total_checked, archive
+        );
+        return;
+    }
+
+    eprintln!(
+        "⚠️  {} of {} source files are newer than the archive: {}",
+        result.newer_sources.len(),
+        result.total_checked,
+        archive
+    );
+    if let Some((newest, _)) = &result.newest_source {
+        eprintln!("  📄 Newest source: {}", newest);
+    }
+    eprintln!("💡 Consider rebuilding the archive.");
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,12 +1878,15 @@ mod tests {
 
         // Test backup without verification (since 7z might not be available in tests)
         let result = run_backup_command(
-            Some(input_file.to_string_lossy().to_string()),
-            output_file.to_string_lossy().to_string(),
-            None,
-            true,  // quiet
-            false, // no verify
-            false, // no retry
+            BackupArgs {
+                input_file: vec![input_file.to_string_lossy().to_string()],
+                output: Some(output_file.to_string_lossy().to_string()),
+                quiet: true,   // quiet
+                verify: false, // no verify
+                retry: false,  // no retry
+                ..Default::default()
+            },
+            &mut ResultLine::new("backup"),
         )
         .await;
 
@@ -348,4 +1896,39 @@ mod tests {
             eprintln!("Expected error in test environment: {:?}", result);
         }
     }
+
+    #[test]
+    fn test_output_derived_from_single_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let photos = temp_dir.path().join("photos");
+        fs::create_dir(&photos).unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        fs::write(&file, "notes").unwrap();
+
+        let photos_input = photos.to_string_lossy().to_string();
+        // The archive goes next to the directory, not into the current directory
+        let archive = fs::canonicalize(temp_dir.path())
+            .unwrap()
+            .join("photos.7z")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(
+            derive_output_path(&[photos_input.clone(), "!*.tmp".to_string()]),
+            Some(archive.clone())
+        );
+        assert_eq!(
+            derive_output_path(&[format!("depth:1:{}", photos_input)]),
+            Some(archive)
+        );
+
+        // A single file or several inputs still need --output
+        assert_eq!(
+            derive_output_path(&[file.to_string_lossy().to_string()]),
+            None
+        );
+        assert_eq!(
+            derive_output_path(&[photos_input.clone(), photos_input]),
+            None
+        );
+    }
 }
@@ -0,0 +1,271 @@
+use infer::MatcherType;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Number of leading bytes sampled from each file
+const HEADER_SAMPLE_SIZE: usize = 64;
+
+/// Fraction of incompressible bytes above which storing without compression is suggested
+pub const STORE_SUGGESTION_THRESHOLD: f64 = 0.5;
+
+/// Compressed-to-original size ratio assumed for compressible data when estimating archive sizes
+pub const DEFAULT_COMPRESSION_RATIO: f64 = 0.5;
+
+/// Magic byte signatures of formats that are already compressed
+const SIGNATURES: &[(&str, usize, &[u8])] = &[
+    ("jpeg", 0, &[0xFF, 0xD8, 0xFF]),
+    ("png", 0, &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]),
+    ("gif", 0, b"GIF8"),
+    ("mp4", 4, b"ftyp"),
+    ("zip", 0, &[b'P', b'K', 0x03, 0x04]),
+    ("gzip", 0, &[0x1F, 0x8B]),
+    ("7z", 0, &[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C]),
+    ("bzip2", 0, b"BZh"),
+    ("xz", 0, &[0xFD, b'7', b'z', b'X', b'Z', 0x00]),
+    ("zstd", 0, &[0x28, 0xB5, 0x2F, 0xFD]),
+    ("rar", 0, b"Rar!\x1A\x07"),
+    ("mp3", 0, b"ID3"),
+    ("ogg", 0, b"OggS"),
+];
+
+/// Identify an already-compressed format from the first bytes of a file
+pub fn detect_compressed_format(header: &[u8]) -> Option<&'static str> {
+    if header.len() >= 12 && &header[0..4] == b"RIFF" && &header[8..12] == b"WEBP" {
+        return Some("webp");
+    }
+
+    SIGNATURES
+        .iter()
+        .find(|(_, offset, magic)| header.get(*offset..*offset + magic.len()) == Some(*magic))
+        .map(|(format, _, _)| *format)
+}
+
+/// A file whose content is already compressed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncompressibleFile {
+    pub path: PathBuf,
+    pub format: &'static str,
+    pub size: u64,
+}
+
+/// Result of sampling the headers of a file set
+#[derive(Debug, Clone, Default)]
+pub struct ContentAnalysis {
+    /// Total size of all sampled files in bytes
+    pub total_bytes: u64,
+    /// Files detected as already compressed
+    pub incompressible_files: Vec<IncompressibleFile>,
+    /// Files that couldn't be read
+    pub unreadable_files: usize,
+}
+
+impl ContentAnalysis {
+    /// Sample the header of each file and classify it
+    pub fn analyze<P: AsRef<Path>>(paths: &[P]) -> Self {
+        let mut analysis = Self::default();
+
+        for path in paths {
+            let path = path.as_ref();
+            let Some((size, header)) = read_header(path) else {
+                analysis.unreadable_files += 1;
+                continue;
+            };
+
+            analysis.total_bytes += size;
+            if let Some(format) = detect_compressed_format(&header) {
+                analysis.incompressible_files.push(IncompressibleFile {
+                    path: path.to_path_buf(),
+                    format,
+                    size,
+                });
+            }
+        }
+
+        analysis
+    }
+
+    /// Total size of the already-compressed files in bytes
+    pub fn incompressible_bytes(&self) -> u64 {
+        self.incompressible_files.iter().map(|f| f.size).sum()
+    }
+
+    /// Fraction of bytes (0.0 to 1.0) that are already compressed
+    pub fn incompressible_fraction(&self) -> f64 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            self.incompressible_bytes() as f64 / self.total_bytes as f64
+        }
+    }
+
+    /// Whether compression is unlikely to pay off for this file set
+    pub fn suggests_store(&self) -> bool {
+        self.incompressible_fraction() >= STORE_SUGGESTION_THRESHOLD
+    }
+}
+
+/// A content type selected with `--exclude-type`: a category such as `image` or `app`,
+/// or an exact MIME type such as `image/png`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentType {
+    Category(MatcherType),
+    Mime(String),
+}
+
+impl ContentType {
+    /// Check if the detected type of a file is of this content type
+    pub fn matches(&self, detected: &infer::Type) -> bool {
+        match self {
+            Self::Category(category) => detected.matcher_type() == *category,
+            Self::Mime(mime) => detected.mime_type().eq_ignore_ascii_case(mime),
+        }
+    }
+}
+
+impl FromStr for ContentType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        if value.contains('/') {
+            return Ok(Self::Mime(value));
+        }
+
+        let category = match value.as_str() {
+            "app" | "executable" => MatcherType::App,
+            "archive" => MatcherType::Archive,
+            "audio" => MatcherType::Audio,
+            "book" => MatcherType::Book,
+            "doc" | "document" => MatcherType::Doc,
+            "font" => MatcherType::Font,
+            "image" => MatcherType::Image,
+            "text" => MatcherType::Text,
+            "video" => MatcherType::Video,
+            _ => {
+                return Err(format!(
+                    "Unknown content type '{}' (expected a MIME type or one of app, archive, audio, book, doc, font, image, text, video)",
+                    value
+                ));
+            }
+        };
+        Ok(Self::Category(category))
+    }
+}
+
+/// Detect the type of a file from its first few kilobytes; `None` if unknown or unreadable
+pub fn detect_content_type(path: &Path) -> Option<infer::Type> {
+    infer::get_from_path(path).ok().flatten()
+}
+
+/// Projected archive size for a file set, without running 7-Zip
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeEstimate {
+    /// Total size of the input files in bytes
+    pub input_bytes: u64,
+    /// Bytes of already-compressed files, assumed to be stored at their original size
+    pub incompressible_bytes: u64,
+    /// Ratio applied to the remaining bytes
+    pub compression_ratio: f64,
+}
+
+impl SizeEstimate {
+    /// Estimate from the content analysis of a file set
+    pub fn from_analysis(analysis: &ContentAnalysis, compression_ratio: f64) -> Self {
+        Self {
+            input_bytes: analysis.total_bytes,
+            incompressible_bytes: analysis.incompressible_bytes(),
+            compression_ratio,
+        }
+    }
+
+    /// Projected size of the archive in bytes
+    pub fn archive_bytes(&self) -> u64 {
+        let compressible = self.input_bytes - self.incompressible_bytes;
+        self.incompressible_bytes + (compressible as f64 * self.compression_ratio).round() as u64
+    }
+}
+
+fn read_header(path: &Path) -> Option<(u64, Vec<u8>)> {
+    let file = File::open(path).ok()?;
+    let size = file.metadata().ok()?.len();
+
+    let mut header = Vec::with_capacity(HEADER_SAMPLE_SIZE);
+    file.take(HEADER_SAMPLE_SIZE as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+    Some((size, header))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fake_jpeg_is_incompressible() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let jpeg = temp_dir.path().join("photo.dat");
+        let mut jpeg_content = vec![0xFF, 0xD8, 0xFF, 0xE0];
+        jpeg_content.extend(std::iter::repeat_n(0u8, 296));
+        std::fs::write(&jpeg, &jpeg_content).unwrap();
+
+        let text = temp_dir.path().join("notes.txt");
+        std::fs::write(&text, "plain text ".repeat(10)).unwrap();
+
+        let analysis = ContentAnalysis::analyze(&[&jpeg, &text]);
+
+        assert_eq!(analysis.total_bytes, 410);
+        assert_eq!(analysis.incompressible_files.len(), 1);
+        assert_eq!(analysis.incompressible_files[0].path, jpeg);
+        assert_eq!(analysis.incompressible_files[0].format, "jpeg");
+        assert_eq!(analysis.incompressible_bytes(), 300);
+        assert!(analysis.suggests_store());
+    }
+
+    #[test]
+    fn test_size_estimate_with_fixed_ratio() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let gzip = temp_dir.path().join("logs.gz");
+        let mut gzip_content = vec![0x1F, 0x8B, 0x08];
+        gzip_content.extend(std::iter::repeat_n(0u8, 997));
+        std::fs::write(&gzip, &gzip_content).unwrap();
+
+        let text = temp_dir.path().join("notes.txt");
+        std::fs::write(&text, "x".repeat(3000)).unwrap();
+
+        let analysis = ContentAnalysis::analyze(&[&gzip, &text]);
+        let estimate = SizeEstimate::from_analysis(&analysis, 0.25);
+
+        assert_eq!(estimate.input_bytes, 4000);
+        assert_eq!(estimate.incompressible_bytes, 1000);
+        // The gzip file is kept as is, the text shrinks to a quarter
+        assert_eq!(estimate.archive_bytes(), 1000 + 750);
+    }
+
+    #[test]
+    fn test_content_type_parsing_and_matching() {
+        let png = infer::get(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]).unwrap();
+
+        assert!("image".parse::<ContentType>().unwrap().matches(&png));
+        assert!("IMAGE/PNG".parse::<ContentType>().unwrap().matches(&png));
+        assert!(!"image/jpeg".parse::<ContentType>().unwrap().matches(&png));
+        assert!(!"executable".parse::<ContentType>().unwrap().matches(&png));
+        assert!("pictures".parse::<ContentType>().is_err());
+    }
+
+    #[test]
+    fn test_detect_compressed_format() {
+        assert_eq!(detect_compressed_format(&[0x1F, 0x8B, 0x08]), Some("gzip"));
+        assert_eq!(detect_compressed_format(b"PK\x03\x04rest"), Some("zip"));
+        assert_eq!(
+            detect_compressed_format(b"\x00\x00\x00\x18ftypmp42"),
+            Some("mp4")
+        );
+        assert_eq!(detect_compressed_format(b"hello world"), None);
+        assert_eq!(detect_compressed_format(&[]), None);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::core::{ArchtreeError, Result};
+use crate::processing::path_processor::{ExclusionMatcher, WildcardMatcher};
+use async_trait::async_trait;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use regex::Regex;
+use std::path::Path;
+
+/// Prefix of exclusion patterns written as a `.gitignore` line, such as `gi:build/`
+pub const GITIGNORE_PREFIX: &str = "gi:";
+
+/// Prefix of exclusion patterns written as a regular expression, such as `re:\.bak$`
+pub const REGEX_PREFIX: &str = "re:";
+
+/// Exclusion matcher using regular expressions, for patterns wildcards can't express
+/// such as `\.(tmp|bak|swp)$`.
+///
+/// Patterns are matched anywhere in the path, with backslashes turned into `/` first;
+/// anchor them with `^` and `$` as needed. Matching is case-sensitive unless a pattern
+/// starts with `(?i)`.
+#[derive(Debug, Clone, Default)]
+pub struct RegexMatcher {
+    patterns: Vec<Regex>,
+}
+
+impl RegexMatcher {
+    /// Compile every pattern once, failing on the first invalid one
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    ArchtreeError::path_processing_with_source(
+                        format!("Invalid exclusion regex: {}", pattern),
+                        None::<String>,
+                        e,
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+#[async_trait]
+impl ExclusionMatcher for RegexMatcher {
+    fn matches(&self, path: &Path, _pattern: &str) -> bool {
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.is_match(&path_str))
+    }
+
+    fn description(&self) -> &'static str {
+        "Regular expression matcher"
+    }
+}
+
+/// Exclusion matcher for a mix of pattern styles, each pattern handled by the matcher its
+/// prefix selects: `gi:` for a `.gitignore` line, `re:` for a regular expression like
+/// [`RegexMatcher`]'s, and no prefix for a wildcard pattern like [`WildcardMatcher`]'s.
+///
+/// Asked about a known pattern, only that pattern is checked; asked about any other, such
+/// as an empty one, all are.
+pub struct CompositeMatcher {
+    patterns: Vec<(String, CompiledPattern)>,
+}
+
+/// A pattern compiled by the matcher its prefix selects
+enum CompiledPattern {
+    Wildcard(WildcardMatcher),
+    Regex(RegexMatcher),
+    Gitignore(Gitignore),
+}
+
+impl CompositeMatcher {
+    /// Compile every pattern, failing on the first invalid one.
+    ///
+    /// `case_sensitive` applies to wildcard and gitignore patterns; regular expressions are
+    /// case-sensitive unless they start with `(?i)`, like `--exclude-regex`.
+    pub fn new(patterns: &[String], case_sensitive: bool) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                let compiled = if let Some(line) = pattern.strip_prefix(GITIGNORE_PREFIX) {
+                    CompiledPattern::Gitignore(compile_gitignore_line(line, case_sensitive)?)
+                } else if let Some(regex) = pattern.strip_prefix(REGEX_PREFIX) {
+                    CompiledPattern::Regex(RegexMatcher::new(&[regex.to_string()])?)
+                } else {
+                    CompiledPattern::Wildcard(WildcardMatcher::with_case_sensitivity(
+                        std::slice::from_ref(pattern),
+                        case_sensitive,
+                    )?)
+                };
+                Ok((pattern.clone(), compiled))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+}
+
+/// Compile a `.gitignore` line rooted at the filesystem root, so patterns without a
+/// slash match at any depth and patterns with one are anchored at the root
+fn compile_gitignore_line(line: &str, case_sensitive: bool) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new("/");
+    builder.case_insensitive(!case_sensitive).map_err(|e| {
+        ArchtreeError::path_processing_with_source(
+            format!("Invalid gitignore pattern: {}", line),
+            None::<String>,
+            e,
+        )
+    })?;
+    builder
+        .add_line(None, line)
+        .and_then(|builder| builder.build())
+        .map_err(|e| {
+            ArchtreeError::path_processing_with_source(
+                format!("Invalid gitignore pattern: {}", line),
+                None::<String>,
+                e,
+            )
+        })
+}
+
+impl CompiledPattern {
+    fn matches(&self, path: &Path) -> bool {
+        match self {
+            Self::Wildcard(matcher) => matcher.matches(path, ""),
+            Self::Regex(matcher) => matcher.matches(path, ""),
+            Self::Gitignore(gitignore) => {
+                // Rooted at `/`, with Windows separators and drive letters normalized away
+                let normalized = path.to_string_lossy().replace('\\', "/");
+                let without_drive = match normalized.as_bytes() {
+                    [drive, b':', ..] if drive.is_ascii_alphabetic() => &normalized[2..],
+                    _ => normalized.as_str(),
+                };
+                let rooted = format!("/{}", without_drive.trim_start_matches('/'));
+                gitignore
+                    .matched_path_or_any_parents(&rooted, path.is_dir())
+                    .is_ignore()
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ExclusionMatcher for CompositeMatcher {
+    fn matches(&self, path: &Path, pattern: &str) -> bool {
+        match self
+            .patterns
+            .iter()
+            .find(|(original, _)| original == pattern)
+        {
+            Some((_, compiled)) => compiled.matches(path),
+            None => self
+                .patterns
+                .iter()
+                .any(|(_, compiled)| compiled.matches(path)),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        "Composite matcher (wildcards, gi: gitignore lines and re: regular expressions)"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_matcher() {
+        let matcher = RegexMatcher::new(&[r"\.(tmp|bak|swp)$".to_string()]).unwrap();
+
+        assert!(matcher.matches(Path::new("/home/me/notes.txt.swp"), ""));
+        assert!(matcher.matches(Path::new("C:\\data\\report.bak"), ""));
+        assert!(!matcher.matches(Path::new("/home/me/tmp/notes.txt"), ""));
+
+        let error = RegexMatcher::new(&["(unclosed".to_string()]).unwrap_err();
+        assert!(matches!(error, ArchtreeError::PathProcessing { .. }));
+        assert!(error.to_string().contains("(unclosed"));
+    }
+
+    #[test]
+    fn test_composite_matcher_dispatches_by_prefix() {
+        let patterns: Vec<String> = ["*.tmp", r"re:\.(bak|swp)$", "gi:build/"]
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .collect();
+        let matcher = CompositeMatcher::new(&patterns, true).unwrap();
+        let excluded_by = |path: &str| -> Vec<&str> {
+            patterns
+                .iter()
+                .filter(|pattern| matcher.matches(Path::new(path), pattern))
+                .map(String::as_str)
+                .collect()
+        };
+
+        assert_eq!(excluded_by("/home/me/notes.tmp"), ["*.tmp"]);
+        assert_eq!(excluded_by("/home/me/notes.txt.swp"), [r"re:\.(bak|swp)$"]);
+        assert_eq!(excluded_by("C:\\work\\report.bak"), [r"re:\.(bak|swp)$"]);
+        assert_eq!(excluded_by("/home/me/project/build/out.o"), ["gi:build/"]);
+        assert!(excluded_by("/home/me/project/build.rs").is_empty());
+        assert!(excluded_by("/home/me/notes.txt").is_empty());
+
+        // Any pattern is checked when the one asked about isn't known
+        assert!(matcher.matches(Path::new("/home/me/project/build/out.o"), ""));
+        assert!(!matcher.matches(Path::new("/home/me/notes.txt"), ""));
+
+        assert!(CompositeMatcher::new(&["re:(unclosed".to_string()], true).is_err());
+    }
+}
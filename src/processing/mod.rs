@@ -1,4 +1,8 @@
+pub mod content;
+pub mod exclusions;
 pub mod path_processor;
 pub mod validation;
 
+pub use content::{ContentAnalysis, ContentType, SizeEstimate};
+pub use exclusions::{CompositeMatcher, RegexMatcher};
 pub use path_processor::{PathProcessor, ProcessingStatus, WildcardMatcher};
@@ -1,8 +1,12 @@
-use crate::core::{Result, ErrorContext};
+use crate::core::{ArchtreeError, ErrorContext, PLATFORM_CASE_SENSITIVE, Result};
+use crate::processing::content::{ContentType, detect_content_type};
+use crate::processing::exclusions::RegexMatcher;
 use async_trait::async_trait;
-use regex::Regex;
-use std::collections::HashSet;
+use regex::{Regex, RegexBuilder};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::fs;
 use walkdir::WalkDir;
 
@@ -10,9 +14,61 @@ use walkdir::WalkDir;
 pub struct PathProcessor {
     input_paths: Vec<String>,
     exclusion_patterns: Vec<String>,
-    current_index: usize,
-    current_walker: Option<Box<dyn Iterator<Item = walkdir::DirEntry> + Send>>,
     yielded_paths: HashSet<PathBuf>,
+    /// Whether files sharing a device and inode are only added once
+    dedupe_hardlinks: bool,
+    /// First path seen for each (device, inode) pair with multiple links
+    seen_inodes: HashMap<(u64, u64), PathBuf>,
+    /// Skipped hard links as (duplicate path, path that was added)
+    hardlinks: Vec<(PathBuf, PathBuf)>,
+    /// Whether hidden (dot-prefixed) files and directories found while walking are skipped
+    skip_hidden: bool,
+    /// Whether explicitly named hidden inputs are kept when hidden files are skipped
+    include_hidden_roots: bool,
+    /// Whether `.gitignore` files found while walking exclude the paths they match
+    respect_gitignore: bool,
+    /// Whether paths are canonicalized before duplicates are detected
+    canonicalize: bool,
+    /// Whether symlinks to directories are descended into while walking
+    follow_symlinks: bool,
+    /// Levels walked below input directories without a `depth:` prefix; `None` walks all
+    max_depth: Option<usize>,
+    /// Files found in directories smaller than this many bytes are excluded
+    min_file_size: Option<u64>,
+    /// Files found in directories larger than this many bytes are excluded
+    max_file_size: Option<u64>,
+    /// Content types excluded from directories by sniffing each file's leading bytes
+    exclude_types: Vec<ContentType>,
+    /// Clock timing the walk of each input directory; `None` when not profiling
+    clock: Option<Arc<dyn Clock>>,
+    /// Time spent walking each input directory, in input order
+    directory_timings: Vec<(PathBuf, Duration)>,
+    /// Files in directories last accessed after this time are skipped
+    accessed_before: Option<SystemTime>,
+    /// Files in directories last modified before this time are excluded
+    modified_after: Option<SystemTime>,
+    /// Threads walking each input directory; 1 walks it on a single thread
+    walk_jobs: usize,
+    /// When set, files found in directories are only added if they match one of its patterns
+    include_matcher: Option<WildcardMatcher>,
+    /// Whether input directories are added as they are instead of being walked
+    whole_directories: bool,
+    /// Regular expression exclusions checked alongside the wildcard patterns
+    exclude_regex: Option<RegexMatcher>,
+}
+
+/// Source of the current time, replaceable in tests
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// Clock reading the system's monotonic time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
 }
 
 /// Trait for exclusion pattern matching
@@ -26,37 +82,90 @@ pub trait ExclusionMatcher: Send + Sync {
 }
 
 /// Wildcard-based exclusion matcher supporting * and ? patterns
+#[derive(Debug, Clone)]
 pub struct WildcardMatcher {
     compiled_patterns: Vec<(String, Regex)>,
+    case_sensitive: bool,
 }
 
 impl WildcardMatcher {
+    /// Matcher without patterns, case-sensitive as the platform's filesystem usually is
     pub fn new() -> Self {
         Self {
             compiled_patterns: Vec::new(),
+            case_sensitive: PLATFORM_CASE_SENSITIVE,
         }
     }
 
+    /// Compile patterns, case-sensitive as the platform's filesystem usually is
     pub fn with_patterns(patterns: &[String]) -> Result<Self> {
+        Self::with_case_sensitivity(patterns, PLATFORM_CASE_SENSITIVE)
+    }
+
+    /// Compile patterns that match paths case-sensitively or ignoring case
+    pub fn with_case_sensitivity(patterns: &[String], case_sensitive: bool) -> Result<Self> {
         let mut compiled_patterns = Vec::new();
 
         for pattern in patterns {
             let regex_pattern = Self::wildcard_to_regex(pattern);
-            let regex = Regex::new(&regex_pattern)
+            let regex = RegexBuilder::new(&regex_pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
                 .context_config(format!("Invalid exclusion pattern: {}", pattern))?;
             compiled_patterns.push((pattern.clone(), regex));
         }
 
-        Ok(Self { compiled_patterns })
+        Ok(Self {
+            compiled_patterns,
+            case_sensitive,
+        })
+    }
+
+    pub fn is_case_sensitive(&self) -> bool {
+        self.case_sensitive
     }
 
     /// Convert a wildcard pattern to a regex pattern
+    ///
+    /// Patterns starting with `/` (or a drive such as `C:/`) are anchored at the filesystem
+    /// root, so `/tmp/*` only matches the top-level `/tmp`. Any other pattern is unanchored
+    /// and may start at any path component, so `tmp/*` also matches `/home/me/tmp/file`.
+    /// `**/` matches any number of directories, none included, so `src/**/*.rs` also
+    /// matches `src/main.rs`. Backslashes in patterns are treated as path separators, and
+    /// drive letters match in either case, as Windows treats them.
     fn wildcard_to_regex(pattern: &str) -> String {
+        let mut pattern = pattern.replace('\\', "/");
         let mut regex = String::new();
-        regex.push('^');
 
-        for c in pattern.chars() {
+        let has_drive = pattern.len() >= 3
+            && pattern.as_bytes()[0].is_ascii_alphabetic()
+            && pattern[1..].starts_with(":/");
+        if has_drive {
+            let drive = pattern.as_bytes()[0];
+            regex.push_str(&format!(
+                "^[{}{}]:",
+                drive.to_ascii_uppercase() as char,
+                drive.to_ascii_lowercase() as char
+            ));
+            pattern.replace_range(..2, "");
+        } else if pattern.starts_with('/') {
+            // Anchored at the root, on whichever drive the path lives
+            regex.push_str("^(?:[a-zA-Z]:)?");
+        } else {
+            // Unanchored: may begin at any path component
+            regex.push_str("(?:^|/)");
+        }
+
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
             match c {
+                '*' if chars.next_if_eq(&'*').is_some() => {
+                    if chars.next_if_eq(&'/').is_some() {
+                        regex.push_str("(?:.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                }
                 '*' => regex.push_str(".*"),
                 '?' => regex.push('.'),
                 '.' | '^' | '$' | '(' | ')' | '[' | ']' | '{' | '}' | '|' | '+' | '\\' => {
@@ -81,8 +190,8 @@ impl Default for WildcardMatcher {
 #[async_trait]
 impl ExclusionMatcher for WildcardMatcher {
     fn matches(&self, path: &Path, _pattern: &str) -> bool {
-        // Normalize path for comparison (handle Windows/Unix differences)
-        let path_str = path.to_string_lossy().to_lowercase().replace('\\', "/");
+        // Normalize separators; case is handled by how the patterns were compiled
+        let path_str = path.to_string_lossy().replace('\\', "/");
 
         // Check against all compiled patterns
         for (_original, regex) in &self.compiled_patterns {
@@ -105,17 +214,179 @@ impl PathProcessor {
         Ok(Self {
             input_paths,
             exclusion_patterns,
-            current_index: 0,
-            current_walker: None,
             yielded_paths: HashSet::new(),
+            dedupe_hardlinks: false,
+            seen_inodes: HashMap::new(),
+            hardlinks: Vec::new(),
+            skip_hidden: false,
+            include_hidden_roots: true,
+            respect_gitignore: false,
+            canonicalize: false,
+            follow_symlinks: false,
+            max_depth: None,
+            min_file_size: None,
+            max_file_size: None,
+            exclude_types: Vec::new(),
+            clock: None,
+            directory_timings: Vec::new(),
+            accessed_before: None,
+            modified_after: None,
+            walk_jobs: 1,
+            include_matcher: None,
+            whole_directories: false,
+            exclude_regex: None,
         })
     }
 
+    /// Skip hidden files and directories discovered while walking input directories.
+    ///
+    /// Hidden means the name starts with a dot. Inputs named explicitly are still
+    /// processed unless `with_include_hidden_roots(false)` is also set.
+    pub fn with_skip_hidden(mut self, skip: bool) -> Self {
+        self.skip_hidden = skip;
+        self
+    }
+
+    /// Whether explicitly named hidden inputs are kept when hidden files are skipped
+    pub fn with_include_hidden_roots(mut self, include: bool) -> Self {
+        self.include_hidden_roots = include;
+        self
+    }
+
+    /// Apply the rules of `.gitignore` files found while walking input directories.
+    ///
+    /// Like git, each file's rules only apply to the directory it is in and below.
+    pub fn with_respect_gitignore(mut self, respect: bool) -> Self {
+        self.respect_gitignore = respect;
+        self
+    }
+
+    /// Canonicalize paths (resolving `.`, `..` and symlinks) before detecting duplicates.
+    ///
+    /// Paths are then added in canonical form, so the archive stores the resolved paths.
+    /// Paths that can't be canonicalized are kept as they are.
+    pub fn with_canonicalize(mut self, canonicalize: bool) -> Self {
+        self.canonicalize = canonicalize;
+        self
+    }
+
+    /// Descend into symlinks to directories found while walking input directories.
+    ///
+    /// Without it such symlinks are skipped, while symlinks to files are added like files.
+    /// A symlink leading back to a directory being walked is skipped, and a file reached
+    /// through several symlinks is only added once.
+    pub fn with_follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Only walk input directories `depth` levels deep, so `Some(1)` adds the files
+    /// directly inside them.
+    ///
+    /// Depth counts from each input directory. A `depth:` prefix on an input overrides it.
+    pub fn with_max_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Exclude files found inside input directories smaller than `min` or larger than `max`
+    /// bytes. Files named explicitly as inputs are always added.
+    pub fn with_file_size_range(mut self, min: Option<u64>, max: Option<u64>) -> Self {
+        self.min_file_size = min;
+        self.max_file_size = max;
+        self
+    }
+
+    /// Only add the first of several hard links to the same file (Unix only)
+    pub fn with_dedupe_hardlinks(mut self, dedupe: bool) -> Self {
+        self.dedupe_hardlinks = dedupe;
+        self
+    }
+
+    /// Exclude files found inside input directories whose detected content type matches.
+    ///
+    /// Each file's first few kilobytes are read, so this costs one extra open and read per file.
+    pub fn with_exclude_types(mut self, types: Vec<ContentType>) -> Self {
+        self.exclude_types = types;
+        self
+    }
+
+    /// Time how long each input directory takes to walk, reading the time from `clock`
+    pub fn with_profiling(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Skip files found inside input directories that were accessed after `cutoff`.
+    ///
+    /// Access times are only as reliable as the filesystem keeps them: `noatime` mounts
+    /// never update them and `relatime` at most once a day.
+    pub fn with_accessed_before(mut self, cutoff: SystemTime) -> Self {
+        self.accessed_before = Some(cutoff);
+        self
+    }
+
+    /// Exclude files found inside input directories last modified before `cutoff`, keeping
+    /// only recently changed files. Files named explicitly as inputs are always added.
+    pub fn with_modified_after(mut self, cutoff: SystemTime) -> Self {
+        self.modified_after = Some(cutoff);
+        self
+    }
+
+    /// Walk each input directory with `jobs` threads, which speeds up huge trees.
+    ///
+    /// The files of each directory are then added sorted by path, instead of in the order
+    /// the filesystem lists them.
+    pub fn with_walk_jobs(mut self, jobs: usize) -> Self {
+        self.walk_jobs = jobs.max(1);
+        self
+    }
+
+    /// Only add files found inside input directories that match one of `matcher`'s patterns.
+    ///
+    /// Exclusions still apply to the files that match. Files named explicitly as inputs are
+    /// always added.
+    pub fn with_include_patterns(mut self, matcher: WildcardMatcher) -> Self {
+        self.include_matcher = Some(matcher);
+        self
+    }
+
+    /// Also exclude paths matching one of `matcher`'s regular expressions.
+    ///
+    /// They apply on top of the wildcard exclusions, to input paths and walked files alike.
+    pub fn with_exclude_regex(mut self, matcher: RegexMatcher) -> Self {
+        self.exclude_regex = Some(matcher);
+        self
+    }
+
+    /// Add input directories themselves instead of the files found inside them.
+    ///
+    /// 7-Zip then archives each directory with its structure, empty subdirectories
+    /// included. Nothing inside is walked, so exclusions and filters only apply to the
+    /// input paths themselves. Directories with a `depth:` prefix are still walked.
+    pub fn with_whole_directories(mut self, whole: bool) -> Self {
+        self.whole_directories = whole;
+        self
+    }
+
+    /// The `count` input directories that took longest to walk, slowest first
+    pub fn slowest_directories(&self, count: usize) -> Vec<(PathBuf, Duration)> {
+        let mut timings = self.directory_timings.clone();
+        timings.sort_by_key(|(_, elapsed)| std::cmp::Reverse(*elapsed));
+        timings.truncate(count);
+        timings
+    }
+
     /// Get the exclusion patterns
     pub fn exclusion_patterns(&self) -> &[String] {
         &self.exclusion_patterns
     }
 
+    /// Hard links skipped during processing, as (duplicate path, path that was added)
+    pub fn hardlinks(&self) -> &[(PathBuf, PathBuf)] {
+        &self.hardlinks
+    }
+
     /// Extract exclusion patterns from input paths (paths starting with '!')
     pub fn extract_exclusion_patterns(paths: &[String]) -> (Vec<String>, Vec<String>) {
         let mut include_paths = Vec::new();
@@ -132,6 +403,36 @@ impl PathProcessor {
         (include_paths, exclude_patterns)
     }
 
+    /// Extract include-only patterns from input paths (paths starting with '+')
+    pub fn extract_include_patterns(paths: &[String]) -> (Vec<String>, Vec<String>) {
+        let mut input_paths = Vec::new();
+        let mut include_patterns = Vec::new();
+
+        for path in paths {
+            if let Some(pattern) = path.strip_prefix('+') {
+                include_patterns.push(pattern.to_string());
+            } else {
+                input_paths.push(path.clone());
+            }
+        }
+
+        (input_paths, include_patterns)
+    }
+
+    /// Split an optional `depth:N:` prefix from an input path.
+    ///
+    /// `depth:2:C:\data` limits the walk of `C:\data` to files at most two levels below it.
+    /// Entries without a valid prefix are returned unchanged with no depth limit.
+    pub fn parse_depth_prefix(input: &str) -> (Option<usize>, &str) {
+        if let Some(rest) = input.strip_prefix("depth:")
+            && let Some((depth, path)) = rest.split_once(':')
+            && let Ok(depth) = depth.parse::<usize>()
+        {
+            return (Some(depth), path);
+        }
+        (None, input)
+    }
+
     /// Convert a path to absolute path, handling both absolute and relative paths
     pub async fn to_absolute_path(path: &str) -> Result<PathBuf> {
         let path_buf = PathBuf::from(path);
@@ -139,13 +440,21 @@ impl PathProcessor {
         if path_buf.is_absolute() {
             Ok(path_buf)
         } else {
-            let current_dir = std::env::current_dir().context_io("Failed to get current directory")?;
+            let current_dir =
+                std::env::current_dir().context_io("Failed to get current directory")?;
             Ok(current_dir.join(path_buf))
         }
     }
 
     /// Check if a path should be excluded based on exclusion patterns
     fn should_exclude(&self, path: &Path, matcher: &dyn ExclusionMatcher) -> bool {
+        if self
+            .exclude_regex
+            .as_ref()
+            .is_some_and(|regex| regex.matches(path, ""))
+        {
+            return true;
+        }
         for pattern in &self.exclusion_patterns {
             if matcher.matches(path, pattern) {
                 return true;
@@ -154,6 +463,25 @@ impl PathProcessor {
         false
     }
 
+    /// Check if a file found while walking is outside the include patterns, if any are set
+    fn is_not_included(&self, path: &Path) -> bool {
+        self.include_matcher
+            .as_ref()
+            .is_some_and(|matcher| !matcher.matches(path, ""))
+    }
+
+    /// Check if the sniffed content type of a file is one of the excluded types
+    fn has_excluded_type(&self, path: &Path) -> bool {
+        if self.exclude_types.is_empty() {
+            return false;
+        }
+        detect_content_type(path).is_some_and(|detected| {
+            self.exclude_types
+                .iter()
+                .any(|content_type| content_type.matches(&detected))
+        })
+    }
+
     /// Process all input paths according to the improved algorithm
     /// Returns an iterator-like interface that yields paths one by one
     pub async fn process_paths<F>(
@@ -166,7 +494,9 @@ impl PathProcessor {
     {
         let mut result_paths = Vec::new();
 
-        for input_path in &self.input_paths.clone() {
+        for input_entry in &self.input_paths.clone() {
+            let (prefix_depth, input_path) = Self::parse_depth_prefix(input_entry);
+            let max_depth = prefix_depth.or(self.max_depth);
             let absolute_path = Self::to_absolute_path(input_path).await?;
 
             // Step 1: Check against exclusion patterns (skip if matches)
@@ -175,36 +505,168 @@ impl PathProcessor {
                 continue;
             }
 
+            // Explicitly named hidden inputs only obey the hidden policy on request
+            if self.skip_hidden && !self.include_hidden_roots && is_hidden(&absolute_path) {
+                on_path(&absolute_path, ProcessingStatus::Excluded);
+                continue;
+            }
+
             // Step 2: Validate the path (check if it exists)
             let metadata = match fs::metadata(&absolute_path).await {
                 Ok(metadata) => metadata,
                 Err(e) => {
-                    on_path(&absolute_path, ProcessingStatus::Invalid(e.to_string()));
+                    on_path(&absolute_path, ProcessingStatus::from_io_error(&e));
                     continue;
                 }
             };
 
             // Step 3: Process based on whether it's a directory or file
-            if metadata.is_dir() {
+            if metadata.is_dir() && self.whole_directories && prefix_depth.is_none() {
+                // Step 3.1: Hand the directory to 7-Zip as a unit
+                self.add_file(absolute_path, None, &mut result_paths, &mut on_path);
+            } else if metadata.is_dir() {
                 // Step 3.2: If it's a directory, expand it
-                self.process_directory(&absolute_path, &mut result_paths, &mut on_path, matcher)
-                    .await?;
+                let started = self.clock.as_ref().map(|clock| clock.now());
+                self.process_directory(
+                    &absolute_path,
+                    max_depth,
+                    &mut result_paths,
+                    &mut on_path,
+                    matcher,
+                )
+                .await?;
+                if let (Some(clock), Some(started)) = (&self.clock, started) {
+                    let elapsed = clock.now().duration_since(started);
+                    self.directory_timings.push((absolute_path, elapsed));
+                }
             } else {
                 // Step 3.3: If it's a file, add it (if not already added)
-                if self.yielded_paths.insert(absolute_path.clone()) {
-                    on_path(&absolute_path, ProcessingStatus::Added);
-                    result_paths.push(absolute_path);
-                }
+                self.add_file(
+                    absolute_path,
+                    Some(&metadata),
+                    &mut result_paths,
+                    &mut on_path,
+                );
             }
         }
 
         Ok(result_paths)
     }
 
-    /// Process a directory recursively using walkdir
+    /// Count the files processing the input paths would consider adding: the named files and
+    /// the files walked in input directories that patterns and metadata filters keep.
+    ///
+    /// Content types, duplicates and hard links aren't checked, so the count is an upper
+    /// bound of the files added. It walks every input directory a second time.
+    pub async fn count_candidates(&self, matcher: &dyn ExclusionMatcher) -> Result<usize> {
+        let mut count = 0;
+
+        for input_entry in &self.input_paths {
+            let (prefix_depth, input_path) = Self::parse_depth_prefix(input_entry);
+            let absolute_path = Self::to_absolute_path(input_path).await?;
+            if self.should_exclude(&absolute_path, matcher)
+                || (self.skip_hidden && !self.include_hidden_roots && is_hidden(&absolute_path))
+            {
+                continue;
+            }
+            let Ok(metadata) = fs::metadata(&absolute_path).await else {
+                continue;
+            };
+
+            if !metadata.is_dir() || (self.whole_directories && prefix_depth.is_none()) {
+                count += 1;
+                continue;
+            }
+            let walk = self
+                .walk_files(&absolute_path, prefix_depth.or(self.max_depth))
+                .await?;
+            count += walk
+                .files
+                .iter()
+                .filter(|(path, metadata)| {
+                    !(self.is_not_included(path)
+                        || self.should_exclude(path, matcher)
+                        || self.is_outside_size_range(metadata.as_ref())
+                        || self.is_modified_too_long_ago(metadata.as_ref()))
+                })
+                .count();
+        }
+
+        Ok(count)
+    }
+
+    /// Add a file to the result unless it was already added or is another link to an added file
+    fn add_file<F>(
+        &mut self,
+        path: PathBuf,
+        metadata: Option<&std::fs::Metadata>,
+        result_paths: &mut Vec<PathBuf>,
+        on_path: &mut F,
+    ) where
+        F: FnMut(&PathBuf, ProcessingStatus),
+    {
+        let path = if self.canonicalize {
+            std::fs::canonicalize(&path).unwrap_or(path)
+        } else {
+            path
+        };
+
+        if !self.yielded_paths.insert(path.clone()) {
+            return;
+        }
+
+        // Followed symlinks can lead to a file again, so it's also tracked by its real path
+        if self.follow_symlinks
+            && !self.canonicalize
+            && let Ok(real_path) = std::fs::canonicalize(&path)
+            && real_path != path
+            && !self.yielded_paths.insert(real_path)
+        {
+            return;
+        }
+
+        if self.dedupe_hardlinks
+            && let Some(metadata) = metadata
+            && let Some(original) = self.record_inode(&path, metadata)
+        {
+            on_path(&path, ProcessingStatus::HardLink(original.clone()));
+            self.hardlinks.push((path, original));
+            return;
+        }
+
+        on_path(&path, ProcessingStatus::Added);
+        result_paths.push(path);
+    }
+
+    /// Remember the inode of a multiply-linked file, returning the first path seen for it
+    #[cfg(unix)]
+    fn record_inode(&mut self, path: &Path, metadata: &std::fs::Metadata) -> Option<PathBuf> {
+        use std::collections::hash_map::Entry;
+        use std::os::unix::fs::MetadataExt;
+
+        if metadata.nlink() < 2 {
+            return None;
+        }
+
+        match self.seen_inodes.entry((metadata.dev(), metadata.ino())) {
+            Entry::Occupied(entry) => Some(entry.get().clone()),
+            Entry::Vacant(entry) => {
+                entry.insert(path.to_path_buf());
+                None
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn record_inode(&mut self, _path: &Path, _metadata: &std::fs::Metadata) -> Option<PathBuf> {
+        None
+    }
+
+    /// Process a directory recursively, descending at most `max_depth` levels
     async fn process_directory<F>(
         &mut self,
         dir_path: &Path,
+        max_depth: Option<usize>,
         result_paths: &mut Vec<PathBuf>,
         on_path: &mut F,
         matcher: &dyn ExclusionMatcher,
@@ -212,40 +674,369 @@ impl PathProcessor {
     where
         F: FnMut(&PathBuf, ProcessingStatus),
     {
+        if self.accessed_before.is_some() && access_times_unreliable(dir_path) {
+            eprintln!(
+                "Warning: {} is on a noatime mount; access times may be stale, so --accessed-before can select recently used files",
+                dir_path.display()
+            );
+        }
+
+        let walk = self.walk_files(dir_path, max_depth).await?;
+        for path in walk.denied {
+            on_path(&path, ProcessingStatus::PermissionDenied);
+        }
+        for (path, reason) in walk.skipped {
+            on_path(&path, ProcessingStatus::Skipped(reason));
+        }
+
+        for (path, metadata) in walk.files {
+            // Apply exclusion patterns to each file
+            if self.is_not_included(&path)
+                || self.should_exclude(&path, matcher)
+                || self.is_outside_size_range(metadata.as_ref())
+                || self.is_modified_too_long_ago(metadata.as_ref())
+                || self.has_excluded_type(&path)
+            {
+                on_path(&path, ProcessingStatus::Excluded);
+                continue;
+            }
+
+            if let Some(cutoff) = self.accessed_before
+                && let Some(accessed) = metadata.as_ref().and_then(|m| m.accessed().ok())
+                && accessed > cutoff
+            {
+                on_path(
+                    &path,
+                    ProcessingStatus::Skipped("accessed recently".to_string()),
+                );
+                continue;
+            }
+
+            // Add file if not already added
+            self.add_file(path, metadata.as_ref(), result_paths, on_path);
+        }
+
+        Ok(())
+    }
+
+    /// Whether walked files need their metadata, for hard link, time or size checks
+    fn needs_metadata(&self) -> bool {
+        self.dedupe_hardlinks
+            || self.accessed_before.is_some()
+            || self.modified_after.is_some()
+            || self.min_file_size.is_some()
+            || self.max_file_size.is_some()
+    }
+
+    /// Check if a walked file was last modified before the modification cutoff, if one is set
+    fn is_modified_too_long_ago(&self, metadata: Option<&std::fs::Metadata>) -> bool {
+        let (Some(cutoff), Some(modified)) = (
+            self.modified_after,
+            metadata.and_then(|metadata| metadata.modified().ok()),
+        ) else {
+            return false;
+        };
+        modified < cutoff
+    }
+
+    /// Check if a walked file's size is outside the size range, if one is set
+    fn is_outside_size_range(&self, metadata: Option<&std::fs::Metadata>) -> bool {
+        let Some(size) = metadata.map(|metadata| metadata.len()) else {
+            return false;
+        };
+        self.min_file_size.is_some_and(|min| size < min)
+            || self.max_file_size.is_some_and(|max| size > max)
+    }
+
+    /// List the files below `dir_path` on a blocking thread, so walking a huge tree
+    /// doesn't hold up the runtime
+    async fn walk_files(&self, dir_path: &Path, max_depth: Option<usize>) -> Result<Walk> {
+        let options = WalkOptions {
+            skip_hidden: self.skip_hidden,
+            respect_gitignore: self.respect_gitignore,
+            follow_symlinks: self.follow_symlinks,
+            walk_jobs: self.walk_jobs,
+            needs_metadata: self.needs_metadata(),
+        };
+        let dir_path = dir_path.to_path_buf();
+        tokio::task::spawn_blocking(move || options.walk(&dir_path, max_depth))
+            .await
+            .map_err(|e| ArchtreeError::other(format!("Failed to walk directory: {}", e)))
+    }
+}
+
+/// Settings of [`PathProcessor`] that decide how a directory is walked
+#[derive(Clone, Copy)]
+struct WalkOptions {
+    skip_hidden: bool,
+    respect_gitignore: bool,
+    follow_symlinks: bool,
+    walk_jobs: usize,
+    needs_metadata: bool,
+}
+
+impl WalkOptions {
+    /// List the files below `dir_path`, with their metadata when `needs_metadata` is set
+    fn walk(&self, dir_path: &Path, max_depth: Option<usize>) -> Walk {
+        // Hidden entries below the root are pruned, so hidden directories aren't descended into
+        let skip_hidden = self.skip_hidden;
+        let mut walk = Walk::default();
+
+        if self.respect_gitignore || self.walk_jobs > 1 {
+            // The ignore walker reads each directory's .gitignore as it descends, so its
+            // rules only apply within that directory's subtree; it can also walk in parallel
+            let mut builder = ignore::WalkBuilder::new(dir_path);
+            builder
+                .standard_filters(false)
+                .git_ignore(self.respect_gitignore)
+                .require_git(false)
+                .max_depth(max_depth)
+                .follow_links(self.follow_symlinks)
+                .threads(self.walk_jobs)
+                .filter_entry(move |entry| {
+                    !(skip_hidden && entry.depth() > 0 && is_hidden(entry.path()))
+                });
+
+            if self.walk_jobs <= 1 {
+                for entry in builder.build() {
+                    if let Some(item) = self.classify_ignore_entry(entry, dir_path) {
+                        walk.push(item);
+                    }
+                }
+                return walk;
+            }
+
+            // Entries arrive in whatever order the threads find them, so they are sorted
+            let shared = Mutex::new(walk);
+            builder.build_parallel().run(|| {
+                Box::new(|entry| {
+                    if let Some(item) = self.classify_ignore_entry(entry, dir_path) {
+                        shared.lock().unwrap_or_else(|e| e.into_inner()).push(item);
+                    }
+                    ignore::WalkState::Continue
+                })
+            });
+            let mut walk = shared.into_inner().unwrap_or_else(|e| e.into_inner());
+            walk.sort();
+            return walk;
+        }
+
         // Use walkdir for efficient directory traversal
-        for entry in WalkDir::new(dir_path).into_iter() {
+        let mut walker = WalkDir::new(dir_path).follow_links(self.follow_symlinks);
+        if let Some(depth) = max_depth {
+            walker = walker.max_depth(depth);
+        }
+
+        let entries = walker
+            .into_iter()
+            .filter_entry(|entry| !(skip_hidden && entry.depth() > 0 && is_hidden(entry.path())));
+
+        for entry in entries {
             let entry = match entry {
                 Ok(entry) => entry,
+                Err(e) if is_permission_denied(e.io_error()) => {
+                    walk.denied.push(e.path().unwrap_or(dir_path).to_path_buf());
+                    continue;
+                }
+                Err(ref e) if let Some(ancestor) = e.loop_ancestor() => {
+                    let child = e.path().unwrap_or(dir_path).to_path_buf();
+                    walk.skipped.push((child, symlink_loop_reason(ancestor)));
+                    continue;
+                }
                 Err(e) => {
                     eprintln!("Warning: Failed to read directory entry: {}", e);
                     continue;
                 }
             };
 
-            let path = entry.path().to_path_buf();
-
             // Skip if it's a directory (we only want files)
             if entry.file_type().is_dir() {
                 continue;
             }
-
-            // Apply exclusion patterns to each file
-            if self.should_exclude(&path, matcher) {
-                on_path(&path, ProcessingStatus::Excluded);
+            if is_unfollowed_directory_link(entry.path_is_symlink(), entry.path()) {
+                walk.skipped
+                    .push((entry.into_path(), UNFOLLOWED_LINK_REASON.to_string()));
                 continue;
             }
 
-            // Add file if not already added
-            if self.yielded_paths.insert(path.clone()) {
-                on_path(&path, ProcessingStatus::Added);
-                result_paths.push(path);
+            let metadata = if self.needs_metadata {
+                entry.metadata().ok()
+            } else {
+                None
+            };
+            walk.files.push((entry.into_path(), metadata));
+        }
+
+        walk
+    }
+
+    /// What an entry of the gitignore-aware walker adds to a walk, if anything
+    fn classify_ignore_entry(
+        &self,
+        entry: std::result::Result<ignore::DirEntry, ignore::Error>,
+        dir_path: &Path,
+    ) -> Option<WalkItem> {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) if is_permission_denied(e.io_error()) => {
+                let path = ignore_error_path(&e).unwrap_or(dir_path).to_path_buf();
+                return Some(WalkItem::Denied(path));
             }
+            Err(ref e) if let Some((ancestor, child)) = ignore_loop(e) => {
+                let reason = symlink_loop_reason(ancestor);
+                return Some(WalkItem::Skipped(child.to_path_buf(), reason));
+            }
+            Err(e) => {
+                eprintln!("Warning: Failed to read directory entry: {}", e);
+                return None;
+            }
+        };
+
+        // Skip if it's a directory (we only want files)
+        if entry.file_type().is_none_or(|file_type| file_type.is_dir()) {
+            return None;
+        }
+        if is_unfollowed_directory_link(entry.path_is_symlink(), entry.path()) {
+            let reason = UNFOLLOWED_LINK_REASON.to_string();
+            return Some(WalkItem::Skipped(entry.into_path(), reason));
         }
 
-        Ok(())
+        let metadata = if self.needs_metadata {
+            entry.metadata().ok()
+        } else {
+            None
+        };
+        Some(WalkItem::File((entry.into_path(), metadata)))
+    }
+}
+
+/// A walked file and its metadata, if it was collected
+type WalkedFile = (PathBuf, Option<std::fs::Metadata>);
+
+/// One entry found while walking that goes into a [`Walk`]
+enum WalkItem {
+    File(WalkedFile),
+    Denied(PathBuf),
+    Skipped(PathBuf, String),
+}
+
+/// What walking an input directory found
+#[derive(Default)]
+struct Walk {
+    files: Vec<WalkedFile>,
+    /// Entries that couldn't be read because access was denied
+    denied: Vec<PathBuf>,
+    /// Entries left out while walking, with the reason
+    skipped: Vec<(PathBuf, String)>,
+}
+
+impl Walk {
+    fn push(&mut self, item: WalkItem) {
+        match item {
+            WalkItem::File(file) => self.files.push(file),
+            WalkItem::Denied(path) => self.denied.push(path),
+            WalkItem::Skipped(path, reason) => self.skipped.push((path, reason)),
+        }
+    }
+
+    /// Sort every list by path
+    fn sort(&mut self) {
+        self.files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.denied.sort();
+        self.skipped.sort();
     }
 }
 
+/// Reason reported for a symlink to a directory when symlinks aren't followed
+pub(crate) const UNFOLLOWED_LINK_REASON: &str = "symlink to a directory (see --follow-symlinks)";
+
+/// Whether a walked entry that isn't a directory is a symlink to one, which is only the
+/// case when symlinks aren't followed
+fn is_unfollowed_directory_link(is_symlink: bool, path: &Path) -> bool {
+    is_symlink && path.is_dir()
+}
+
+fn symlink_loop_reason(ancestor: &Path) -> String {
+    format!("symlink loop back to {}", ancestor.display())
+}
+
+/// Ancestor and looping child of a loop error of the gitignore-aware walker
+fn ignore_loop(error: &ignore::Error) -> Option<(&Path, &Path)> {
+    match error {
+        ignore::Error::Loop { ancestor, child } => Some((ancestor, child)),
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => ignore_loop(err),
+        _ => None,
+    }
+}
+
+/// Advice for paths that couldn't be read because access was denied
+#[cfg(windows)]
+pub const PERMISSION_DENIED_HINT: &str = "run archtree from an elevated (Administrator) prompt, or grant your account read access (e.g. with icacls)";
+
+/// Advice for paths that couldn't be read because access was denied
+#[cfg(not(windows))]
+pub const PERMISSION_DENIED_HINT: &str = "run archtree as a user that can read it (e.g. with sudo), or adjust its permissions or ACLs (chmod, setfacl)";
+
+/// Message reported for a path that couldn't be read because access was denied
+pub fn permission_denied_message(path: &Path) -> String {
+    format!(
+        "Permission denied: {} ({})",
+        path.display(),
+        PERMISSION_DENIED_HINT
+    )
+}
+
+fn is_permission_denied(error: Option<&std::io::Error>) -> bool {
+    error.is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied)
+}
+
+/// Path an error of the gitignore-aware walker is about
+fn ignore_error_path(error: &ignore::Error) -> Option<&Path> {
+    match error {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            ignore_error_path(err)
+        }
+        _ => None,
+    }
+}
+
+/// Check if `dir` is on a filesystem mounted with `noatime`, where access times never change
+#[cfg(target_os = "linux")]
+fn access_times_unreliable(dir: &Path) -> bool {
+    let Ok(dir) = std::fs::canonicalize(dir) else {
+        return false;
+    };
+    let Ok(mounts) = std::fs::read_to_string("/proc/self/mounts") else {
+        return false;
+    };
+
+    // The mount with the longest mount point containing `dir` is the one it lives on
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            Some((*fields.get(1)?, *fields.get(3)?))
+        })
+        .filter(|(mount_point, _)| dir.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .is_some_and(|(_, options)| options.split(',').any(|option| option == "noatime"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn access_times_unreliable(_dir: &Path) -> bool {
+    false
+}
+
+/// Check if a path names a hidden file or directory (name starting with a dot)
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().starts_with('.'))
+        .unwrap_or(false)
+}
+
 /// Status of path processing for callback reporting
 #[derive(Debug, Clone)]
 pub enum ProcessingStatus {
@@ -255,6 +1046,23 @@ pub enum ProcessingStatus {
     Excluded,
     /// Path was invalid (doesn't exist or inaccessible)
     Invalid(String),
+    /// Path is a hard link to a file that was already added (the contained path)
+    HardLink(PathBuf),
+    /// Path didn't pass a file filter (the reason)
+    Skipped(String),
+    /// Path or directory couldn't be read because access was denied
+    PermissionDenied,
+}
+
+impl ProcessingStatus {
+    /// Status of a path whose metadata couldn't be read
+    pub fn from_io_error(error: &std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::PermissionDenied {
+            Self::PermissionDenied
+        } else {
+            Self::Invalid(error.to_string())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -292,6 +1100,14 @@ mod tests {
 
         assert_eq!(include, vec!["file1.txt", "dir/file2.txt"]);
         assert_eq!(exclude, vec!["*.tmp", "cache/*"]);
+
+        let (inputs, include_patterns) = PathProcessor::extract_include_patterns(&[
+            "src".to_string(),
+            "+*.rs".to_string(),
+            "+Cargo.toml".to_string(),
+        ]);
+        assert_eq!(inputs, vec!["src"]);
+        assert_eq!(include_patterns, vec!["*.rs", "Cargo.toml"]);
     }
 
     #[tokio::test]
@@ -304,6 +1120,360 @@ mod tests {
         assert!(!matcher.matches(Path::new("file.txt"), ""));
     }
 
+    #[test]
+    fn test_patterns_match_deep_absolute_paths() {
+        let matcher = WildcardMatcher::with_patterns(&[
+            "*.tmp".to_string(),
+            "cache/*".to_string(),
+            "node_modules/**".to_string(),
+        ])
+        .unwrap();
+
+        assert!(matcher.matches(Path::new("C:\\Users\\me\\project\\build\\out.tmp"), ""));
+        assert!(matcher.matches(Path::new("C:/users/me/project/cache/data.json"), ""));
+        assert!(matcher.matches(Path::new("/home/me/project/cache/nested/data.json"), ""));
+        assert!(matcher.matches(
+            Path::new("/home/me/project/web/node_modules/react/index.js"),
+            ""
+        ));
+        assert!(!matcher.matches(Path::new("/home/me/project/mycache/data.json"), ""));
+        assert!(!matcher.matches(Path::new("/home/me/project/src/main.rs"), ""));
+
+        // `**/` also matches no directory at all
+        let nested = WildcardMatcher::with_patterns(&["src/**/*.rs".to_string()]).unwrap();
+        assert!(nested.matches(Path::new("/home/me/project/src/main.rs"), ""));
+        assert!(nested.matches(Path::new("/home/me/project/src/io/input.rs"), ""));
+        assert!(!nested.matches(Path::new("/home/me/project/tests/main.rs"), ""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_case_sensitive_patterns() {
+        let patterns = vec!["*.TMP".to_string()];
+
+        let sensitive = WildcardMatcher::with_case_sensitivity(&patterns, true).unwrap();
+        assert!(!sensitive.matches(Path::new("/data/file.tmp"), ""));
+        assert!(sensitive.matches(Path::new("/data/FILE.TMP"), ""));
+
+        let insensitive = WildcardMatcher::with_case_sensitivity(&patterns, false).unwrap();
+        assert!(insensitive.matches(Path::new("/data/file.tmp"), ""));
+
+        // Compiling patterns without an override agrees with the empty matcher
+        let default = WildcardMatcher::with_patterns(&patterns).unwrap();
+        assert_eq!(
+            default.is_case_sensitive(),
+            WildcardMatcher::new().is_case_sensitive()
+        );
+        assert_eq!(
+            default.matches(Path::new("/data/file.tmp"), ""),
+            !default.is_case_sensitive()
+        );
+    }
+
+    #[test]
+    fn test_uppercase_pattern_excludes_lowercase_file_only_when_insensitive() {
+        let patterns = vec!["*.TXT".to_string()];
+        let file = Path::new("/data/file.txt");
+
+        let insensitive = WildcardMatcher::with_case_sensitivity(&patterns, false).unwrap();
+        assert!(!insensitive.is_case_sensitive());
+        assert!(insensitive.matches(file, ""));
+
+        let sensitive = WildcardMatcher::with_case_sensitivity(&patterns, true).unwrap();
+        assert!(sensitive.is_case_sensitive());
+        assert!(!sensitive.matches(file, ""));
+        assert!(sensitive.matches(Path::new("/data/FILE.TXT"), ""));
+    }
+
+    #[tokio::test]
+    async fn test_anchored_and_unanchored_patterns() {
+        let anchored = WildcardMatcher::with_patterns(&["/tmp/*".to_string()]).unwrap();
+        assert!(anchored.matches(Path::new("/tmp/session.log"), ""));
+        assert!(anchored.matches(Path::new("C:\\tmp\\session.log"), ""));
+        assert!(!anchored.matches(Path::new("/home/me/tmp/session.log"), ""));
+
+        let unanchored = WildcardMatcher::with_patterns(&["tmp/*".to_string()]).unwrap();
+        assert!(unanchored.matches(Path::new("/tmp/session.log"), ""));
+        assert!(unanchored.matches(Path::new("/home/me/tmp/session.log"), ""));
+        assert!(!unanchored.matches(Path::new("/home/me/mytmp/session.log"), ""));
+    }
+
+    #[test]
+    fn test_drive_anchored_patterns() {
+        let patterns = vec!["C:\\Data\\*".to_string()];
+
+        let insensitive = WildcardMatcher::with_case_sensitivity(&patterns, false).unwrap();
+        assert!(insensitive.matches(Path::new("C:\\Data\\report.txt"), ""));
+        assert!(insensitive.matches(Path::new("c:\\data\\report.txt"), ""));
+        assert!(insensitive.matches(Path::new("C:/DATA/report.txt"), ""));
+        assert!(!insensitive.matches(Path::new("D:\\Data\\report.txt"), ""));
+        assert!(!insensitive.matches(Path::new("C:\\Backup\\Data\\report.txt"), ""));
+
+        // The drive letter ignores case even when the rest of the path doesn't
+        let sensitive = WildcardMatcher::with_case_sensitivity(&patterns, true).unwrap();
+        assert!(sensitive.matches(Path::new("c:\\Data\\report.txt"), ""));
+        assert!(!sensitive.matches(Path::new("C:\\data\\report.txt"), ""));
+    }
+
+    #[test]
+    fn test_parse_depth_prefix() {
+        assert_eq!(
+            PathProcessor::parse_depth_prefix("depth:2:C:\\data"),
+            (Some(2), "C:\\data")
+        );
+        assert_eq!(
+            PathProcessor::parse_depth_prefix("depth:0:/srv"),
+            (Some(0), "/srv")
+        );
+        assert_eq!(PathProcessor::parse_depth_prefix("/srv"), (None, "/srv"));
+        assert_eq!(
+            PathProcessor::parse_depth_prefix("depth:x:/srv"),
+            (None, "depth:x:/srv")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_per_root_depth_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        for root in ["limited", "unlimited"] {
+            let nested = temp_dir.path().join(root).join("a").join("b");
+            fs::create_dir_all(&nested).unwrap();
+            fs::write(temp_dir.path().join(root).join("top.txt"), "top").unwrap();
+            fs::write(nested.join("deep.txt"), "deep").unwrap();
+        }
+
+        let limited = temp_dir.path().join("limited");
+        let unlimited = temp_dir.path().join("unlimited");
+        let input_paths = vec![
+            format!("depth:1:{}", limited.display()),
+            unlimited.to_string_lossy().to_string(),
+        ];
+
+        let mut processor = PathProcessor::new(input_paths, Vec::new()).unwrap();
+        let matcher = WildcardMatcher::new();
+        let result_paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+
+        assert_eq!(result_paths.len(), 3);
+        assert!(result_paths.contains(&limited.join("top.txt")));
+        assert!(!result_paths.contains(&limited.join("a").join("b").join("deep.txt")));
+        assert!(result_paths.contains(&unlimited.join("top.txt")));
+        assert!(result_paths.contains(&unlimited.join("a").join("b").join("deep.txt")));
+    }
+
+    #[tokio::test]
+    async fn test_max_depth_applies_per_input_root() {
+        let temp_dir = TempDir::new().unwrap();
+        for root in ["first", "second"] {
+            let nested = temp_dir.path().join(root).join("a").join("b");
+            fs::create_dir_all(&nested).unwrap();
+            fs::write(temp_dir.path().join(root).join("top.txt"), "top").unwrap();
+            fs::write(temp_dir.path().join(root).join("a").join("mid.txt"), "mid").unwrap();
+            fs::write(nested.join("deep.txt"), "deep").unwrap();
+        }
+        let first = temp_dir.path().join("first");
+        let second = temp_dir.path().join("second");
+        let matcher = WildcardMatcher::new();
+
+        // Both roots keep only their immediate children, however deep each root is
+        let input_paths = vec![
+            first.to_string_lossy().to_string(),
+            second.join("a").to_string_lossy().to_string(),
+        ];
+        let mut processor = PathProcessor::new(input_paths, Vec::new())
+            .unwrap()
+            .with_max_depth(Some(1));
+        let mut result_paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        result_paths.sort();
+        assert_eq!(
+            result_paths,
+            vec![first.join("top.txt"), second.join("a").join("mid.txt")]
+        );
+
+        // A depth: prefix takes precedence
+        let input_paths = vec![format!("depth:2:{}", first.display())];
+        let mut processor = PathProcessor::new(input_paths, Vec::new())
+            .unwrap()
+            .with_max_depth(Some(1));
+        let result_paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        assert_eq!(result_paths.len(), 2);
+        assert!(result_paths.contains(&first.join("a").join("mid.txt")));
+    }
+
+    #[tokio::test]
+    async fn test_count_candidates_applies_patterns_and_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("src");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.join("nested").join("lib.rs"), "").unwrap();
+        fs::write(dir.join("notes.tmp"), "tmp").unwrap();
+        fs::write(dir.join("big.rs"), vec![b'b'; 5000]).unwrap();
+        let named = temp_dir.path().join("named.tmp");
+        fs::write(&named, "named").unwrap();
+
+        let inputs = vec![
+            dir.to_string_lossy().to_string(),
+            named.to_string_lossy().to_string(),
+            temp_dir
+                .path()
+                .join("missing")
+                .to_string_lossy()
+                .to_string(),
+        ];
+        let exclusions = vec!["*/src/*.tmp".to_string()];
+        let matcher = WildcardMatcher::with_patterns(&exclusions).unwrap();
+        let mut processor = PathProcessor::new(inputs, exclusions)
+            .unwrap()
+            .with_file_size_range(None, Some(1000));
+
+        // main.rs, lib.rs and the named file; the excluded and the too large files aren't
+        let count = processor.count_candidates(&matcher).await.unwrap();
+        assert_eq!(count, 3);
+        let mut added = 0;
+        processor
+            .process_paths(
+                |_, status| {
+                    if let ProcessingStatus::Added = status {
+                        added += 1;
+                    }
+                },
+                &matcher,
+            )
+            .await
+            .unwrap();
+        assert_eq!(added, count);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_walk_matches_serial_walk_sorted() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("tree");
+        for i in 0..8 {
+            let nested = root.join(format!("dir{}", i)).join("sub");
+            fs::create_dir_all(&nested).unwrap();
+            for j in 0..5 {
+                fs::write(
+                    root.join(format!("dir{}", i)).join(format!("{}.txt", j)),
+                    "x",
+                )
+                .unwrap();
+                fs::write(nested.join(format!("{}.log", j)), "x").unwrap();
+            }
+        }
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+        fs::write(root.join(".hidden").join("secret.txt"), "x").unwrap();
+
+        let inputs = vec![root.to_string_lossy().to_string()];
+        let exclusions = vec!["*.log".to_string()];
+        let matcher = WildcardMatcher::with_patterns(&exclusions).unwrap();
+        let walk = |jobs: usize| {
+            let processor = PathProcessor::new(inputs.clone(), exclusions.clone())
+                .unwrap()
+                .with_skip_hidden(true)
+                .with_walk_jobs(jobs);
+            let matcher = &matcher;
+            async move {
+                let mut processor = processor;
+                let mut reported = Vec::new();
+                let paths = processor
+                    .process_paths(|path, _| reported.push(path.to_path_buf()), matcher)
+                    .await
+                    .unwrap();
+                (paths, reported)
+            }
+        };
+
+        let (mut serial, _) = walk(1).await;
+        serial.sort();
+        let (parallel, reported) = walk(4).await;
+        assert_eq!(serial.len(), 40);
+        assert_eq!(parallel, serial);
+        assert!(reported.is_sorted());
+    }
+
+    #[tokio::test]
+    async fn test_file_size_range_excludes_walked_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("docs");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("empty.txt"), "").unwrap();
+        fs::write(dir.join("notes.txt"), vec![b'n'; 100]).unwrap();
+        fs::write(dir.join("huge.log"), vec![b'l'; 5000]).unwrap();
+        let named = temp_dir.path().join("named.log");
+        fs::write(&named, vec![b'l'; 5000]).unwrap();
+
+        let inputs = vec![
+            dir.to_string_lossy().to_string(),
+            named.to_string_lossy().to_string(),
+        ];
+        let mut processor = PathProcessor::new(inputs, Vec::new())
+            .unwrap()
+            .with_file_size_range(Some(1), Some(1000));
+        let mut excluded = Vec::new();
+        let paths = processor
+            .process_paths(
+                |path, status| {
+                    if let ProcessingStatus::Excluded = status {
+                        excluded.push(path.clone());
+                    }
+                },
+                &WildcardMatcher::new(),
+            )
+            .await
+            .unwrap();
+
+        // Explicitly named files are kept whatever their size
+        assert_eq!(paths, vec![dir.join("notes.txt"), named]);
+        excluded.sort();
+        assert_eq!(excluded, vec![dir.join("empty.txt"), dir.join("huge.log")]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_dedupe_hardlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let original = temp_dir.path().join("a_original.txt");
+        let link = temp_dir.path().join("b_link.txt");
+        let other = temp_dir.path().join("c_other.txt");
+        fs::write(&original, "shared").unwrap();
+        fs::hard_link(&original, &link).unwrap();
+        fs::write(&other, "other").unwrap();
+
+        let input_paths = vec![temp_dir.path().to_string_lossy().to_string()];
+        let matcher = WildcardMatcher::new();
+
+        // Without deduplication both links are archived
+        let mut processor = PathProcessor::new(input_paths.clone(), Vec::new()).unwrap();
+        let result_paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        assert_eq!(result_paths.len(), 3);
+
+        let mut processor = PathProcessor::new(input_paths, Vec::new())
+            .unwrap()
+            .with_dedupe_hardlinks(true);
+        let mut hardlink_statuses = 0;
+        let result_paths = processor
+            .process_paths(
+                |_, status| {
+                    if matches!(status, ProcessingStatus::HardLink(_)) {
+                        hardlink_statuses += 1;
+                    }
+                },
+                &matcher,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result_paths.len(), 2);
+        assert!(result_paths.contains(&other));
+        assert_eq!(hardlink_statuses, 1);
+        assert_eq!(processor.hardlinks().len(), 1);
+
+        // Exactly one of the two links was kept, and the skipped one points at it
+        let (duplicate, kept) = &processor.hardlinks()[0];
+        assert!(result_paths.contains(kept));
+        assert!(!result_paths.contains(duplicate));
+    }
+
     #[tokio::test]
     async fn test_path_processor() {
         // Create temporary test structure
@@ -345,4 +1515,426 @@ mod tests {
         assert!(result_paths.iter().any(|p| p.ends_with("test3.txt")));
         assert!(!result_paths.iter().any(|p| p.ends_with("test2.tmp")));
     }
+
+    #[tokio::test]
+    async fn test_regex_exclusions_combine_with_wildcards() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["keep.txt", "draft.bak", "notes.swp", "debug.log"] {
+            fs::write(temp_dir.path().join(name), name).unwrap();
+        }
+
+        let mut processor = PathProcessor::new(
+            vec![temp_dir.path().to_string_lossy().to_string()],
+            vec!["*.log".to_string()],
+        )
+        .unwrap()
+        .with_exclude_regex(RegexMatcher::new(&[r"\.(tmp|bak|swp)$".to_string()]).unwrap());
+        let matcher = WildcardMatcher::with_patterns(&processor.exclusion_patterns).unwrap();
+
+        let result_paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        assert_eq!(result_paths, vec![temp_dir.path().join("keep.txt")]);
+    }
+
+    #[tokio::test]
+    async fn test_hidden_root_included_but_discovered_hidden_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_dir = temp_dir.path().join(".config");
+        fs::create_dir_all(config_dir.join(".cache")).unwrap();
+        fs::write(config_dir.join("settings.toml"), "settings").unwrap();
+        fs::write(config_dir.join(".cache").join("blob"), "blob").unwrap();
+        fs::write(config_dir.join(".token"), "token").unwrap();
+
+        let inputs = vec![config_dir.to_string_lossy().to_string()];
+        let matcher = WildcardMatcher::new();
+
+        let mut processor = PathProcessor::new(inputs.clone(), Vec::new())
+            .unwrap()
+            .with_skip_hidden(true);
+        let paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        assert_eq!(paths, vec![config_dir.join("settings.toml")]);
+
+        let mut processor = PathProcessor::new(inputs, Vec::new())
+            .unwrap()
+            .with_skip_hidden(true)
+            .with_include_hidden_roots(false);
+        let paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        assert!(paths.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_nested_gitignore_applies_to_its_subtree() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("project");
+        let sub = root.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(sub.join(".gitignore"), "*.tmp\n").unwrap();
+        for file in ["a.log", "a.tmp", "a.txt"] {
+            fs::write(root.join(file), file).unwrap();
+            fs::write(sub.join(file), file).unwrap();
+        }
+
+        let inputs = vec![root.to_string_lossy().to_string()];
+        let matcher = WildcardMatcher::new();
+        let mut processor = PathProcessor::new(inputs, Vec::new())
+            .unwrap()
+            .with_respect_gitignore(true);
+        let mut paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        paths.sort();
+
+        // *.log is ignored everywhere, *.tmp only below sub/
+        let mut expected = vec![
+            root.join(".gitignore"),
+            root.join("a.tmp"),
+            root.join("a.txt"),
+            sub.join(".gitignore"),
+            sub.join("a.txt"),
+        ];
+        expected.sort();
+        assert_eq!(paths, expected);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_canonicalize_collapses_aliases() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let dir = root.join("dir");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("file.txt"), "content").unwrap();
+
+        let inputs = vec![
+            format!("{}/./file.txt", dir.display()),
+            format!("{}/file.txt", dir.display()),
+            format!("{}/sub/../file.txt", dir.display()),
+        ];
+        let matcher = WildcardMatcher::new();
+
+        let mut processor = PathProcessor::new(inputs.clone(), Vec::new()).unwrap();
+        let paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        assert_eq!(paths.len(), 2);
+
+        let mut processor = PathProcessor::new(inputs, Vec::new())
+            .unwrap()
+            .with_canonicalize(true);
+        let paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        assert_eq!(paths, vec![dir.join("file.txt")]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_follow_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let input = root.join("input");
+        let shared = root.join("shared");
+        fs::create_dir_all(&input).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+        fs::write(input.join("own.txt"), "own").unwrap();
+        fs::write(shared.join("data.txt"), "data").unwrap();
+        fs::write(shared.join("more.txt"), "more").unwrap();
+        symlink(shared.join("data.txt"), input.join("file_link")).unwrap();
+        symlink(&shared, input.join("dir_link")).unwrap();
+        symlink(&input, input.join("loop")).unwrap();
+
+        let inputs = vec![input.to_string_lossy().to_string()];
+        let matcher = WildcardMatcher::new();
+        let run = |follow: bool| {
+            let inputs = inputs.clone();
+            let matcher = &matcher;
+            async move {
+                let mut processor = PathProcessor::new(inputs, Vec::new())
+                    .unwrap()
+                    .with_follow_symlinks(follow);
+                let mut skipped = Vec::new();
+                let mut paths = processor
+                    .process_paths(
+                        |path, status| {
+                            if let ProcessingStatus::Skipped(reason) = status {
+                                skipped.push((path.clone(), reason));
+                            }
+                        },
+                        matcher,
+                    )
+                    .await
+                    .unwrap();
+                paths.sort();
+                skipped.sort();
+                (paths, skipped)
+            }
+        };
+
+        // Symlinks to files are added, symlinked directories aren't descended into
+        let (paths, skipped) = run(false).await;
+        assert_eq!(paths, vec![input.join("file_link"), input.join("own.txt")]);
+        assert_eq!(
+            skipped.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            vec![&input.join("dir_link"), &input.join("loop")]
+        );
+        assert!(skipped[0].1.contains("--follow-symlinks"));
+
+        // Followed, each file is added once and the loop is cut
+        let (paths, skipped) = run(true).await;
+        let mut real_paths: Vec<PathBuf> = paths
+            .iter()
+            .map(|path| path.canonicalize().unwrap())
+            .collect();
+        real_paths.sort();
+        assert_eq!(
+            real_paths,
+            vec![
+                input.join("own.txt"),
+                shared.join("data.txt"),
+                shared.join("more.txt")
+            ]
+        );
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].0, input.join("loop"));
+        assert!(skipped[0].1.contains("symlink loop"), "{}", skipped[0].1);
+    }
+
+    #[tokio::test]
+    async fn test_exclude_type_skips_images() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        png.extend(std::iter::repeat_n(0u8, 64));
+        // The extension doesn't give the image away, only its magic bytes do
+        fs::write(temp_dir.path().join("scan.dat"), &png).unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "plain text").unwrap();
+
+        let inputs = vec![temp_dir.path().to_string_lossy().to_string()];
+        let mut processor = PathProcessor::new(inputs, Vec::new())
+            .unwrap()
+            .with_exclude_types(vec!["image".parse().unwrap()]);
+
+        let mut excluded = Vec::new();
+        let paths = processor
+            .process_paths(
+                |path, status| {
+                    if matches!(status, ProcessingStatus::Excluded) {
+                        excluded.push(path.clone());
+                    }
+                },
+                &WildcardMatcher::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(paths, vec![temp_dir.path().join("notes.txt")]);
+        assert_eq!(excluded, vec![temp_dir.path().join("scan.dat")]);
+    }
+
+    #[tokio::test]
+    async fn test_include_patterns_keep_only_matching_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let src = temp_dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]").unwrap();
+        fs::write(temp_dir.path().join("README.md"), "readme").unwrap();
+        fs::write(src.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(src.join("generated.rs"), "// generated").unwrap();
+        fs::write(src.join("logo.png"), "png").unwrap();
+
+        let inputs = vec![temp_dir.path().to_string_lossy().to_string()];
+        let mut processor = PathProcessor::new(inputs, vec!["generated.rs".to_string()])
+            .unwrap()
+            .with_include_patterns(WildcardMatcher::with_patterns(&["*.rs".to_string()]).unwrap());
+        let matcher = WildcardMatcher::with_patterns(processor.exclusion_patterns()).unwrap();
+
+        let mut paths = processor.process_paths(|_, _| {}, &matcher).await.unwrap();
+        paths.sort();
+
+        // Exclusions still apply on top of the includes
+        assert_eq!(paths, vec![src.join("main.rs")]);
+    }
+
+    /// Clock returning a scripted sequence of offsets from a fixed start
+    struct SpyClock {
+        start: Instant,
+        offsets: std::sync::Mutex<std::collections::VecDeque<u64>>,
+    }
+
+    impl Clock for SpyClock {
+        fn now(&self) -> Instant {
+            let offset = self.offsets.lock().unwrap().pop_front().unwrap();
+            self.start + Duration::from_secs(offset)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slowest_directory_reported_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let fast = temp_dir.path().join("fast");
+        let slow = temp_dir.path().join("slow");
+        for dir in [&fast, &slow] {
+            fs::create_dir(dir).unwrap();
+            fs::write(dir.join("file.txt"), "content").unwrap();
+        }
+
+        // The walk of `fast` takes 1s, the walk of `slow` 3s
+        let clock = SpyClock {
+            start: Instant::now(),
+            offsets: std::sync::Mutex::new([0, 1, 1, 4].into()),
+        };
+        let inputs = vec![
+            fast.to_string_lossy().to_string(),
+            slow.to_string_lossy().to_string(),
+        ];
+        let mut processor = PathProcessor::new(inputs, Vec::new())
+            .unwrap()
+            .with_profiling(Arc::new(clock));
+        processor
+            .process_paths(|_, _| {}, &WildcardMatcher::new())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            processor.slowest_directories(5),
+            vec![
+                (slow.clone(), Duration::from_secs(3)),
+                (fast, Duration::from_secs(1))
+            ]
+        );
+        assert_eq!(
+            processor.slowest_directories(1),
+            vec![(slow, Duration::from_secs(3))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_accessed_before_selects_cold_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let cold = temp_dir.path().join("cold.txt");
+        let warm = temp_dir.path().join("warm.txt");
+        fs::write(&cold, "cold").unwrap();
+        fs::write(&warm, "warm").unwrap();
+
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        for (path, accessed) in [(&cold, now - 60 * day), (&warm, now - day)] {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_times(std::fs::FileTimes::new().set_accessed(accessed))
+                .unwrap();
+        }
+
+        let inputs = vec![temp_dir.path().to_string_lossy().to_string()];
+        let mut processor = PathProcessor::new(inputs, Vec::new())
+            .unwrap()
+            .with_accessed_before(now - 30 * day);
+
+        let mut skipped = Vec::new();
+        let paths = processor
+            .process_paths(
+                |path, status| {
+                    if let ProcessingStatus::Skipped(_) = status {
+                        skipped.push(path.clone());
+                    }
+                },
+                &WildcardMatcher::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(paths, vec![cold]);
+        assert_eq!(skipped, vec![warm]);
+    }
+
+    #[tokio::test]
+    async fn test_modified_after_excludes_old_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let old = temp_dir.path().join("old.txt");
+        let recent = temp_dir.path().join("recent.txt");
+        let large = temp_dir.path().join("large.txt");
+        fs::write(&old, "old").unwrap();
+        fs::write(&recent, "recent").unwrap();
+        fs::write(&large, vec![b'l'; 5000]).unwrap();
+
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(now - 10 * day)
+            .unwrap();
+
+        let inputs = vec![temp_dir.path().to_string_lossy().to_string()];
+        let mut processor = PathProcessor::new(inputs, Vec::new())
+            .unwrap()
+            .with_modified_after(now - 7 * day)
+            .with_file_size_range(None, Some(1000));
+
+        let mut excluded = Vec::new();
+        let paths = processor
+            .process_paths(
+                |path, status| {
+                    if let ProcessingStatus::Excluded = status {
+                        excluded.push(path.clone());
+                    }
+                },
+                &WildcardMatcher::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(paths, vec![recent]);
+        excluded.sort();
+        assert_eq!(excluded, vec![large, old]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_permission_denied_is_reported_with_hint() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(matches!(
+            ProcessingStatus::from_io_error(&denied),
+            ProcessingStatus::PermissionDenied
+        ));
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(matches!(
+            ProcessingStatus::from_io_error(&missing),
+            ProcessingStatus::Invalid(_)
+        ));
+
+        let temp_dir = TempDir::new().unwrap();
+        let locked = temp_dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("secret.txt"), "secret").unwrap();
+        let message = permission_denied_message(&locked);
+        assert!(message.starts_with(&format!("Permission denied: {}", locked.display())));
+        assert!(message.contains(PERMISSION_DENIED_HINT));
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        if fs::read_dir(&locked).is_ok() {
+            // Root reads the directory regardless of its mode, so nothing can be denied
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            eprintln!("skipping test_permission_denied_is_reported_with_hint: running as root");
+            return;
+        }
+
+        let inputs = vec![temp_dir.path().to_string_lossy().to_string()];
+        let mut processor = PathProcessor::new(inputs, Vec::new()).unwrap();
+        let mut reported = Vec::new();
+        processor
+            .process_paths(
+                |path, status| {
+                    if let ProcessingStatus::PermissionDenied = status {
+                        reported.push(path.clone());
+                    }
+                },
+                &WildcardMatcher::new(),
+            )
+            .await
+            .unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(reported, vec![locked]);
+    }
 }
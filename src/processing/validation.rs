@@ -1,6 +1,8 @@
 use crate::core::{ArchtreeError, Result};
+use crate::processing::path_processor::permission_denied_message;
 use async_trait::async_trait;
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
 /// Trait for path validation strategies
@@ -12,14 +14,14 @@ pub trait PathValidator: Send + Sync {
     /// Validate multiple paths and return only the valid ones
     async fn validate_paths(&self, paths: &[String]) -> Result<Vec<String>> {
         let mut valid_paths = Vec::new();
-        
+
         for path_str in paths {
             let path = Path::new(path_str);
             if self.validate(path).await? {
                 valid_paths.push(path_str.clone());
             }
         }
-        
+
         Ok(valid_paths)
     }
 
@@ -48,6 +50,13 @@ impl PathValidator for FileSystemValidator {
         match fs::metadata(path).await {
             Ok(_) => Ok(true),
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                Err(ArchtreeError::path_processing_with_source(
+                    permission_denied_message(path),
+                    Some(path.to_string_lossy().to_string()),
+                    e,
+                ))
+            }
             Err(e) => Err(ArchtreeError::path_processing_with_source(
                 "Failed to validate path",
                 Some(path.to_string_lossy().to_string()),
@@ -61,6 +70,28 @@ impl PathValidator for FileSystemValidator {
     }
 }
 
+/// Find paths that differ only by letter case.
+///
+/// Such paths can be archived from a case-sensitive filesystem, but restoring them to a
+/// case-insensitive one (Windows, default macOS) makes one overwrite the other. Each
+/// returned group holds the colliding paths in their original order.
+pub fn find_case_collisions(paths: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+    let mut by_folded: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for path in paths {
+        let group = by_folded
+            .entry(path.to_string_lossy().to_lowercase())
+            .or_default();
+        if !group.contains(path) {
+            group.push(path.clone());
+        }
+    }
+
+    by_folded
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,15 +103,34 @@ mod tests {
         let validator = FileSystemValidator::new();
         let temp_dir = TempDir::new().unwrap();
         let test_file = temp_dir.path().join("test.txt");
-        
+
         // Create test file
         fs::write(&test_file, "test content").unwrap();
-        
+
         // Should validate existing file
         assert!(validator.validate(&test_file).await.unwrap());
-        
+
         // Should not validate non-existent file
         let non_existent = temp_dir.path().join("non_existent.txt");
         assert!(!validator.validate(&non_existent).await.unwrap());
     }
+
+    #[test]
+    fn test_find_case_collisions() {
+        let paths = vec![
+            PathBuf::from("/data/README.md"),
+            PathBuf::from("/data/notes.txt"),
+            PathBuf::from("/data/readme.md"),
+            PathBuf::from("/data/Docs/a.txt"),
+            PathBuf::from("/data/docs/b.txt"),
+        ];
+
+        assert_eq!(
+            find_case_collisions(&paths),
+            vec![vec![
+                PathBuf::from("/data/README.md"),
+                PathBuf::from("/data/readme.md")
+            ]]
+        );
+    }
 }
@@ -1,8 +1,160 @@
-use crate::core::{ArchtreeError, Config, ErrorContext, Result};
-use crate::io::{Archiver, InputReader};
-use crate::processing::{PathProcessor, ProcessingStatus, WildcardMatcher};
-use std::path::PathBuf;
-use std::sync::OnceLock;
+use crate::core::{ArchtreeError, Config, ErrorContext, ProgressStyle, Result};
+use crate::io::archiver::check_list_paths;
+use crate::io::space::check_free_space;
+use crate::io::volumes::archive_size;
+use crate::io::{
+    Archiver, FreeSpaceProvider, InputReader, SummaryTable, SystemFreeSpace, without_emoji,
+};
+use crate::processing::path_processor::{
+    PERMISSION_DENIED_HINT, SystemClock, permission_denied_message,
+};
+use crate::processing::validation::find_case_collisions;
+use crate::processing::{
+    CompositeMatcher, ContentAnalysis, ContentType, PathProcessor, ProcessingStatus, RegexMatcher,
+    SizeEstimate, WildcardMatcher,
+};
+use crate::services::RunReport;
+use crate::services::chain::{ChainListing, changed_since, check_incremental_size};
+use crate::services::index::{write_hardlinks, write_index};
+use crate::services::structure::{
+    read_structure_hash, structure_hash, structure_hash_path, write_structure_hash,
+};
+use crate::verification::expand_cache::ExpandCache;
+use crate::verification::verifier::ArchiveVerifier;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Minimum time between updates of the `counter` progress line
+const COUNTER_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Progress line for the outcome of processing one path; added files get none
+fn path_status_message(path: &Path, status: &ProcessingStatus) -> Option<String> {
+    match status {
+        ProcessingStatus::Added => None,
+        ProcessingStatus::Excluded => Some(format!("🚫 Excluded: {}", path.display())),
+        ProcessingStatus::Invalid(error) => {
+            Some(format!("⚠️  Invalid path: {} ({})", path.display(), error))
+        }
+        ProcessingStatus::Skipped(reason) => {
+            Some(format!("⏭️  Skipped: {} ({})", path.display(), reason))
+        }
+        ProcessingStatus::HardLink(original) => Some(format!(
+            "🔗 Hard link: {} (same file as {})",
+            path.display(),
+            original.display()
+        )),
+        ProcessingStatus::PermissionDenied => {
+            Some(format!("🔒 {}", permission_denied_message(path)))
+        }
+    }
+}
+
+/// Counter progress line, starting with a carriage return so each update overwrites the last.
+///
+/// With the `total` of candidate files counted beforehand, the added files show against it.
+fn counter_line(summary: &BackupSummary, total: Option<usize>) -> String {
+    let processed = summary.added
+        + summary.excluded
+        + summary.invalid
+        + summary.hard_links
+        + summary.skipped
+        + summary.permission_denied;
+    let added = match total {
+        Some(total) => format!("{}/{}", summary.added, total),
+        None => summary.added.to_string(),
+    };
+    format!(
+        "\r⏳ Processed {} paths ({} added, {} excluded)",
+        processed, added, summary.excluded
+    )
+}
+
+/// Final statistics of a backup run, serializable for machine consumption
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackupSummary {
+    /// Path of the archive being written
+    pub output_path: String,
+    /// Files added to the archive
+    pub added: usize,
+    /// Paths excluded by exclusion patterns
+    pub excluded: usize,
+    /// Input paths that didn't exist or were inaccessible
+    pub invalid: usize,
+    /// Hard links skipped because their file was already added
+    pub hard_links: usize,
+    /// Files skipped by a file filter such as `--accessed-before`
+    pub skipped: usize,
+    /// Paths and directories that couldn't be read because access was denied
+    pub permission_denied: usize,
+    /// Total number of files handed to the archiver
+    pub total_files: usize,
+}
+
+impl BackupSummary {
+    /// Count a path under its processing status
+    pub fn record(&mut self, status: &ProcessingStatus) {
+        match status {
+            ProcessingStatus::Added => self.added += 1,
+            ProcessingStatus::Excluded => self.excluded += 1,
+            ProcessingStatus::Invalid(_) => self.invalid += 1,
+            ProcessingStatus::HardLink(_) => self.hard_links += 1,
+            ProcessingStatus::Skipped(_) => self.skipped += 1,
+            ProcessingStatus::PermissionDenied => self.permission_denied += 1,
+        }
+    }
+}
+
+/// Events that occur while the archive is being created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupEvent {
+    /// The archiver is about to be invoked
+    ArchivingStarted {
+        output_path: String,
+        file_count: usize,
+    },
+    /// The archiver finished creating the archive
+    ArchivingComplete {
+        output_path: String,
+        file_count: usize,
+    },
+}
+
+/// Trait for observing the archive creation phase
+pub trait BackupCallback: Send + Sync {
+    /// Called when a backup event occurs
+    fn on_event(&self, event: BackupEvent);
+}
+
+/// Console-based backup callback for CLI output
+pub struct ConsoleBackupCallback {
+    show_progress: bool,
+}
+
+impl ConsoleBackupCallback {
+    pub fn new(show_progress: bool) -> Self {
+        Self { show_progress }
+    }
+}
+
+impl BackupCallback for ConsoleBackupCallback {
+    fn on_event(&self, event: BackupEvent) {
+        if !self.show_progress {
+            return;
+        }
+
+        match event {
+            BackupEvent::ArchivingStarted { output_path, .. } => {
+                eprintln!("\n📦 Creating archive: {}", output_path);
+            }
+            BackupEvent::ArchivingComplete { output_path, .. } => {
+                eprintln!("✅ Archive created successfully: {}", output_path);
+            }
+        }
+    }
+}
 
 /// Backup service using the improved path processing algorithm
 pub struct BackupService<A>
@@ -14,6 +166,22 @@ where
     config: Config,
     /// Cached processed paths to avoid recomputation during verification
     processed_paths: OnceLock<Vec<PathBuf>>,
+    /// Statistics from the first processing pass
+    summary: OnceLock<BackupSummary>,
+    /// Hard links left out by the first processing pass, as (left out path, archived path)
+    hardlinks: OnceLock<Vec<(PathBuf, PathBuf)>>,
+    /// Observer for the archive creation phase
+    callback: Box<dyn BackupCallback>,
+    /// Destination for the paths of added files, one per line
+    added_output: Option<Mutex<Box<dyn Write + Send>>>,
+    /// Destination for progress while input paths are processed
+    progress_output: Mutex<Box<dyn Write + Send>>,
+    /// Files already archived by earlier chain members; only changes are archived
+    baseline: Option<ChainListing>,
+    /// Lists a created archive to catch 7-Zip succeeding without storing any file
+    archive_check: Option<Box<dyn ArchiveVerifier>>,
+    /// Free space on the output volume, checked before archiving
+    free_space: Box<dyn FreeSpaceProvider>,
 }
 
 impl<A> BackupService<A>
@@ -22,15 +190,87 @@ where
 {
     /// Create a new backup service with the given components
     pub fn new(archiver: A, reader: Box<dyn InputReader>, config: Config) -> Self {
+        let callback = Box::new(ConsoleBackupCallback::new(config.show_progress));
+        let added_output: Option<Mutex<Box<dyn Write + Send>>> = if config.print_added {
+            Some(Mutex::new(Box::new(std::io::stdout())))
+        } else {
+            None
+        };
         Self {
             archiver,
             reader,
             config,
             processed_paths: OnceLock::new(),
+            summary: OnceLock::new(),
+            hardlinks: OnceLock::new(),
+            callback,
+            added_output,
+            progress_output: Mutex::new(Box::new(std::io::stderr())),
+            baseline: None,
+            archive_check: None,
+            free_space: Box::new(SystemFreeSpace),
         }
     }
 
-    /// Get processed paths as strings (for verification compatibility)
+    /// Write the path of every added file to `output` instead of standard output
+    pub fn with_added_output<W: Write + Send + 'static>(mut self, output: W) -> Self {
+        self.added_output = Some(Mutex::new(Box::new(output)));
+        self
+    }
+
+    /// Write processing progress to `output` instead of standard error
+    pub fn with_progress_output<W: Write + Send + 'static>(mut self, output: W) -> Self {
+        self.progress_output = Mutex::new(Box::new(output));
+        self
+    }
+
+    /// Replace the callback that observes the archive creation phase
+    pub fn with_callback<C: BackupCallback + 'static>(mut self, callback: C) -> Self {
+        self.callback = Box::new(callback);
+        self
+    }
+
+    /// Archive only files that are missing from or newer than `baseline`
+    pub fn with_baseline(mut self, baseline: ChainListing) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// List each created archive with `verifier` and fail if it holds no files
+    pub fn with_archive_check<V: ArchiveVerifier + 'static>(mut self, verifier: V) -> Self {
+        self.archive_check = Some(Box::new(verifier));
+        self
+    }
+
+    /// Read the free space of the output volume from `provider`
+    pub fn with_free_space<P: FreeSpaceProvider + 'static>(mut self, provider: P) -> Self {
+        self.free_space = Box::new(provider);
+        self
+    }
+
+    /// Get the statistics of the last run, if paths were processed
+    pub fn summary(&self) -> Option<&BackupSummary> {
+        self.summary.get()
+    }
+
+    /// Write the run statistics as a JSON object to the given file
+    pub async fn write_summary_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let summary = self
+            .summary()
+            .ok_or_else(|| ArchtreeError::other("No paths have been processed yet"))?;
+        let json = serde_json::to_string_pretty(summary)
+            .context_io("Failed to serialize backup summary")?;
+
+        tokio::fs::write(path, json)
+            .await
+            .context_io(format!("Failed to write summary file: {}", path.display()))
+    }
+
+    /// Get processed paths as strings (for verification compatibility).
+    ///
+    /// With an expand cache in the config, the result is read from and written to it, as
+    /// long as no filter relative to the current time is set (see [`ExpandCache`]).
     pub async fn get_input_paths(&self) -> Result<Vec<String>> {
         if let Some(cached_paths) = self.processed_paths.get() {
             return Ok(cached_paths
@@ -39,7 +279,27 @@ where
                 .collect());
         }
 
-        let processed_paths = self.process_input_paths().await?;
+        // Times relative to now select other files on every run, so they aren't cached
+        let cache = self.config.expand_cache.as_ref().filter(|_| {
+            self.config.accessed_before.is_none() && self.config.modified_within.is_none()
+        });
+        let processed_paths = match cache {
+            Some(cache) => {
+                let input_paths = self.read_input_paths().await?;
+                let files = ExpandCache::new(cache)
+                    .expand_with(&input_paths, &self.config.selection_settings(), || async {
+                        let processed = self.process_paths(input_paths.clone()).await?;
+                        Ok(processed
+                            .iter()
+                            .map(|p| p.to_string_lossy().to_string())
+                            .collect())
+                    })
+                    .await?;
+                files.iter().map(PathBuf::from).collect()
+            }
+            None => self.process_input_paths().await?,
+        };
+        self.check_not_empty(&processed_paths)?;
         let string_paths = processed_paths
             .iter()
             .map(|p| p.to_string_lossy().to_string())
@@ -48,8 +308,18 @@ where
         Ok(string_paths)
     }
 
-    /// Process input paths using the improved algorithm
-    async fn process_input_paths(&self) -> Result<Vec<PathBuf>> {
+    /// Fail with --fail-on-empty if expanding the inputs left no files
+    fn check_not_empty(&self, processed_paths: &[PathBuf]) -> Result<()> {
+        if processed_paths.is_empty() && self.config.fail_on_empty {
+            return Err(ArchtreeError::config(
+                "No files left after expanding input paths and applying exclusions (--fail-on-empty)",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Read the input paths, failing if there are none
+    async fn read_input_paths(&self) -> Result<Vec<String>> {
         let input_paths = self
             .reader
             .read_paths()
@@ -59,16 +329,62 @@ where
         if input_paths.is_empty() {
             return Err(ArchtreeError::config("No input paths provided"));
         }
+        Ok(input_paths)
+    }
+
+    /// Process input paths using the improved algorithm
+    async fn process_input_paths(&self) -> Result<Vec<PathBuf>> {
+        let input_paths = self.read_input_paths().await?;
+        self.process_paths(input_paths).await
+    }
 
-        // Extract exclusion patterns from input
-        let (include_paths, exclude_patterns) =
+    /// Expand and filter input paths that were already read
+    async fn process_paths(&self, input_paths: Vec<String>) -> Result<Vec<PathBuf>> {
+        // Extract exclusion and include-only patterns from input
+        let (include_paths, inline_patterns) =
             PathProcessor::extract_exclusion_patterns(&input_paths);
+        let (include_paths, inline_includes) =
+            PathProcessor::extract_include_patterns(&include_paths);
 
-        if !exclude_patterns.is_empty() && self.config.show_progress {
-            eprintln!("Found {} exclusion patterns:", exclude_patterns.len());
+        let mut include_patterns = self.config.include_patterns.clone();
+        for pattern in inline_includes {
+            if !include_patterns.contains(&pattern) {
+                include_patterns.push(pattern);
+            }
+        }
+        if self.config.whole_directories && !include_patterns.is_empty() {
+            return Err(ArchtreeError::config(
+                "Include patterns (+pattern) can't be combined with --archive-dirs-whole",
+            ));
+        }
+
+        // Baseline patterns from the environment come first, input patterns add on top
+        let mut exclude_patterns = self.config.default_excludes.clone();
+        for pattern in inline_patterns {
+            if !exclude_patterns.contains(&pattern) {
+                exclude_patterns.push(pattern);
+            }
+        }
+
+        // Compiled before any path is touched, so a typo fails the run right away
+        let exclude_regex = RegexMatcher::new(&self.config.exclude_regexes)?;
+
+        let pattern_count = exclude_patterns.len() + self.config.exclude_regexes.len();
+        if pattern_count > 0 && self.config.show_progress {
+            eprintln!("Found {} exclusion patterns:", pattern_count);
             for pattern in &exclude_patterns {
                 eprintln!("  🚫 {}", pattern);
             }
+            for pattern in &self.config.exclude_regexes {
+                eprintln!("  🚫 regex {}", pattern);
+            }
+        }
+
+        if !include_patterns.is_empty() && self.config.show_progress {
+            eprintln!("Found {} include patterns:", include_patterns.len());
+            for pattern in &include_patterns {
+                eprintln!("  ➕ {}", pattern);
+            }
         }
 
         if include_paths.is_empty() {
@@ -79,140 +395,1669 @@ where
 
         // Create path processor and matcher
         let mut processor = PathProcessor::new(include_paths, exclude_patterns)
-            .context_config("Failed to create path processor")?;
-        let matcher = WildcardMatcher::with_patterns(processor.exclusion_patterns())
-            .context_config("Failed to create wildcard matcher")?;
+            .context_config("Failed to create path processor")?
+            .with_dedupe_hardlinks(self.config.dedupe_hardlinks)
+            .with_skip_hidden(self.config.skip_hidden)
+            .with_include_hidden_roots(self.config.include_hidden_roots)
+            .with_respect_gitignore(self.config.respect_gitignore)
+            .with_canonicalize(self.config.canonicalize)
+            .with_follow_symlinks(self.config.follow_symlinks)
+            .with_max_depth(self.config.max_depth)
+            .with_file_size_range(self.config.min_file_size, self.config.max_file_size)
+            .with_walk_jobs(self.config.walk_jobs)
+            .with_whole_directories(self.config.whole_directories)
+            .with_exclude_types(self.exclude_types()?);
+        if self.config.profile.is_some() {
+            processor = processor.with_profiling(Arc::new(SystemClock));
+        }
+        if let Some(age) = self.config.accessed_before {
+            processor = processor.with_accessed_before(time_ago(age, "--accessed-before")?);
+        }
+        if let Some(age) = self.config.modified_within {
+            processor = processor.with_modified_after(time_ago(age, "--modified-within")?);
+        }
+        if !exclude_regex.is_empty() {
+            processor = processor.with_exclude_regex(exclude_regex);
+        }
+        if !include_patterns.is_empty() {
+            let includes = WildcardMatcher::with_case_sensitivity(
+                &include_patterns,
+                self.config.case_sensitive,
+            )
+            .context_config("Failed to create include matcher")?;
+            processor = processor.with_include_patterns(includes);
+        }
+        let matcher =
+            CompositeMatcher::new(processor.exclusion_patterns(), self.config.case_sensitive)
+                .context_config("Failed to create exclusion matcher")?;
 
         // Track statistics for reporting
-        let mut added_count = 0;
-        let mut excluded_count = 0;
-        let mut invalid_count = 0;
+        let mut summary = BackupSummary {
+            output_path: self.config.output_path.clone(),
+            ..Default::default()
+        };
+
+        // The counter shows added files out of a total counted in a first pass
+        let counting = self.config.show_progress
+            && self.config.progress_style == ProgressStyle::Counter
+            && self.config.precount;
+        let total = if counting {
+            self.write_progress("⏳ Counting files...");
+            Some(
+                processor
+                    .count_candidates(&matcher)
+                    .await
+                    .context_config("Failed to count files")?,
+            )
+        } else {
+            None
+        };
 
         // Process paths using the improved algorithm
+        let mut last_counter = None;
         let processed_paths = processor
             .process_paths(
-                |path, status| match status {
-                    ProcessingStatus::Added => {
-                        added_count += 1;
-                        // if self.config.show_progress {
-                        //     eprintln!("✓ {}", path.display());
-                        // }
+                |path, status| {
+                    summary.record(&status);
+                    if !self.config.show_progress {
+                        return;
                     }
-                    ProcessingStatus::Excluded => {
-                        excluded_count += 1;
-                        if self.config.show_progress {
-                            eprintln!("🚫 Excluded: {}", path.display());
+                    match self.config.progress_style {
+                        ProgressStyle::Line => {
+                            if let Some(message) = path_status_message(path, &status) {
+                                self.write_progress(&format!("{}\n", message));
+                            }
                         }
-                    }
-                    ProcessingStatus::Invalid(ref error) => {
-                        invalid_count += 1;
-                        if self.config.show_progress {
-                            eprintln!("⚠️  Invalid path: {} ({})", path.display(), error);
+                        ProgressStyle::Counter => {
+                            // Throttled, so huge trees don't spend their time redrawing
+                            let now = Instant::now();
+                            if last_counter.is_none_or(|last: Instant| {
+                                now.duration_since(last) >= COUNTER_INTERVAL
+                            }) {
+                                last_counter = Some(now);
+                                self.write_progress(&counter_line(&summary, total));
+                            }
                         }
+                        ProgressStyle::None => {}
                     }
                 },
                 &matcher,
             )
             .await
             .context_config("Failed to process paths")?;
+        summary.total_files = processed_paths.len();
+        if self.config.show_progress && self.config.progress_style == ProgressStyle::Counter {
+            // Final count, ending the line that was updated in place
+            self.write_progress(&format!("{}\n", counter_line(&summary, total)));
+        }
 
         // Report final statistics
-        if self.config.show_progress {
-            eprintln!("\n📊 Processing Summary:");
-            eprintln!("  ✓ Added: {} files", added_count);
-            if excluded_count > 0 {
-                eprintln!("  🚫 Excluded: {} files", excluded_count);
+        if self.config.show_progress && self.config.table_summary {
+            eprintln!();
+            SummaryTable::new("Processing Summary")
+                .row("Added", summary.added)
+                .row("Excluded", summary.excluded)
+                .row("Invalid", summary.invalid)
+                .row("Permission denied", summary.permission_denied)
+                .row("Hard links", summary.hard_links)
+                .row("Skipped", summary.skipped)
+                .row("Total for archive", summary.total_files)
+                .print();
+        } else if self.config.show_progress {
+            self.summary_line("\n📊 Processing Summary:".to_string());
+            self.summary_line(format!("  ✓ Added: {} files", summary.added));
+            if summary.excluded > 0 {
+                self.summary_line(format!("  🚫 Excluded: {} files", summary.excluded));
+            }
+            if summary.invalid > 0 {
+                self.summary_line(format!("  ⚠️  Invalid: {} paths", summary.invalid));
+            }
+            if summary.permission_denied > 0 {
+                self.summary_line(format!(
+                    "  🔒 Permission denied: {} paths ({})",
+                    summary.permission_denied, PERMISSION_DENIED_HINT
+                ));
+            }
+            if summary.hard_links > 0 {
+                self.summary_line(format!(
+                    "  🔗 Hard links skipped: {} files",
+                    summary.hard_links
+                ));
             }
-            if invalid_count > 0 {
-                eprintln!("  ⚠️  Invalid: {} paths", invalid_count);
+            if summary.skipped > 0 {
+                self.summary_line(format!(
+                    "  ⏭️  Skipped by filters: {} files",
+                    summary.skipped
+                ));
+            }
+            self.summary_line(format!(
+                "  📁 Total for archive: {} files",
+                summary.total_files
+            ));
+        }
+        let _ = self.summary.set(summary);
+        let _ = self.hardlinks.set(processor.hardlinks().to_vec());
+
+        if let Some(count) = self.config.profile {
+            let slowest = processor.slowest_directories(count);
+            if !slowest.is_empty() {
+                eprintln!("\n⏱️  Slowest input directories:");
+                for (path, elapsed) in slowest {
+                    eprintln!("  {:>8.2}s  {}", elapsed.as_secs_f64(), path.display());
+                }
             }
-            eprintln!("  📁 Total for archive: {} files", processed_paths.len());
         }
 
         Ok(processed_paths)
     }
 
-    /// Run the complete backup process
-    pub async fn run(&self) -> Result<()> {
-        // Check if archiver is available
-        if !self.archiver.is_available().await {
-            return Err(ArchtreeError::external_tool(
-                self.archiver.name(),
-                format!("{} is not available on this system", self.archiver.name()),
+    /// Warn about paths differing only by case, failing instead in strict mode
+    fn check_case_collisions(&self, paths: &[PathBuf]) -> Result<()> {
+        let collisions = find_case_collisions(paths);
+        if collisions.is_empty() {
+            return Ok(());
+        }
+
+        eprintln!(
+            "⚠️  {} sets of paths differ only by case; restoring to a case-insensitive filesystem (Windows, macOS) keeps only one of each:",
+            collisions.len()
+        );
+        for group in &collisions {
+            let names: Vec<String> = group.iter().map(|p| p.display().to_string()).collect();
+            eprintln!("  🔠 {}", names.join(" <-> "));
+        }
+
+        if self.config.strict {
+            return Err(ArchtreeError::path_processing(
+                format!(
+                    "{} sets of paths differ only by case (--strict)",
+                    collisions.len()
+                ),
+                collisions
+                    .first()
+                    .and_then(|group| group.first())
+                    .map(|path| path.to_string_lossy().to_string()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parse the configured content types to exclude
+    fn exclude_types(&self) -> Result<Vec<ContentType>> {
+        self.config
+            .exclude_types
+            .iter()
+            .map(|value| value.parse().map_err(ArchtreeError::config))
+            .collect()
+    }
+
+    /// Re-check that every path still exists, dropping vanished ones with a warning
+    /// (or failing with `fail_on_vanished`)
+    fn drop_vanished(&self, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+        let (existing, vanished): (Vec<PathBuf>, Vec<PathBuf>) = paths
+            .into_iter()
+            .partition(|path| path.symlink_metadata().is_ok());
+        if vanished.is_empty() {
+            return Ok(existing);
+        }
+
+        eprintln!(
+            "⚠️  {} files vanished after being listed and will not be archived:",
+            vanished.len()
+        );
+        for path in &vanished {
+            eprintln!("  👻 {}", path.display());
+        }
+
+        if self.config.fail_on_vanished {
+            return Err(ArchtreeError::path_processing(
+                format!(
+                    "{} files vanished before archiving (--fail-on-vanished)",
+                    vanished.len()
+                ),
+                Some(vanished[0].to_string_lossy().to_string()),
+            ));
+        }
+        if existing.is_empty() {
+            return Err(ArchtreeError::config("All files vanished before archiving"));
+        }
+        Ok(existing)
+    }
+
+    /// Report how much of the file set is already compressed
+    fn report_content_analysis(&self, analysis: &ContentAnalysis) {
+        let fraction = analysis.incompressible_fraction() * 100.0;
+
+        if self.config.show_progress {
+            eprintln!("\n🔬 Content Analysis:");
+            for file in &analysis.incompressible_files {
+                eprintln!("  🗜️  {} ({})", file.path.display(), file.format);
+            }
+            eprintln!(
+                "  Already compressed: {} files, {} of {} bytes ({:.1}%)",
+                analysis.incompressible_files.len(),
+                analysis.incompressible_bytes(),
+                analysis.total_bytes,
+                fraction
+            );
+            if analysis.unreadable_files > 0 {
+                eprintln!(
+                    "  ⚠️  Could not sample: {} files",
+                    analysis.unreadable_files
+                );
+            }
+        }
+
+        if analysis.suggests_store() {
+            eprintln!(
+                "⚠️  {:.1}% of the data is already compressed; compression will yield little benefit. \
+                 Consider storing files uncompressed by size with --store-below or --store-above.",
+                fraction
+            );
+        }
+    }
+
+    /// Print the projected archive size of an estimate-only run
+    fn report_size_estimate(&self, estimate: &SizeEstimate) {
+        eprintln!("\n📐 Size Estimate:");
+        eprintln!("  Input: {} bytes", estimate.input_bytes);
+        eprintln!(
+            "  Already compressed (stored as is): {} bytes",
+            estimate.incompressible_bytes
+        );
+        eprintln!(
+            "  Estimated archive size: {} bytes (assuming other data compresses to {:.0}%)",
+            estimate.archive_bytes(),
+            estimate.compression_ratio * 100.0
+        );
+    }
+
+    /// Write the files added at the root of the archive to `dir`, returning their paths
+    async fn write_root_files(
+        &self,
+        processed_paths: &[PathBuf],
+        dir: &Path,
+    ) -> Result<Vec<String>> {
+        let mut root_files = Vec::new();
+        if self.config.embed_index {
+            let index_path = write_index(processed_paths, dir).await?;
+            root_files.push(index_path.to_string_lossy().to_string());
+        }
+        // Records which left out paths to link again on restore
+        if let Some(hardlinks) = self.hardlinks.get().filter(|links| !links.is_empty()) {
+            let hardlinks_path = write_hardlinks(hardlinks, dir).await?;
+            root_files.push(hardlinks_path.to_string_lossy().to_string());
+        }
+        Ok(root_files)
+    }
+
+    /// Fail if the created archive holds no files although some were handed to 7-Zip.
+    ///
+    /// 7-Zip can exit successfully after skipping every input it couldn't open, leaving a
+    /// valid but empty archive, or one holding only the `root_files` added to it.
+    async fn check_archive_not_empty(&self, root_files: &[String]) -> Result<()> {
+        let Some(verifier) = &self.archive_check else {
+            return Ok(());
+        };
+
+        // Files added at the root are stored under their name alone
+        let root_names: Vec<String> = root_files
+            .iter()
+            .filter_map(|path| Path::new(path).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .collect();
+        let files = verifier
+            .list_archive_contents(&self.config.output_path)
+            .await?;
+        if files.iter().all(|file| root_names.contains(file)) {
+            return Err(ArchtreeError::verification(
+                "7-Zip reported success but the archive contains no files; check that the input files are readable",
+                Some(self.config.output_path.clone()),
             ));
         }
+        Ok(())
+    }
+
+    /// Print the path of every archived file when added files are printed
+    fn print_added(&self, processed_paths: &[PathBuf]) {
+        // Machine-readable output, kept apart from the progress on stderr
+        if let Some(output) = &self.added_output
+            && let Ok(mut output) = output.lock()
+        {
+            for path in processed_paths {
+                let _ = writeln!(output, "{}", path.display());
+            }
+        }
+    }
+
+    /// Write a line of the processing summary, without its emoji with --no-emoji
+    fn summary_line(&self, line: String) {
+        if self.config.no_emoji {
+            eprintln!("{}", without_emoji(&line));
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+
+    fn write_progress(&self, text: &str) {
+        if let Ok(mut output) = self.progress_output.lock() {
+            let _ = output.write_all(text.as_bytes());
+            let _ = output.flush();
+        }
+    }
+
+    /// Hand a prepared list file to the archiver without reading or processing the input
+    async fn archive_raw_list(&self, list_path: &str) -> Result<()> {
+        if self.config.show_progress {
+            eprintln!("📜 Archiving the paths of {} as-is", list_path);
+        }
+        self.archiver
+            .create_archive_from_list(list_path, &self.config.output_path)
+            .await
+            .context_io("Failed to create archive")
+    }
+
+    /// Run the complete backup process and report what it did
+    pub async fn run(&self) -> Result<RunReport> {
+        // Check if archiver is available; an estimate or a dry run doesn't need it
+        if self.config.estimate_ratio.is_none()
+            && !self.config.dry_run
+            && !self.archiver.is_available().await
+        {
+            return Err(ArchtreeError::tool_not_available(self.archiver.name()));
+        }
 
         if self.config.show_progress {
             eprintln!("🚀 Starting backup process...");
         }
 
+        let mut report = RunReport::new(self.config.clone());
+
+        if let Some(list_path) = &self.config.raw_list {
+            self.archive_raw_list(list_path).await?;
+            report.archive_size = archive_size(&self.config.output_path);
+            return Ok(report);
+        }
+
         // Process paths using the new algorithm
-        let processed_paths = self.process_input_paths().await?;
+        let mut processed_paths = self.process_input_paths().await?;
+        self.check_not_empty(&processed_paths)?;
+        report.processing = self.summary().cloned().unwrap_or_default();
+
+        if self.config.dry_run {
+            self.print_added(&processed_paths);
+            if self.config.show_progress {
+                eprintln!(
+                    "\n🧪 Dry run: {} files would be archived to {}; no archive was created",
+                    processed_paths.len(),
+                    self.config.output_path
+                );
+            }
+            return Ok(report);
+        }
 
         if processed_paths.is_empty() {
             return Err(ArchtreeError::config("No valid paths found to archive"));
         }
 
-        // Cache the processed paths for potential later use (e.g., verification)
-        let _ = self.processed_paths.set(processed_paths.clone());
+        self.check_case_collisions(&processed_paths)?;
 
-        if self.config.show_progress {
-            eprintln!("\n📦 Creating archive: {}", self.config.output_path);
+        // Hashed before archiving, so a file changing meanwhile is archived again next time
+        let structure = if self.config.skip_if_unchanged {
+            let hash = structure_hash(&processed_paths, &self.config.archive_settings());
+            let hash_path = structure_hash_path(&self.config.output_path);
+            if read_structure_hash(&hash_path).await.as_deref() == Some(hash.as_str())
+                && archive_size(&self.config.output_path).is_some()
+            {
+                if self.config.show_progress {
+                    eprintln!(
+                        "✅ Nothing changed since the last backup to {}; skipping",
+                        self.config.output_path
+                    );
+                }
+                report.unchanged = true;
+                return Ok(report);
+            }
+            Some((hash_path, hash))
+        } else {
+            None
+        };
+
+        if let Some(baseline) = &self.baseline {
+            processed_paths = changed_since(&processed_paths, baseline);
+            if self.config.show_progress {
+                eprintln!(
+                    "🔗 {} files changed since the last chain member",
+                    processed_paths.len()
+                );
+            }
+            if processed_paths.is_empty() {
+                if self.config.show_progress {
+                    eprintln!("✅ Nothing to archive");
+                }
+                return Ok(report);
+            }
+            if let Some(limit) = self.config.max_incremental_size {
+                check_incremental_size(&processed_paths, limit)?;
+            }
+        }
+
+        if self.config.analyze_content || self.config.estimate_ratio.is_some() {
+            let analysis = ContentAnalysis::analyze(&processed_paths);
+            if self.config.analyze_content {
+                self.report_content_analysis(&analysis);
+            }
+            if let Some(ratio) = self.config.estimate_ratio {
+                let estimate = SizeEstimate::from_analysis(&analysis, ratio);
+                self.report_size_estimate(&estimate);
+                report.total_bytes = estimate.input_bytes;
+                report.estimated_size = Some(estimate.archive_bytes());
+                return Ok(report);
+            }
         }
 
+        // Files can disappear between walking the inputs and 7-Zip reading them
+        let processed_paths = self.drop_vanished(processed_paths)?;
+
+        // Cache the archived paths so verification can compare against them directly
+        let _ = self.processed_paths.set(processed_paths.clone());
+        report.total_bytes = processed_paths
+            .iter()
+            .filter_map(|path| path.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum();
+
         // Convert paths to strings for archiver compatibility
         let string_paths: Vec<String> = processed_paths
             .iter()
             .map(|p| p.to_string_lossy().to_string())
             .collect();
+        check_list_paths(&string_paths)?;
+
+        // Fail before 7-Zip leaves a partial archive on a full disk. Without a set minimum
+        // the input size is only a guess, since compressed data needs less, so falling
+        // short of it or failing to read the free space is just a warning
+        match self.config.min_free_space {
+            Some(min) => check_free_space(
+                self.free_space.as_ref(),
+                &self.config.output_path,
+                min,
+                "--min-free-space",
+            )?,
+            None => {
+                if let Err(e) = check_free_space(
+                    self.free_space.as_ref(),
+                    &self.config.output_path,
+                    report.total_bytes,
+                    "the input size, for incompressible data; require a size with --min-free-space",
+                ) {
+                    eprintln!("⚠️  {}", e);
+                }
+            }
+        }
+
+        self.callback.on_event(BackupEvent::ArchivingStarted {
+            output_path: self.config.output_path.clone(),
+            file_count: processed_paths.len(),
+        });
+
+        // Files such as the index go to the root of the archive rather than under the full
+        // path of the temporary directory they are written to. They are added as part of
+        // creating the archive, so with --atomic none is missing once it is in place
+        let root_dir = tempfile::Builder::new()
+            .prefix("archtree_index_")
+            .tempdir()
+            .context_io("Failed to create index directory")?;
+        let root_files = self
+            .write_root_files(&processed_paths, root_dir.path())
+            .await?;
 
         // Create archive
         self.archiver
-            .create_archive(&string_paths, &self.config.output_path)
+            .create_archive_with_root_files(&string_paths, &root_files, &self.config.output_path)
             .await
             .context_io("Failed to create archive")?;
+        self.check_archive_not_empty(&root_files).await?;
+        self.print_added(&processed_paths);
 
-        if self.config.show_progress {
-            eprintln!(
-                "✅ Archive created successfully: {}",
-                self.config.output_path
-            );
+        self.callback.on_event(BackupEvent::ArchivingComplete {
+            output_path: self.config.output_path.clone(),
+            file_count: processed_paths.len(),
+        });
+
+        if let Some((hash_path, hash)) = &structure {
+            write_structure_hash(hash_path, hash).await?;
         }
 
-        Ok(())
+        report.archive_size = archive_size(&self.config.output_path);
+        Ok(report)
     }
 }
 
+/// The time `age` before now, or a configuration error naming `option` if that is
+/// earlier than the system clock can represent
+fn time_ago(age: Duration, option: &str) -> Result<SystemTime> {
+    SystemTime::now().checked_sub(age).ok_or_else(|| {
+        ArchtreeError::config(format!(
+            "{} reaches further back than the system clock can represent",
+            option
+        ))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::io::{SevenZipArchiver, VecReader};
+    use crate::testing::{MockArchiver, MockVerifier, file_entry};
+    use crate::verification::verifier::{ArchiveEntry, comparison_key};
+    use async_trait::async_trait;
     use std::fs;
     use tempfile::TempDir;
 
+    /// Callback that records every event it receives
+    #[derive(Clone, Default)]
+    struct RecordingCallback {
+        events: std::sync::Arc<std::sync::Mutex<Vec<BackupEvent>>>,
+    }
+
+    impl BackupCallback for RecordingCallback {
+        fn on_event(&self, event: BackupEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
     #[tokio::test]
-    async fn test_backup_service_with_valid_paths() {
-        // Create temporary test files
+    async fn test_backup_events_for_archiving_phase() {
         let temp_dir = TempDir::new().unwrap();
-        let test_file1 = temp_dir.path().join("test1.txt");
-        let test_file2 = temp_dir.path().join("test2.txt");
-        let test_file3 = temp_dir.path().join("test3.tmp");
+        fs::write(temp_dir.path().join("a.txt"), "a").unwrap();
+        fs::write(temp_dir.path().join("b.txt"), "b").unwrap();
 
-        fs::write(&test_file1, "Hello, World!").unwrap();
-        fs::write(&test_file2, "Test content").unwrap();
-        fs::write(&test_file3, "Temp file").unwrap();
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+        let callback = RecordingCallback::default();
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config)
+                .with_callback(callback.clone());
+
+        service.run().await.unwrap();
+
+        let events = callback.events.lock().unwrap();
+        assert_eq!(
+            *events,
+            vec![
+                BackupEvent::ArchivingStarted {
+                    output_path: "out.7z".to_string(),
+                    file_count: 2,
+                },
+                BackupEvent::ArchivingComplete {
+                    output_path: "out.7z".to_string(),
+                    file_count: 2,
+                },
+            ]
+        );
+    }
+
+    /// Archiver that reports 7-Zip as missing
+    struct UnavailableArchiver;
+
+    #[async_trait]
+    impl Archiver for UnavailableArchiver {
+        async fn create_archive(&self, _paths: &[String], _output_path: &str) -> Result<()> {
+            unreachable!("archiving must not start without the archiver")
+        }
+
+        async fn add_to_archive(&self, _paths: &[String], _archive_path: &str) -> Result<()> {
+            unreachable!("archiving must not start without the archiver")
+        }
+
+        async fn is_available(&self) -> bool {
+            false
+        }
+
+        fn name(&self) -> &'static str {
+            "7-Zip"
+        }
+    }
 
+    #[tokio::test]
+    async fn test_unavailable_archiver_exit_code() {
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+        let reader = Box::new(VecReader::new(vec!["file.txt".to_string()]));
+        let service = BackupService::new(UnavailableArchiver, reader, config);
+
+        let error = service.run().await.unwrap_err();
+        assert!(matches!(error, ArchtreeError::ToolNotAvailable { .. }));
+        assert_eq!(error.exit_code(), crate::core::EXIT_TOOL_NOT_AVAILABLE);
+    }
+
+    /// Writer appending into a buffer shared with the test
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_print_added_writes_only_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let kept = temp_dir.path().join("kept.txt");
+        fs::write(&kept, "kept").unwrap();
+        fs::write(temp_dir.path().join("skipped.tmp"), "skipped").unwrap();
+
+        // Progress stays enabled: it goes to stderr, never into the added-files output
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(true)
+            .print_added(true)
+            .build()
+            .unwrap();
         let paths = vec![
-            test_file1.to_string_lossy().to_string(),
-            test_file2.to_string_lossy().to_string(),
-            test_file3.to_string_lossy().to_string(),
+            temp_dir.path().to_string_lossy().to_string(),
             "!*.tmp".to_string(),
         ];
+        let output = SharedBuffer::default();
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config)
+                .with_added_output(output.clone());
 
-        let archiver = SevenZipArchiver::new();
-        let reader = Box::new(VecReader::new(paths));
+        service.run().await.unwrap();
+
+        let printed = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(printed, format!("{}\n", kept.display()));
+    }
+
+    #[tokio::test]
+    async fn test_print_added_leaves_out_files_in_the_baseline() {
+        let temp_dir = TempDir::new().unwrap();
+        let unchanged = temp_dir.path().join("unchanged.txt");
+        let changed = temp_dir.path().join("changed.txt");
+        fs::write(&unchanged, "unchanged").unwrap();
+        fs::write(&changed, "changed").unwrap();
+
+        // The last chain member holds the unchanged file as it is on disk
+        let key = comparison_key(&unchanged.to_string_lossy(), 0).unwrap();
+        let baseline = ChainListing::from([(
+            key.clone(),
+            ArchiveEntry {
+                modified: Some(fs::metadata(&unchanged).unwrap().modified().unwrap()),
+                ..file_entry(&key)
+            },
+        )]);
         let config = Config::builder()
-            .output_path(Some("test.7z"), false)
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .print_added(true)
+            .build()
+            .unwrap();
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+        let output = SharedBuffer::default();
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config)
+                .with_baseline(baseline)
+                .with_added_output(output.clone());
+
+        service.run().await.unwrap();
+
+        let printed = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(printed, format!("{}\n", changed.display()));
+    }
+
+    #[tokio::test]
+    async fn test_counter_progress_emits_few_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..500 {
+            fs::write(temp_dir.path().join(format!("{}.tmp", i)), "tmp").unwrap();
+        }
+        fs::write(temp_dir.path().join("kept.txt"), "kept").unwrap();
+        let paths = vec![
+            temp_dir.path().to_string_lossy().to_string(),
+            "!*.tmp".to_string(),
+        ];
+
+        let progress_of = |style, precount| {
+            let config = Config::builder()
+                .output_path(Some("out.7z"), false)
+                .show_progress(true)
+                .progress_style(style)
+                .precount(precount)
+                .build()
+                .unwrap();
+            let output = SharedBuffer::default();
+            let service = BackupService::new(
+                MockArchiver::new(),
+                Box::new(VecReader::new(paths.clone())),
+                config,
+            )
+            .with_progress_output(output.clone());
+            (service, output)
+        };
+
+        let (service, lines) = progress_of(ProgressStyle::Line, true);
+        service.get_input_paths().await.unwrap();
+        let lines = String::from_utf8(lines.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(lines.lines().count(), 500);
+
+        let (service, counter) = progress_of(ProgressStyle::Counter, false);
+        service.get_input_paths().await.unwrap();
+        let counter = String::from_utf8(counter.0.lock().unwrap().clone()).unwrap();
+        let updates = counter.matches('\r').count();
+        assert!(updates < 50, "{} counter updates", updates);
+        assert!(counter.ends_with("\r⏳ Processed 501 paths (1 added, 500 excluded)\n"));
+
+        // Counted first, the added files show against the total
+        let (service, counter) = progress_of(ProgressStyle::Counter, true);
+        service.get_input_paths().await.unwrap();
+        let counter = String::from_utf8(counter.0.lock().unwrap().clone()).unwrap();
+        assert!(counter.starts_with("⏳ Counting files..."));
+        assert!(counter.ends_with("\r⏳ Processed 501 paths (1/1 added, 500 excluded)\n"));
+
+        let (service, none) = progress_of(ProgressStyle::None, true);
+        service.get_input_paths().await.unwrap();
+        assert!(none.0.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_summary_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let kept = temp_dir.path().join("kept.txt");
+        let skipped = temp_dir.path().join("skipped.tmp");
+        fs::write(&kept, "kept").unwrap();
+        fs::write(&skipped, "skipped").unwrap();
+
+        let paths = vec![
+            temp_dir.path().to_string_lossy().to_string(),
+            temp_dir
+                .path()
+                .join("gone.txt")
+                .to_string_lossy()
+                .to_string(),
+            "!*.tmp".to_string(),
+        ];
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config);
+        service.run().await.unwrap();
+
+        let summary_path = temp_dir.path().join("summary.json");
+        service.write_summary_json(&summary_path).await.unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&summary_path).unwrap()).unwrap();
+        assert_eq!(json["output_path"], "out.7z");
+        assert_eq!(json["added"], 1);
+        assert_eq!(json["excluded"], 1);
+        assert_eq!(json["invalid"], 1);
+        assert_eq!(json["hard_links"], 0);
+        assert_eq!(json["total_files"], 1);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_case_collision_fails_in_strict_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("README.md"), "upper").unwrap();
+        fs::write(temp_dir.path().join("readme.md"), "lower").unwrap();
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+
+        // Only a warning by default
+        let service = BackupService::new(
+            MockArchiver::new(),
+            Box::new(VecReader::new(paths.clone())),
+            config.clone(),
+        );
+        service.run().await.unwrap();
+
+        let config = Config {
+            strict: true,
+            ..config
+        };
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config);
+        let error = service.run().await.unwrap_err();
+        assert!(error.to_string().contains("differ only by case"));
+    }
+
+    /// Archiver that records the paths it archives and the files added at its root
+    #[derive(Clone, Default)]
+    struct IndexRecordingArchiver {
+        paths: std::sync::Arc<Mutex<Vec<String>>>,
+        root_files: std::sync::Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    #[async_trait]
+    impl Archiver for IndexRecordingArchiver {
+        async fn create_archive(&self, paths: &[String], _output_path: &str) -> Result<()> {
+            *self.paths.lock().unwrap() = paths.to_vec();
+            Ok(())
+        }
+
+        async fn add_to_archive(&self, paths: &[String], _archive_path: &str) -> Result<()> {
+            self.paths.lock().unwrap().extend_from_slice(paths);
+            Ok(())
+        }
+
+        async fn add_at_root(&self, path: &str, _archive_path: &str) -> Result<()> {
+            // Root files only exist while they are being added
+            self.root_files
+                .lock()
+                .unwrap()
+                .push((path.to_string(), fs::read_to_string(path).unwrap()));
+            Ok(())
+        }
+
+        async fn is_available(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &'static str {
+            "Mock"
+        }
+    }
+
+    /// Free space provider reporting a fixed number of bytes
+    struct FixedFreeSpace(u64);
+
+    impl FreeSpaceProvider for FixedFreeSpace {
+        fn available_space(&self, _path: &Path) -> std::io::Result<u64> {
+            Ok(self.0)
+        }
+    }
+
+    /// Free space provider failing like an unsupported filesystem
+    struct UnreadableFreeSpace;
+
+    impl FreeSpaceProvider for UnreadableFreeSpace {
+        fn available_space(&self, _path: &Path) -> std::io::Result<u64> {
+            Err(std::io::Error::from(std::io::ErrorKind::Unsupported))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_free_space_fails_only_with_set_minimum() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("data.bin");
+        fs::write(&file, vec![0u8; 1000]).unwrap();
+        let output = temp_dir.path().join("out.7z").to_string_lossy().to_string();
+        let paths = vec![file.to_string_lossy().to_string()];
+
+        let run = |min_free_space: Option<u64>| {
+            let config = Config::builder()
+                .output_path(Some(&output), false)
+                .show_progress(false)
+                .min_free_space(min_free_space)
+                .build()
+                .unwrap();
+            let service = BackupService::new(
+                IndexRecordingArchiver::default(),
+                Box::new(VecReader::new(paths.clone())),
+                config,
+            )
+            .with_free_space(UnreadableFreeSpace);
+            async move { service.run().await }
+        };
+
+        run(None).await.unwrap();
+        let error = run(Some(1)).await.unwrap_err();
+        assert_eq!(error.category(), "io");
+    }
+
+    #[tokio::test]
+    async fn test_low_free_space_aborts_only_below_set_minimum() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("data.bin");
+        fs::write(&file, vec![0u8; 1000]).unwrap();
+        let output = temp_dir.path().join("out.7z").to_string_lossy().to_string();
+        let paths = vec![file.to_string_lossy().to_string()];
+
+        let run = |min_free_space: Option<u64>| {
+            let config = Config::builder()
+                .output_path(Some(&output), false)
+                .show_progress(false)
+                .min_free_space(min_free_space)
+                .build()
+                .unwrap();
+            let archiver = IndexRecordingArchiver::default();
+            let service = BackupService::new(
+                archiver.clone(),
+                Box::new(VecReader::new(paths.clone())),
+                config,
+            )
+            .with_free_space(FixedFreeSpace(100));
+            async move { (service.run().await, archiver.paths.lock().unwrap().clone()) }
+        };
+
+        // Less free space than the input size only warns by default
+        let (result, archived) = run(None).await;
+        result.unwrap();
+        assert_eq!(archived, paths);
+
+        let (result, archived) = run(Some(200)).await;
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("Not enough free space"), "{}", error);
+        assert!(error.contains("100 bytes available"), "{}", error);
+        assert!(error.contains("200 bytes needed"), "{}", error);
+        assert!(error.contains("--min-free-space"), "{}", error);
+        assert!(archived.is_empty());
+
+        let (result, archived) = run(Some(50)).await;
+        result.unwrap();
+        assert_eq!(archived, paths);
+    }
+
+    #[tokio::test]
+    async fn test_inline_include_and_exclusion_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let src = temp_dir.path().join("src");
+        fs::create_dir_all(src.join("gen")).unwrap();
+        for file in ["main.rs", "lib.rs", "notes.txt", "gen/out.rs"] {
+            fs::write(src.join(file), "content").unwrap();
+        }
+        let root = src.to_string_lossy().to_string();
+
+        let selected = |patterns: &[&str]| {
+            let mut inputs = vec![root.clone()];
+            inputs.extend(patterns.iter().map(|pattern| pattern.to_string()));
+            let config = Config::builder()
+                .output_path(Some("out.7z"), false)
+                .show_progress(false)
+                .build()
+                .unwrap();
+            let service = BackupService::new(
+                IndexRecordingArchiver::default(),
+                Box::new(VecReader::new(inputs)),
+                config,
+            );
+            let src = src.clone();
+            async move {
+                let mut names: Vec<String> = service
+                    .get_input_paths()
+                    .await
+                    .unwrap()
+                    .iter()
+                    .map(|path| {
+                        let relative = Path::new(path).strip_prefix(&src).unwrap();
+                        relative.to_string_lossy().replace('\\', "/")
+                    })
+                    .collect();
+                names.sort();
+                names
+            }
+        };
+
+        assert_eq!(
+            selected(&["+*.rs"]).await,
+            ["gen/out.rs", "lib.rs", "main.rs"]
+        );
+        assert_eq!(
+            selected(&["!gen/*"]).await,
+            ["lib.rs", "main.rs", "notes.txt"]
+        );
+        // The include gate applies first, then exclusions remove from what it kept
+        assert_eq!(selected(&["+*.rs", "!gen/*"]).await, ["lib.rs", "main.rs"]);
+        assert_eq!(
+            selected(&["+*.rs", "+*.txt", "!main.*"]).await,
+            ["gen/out.rs", "lib.rs", "notes.txt"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_input_paths_are_read_from_expand_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("a.txt"), "a").unwrap();
+        fs::write(source.join("big.txt"), "big file").unwrap();
+        let cache = temp_dir.path().join("expand.cache");
+        let inputs = vec![source.to_string_lossy().to_string()];
+
+        let input_paths = |max_file_size: Option<u64>| {
+            let config = Config::builder()
+                .output_path(Some("out.7z"), false)
+                .show_progress(false)
+                .expand_cache(Some(&cache.to_string_lossy()))
+                .max_file_size(max_file_size)
+                .build()
+                .unwrap();
+            let service = BackupService::new(
+                MockArchiver::new(),
+                Box::new(VecReader::new(inputs.clone())),
+                config,
+            );
+            async move {
+                let mut paths = service.get_input_paths().await.unwrap();
+                paths.sort();
+                paths
+            }
+        };
+        let a = source.join("a.txt").to_string_lossy().to_string();
+        let big = source.join("big.txt").to_string_lossy().to_string();
+        assert_eq!(input_paths(None).await, [a.clone(), big.clone()]);
+
+        // Remove a file behind the cache's back: the folder keeps its time, so the
+        // cached paths are returned without walking the tree again
+        let modified = fs::metadata(&source).unwrap().modified().unwrap();
+        fs::remove_file(source.join("a.txt")).unwrap();
+        fs::File::open(&source)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_eq!(input_paths(None).await, [a, big.clone()]);
+
+        // Other filters select other files, so the tree is walked again
+        assert_eq!(input_paths(Some(4)).await, Vec::<String>::new());
+        assert_eq!(input_paths(None).await, [big]);
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_tree_is_not_archived_again() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("a.txt"), "a").unwrap();
+        fs::write(source.join("b.txt"), "b").unwrap();
+        let output = temp_dir.path().join("out.7z").to_string_lossy().to_string();
+        let inputs = vec![source.to_string_lossy().to_string()];
+
+        let run_at = |level: Option<u8>| {
+            let config = Config::builder()
+                .output_path(Some(&output), false)
+                .show_progress(false)
+                .skip_if_unchanged(true)
+                .compression_level(level)
+                .build()
+                .unwrap();
+            let archiver = IndexRecordingArchiver::default();
+            let service = BackupService::new(
+                archiver.clone(),
+                Box::new(VecReader::new(inputs.clone())),
+                config,
+            );
+            async move {
+                let report = service.run().await.unwrap();
+                let archived = archiver.paths.lock().unwrap().len();
+                (report.unchanged, archived)
+            }
+        };
+        let run = || run_at(None);
+
+        assert_eq!(run().await, (false, 2));
+        assert!(structure_hash_path(&output).exists());
+        // The mock archiver writes nothing; a missing archive is always rebuilt
+        assert_eq!(run().await, (false, 2));
+        fs::write(&output, "archive").unwrap();
+        assert_eq!(run().await, (true, 0));
+
+        let file = fs::File::options()
+            .write(true)
+            .open(source.join("a.txt"))
+            .unwrap();
+        file.set_modified(std::time::SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+        assert_eq!(run().await, (false, 2));
+        assert_eq!(run().await, (true, 0));
+
+        // Archiving the same files with other settings builds the archive again
+        assert_eq!(run_at(Some(9)).await, (false, 2));
+        assert_eq!(run_at(Some(9)).await, (true, 0));
+
+        // Directories handed over whole would only hash the directories themselves
+        let whole = Config::builder()
+            .output_path(Some(&output), false)
+            .skip_if_unchanged(true)
+            .whole_directories(true)
+            .build();
+        assert!(whole.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_whole_directories_are_passed_as_directory_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("project");
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        fs::write(&file, "notes").unwrap();
+        let paths = vec![
+            dir.to_string_lossy().to_string(),
+            file.to_string_lossy().to_string(),
+        ];
+
+        let archived = |whole: bool| {
+            let config = Config::builder()
+                .output_path(Some("out.7z"), false)
+                .show_progress(false)
+                .whole_directories(whole)
+                .build()
+                .unwrap();
+            let archiver = IndexRecordingArchiver::default();
+            let service = BackupService::new(
+                archiver.clone(),
+                Box::new(VecReader::new(paths.clone())),
+                config,
+            );
+            async move {
+                service.run().await.unwrap();
+                archiver.paths.lock().unwrap().clone()
+            }
+        };
+
+        assert_eq!(
+            archived(false).await,
+            vec![
+                dir.join("a.txt").to_string_lossy().to_string(),
+                file.to_string_lossy().to_string(),
+            ]
+        );
+        assert_eq!(archived(true).await, paths);
+    }
+
+    #[tokio::test]
+    async fn test_embed_index_adds_tree_of_processed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let a = temp_dir.path().join("a.txt");
+        let b = temp_dir.path().join("b.txt");
+        fs::write(&a, "a".repeat(10)).unwrap();
+        fs::write(&b, "b".repeat(2000)).unwrap();
+
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .embed_index(true)
+            .build()
+            .unwrap();
+        let paths = vec![
+            a.to_string_lossy().to_string(),
+            b.to_string_lossy().to_string(),
+        ];
+        let archiver = IndexRecordingArchiver::default();
+        let service = BackupService::new(archiver.clone(), Box::new(VecReader::new(paths)), config);
+
+        service.run().await.unwrap();
+
+        // The files keep their full paths; the index is added separately at the root
+        let archived = archiver.paths.lock().unwrap().clone();
+        assert_eq!(archived, [a.to_string_lossy(), b.to_string_lossy()]);
+        let root_files = archiver.root_files.lock().unwrap().clone();
+        assert_eq!(root_files.len(), 1);
+        let (index_path, index) = root_files[0].clone();
+        assert!(index_path.ends_with("tree.txt"));
+        assert!(!Path::new(&index_path).exists());
+
+        assert_eq!(
+            index,
+            format!(
+                "# archtree index: 2 files, 2010 bytes\n{:>14}  {}\n{:>14}  {}\n",
+                10,
+                a.display(),
+                2000,
+                b.display()
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_dedupe_hardlink_adds_left_out_links_at_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let original = temp_dir.path().join("a_original.txt");
+        let link = temp_dir.path().join("b_link.txt");
+        fs::write(&original, "shared").unwrap();
+        fs::hard_link(&original, &link).unwrap();
+
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .dedupe_hardlinks(true)
+            .build()
+            .unwrap();
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+        let archiver = IndexRecordingArchiver::default();
+        let service = BackupService::new(archiver.clone(), Box::new(VecReader::new(paths)), config);
+
+        service.run().await.unwrap();
+
+        // One link is archived, the other is recorded next to it for restoring
+        let archived = archiver.paths.lock().unwrap().clone();
+        assert_eq!(archived.len(), 1);
+        let kept = PathBuf::from(&archived[0]);
+        let duplicate = if kept == original { &link } else { &original };
+        let root_files = archiver.root_files.lock().unwrap().clone();
+        assert_eq!(root_files.len(), 1);
+        let (hardlinks_path, hardlinks) = &root_files[0];
+        assert!(hardlinks_path.ends_with("hardlinks.txt"));
+        assert_eq!(
+            hardlinks.lines().nth(1),
+            Some(format!("{}\t{}", duplicate.display(), kept.display()).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_archive_after_successful_create_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "a").unwrap();
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+
+        // Only a directory entry: 7-Zip skipped every file
+        let directory = ArchiveEntry {
+            path: "data".to_string(),
+            is_directory: true,
+            size: 0,
+            modified: None,
+            packed_size: None,
+            crc: None,
+        };
+        let service = BackupService::new(
+            MockArchiver::new(),
+            Box::new(VecReader::new(paths.clone())),
+            config.clone(),
+        )
+        .with_archive_check(MockVerifier::new().with_archive("out.7z", vec![directory.clone()]));
+        let error = service.run().await.unwrap_err();
+        assert!(matches!(error, ArchtreeError::Verification { .. }));
+        assert!(error.to_string().contains("contains no files"));
+
+        // The embedded index alone doesn't count as an archived file
+        let service = BackupService::new(
+            MockArchiver::new(),
+            Box::new(VecReader::new(paths.clone())),
+            Config {
+                embed_index: true,
+                ..config.clone()
+            },
+        )
+        .with_archive_check(
+            MockVerifier::new().with_archive("out.7z", vec![file_entry("tree.txt")]),
+        );
+        let error = service.run().await.unwrap_err();
+        assert!(error.to_string().contains("contains no files"));
+
+        let file = ArchiveEntry {
+            path: "data/a.txt".to_string(),
+            is_directory: false,
+            size: 1,
+            ..directory
+        };
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config)
+                .with_archive_check(MockVerifier::new().with_archive("out.7z", vec![file]));
+        service.run().await.unwrap();
+    }
+
+    #[test]
+    fn test_permission_denied_counted_separately() {
+        let mut summary = BackupSummary::default();
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        summary.record(&ProcessingStatus::from_io_error(&denied));
+        summary.record(&ProcessingStatus::from_io_error(&denied));
+        summary.record(&ProcessingStatus::from_io_error(&missing));
+        summary.record(&ProcessingStatus::Added);
+
+        assert_eq!(summary.permission_denied, 2);
+        assert_eq!(summary.invalid, 1);
+        assert_eq!(summary.added, 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unreadable_directory_counts_as_permission_denied() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let locked = temp_dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("secret.txt"), "secret").unwrap();
+        fs::write(temp_dir.path().join("open.txt"), "open").unwrap();
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        if fs::read_dir(&locked).is_ok() {
+            // Root reads the directory regardless of its mode, so nothing can be denied
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            eprintln!(
+                "skipping test_unreadable_directory_counts_as_permission_denied: running as root"
+            );
+            return;
+        }
+
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config);
+        let result = service.get_input_paths().await;
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(result.unwrap(), vec![temp_dir.path().join("open.txt")]);
+        let summary = service.summary().unwrap();
+        assert_eq!(summary.permission_denied, 1);
+        assert_eq!(summary.invalid, 0);
+    }
+
+    #[tokio::test]
+    async fn test_file_vanished_before_archiving_is_dropped() {
+        let temp_dir = TempDir::new().unwrap();
+        let kept = temp_dir.path().join("kept.txt");
+        let vanished = temp_dir.path().join("vanished.txt");
+        fs::write(&kept, "kept").unwrap();
+        fs::write(&vanished, "gone soon").unwrap();
+
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+        let service = BackupService::new(
+            MockArchiver::new(),
+            Box::new(VecReader::new(paths.clone())),
+            config.clone(),
+        );
+        let processed = service.process_input_paths().await.unwrap();
+        assert_eq!(processed.len(), 2);
+
+        // Deleted after the walk, before 7-Zip would read it
+        fs::remove_file(&vanished).unwrap();
+        assert_eq!(
+            service.drop_vanished(processed.clone()).unwrap(),
+            vec![kept]
+        );
+
+        let config = Config {
+            fail_on_vanished: true,
+            ..config
+        };
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config);
+        assert!(service.drop_vanished(processed).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_report_of_estimate_only_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "a".repeat(300)).unwrap();
+        fs::write(temp_dir.path().join("b.txt"), "b".repeat(100)).unwrap();
+        fs::write(temp_dir.path().join("skip.tmp"), "tmp").unwrap();
+
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .estimate_ratio(Some(0.5))
+            .build()
+            .unwrap();
+        let paths = vec![
+            temp_dir.path().to_string_lossy().to_string(),
+            "!*.tmp".to_string(),
+        ];
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config);
+
+        let report = service.run().await.unwrap();
+
+        assert_eq!(report.archive_path, "out.7z");
+        assert_eq!(report.config.estimate_ratio, Some(0.5));
+        assert_eq!(report.processing.added, 2);
+        assert_eq!(report.processing.excluded, 1);
+        assert_eq!(report.processing.total_files, 2);
+        assert_eq!(report.total_bytes, 400);
+        assert_eq!(report.estimated_size, Some(200));
+        assert!(!report.wrote_archive());
+        assert!(report.verification.is_none());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["processing"]["added"], 2);
+        assert_eq!(json["config"]["format"], "7z");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_skips_archiving() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "a").unwrap();
+        fs::write(temp_dir.path().join("skip.tmp"), "tmp").unwrap();
+        let missing = temp_dir.path().join("missing.txt");
+
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .dry_run(true)
+            .build()
+            .unwrap();
+        let paths = vec![
+            temp_dir.path().to_string_lossy().to_string(),
+            missing.to_string_lossy().to_string(),
+            "!*.tmp".to_string(),
+        ];
+        // Neither the availability check nor archiving may be reached
+        let service =
+            BackupService::new(UnavailableArchiver, Box::new(VecReader::new(paths)), config);
+
+        let report = service.run().await.unwrap();
+
+        assert_eq!(report.processing.added, 1);
+        assert_eq!(report.processing.excluded, 1);
+        assert_eq!(report.processing.invalid, 1);
+        assert!(!report.wrote_archive());
+        assert_eq!(
+            service.get_input_paths().await.unwrap(),
+            vec![temp_dir.path().join("a.txt").to_string_lossy().to_string()]
+        );
+    }
+
+    /// Archiver that records the list files it is given
+    #[derive(Clone, Default)]
+    struct ListRecordingArchiver {
+        lists: std::sync::Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    #[async_trait]
+    impl Archiver for ListRecordingArchiver {
+        async fn create_archive(&self, _paths: &[String], _output_path: &str) -> Result<()> {
+            unreachable!("a raw list must not be processed into paths")
+        }
+
+        async fn add_to_archive(&self, _paths: &[String], _archive_path: &str) -> Result<()> {
+            unreachable!("a raw list must not be processed into paths")
+        }
+
+        async fn create_archive_from_list(&self, list_path: &str, output_path: &str) -> Result<()> {
+            self.lists
+                .lock()
+                .unwrap()
+                .push((list_path.to_string(), output_path.to_string()));
+            Ok(())
+        }
+
+        async fn is_available(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &'static str {
+            "Mock"
+        }
+    }
+
+    /// Reader that fails the test if the input is read
+    struct UnreadReader;
+
+    #[async_trait]
+    impl InputReader for UnreadReader {
+        async fn read_paths(&self) -> Result<Vec<String>> {
+            unreachable!("a raw list backup must not read the input")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_raw_list_skips_processing() {
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .raw_list(Some("prepared.txt"))
+            .build()
+            .unwrap();
+        let archiver = ListRecordingArchiver::default();
+        let service = BackupService::new(archiver.clone(), Box::new(UnreadReader), config);
+
+        let report = service.run().await.unwrap();
+
+        assert_eq!(
+            *archiver.lists.lock().unwrap(),
+            vec![("prepared.txt".to_string(), "out.7z".to_string())]
+        );
+        assert!(service.summary().is_none());
+        assert_eq!(report.processing.total_files, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fail_on_empty_file_set() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("only.log"), "log").unwrap();
+        let paths = vec![
+            temp_dir.path().to_string_lossy().to_string(),
+            "!*.log".to_string(),
+        ];
+
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+
+        // By default an empty expected set is accepted (and verifies as 100%)
+        let service = BackupService::new(
+            MockArchiver::new(),
+            Box::new(VecReader::new(paths.clone())),
+            config.clone(),
+        );
+        assert!(service.get_input_paths().await.unwrap().is_empty());
+
+        let config = Config {
+            fail_on_empty: true,
+            ..config
+        };
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config);
+        let error = service.get_input_paths().await.unwrap_err();
+        assert!(error.to_string().contains("--fail-on-empty"));
+    }
+
+    #[tokio::test]
+    async fn test_fail_on_empty_file_set_from_expand_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("only.log"), "log").unwrap();
+        let cache = temp_dir.path().join("expand.cache");
+        let paths = vec![source.to_string_lossy().to_string(), "!*.log".to_string()];
+
+        let service = |fail_on_empty: bool| {
+            let config = Config::builder()
+                .output_path(Some("out.7z"), false)
+                .show_progress(false)
+                .expand_cache(Some(&cache.to_string_lossy()))
+                .fail_on_empty(fail_on_empty)
+                .build()
+                .unwrap();
+            BackupService::new(
+                MockArchiver::new(),
+                Box::new(VecReader::new(paths.clone())),
+                config,
+            )
+        };
+
+        // A verification without the flag caches the empty set...
+        assert!(service(false).get_input_paths().await.unwrap().is_empty());
+        assert!(cache.exists());
+
+        // ...and one with it fails although the set is read from the cache
+        let error = service(true).get_input_paths().await.unwrap_err();
+        assert!(error.to_string().contains("--fail-on-empty"));
+    }
+
+    #[tokio::test]
+    async fn test_default_excludes_are_applied() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("kept.txt"), "kept").unwrap();
+        fs::write(temp_dir.path().join("build.log"), "log").unwrap();
+        fs::write(temp_dir.path().join("cache.tmp"), "tmp").unwrap();
+        fs::write(temp_dir.path().join("notes.bak"), "bak").unwrap();
+
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .default_excludes(Some("*.log; *.tmp"), false)
+            .build()
+            .unwrap();
+        assert_eq!(config.default_excludes, vec!["*.log", "*.tmp"]);
+
+        let paths = vec![
+            temp_dir.path().to_string_lossy().to_string(),
+            "!*.bak".to_string(),
+        ];
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config);
+        let input_paths = service.get_input_paths().await.unwrap();
+
+        assert_eq!(input_paths.len(), 1);
+        assert!(input_paths[0].ends_with("kept.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_backup_service_with_valid_paths() {
+        // Create temporary test files
+        let temp_dir = TempDir::new().unwrap();
+        let test_file1 = temp_dir.path().join("test1.txt");
+        let test_file2 = temp_dir.path().join("test2.txt");
+        let test_file3 = temp_dir.path().join("test3.tmp");
+
+        fs::write(&test_file1, "Hello, World!").unwrap();
+        fs::write(&test_file2, "Test content").unwrap();
+        fs::write(&test_file3, "Temp file").unwrap();
+
+        let paths = vec![
+            test_file1.to_string_lossy().to_string(),
+            test_file2.to_string_lossy().to_string(),
+            test_file3.to_string_lossy().to_string(),
+            "!*.tmp".to_string(),
+        ];
+
+        let archiver = SevenZipArchiver::new();
+        let reader = Box::new(VecReader::new(paths));
+        let config = Config::builder()
+            .output_path(Some("test.7z"), false)
             .show_progress(false)
             .build()
             .unwrap();
@@ -227,6 +2072,73 @@ mod tests {
         assert!(!input_paths.iter().any(|p| p.contains("test3.tmp")));
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_null_delimited_name_with_line_break_is_rejected() {
+        use crate::io::FileReader;
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let plain = temp_dir.path().join("plain.txt");
+        let broken = temp_dir.path().join("line\nbreak.txt");
+        fs::write(&plain, "plain").unwrap();
+        fs::write(&broken, "broken").unwrap();
+        let list = temp_dir.path().join("list");
+        fs::write(
+            &list,
+            format!("{}\0{}\0", plain.display(), broken.display()),
+        )
+        .unwrap();
+
+        // Stand-in for 7-Zip that records every invocation
+        let calls = temp_dir.path().join("calls.txt");
+        let runner = temp_dir.path().join("fake7z");
+        fs::write(
+            &runner,
+            format!("#!/bin/sh\necho \"$1\" >> '{}'\n", calls.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&runner, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let output = temp_dir.path().join("out.7z");
+        let config = Config::builder()
+            .output_path(Some(&output.to_string_lossy()), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+        let reader = FileReader::new(&list.to_string_lossy()).with_null_delimiter(true);
+        let service = BackupService::new(
+            SevenZipArchiver::with_path(runner.to_string_lossy().to_string()),
+            Box::new(reader),
+            config,
+        );
+
+        // The name reaches the archiver whole, which refuses to split it across lines
+        let error = service.run().await.unwrap_err();
+        assert_eq!(error.category(), "config");
+        assert!(error.to_string().contains("line\\nbreak.txt"), "{}", error);
+        let calls = fs::read_to_string(&calls).unwrap_or_default();
+        assert!(!calls.lines().any(|command| command == "a"), "{}", calls);
+    }
+
+    #[tokio::test]
+    async fn test_age_beyond_clock_range_is_config_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .show_progress(false)
+            .accessed_before(Some(Duration::from_secs(u64::MAX)))
+            .build()
+            .unwrap();
+        let paths = vec![temp_dir.path().to_string_lossy().to_string()];
+        let service =
+            BackupService::new(MockArchiver::new(), Box::new(VecReader::new(paths)), config);
+
+        let error = service.get_input_paths().await.unwrap_err();
+        assert_eq!(error.category(), "config");
+        assert!(error.to_string().contains("--accessed-before"), "{}", error);
+    }
+
     #[tokio::test]
     async fn test_relative_path_conversion() {
         // Create temporary test structure
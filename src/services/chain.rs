@@ -0,0 +1,360 @@
+use crate::core::{ArchtreeError, ErrorContext, Result};
+use crate::verification::verifier::{
+    ArchiveEntry, ArchiveVerifier, FreshnessStatus, compare_modification_times, comparison_key,
+};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// File name of the first member of a chain
+pub const FULL_ARCHIVE_NAME: &str = "full.7z";
+
+/// Number of files named when an incremental exceeds its size limit
+const LARGEST_CONTRIBUTORS: usize = 5;
+
+/// Archived files of a whole chain, keyed by the [`comparison_key`] of their archive path,
+/// so `home/user/file.txt` as 7-Zip lists it matches `/home/user/file.txt` on disk. Later
+/// members override earlier ones.
+pub type ChainListing = HashMap<String, ArchiveEntry>;
+
+/// A directory holding an incremental backup chain: `full.7z` followed by
+/// `inc-YYYYMMDD.7z` archives that each contain the files changed since the
+/// previous member. A second incremental on the same day is named `inc-YYYYMMDD-2.7z`.
+#[derive(Debug, Clone)]
+pub struct BackupChain {
+    dir: PathBuf,
+}
+
+/// The archive the next backup of a chain writes to
+#[derive(Debug, Clone)]
+pub struct ChainMember {
+    /// Path of the archive to create
+    pub archive_path: String,
+    /// Files already in the chain; `None` when this member is the full archive
+    pub baseline: Option<ChainListing>,
+}
+
+impl BackupChain {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Existing chain members in order: the full archive, then incrementals by date
+    pub fn members(&self) -> Result<Vec<PathBuf>> {
+        let full = self.dir.join(FULL_ARCHIVE_NAME);
+        if !full.is_file() {
+            return Ok(Vec::new());
+        }
+
+        let entries = std::fs::read_dir(&self.dir).context_io(format!(
+            "Failed to read chain directory: {}",
+            self.dir.display()
+        ))?;
+
+        let mut incrementals: Vec<((String, u32), PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                parse_incremental_name(&name).map(|key| (key, entry.path()))
+            })
+            .collect();
+        incrementals.sort();
+
+        let mut members = vec![full];
+        members.extend(incrementals.into_iter().map(|(_, path)| path));
+        Ok(members)
+    }
+
+    /// Path of the archive to create next: the full archive for a new chain,
+    /// otherwise the first unused incremental name for `date`
+    pub fn next_archive_path(&self, date: NaiveDate) -> PathBuf {
+        let full = self.dir.join(FULL_ARCHIVE_NAME);
+        if !full.exists() {
+            return full;
+        }
+
+        let stamp = date.format("%Y%m%d");
+        let mut path = self.dir.join(format!("inc-{}.7z", stamp));
+        let mut sequence = 2;
+        while path.exists() {
+            path = self.dir.join(format!("inc-{}-{}.7z", stamp, sequence));
+            sequence += 1;
+        }
+        path
+    }
+
+    /// Files of all members combined, the most recent member winning for each path
+    pub async fn listing<V: ArchiveVerifier>(&self, verifier: &V) -> Result<ChainListing> {
+        let mut listing = ChainListing::new();
+        for member in self.members()? {
+            let entries = verifier
+                .list_archive_entries(&member.to_string_lossy())
+                .await?;
+            for entry in entries.into_iter().filter(|entry| !entry.is_directory) {
+                if let Some(key) = comparison_key(&entry.path, 0) {
+                    listing.insert(key, entry);
+                }
+            }
+        }
+        Ok(listing)
+    }
+
+    /// Work out the next member, listing the existing chain when it is an incremental
+    pub async fn next_member<V: ArchiveVerifier>(
+        &self,
+        verifier: &V,
+        date: NaiveDate,
+    ) -> Result<ChainMember> {
+        std::fs::create_dir_all(&self.dir).context_io(format!(
+            "Failed to create chain directory: {}",
+            self.dir.display()
+        ))?;
+
+        let archive_path = self.next_archive_path(date);
+        let baseline = if archive_path.ends_with(FULL_ARCHIVE_NAME) {
+            None
+        } else {
+            Some(self.listing(verifier).await?)
+        };
+
+        Ok(ChainMember {
+            archive_path: archive_path.to_string_lossy().to_string(),
+            baseline,
+        })
+    }
+}
+
+/// Sort key of an incremental archive name (`inc-YYYYMMDD.7z` or `inc-YYYYMMDD-N.7z`)
+fn parse_incremental_name(name: &str) -> Option<(String, u32)> {
+    let stem = name.strip_prefix("inc-")?.strip_suffix(".7z")?;
+    let (date, sequence) = match stem.split_once('-') {
+        Some((date, sequence)) => (date, sequence.parse().ok()?),
+        None => (stem, 1),
+    };
+
+    if date.len() != 8 || !date.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((date.to_string(), sequence))
+}
+
+/// Files that are not in the chain yet or are newer on the filesystem than their archived copy
+pub fn changed_since(paths: &[PathBuf], listing: &ChainListing) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .filter(|path| is_changed(path, listing))
+        .cloned()
+        .collect()
+}
+
+/// Fail if the files an incremental would add total more than `limit` bytes.
+///
+/// A sudden jump usually means something unintended got into the inputs, like a cache
+/// folder, so the error names the largest files.
+pub fn check_incremental_size(paths: &[PathBuf], limit: u64) -> Result<()> {
+    let mut sizes: Vec<(u64, &PathBuf)> = paths
+        .iter()
+        .filter_map(|path| Some((path.metadata().ok()?.len(), path)))
+        .collect();
+    let total: u64 = sizes.iter().map(|(size, _)| size).sum();
+    if total <= limit {
+        return Ok(());
+    }
+
+    sizes.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    let largest: Vec<String> = sizes
+        .iter()
+        .take(LARGEST_CONTRIBUTORS)
+        .map(|(size, path)| format!("  {:>14}  {}", size, path.display()))
+        .collect();
+    Err(ArchtreeError::config(format!(
+        "Incremental backup would add {} bytes in {} files, more than --max-incremental-size ({} bytes). Largest files:\n{}",
+        total,
+        paths.len(),
+        limit,
+        largest.join("\n")
+    )))
+}
+
+fn is_changed(path: &Path, listing: &ChainListing) -> bool {
+    let Some(entry) = comparison_key(&path.to_string_lossy(), 0).and_then(|key| listing.get(&key))
+    else {
+        return true;
+    };
+
+    let fs_modified = std::fs::metadata(path).and_then(|metadata| metadata.modified());
+    match (entry.modified, fs_modified) {
+        (Some(archive_modified), Ok(fs_modified)) => {
+            compare_modification_times(archive_modified, fs_modified) == FreshnessStatus::Outdated
+        }
+        // Without both timestamps the file can't be shown to be unchanged
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Config;
+    use crate::io::VecReader;
+    use crate::services::BackupService;
+    use crate::testing::MockArchiver;
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    async fn backup_to_chain(
+        chain: &BackupChain,
+        archiver: &MockArchiver,
+        inputs: Vec<String>,
+        date: NaiveDate,
+    ) -> String {
+        let member = chain.next_member(&archiver.verifier(), date).await.unwrap();
+        let config = Config::builder()
+            .output_path(Some(&member.archive_path), false)
+            .show_progress(false)
+            .build()
+            .unwrap();
+
+        let mut service =
+            BackupService::new(archiver.clone(), Box::new(VecReader::new(inputs)), config);
+        if let Some(baseline) = member.baseline {
+            service = service.with_baseline(baseline);
+        }
+        service.run().await.unwrap();
+        member.archive_path
+    }
+
+    #[tokio::test]
+    async fn test_incremental_contains_only_changed_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = temp_dir.path().join("data");
+        fs::create_dir(&data).unwrap();
+        let unchanged = data.join("unchanged.txt");
+        let changed = data.join("changed.txt");
+        fs::write(&unchanged, "same").unwrap();
+        fs::write(&changed, "before").unwrap();
+
+        let chain = BackupChain::new(temp_dir.path().join("chain"));
+        let archiver = MockArchiver::new().writing_files();
+        let inputs = vec![data.to_string_lossy().to_string()];
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let full = backup_to_chain(&chain, &archiver, inputs.clone(), day).await;
+        assert!(full.ends_with(FULL_ARCHIVE_NAME));
+        assert_eq!(archiver.entries(&full).len(), 2);
+
+        // Modify one file; its timestamp moves past the freshness tolerance
+        fs::write(&changed, "after").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&changed)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+
+        let next_day = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        let incremental = backup_to_chain(&chain, &archiver, inputs, next_day).await;
+        assert!(incremental.ends_with("inc-20240302.7z"));
+
+        // 7-Zip lists the entry without the leading `/`, which must still match the file
+        let archived: Vec<String> = archiver
+            .entries(&incremental)
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        let changed = changed.to_string_lossy();
+        assert_eq!(archived, vec![changed.trim_start_matches('/')]);
+
+        assert_eq!(
+            chain.members().unwrap(),
+            vec![PathBuf::from(&full), PathBuf::from(&incremental)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_incremental_over_size_limit_is_aborted() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = temp_dir.path().join("data");
+        fs::create_dir(&data).unwrap();
+        fs::write(data.join("notes.txt"), "notes").unwrap();
+
+        let chain = BackupChain::new(temp_dir.path().join("chain"));
+        let archiver = MockArchiver::new().writing_files();
+        let inputs = vec![data.to_string_lossy().to_string()];
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let full = backup_to_chain(&chain, &archiver, inputs.clone(), day).await;
+        // Entries are listed the way 7-Zip stores them, without the leading `/`
+        let notes = data.join("notes.txt");
+        let notes_entry = notes.to_string_lossy().trim_start_matches('/').to_string();
+        assert_eq!(archiver.entries(&full)[0].path, notes_entry);
+
+        // A cache folder shows up after the full backup
+        let cache = data.join("cache");
+        fs::create_dir(&cache).unwrap();
+        fs::write(cache.join("big.bin"), vec![0u8; 3000]).unwrap();
+        fs::write(cache.join("small.bin"), vec![0u8; 1000]).unwrap();
+
+        let next_day = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        let member = chain
+            .next_member(&archiver.verifier(), next_day)
+            .await
+            .unwrap();
+        let config = Config::builder()
+            .output_path(Some(&member.archive_path), false)
+            .show_progress(false)
+            .max_incremental_size(Some(2000))
+            .build()
+            .unwrap();
+        let service =
+            BackupService::new(archiver.clone(), Box::new(VecReader::new(inputs)), config)
+                .with_baseline(member.baseline.unwrap());
+
+        // Only the new files count against the limit, not the already archived notes
+        let error = service.run().await.unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("4000 bytes in 2 files"), "{}", message);
+        assert!(!message.contains("notes.txt"), "{}", message);
+        assert!(message.contains("(2000 bytes)"), "{}", message);
+        let big = message.find("big.bin").unwrap();
+        let small = message.find("small.bin").unwrap();
+        assert!(big < small, "largest file first: {}", message);
+        assert!(!Path::new(&member.archive_path).exists());
+
+        // The same delta fits a larger limit
+        let paths = vec![cache.join("big.bin"), cache.join("small.bin")];
+        assert!(check_incremental_size(&paths, 4000).is_ok());
+    }
+
+    #[test]
+    fn test_incremental_names_order_by_date_and_sequence() {
+        let temp_dir = TempDir::new().unwrap();
+        let chain = BackupChain::new(temp_dir.path());
+        let day = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+
+        assert!(chain.next_archive_path(day).ends_with(FULL_ARCHIVE_NAME));
+        fs::write(temp_dir.path().join(FULL_ARCHIVE_NAME), b"").unwrap();
+        for name in ["inc-20240302.7z", "inc-20240301.7z", "notes.txt"] {
+            fs::write(temp_dir.path().join(name), b"").unwrap();
+        }
+
+        assert!(chain.next_archive_path(day).ends_with("inc-20240302-2.7z"));
+        fs::write(temp_dir.path().join("inc-20240302-2.7z"), b"").unwrap();
+
+        let names: Vec<String> = chain
+            .members()
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "full.7z",
+                "inc-20240301.7z",
+                "inc-20240302.7z",
+                "inc-20240302-2.7z"
+            ]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::core::{ErrorContext, Result};
+use std::path::{Path, PathBuf};
+
+/// Name of the file tree index embedded with `--embed-index`
+pub const INDEX_FILE_NAME: &str = "tree.txt";
+
+/// Name of the list of hard links left out with `--dedupe-hardlink`
+pub const HARDLINKS_FILE_NAME: &str = "hardlinks.txt";
+
+/// Render the index of an archive's files: a header, then one `size  path` line per file.
+///
+/// Files are listed in the order they are archived; a file whose size can't be read
+/// is listed with `-`.
+pub fn render_index(paths: &[PathBuf]) -> String {
+    let mut total_bytes = 0;
+    let mut lines = Vec::with_capacity(paths.len());
+    for path in paths {
+        let size = match path.metadata() {
+            Ok(metadata) => {
+                total_bytes += metadata.len();
+                metadata.len().to_string()
+            }
+            Err(_) => "-".to_string(),
+        };
+        lines.push(format!("{:>14}  {}", size, path.display()));
+    }
+
+    format!(
+        "# archtree index: {} files, {} bytes\n{}\n",
+        paths.len(),
+        total_bytes,
+        lines.join("\n")
+    )
+}
+
+/// Write the index of `paths` to [`INDEX_FILE_NAME`] in `dir`, returning its path.
+///
+/// The index takes the modification time of the newest file it lists, so it doesn't
+/// change the bytes of a `--reproducible` archive between runs over the same files.
+pub async fn write_index(paths: &[PathBuf], dir: &Path) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir).await.context_io(format!(
+        "Failed to create index directory: {}",
+        dir.display()
+    ))?;
+
+    let index_path = dir.join(INDEX_FILE_NAME);
+    tokio::fs::write(&index_path, render_index(paths))
+        .await
+        .context_io(format!("Failed to write index: {}", index_path.display()))?;
+
+    set_modified_to_newest(&index_path, paths)?;
+    Ok(index_path)
+}
+
+/// Render the hard links left out of an archive: a header, then one line per left out
+/// path with the archived path it links to, separated by a tab.
+pub fn render_hardlinks(hardlinks: &[(PathBuf, PathBuf)]) -> String {
+    let mut rendered = format!(
+        "# archtree hard links: {} paths left out, each followed by the archived path it links to\n",
+        hardlinks.len()
+    );
+    for (duplicate, kept) in hardlinks {
+        rendered.push_str(&format!("{}\t{}\n", duplicate.display(), kept.display()));
+    }
+    rendered
+}
+
+/// Write the hard links of `hardlinks` to [`HARDLINKS_FILE_NAME`] in `dir`, returning
+/// its path.
+///
+/// Like the index, it takes the modification time of the newest archived file it lists.
+pub async fn write_hardlinks(hardlinks: &[(PathBuf, PathBuf)], dir: &Path) -> Result<PathBuf> {
+    let hardlinks_path = dir.join(HARDLINKS_FILE_NAME);
+    tokio::fs::write(&hardlinks_path, render_hardlinks(hardlinks))
+        .await
+        .context_io(format!(
+            "Failed to write hard links: {}",
+            hardlinks_path.display()
+        ))?;
+
+    let kept: Vec<PathBuf> = hardlinks.iter().map(|(_, kept)| kept.clone()).collect();
+    set_modified_to_newest(&hardlinks_path, &kept)?;
+    Ok(hardlinks_path)
+}
+
+/// Give `file` the modification time of the newest of `paths`, if any can be read
+fn set_modified_to_newest(file: &Path, paths: &[PathBuf]) -> Result<()> {
+    let newest = paths
+        .iter()
+        .filter_map(|path| {
+            path.metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .max();
+    if let Some(newest) = newest {
+        std::fs::File::options()
+            .write(true)
+            .open(file)
+            .and_then(|file| file.set_modified(newest))
+            .context_io(format!(
+                "Failed to set modification time of {}",
+                file.display()
+            ))?;
+    }
+    Ok(())
+}
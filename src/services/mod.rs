@@ -1,3 +1,11 @@
 pub mod backup;
+pub mod chain;
+pub mod index;
+pub mod report;
+pub mod structure;
 
-pub use backup::BackupService;
+pub use backup::{
+    BackupCallback, BackupEvent, BackupService, BackupSummary, ConsoleBackupCallback,
+};
+pub use chain::{BackupChain, ChainMember};
+pub use report::{ResultLine, RunReport};
@@ -0,0 +1,192 @@
+use crate::core::{Config, Result};
+use crate::services::BackupSummary;
+use crate::verification::verifier::{FreshnessVerificationResult, VerificationResult};
+use serde::Serialize;
+
+/// Everything a backup run produced, in one object for embedders to inspect or serialize
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    /// Configuration the run used
+    pub config: Config,
+    /// Counts from processing the input paths
+    pub processing: BackupSummary,
+    /// Total size of the files selected for the archive in bytes
+    pub total_bytes: u64,
+    /// Path of the archive
+    pub archive_path: String,
+    /// Size of the written archive; `None` if no archive was written
+    pub archive_size: Option<u64>,
+    /// Projected archive size of an estimate-only run
+    pub estimated_size: Option<u64>,
+    /// Result of verifying the archive, if it was verified
+    pub verification: Option<VerificationResult>,
+    /// Result of comparing modification times, if freshness was checked
+    pub freshness: Option<FreshnessVerificationResult>,
+    /// Whether archiving was skipped because the source tree matched its stored structure hash
+    pub unchanged: bool,
+}
+
+impl RunReport {
+    /// Start an empty report for a run with `config`
+    pub fn new(config: Config) -> Self {
+        Self {
+            archive_path: config.output_path.clone(),
+            config,
+            processing: BackupSummary::default(),
+            total_bytes: 0,
+            archive_size: None,
+            estimated_size: None,
+            verification: None,
+            freshness: None,
+            unchanged: false,
+        }
+    }
+
+    /// Start an empty report for verifying `archive_path`, which with several archives
+    /// needn't be the output path of `config`
+    pub fn for_archive(config: Config, archive_path: &str) -> Self {
+        Self {
+            archive_path: archive_path.to_string(),
+            ..Self::new(config)
+        }
+    }
+
+    /// Attach the result of verifying the archive
+    pub fn with_verification(mut self, verification: VerificationResult) -> Self {
+        self.verification = Some(verification);
+        self
+    }
+
+    /// Attach the result of a freshness check
+    pub fn with_freshness(mut self, freshness: FreshnessVerificationResult) -> Self {
+        self.freshness = Some(freshness);
+        self
+    }
+
+    /// Whether an archive was written
+    pub fn wrote_archive(&self) -> bool {
+        self.archive_size.is_some()
+    }
+}
+
+/// Prefix of the line printed by `--result-line`
+pub const RESULT_LINE_PREFIX: &str = "ARCHTREE_RESULT";
+
+/// Counters for the one-line `key=value` summary printed by `--result-line`.
+///
+/// The line always has the same keys in the same order, so it can be parsed with a
+/// fixed pattern; counters that don't apply to a command are printed as `-`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResultLine {
+    /// Name of the command that ran
+    pub command: &'static str,
+    /// Files added to the archive
+    pub added: Option<usize>,
+    /// Expected files missing from the verified archives
+    pub missing: Option<usize>,
+    /// Size of the written archive in bytes
+    pub size: Option<u64>,
+}
+
+impl ResultLine {
+    pub fn new(command: &'static str) -> Self {
+        Self {
+            command,
+            ..Self::default()
+        }
+    }
+
+    /// Take the added count, archive size and verification outcome of a backup
+    pub fn record_report(&mut self, report: &RunReport) {
+        self.added = Some(report.processing.added);
+        self.size = report.archive_size;
+        if let Some(verification) = &report.verification {
+            self.record_verification(verification);
+        }
+    }
+
+    /// Add the missing files of one verified archive
+    pub fn record_verification(&mut self, result: &VerificationResult) {
+        *self.missing.get_or_insert(0) += result.missing_files.len();
+    }
+
+    /// Render the line for the outcome of the command
+    pub fn render(&self, result: &Result<()>) -> String {
+        fn value<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(|| "-".to_string(), |value| value.to_string())
+        }
+
+        let (status, exit_code, error) = match result {
+            Ok(()) => ("ok", 0, "-"),
+            Err(e) => ("error", e.exit_code(), e.category()),
+        };
+        format!(
+            "{} command={} status={} exit_code={} error={} added={} missing={} size={}",
+            RESULT_LINE_PREFIX,
+            self.command,
+            status,
+            exit_code,
+            error,
+            value(self.added),
+            value(self.missing),
+            value(self.size)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ArchtreeError;
+
+    fn verification(missing: &[&str]) -> VerificationResult {
+        VerificationResult {
+            missing_files: missing.iter().map(|path| path.to_string()).collect(),
+            expected_missing: Vec::new(),
+            archived_files: vec!["/data/a.txt".to_string()],
+            all_expected_files: Vec::new(),
+            total_expected: 1 + missing.len(),
+            total_archived: 1,
+            listing_warnings: Vec::new(),
+            corrupted_files: Vec::new(),
+            unchecked_files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_result_line_for_backup() {
+        let config = Config::builder()
+            .output_path(Some("out.7z"), false)
+            .build()
+            .unwrap();
+        let mut report = RunReport::new(config).with_verification(verification(&[]));
+        report.processing.added = 42;
+        report.archive_size = Some(12345);
+
+        let mut line = ResultLine::new("backup");
+        line.record_report(&report);
+
+        assert_eq!(
+            line.render(&Ok(())),
+            "ARCHTREE_RESULT command=backup status=ok exit_code=0 error=- added=42 missing=0 size=12345"
+        );
+    }
+
+    #[test]
+    fn test_result_line_for_verify() {
+        let mut line = ResultLine::new("verify");
+        line.record_verification(&verification(&["/data/b.txt"]));
+        line.record_verification(&verification(&["/data/c.txt", "/data/d.txt"]));
+
+        assert_eq!(
+            line.render(&Ok(())),
+            "ARCHTREE_RESULT command=verify status=ok exit_code=0 error=- added=- missing=3 size=-"
+        );
+
+        let failed = Err(ArchtreeError::tool_not_available("7z"));
+        assert_eq!(
+            line.render(&failed),
+            "ARCHTREE_RESULT command=verify status=error exit_code=127 error=tool_not_available added=- missing=3 size=-"
+        );
+    }
+}
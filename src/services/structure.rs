@@ -0,0 +1,92 @@
+use crate::core::{ErrorContext, Result};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Suffix of the file next to an archive holding the structure hash of its source tree
+pub const STRUCTURE_HASH_SUFFIX: &str = ".tree-hash";
+
+/// Hash over `settings` and the sorted (path, size, modification time) of `paths`,
+/// identifying the state of a source tree and how it is archived without reading any
+/// file contents.
+///
+/// Adding, removing, renaming, resizing or touching a file changes the hash, as does any
+/// change to `settings`; input order doesn't. A file whose metadata can't be read is
+/// hashed by its path alone.
+pub fn structure_hash(paths: &[PathBuf], settings: &str) -> String {
+    let mut sorted: Vec<&PathBuf> = paths.iter().collect();
+    sorted.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(settings.as_bytes());
+    hasher.update([0]);
+    for path in sorted {
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        let metadata = path.metadata().ok();
+        let size = metadata.as_ref().map(|metadata| metadata.len());
+        let modified = metadata
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|modified| modified.as_nanos());
+        hasher.update(format!("{:?}\t{:?}\n", size, modified).as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Path of the structure hash file of the archive at `output_path`
+pub fn structure_hash_path(output_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}{}", output_path, STRUCTURE_HASH_SUFFIX))
+}
+
+/// The stored structure hash, if the file exists and can be read
+pub async fn read_structure_hash(path: &Path) -> Option<String> {
+    let content = tokio::fs::read_to_string(path).await.ok()?;
+    Some(content.trim().to_string())
+}
+
+/// Store `hash` in the file at `path`
+pub async fn write_structure_hash(path: &Path, hash: &str) -> Result<()> {
+    tokio::fs::write(path, format!("{}\n", hash))
+        .await
+        .context_io(format!(
+            "Failed to write structure hash: {}",
+            path.display()
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_structure_hash_follows_modification_times() {
+        let temp_dir = TempDir::new().unwrap();
+        let a = temp_dir.path().join("a.txt");
+        let b = temp_dir.path().join("b.txt");
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+
+        let hash = structure_hash(&[a.clone(), b.clone()], "");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(structure_hash(&[b.clone(), a.clone()], ""), hash);
+        assert_ne!(
+            structure_hash(&[a.clone(), b.clone()], "level=Some(9)"),
+            hash
+        );
+
+        // Same size and content, only the modification time moves
+        let file = fs::File::options().write(true).open(&a).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+        assert_ne!(structure_hash(&[a.clone(), b.clone()], ""), hash);
+        assert_ne!(structure_hash(&[a], ""), structure_hash(&[b], ""));
+    }
+}
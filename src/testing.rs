@@ -0,0 +1,306 @@
+//! Test doubles for 7-Zip shared by the unit tests of several modules
+
+use crate::core::{ArchtreeError, ErrorContext, Result};
+use crate::io::Archiver;
+use crate::verification::verifier::{
+    ArchiveEntry, ArchiveVerifier, DirectoryEnumerator, FileEnumerator, FreshnessStatus,
+    FreshnessVerificationResult, OutdatedFile, VerificationResult, compare_modification_times,
+    comparison_key,
+};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+type Archives = Arc<Mutex<HashMap<String, Vec<ArchiveEntry>>>>;
+
+/// Archiver keeping its archives in memory.
+///
+/// Entries are stored the way 7-Zip lists an archive created with full paths: without
+/// the leading `/`, with directories walked and the size and modification time of
+/// each file. Paths that don't exist are skipped, as 7-Zip does after warning.
+#[derive(Clone, Default)]
+pub(crate) struct MockArchiver {
+    archives: Archives,
+    write_files: bool,
+}
+
+impl MockArchiver {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also write an empty file at each archive path, for code that looks for it on disk
+    pub(crate) fn writing_files(mut self) -> Self {
+        self.write_files = true;
+        self
+    }
+
+    /// Entries of an archive, empty if it was never created
+    pub(crate) fn entries(&self, archive_path: &str) -> Vec<ArchiveEntry> {
+        self.archives
+            .lock()
+            .unwrap()
+            .get(archive_path)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Verifier listing the archives this archiver created
+    pub(crate) fn verifier(&self) -> MockVerifier {
+        MockVerifier {
+            archives: self.archives.clone(),
+        }
+    }
+
+    fn store(&self, paths: &[String], archive_path: &str, replace: bool) -> Result<()> {
+        if self.write_files {
+            fs::write(archive_path, b"")
+                .context_io(format!("Failed to write archive: {}", archive_path))?;
+        }
+
+        let mut added = Vec::new();
+        for path in paths {
+            add_entries(Path::new(path), &mut added);
+        }
+
+        let mut archives = self.archives.lock().unwrap();
+        let entries = archives.entry(archive_path.to_string()).or_default();
+        if replace {
+            entries.clear();
+        }
+        let added_paths: HashSet<&str> = added.iter().map(|entry| entry.path.as_str()).collect();
+        entries.retain(|entry| !added_paths.contains(entry.path.as_str()));
+        entries.extend(added);
+        Ok(())
+    }
+}
+
+/// Add the entry of `path`, and of everything below it if it's a directory
+fn add_entries(path: &Path, entries: &mut Vec<ArchiveEntry>) {
+    let Ok(metadata) = fs::metadata(path) else {
+        return;
+    };
+
+    entries.push(ArchiveEntry {
+        path: path.to_string_lossy().trim_start_matches('/').to_string(),
+        is_directory: metadata.is_dir(),
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        modified: metadata.modified().ok(),
+        packed_size: None,
+        crc: None,
+    });
+
+    if metadata.is_dir()
+        && let Ok(children) = fs::read_dir(path)
+    {
+        let mut children: Vec<_> = children.filter_map(|child| child.ok()).collect();
+        children.sort_by_key(|child| child.file_name());
+        for child in children {
+            add_entries(&child.path(), entries);
+        }
+    }
+}
+
+#[async_trait]
+impl Archiver for MockArchiver {
+    async fn create_archive(&self, paths: &[String], output_path: &str) -> Result<()> {
+        self.store(paths, output_path, true)
+    }
+
+    async fn add_to_archive(&self, paths: &[String], archive_path: &str) -> Result<()> {
+        self.store(paths, archive_path, false)
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "Mock"
+    }
+}
+
+/// Verifier comparing against in-memory listings, from a [`MockArchiver`] or given
+/// with [`MockVerifier::with_archive`].
+///
+/// Expected directories are walked; other expected paths are matched as given, whether
+/// or not they exist. Archives it doesn't know fail to list.
+#[derive(Clone, Default)]
+pub(crate) struct MockVerifier {
+    archives: Archives,
+}
+
+impl MockVerifier {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `entries` as the listing of `archive_path`
+    pub(crate) fn with_archive(self, archive_path: &str, entries: Vec<ArchiveEntry>) -> Self {
+        self.archives
+            .lock()
+            .unwrap()
+            .insert(archive_path.to_string(), entries);
+        self
+    }
+
+    /// Serve files at `paths` as the listing of `archive_path`
+    pub(crate) fn with_files(self, archive_path: &str, paths: &[&str]) -> Self {
+        let entries = paths.iter().map(|path| file_entry(path)).collect();
+        self.with_archive(archive_path, entries)
+    }
+
+    /// Archived files by comparison key, leaving out directories
+    async fn files_by_key(&self, archive_path: &str) -> Result<HashMap<String, ArchiveEntry>> {
+        Ok(self
+            .list_archive_entries(archive_path)
+            .await?
+            .into_iter()
+            .filter(|entry| !entry.is_directory)
+            .filter_map(|entry| Some((comparison_key(&entry.path, 0)?, entry)))
+            .collect())
+    }
+}
+
+/// Entry of a file at `path` without size, time or checksum
+pub(crate) fn file_entry(path: &str) -> ArchiveEntry {
+    ArchiveEntry {
+        path: path.to_string(),
+        is_directory: false,
+        size: 0,
+        modified: None,
+        packed_size: None,
+        crc: None,
+    }
+}
+
+/// Files below the expected directories, other expected paths as given
+fn expand(expected_paths: &[String]) -> Vec<String> {
+    let mut files = Vec::new();
+    for path in expected_paths {
+        files_below(Path::new(path), &mut files);
+    }
+    files
+}
+
+fn files_below(path: &Path, files: &mut Vec<String>) {
+    let Ok(children) = fs::read_dir(path) else {
+        files.push(path.to_string_lossy().to_string());
+        return;
+    };
+    let mut children: Vec<_> = children.filter_map(|child| child.ok()).collect();
+    children.sort_by_key(|child| child.file_name());
+    for child in children {
+        files_below(&child.path(), files);
+    }
+}
+
+#[async_trait]
+impl ArchiveVerifier for MockVerifier {
+    async fn list_archive_entries(&self, archive_path: &str) -> Result<Vec<ArchiveEntry>> {
+        self.archives
+            .lock()
+            .unwrap()
+            .get(archive_path)
+            .cloned()
+            .ok_or_else(|| ArchtreeError::verification("Archive not found", Some(archive_path)))
+    }
+
+    async fn verify_archive(
+        &self,
+        archive_path: &str,
+        expected_paths: &[String],
+    ) -> Result<VerificationResult> {
+        let archived = self.files_by_key(archive_path).await?;
+        let expected = expand(expected_paths);
+        let (archived_files, missing_files): (Vec<String>, Vec<String>) =
+            expected.iter().cloned().partition(|path| {
+                comparison_key(path, 0).is_some_and(|key| archived.contains_key(&key))
+            });
+
+        Ok(VerificationResult {
+            total_expected: expected.len(),
+            total_archived: archived_files.len(),
+            missing_files,
+            expected_missing: Vec::new(),
+            archived_files,
+            all_expected_files: expected,
+            listing_warnings: Vec::new(),
+            corrupted_files: Vec::new(),
+            unchecked_files: Vec::new(),
+        })
+    }
+
+    async fn verify_archive_freshness(
+        &self,
+        archive_path: &str,
+        expected_paths: &[String],
+    ) -> Result<FreshnessVerificationResult> {
+        let archived = self.files_by_key(archive_path).await?;
+        let expected = expand(expected_paths);
+        let mut result = FreshnessVerificationResult {
+            outdated_files: Vec::new(),
+            up_to_date_files: Vec::new(),
+            unverifiable_files: Vec::new(),
+            newer_in_archive_files: Vec::new(),
+            total_checked: expected.len(),
+        };
+
+        for path in &expected {
+            let Some(entry) = comparison_key(path, 0).and_then(|key| archived.get(&key)) else {
+                continue;
+            };
+            let fs_modified = fs::metadata(path).and_then(|metadata| metadata.modified());
+            match (entry.modified, fs_modified) {
+                (Some(archive_modified), Ok(fs_modified)) => {
+                    if compare_modification_times(archive_modified, fs_modified)
+                        == FreshnessStatus::Outdated
+                    {
+                        result.outdated_files.push(OutdatedFile {
+                            path: path.clone(),
+                            archive_modified: Some(archive_modified),
+                            filesystem_modified: Some(fs_modified),
+                        });
+                    } else {
+                        result.up_to_date_files.push(path.clone());
+                    }
+                }
+                _ => result.unverifiable_files.push(path.clone()),
+            }
+        }
+        Ok(result)
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "Mock Verifier"
+    }
+}
+
+/// Enumerator walking the filesystem like [`DirectoryEnumerator`], counting how often it
+/// is asked to
+#[derive(Debug, Default)]
+pub(crate) struct SpyEnumerator {
+    calls: AtomicUsize,
+}
+
+impl SpyEnumerator {
+    /// Number of paths enumerated so far
+    pub(crate) fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl FileEnumerator for SpyEnumerator {
+    async fn enumerate(&self, path: &str) -> Result<Vec<String>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        DirectoryEnumerator::default().enumerate(path).await
+    }
+}
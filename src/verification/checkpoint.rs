@@ -0,0 +1,223 @@
+use crate::core::{ErrorContext, Result};
+use crate::io::volumes::resolve_archive_path;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Number of newly checked files buffered before they are appended to the checkpoint file
+const FLUSH_INTERVAL: usize = 1000;
+
+/// Persistent record of which expected files were already checked during a verification.
+///
+/// The file starts with an `archive<TAB>path<TAB>size<TAB>mtime` line identifying the
+/// archive that was verified, followed by one entry per line: `+<TAB>path` for a file
+/// found in the archive and `-<TAB>path` for a missing one. Entries are appended, so an
+/// interrupted run keeps everything flushed so far.
+pub struct VerificationCheckpoint {
+    path: PathBuf,
+    /// First line of the file, identifying the archive being verified
+    header: String,
+    /// Whether the file already starts with `header`, so entries can be appended
+    header_written: bool,
+    checked: HashMap<String, bool>,
+    pending: Vec<(String, bool)>,
+}
+
+impl VerificationCheckpoint {
+    /// Load a checkpoint file for verifying `archive_path`, starting empty if it doesn't
+    /// exist yet.
+    ///
+    /// A checkpoint written for another archive, or for this one before it was replaced or
+    /// modified (its size or modification time differ), is discarded.
+    pub fn load<P: AsRef<Path>>(path: P, archive_path: &str) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let header = archive_header(archive_path)?;
+        let mut checked = HashMap::new();
+        let mut header_written = false;
+
+        if path.exists() {
+            let content = std::fs::read_to_string(&path).context_io(format!(
+                "Failed to read checkpoint file: {}",
+                path.display()
+            ))?;
+
+            let mut lines = content.lines();
+            header_written = lines.next() == Some(header.as_str());
+            for line in lines.filter(|_| header_written) {
+                match line.split_once('\t') {
+                    Some(("+", file)) => {
+                        checked.insert(file.to_string(), true);
+                    }
+                    Some(("-", file)) => {
+                        checked.insert(file.to_string(), false);
+                    }
+                    // A torn last line from an interrupted write is simply re-checked
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(Self {
+            path,
+            header,
+            header_written,
+            checked,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Result recorded for a file by a previous run (`Some(true)` if it was found)
+    pub fn result_for(&self, file: &str) -> Option<bool> {
+        self.checked.get(file).copied()
+    }
+
+    /// Number of files recorded in the checkpoint
+    pub fn len(&self) -> usize {
+        self.checked.len()
+    }
+
+    /// Whether no files have been recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.checked.is_empty()
+    }
+
+    /// Record the result for a file, flushing periodically
+    pub fn record(&mut self, file: &str, found: bool) -> Result<()> {
+        self.checked.insert(file.to_string(), found);
+        self.pending.push((file.to_string(), found));
+
+        if self.pending.len() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Append all buffered results to the checkpoint file, replacing a discarded one
+    pub fn flush(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut options = OpenOptions::new();
+        if self.header_written {
+            options.append(true);
+        } else {
+            options.write(true).truncate(true);
+        }
+        let mut file = options.create(true).open(&self.path).context_io(format!(
+            "Failed to open checkpoint file: {}",
+            self.path.display()
+        ))?;
+
+        let mut content = String::new();
+        if !self.header_written {
+            content.push_str(&self.header);
+            content.push('\n');
+        }
+        for (path, found) in self.pending.drain(..) {
+            content.push(if found { '+' } else { '-' });
+            content.push('\t');
+            content.push_str(&path);
+            content.push('\n');
+        }
+
+        file.write_all(content.as_bytes())
+            .context_io("Failed to write checkpoint file")?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    /// Remove the checkpoint file once a verification ran to completion
+    pub fn finish(mut self) -> Result<()> {
+        self.pending.clear();
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).context_io("Failed to remove checkpoint file")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Header line identifying an archive by its path, size and modification time
+fn archive_header(archive_path: &str) -> Result<String> {
+    // A split archive is identified by its first volume
+    let resolved = resolve_archive_path(archive_path);
+    let metadata = std::fs::metadata(&resolved)
+        .context_io(format!("Failed to read archive metadata: {}", resolved))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|since| since.as_nanos())
+        .unwrap_or(0);
+    Ok(format!(
+        "archive\t{}\t{}\t{}",
+        archive_path,
+        metadata.len(),
+        modified
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("verify.checkpoint");
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, b"7z").unwrap();
+        let archive = archive.to_string_lossy().to_string();
+
+        let mut checkpoint = VerificationCheckpoint::load(&path, &archive).unwrap();
+        assert!(checkpoint.is_empty());
+        checkpoint.record("/data/a.txt", true).unwrap();
+        checkpoint.record("/data/b.txt", false).unwrap();
+        checkpoint.flush().unwrap();
+
+        let reloaded = VerificationCheckpoint::load(&path, &archive).unwrap();
+        assert_eq!(reloaded.len(), 2);
+        assert_eq!(reloaded.result_for("/data/a.txt"), Some(true));
+        assert_eq!(reloaded.result_for("/data/b.txt"), Some(false));
+        assert_eq!(reloaded.result_for("/data/c.txt"), None);
+
+        reloaded.finish().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_checkpoint_of_changed_archive_is_discarded() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("verify.checkpoint");
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, b"7z").unwrap();
+        let archive = archive.to_string_lossy().to_string();
+
+        let mut checkpoint = VerificationCheckpoint::load(&path, &archive).unwrap();
+        checkpoint.record("/data/a.txt", false).unwrap();
+        checkpoint.flush().unwrap();
+
+        // Another archive doesn't pick up the recorded results
+        let other = temp_dir.path().join("other.7z");
+        std::fs::write(&other, b"7z").unwrap();
+        let checkpoint = VerificationCheckpoint::load(&path, &other.to_string_lossy()).unwrap();
+        assert!(checkpoint.is_empty());
+
+        // Neither does the same archive once it was rewritten
+        std::fs::write(&archive, b"7z with the missing file").unwrap();
+        let mut checkpoint = VerificationCheckpoint::load(&path, &archive).unwrap();
+        assert!(checkpoint.is_empty());
+
+        // The stale entries are replaced as soon as new ones are flushed
+        checkpoint.record("/data/a.txt", true).unwrap();
+        checkpoint.flush().unwrap();
+        let reloaded = VerificationCheckpoint::load(&path, &archive).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded.result_for("/data/a.txt"), Some(true));
+    }
+}
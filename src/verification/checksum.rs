@@ -0,0 +1,305 @@
+use crate::core::{ArchtreeError, ErrorContext, Result};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Hash algorithm of a checksum, identified by the length of its hex digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Crc32,
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// Identify the algorithm producing hex digests of `len` characters
+    pub fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            8 => Some(Self::Crc32),
+            32 => Some(Self::Md5),
+            40 => Some(Self::Sha1),
+            64 => Some(Self::Sha256),
+            128 => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Crc32 => "CRC32",
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Sha512 => "SHA-512",
+        }
+    }
+
+    /// Lowercase hex digest of the file at `path`
+    pub fn hash_file(&self, path: &Path) -> std::io::Result<String> {
+        let mut file = File::open(path)?;
+        match self {
+            Self::Crc32 => Ok(format!("{:08x}", crc32_of(&mut file)?)),
+            Self::Md5 => digest_file::<Md5>(&mut file),
+            Self::Sha1 => digest_file::<Sha1>(&mut file),
+            Self::Sha256 => digest_file::<Sha256>(&mut file),
+            Self::Sha512 => digest_file::<Sha512>(&mut file),
+        }
+    }
+}
+
+/// CRC32 of the file at `path`, as 7-Zip stores it for each archived file
+pub fn crc32_file(path: &Path) -> std::io::Result<u32> {
+    crc32_of(&mut File::open(path)?)
+}
+
+fn crc32_of(file: &mut File) -> std::io::Result<u32> {
+    let mut hasher = crc32fast::Hasher::new();
+    read_chunks(file, |chunk| hasher.update(chunk))?;
+    Ok(hasher.finalize())
+}
+
+fn read_chunks(file: &mut File, mut update: impl FnMut(&[u8])) -> std::io::Result<()> {
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        update(&buffer[..read]);
+    }
+}
+
+fn digest_file<D: Digest>(file: &mut File) -> std::io::Result<String> {
+    let mut hasher = D::new();
+    read_chunks(file, |chunk| hasher.update(chunk))?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// One line of a checksum file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumEntry {
+    /// Path as written in the checksum file
+    pub path: String,
+    /// Expected digest in lowercase hex
+    pub expected: String,
+    pub algorithm: ChecksumAlgorithm,
+}
+
+/// Parse a checksum file in SFV (`path CRC32`) or `md5sum`/`sha*sum` (`hash  path`) format.
+///
+/// The algorithm of each line is detected from the length of its digest. Blank lines and
+/// comments starting with `;` or `#` are skipped.
+pub fn parse_checksum_file(content: &str) -> Result<Vec<ChecksumEntry>> {
+    let mut entries = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        let entry = parse_sums_line(line)
+            .or_else(|| parse_sfv_line(line))
+            .ok_or_else(|| {
+                ArchtreeError::config(format!(
+                    "Unrecognized checksum line {}: {}",
+                    number + 1,
+                    line
+                ))
+            })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// `<hash>  <path>`, or `<hash> *<path>` for files hashed in binary mode
+fn parse_sums_line(line: &str) -> Option<ChecksumEntry> {
+    let (hash, rest) = line.split_once(' ')?;
+    let algorithm =
+        hex_algorithm(hash).filter(|algorithm| *algorithm != ChecksumAlgorithm::Crc32)?;
+    let path = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+    (!path.is_empty()).then(|| ChecksumEntry {
+        path: path.to_string(),
+        expected: hash.to_ascii_lowercase(),
+        algorithm,
+    })
+}
+
+/// `<path> <crc32>`; the path may contain spaces
+fn parse_sfv_line(line: &str) -> Option<ChecksumEntry> {
+    let (path, hash) = line.trim_end().rsplit_once(' ')?;
+    let algorithm =
+        hex_algorithm(hash).filter(|algorithm| *algorithm == ChecksumAlgorithm::Crc32)?;
+    let path = path.trim_end();
+    (!path.is_empty()).then(|| ChecksumEntry {
+        path: path.to_string(),
+        expected: hash.to_ascii_lowercase(),
+        algorithm,
+    })
+}
+
+fn hex_algorithm(token: &str) -> Option<ChecksumAlgorithm> {
+    if !token.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    ChecksumAlgorithm::from_hex_len(token.len())
+}
+
+/// A file whose digest differs from the checksum file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    pub path: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Result of checking files on disk against a checksum file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChecksumVerificationResult {
+    /// Files whose digest matches
+    pub matched: Vec<String>,
+    /// Files whose digest differs
+    pub mismatched: Vec<ChecksumMismatch>,
+    /// Files that couldn't be read, with the reason
+    pub unreadable: Vec<(String, String)>,
+}
+
+impl ChecksumVerificationResult {
+    /// Check if every listed file was read and matched
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.unreadable.is_empty()
+    }
+
+    pub fn total(&self) -> usize {
+        self.matched.len() + self.mismatched.len() + self.unreadable.len()
+    }
+}
+
+/// Hash every file of `entries` and compare it with its expected digest.
+///
+/// Relative paths are resolved against `base_dir`, normally the checksum file's directory.
+pub fn verify_checksums(entries: &[ChecksumEntry], base_dir: &Path) -> ChecksumVerificationResult {
+    let mut result = ChecksumVerificationResult::default();
+    for entry in entries {
+        let path: PathBuf = base_dir.join(&entry.path);
+        match entry.algorithm.hash_file(&path) {
+            Ok(actual) if actual == entry.expected => result.matched.push(entry.path.clone()),
+            Ok(actual) => result.mismatched.push(ChecksumMismatch {
+                path: entry.path.clone(),
+                expected: entry.expected.clone(),
+                actual,
+            }),
+            Err(e) => result.unreadable.push((entry.path.clone(), e.to_string())),
+        }
+    }
+    result
+}
+
+/// Read, parse and check a checksum file against the files on disk
+pub async fn verify_checksum_file(checksum_file: &str) -> Result<ChecksumVerificationResult> {
+    let content = tokio::fs::read_to_string(checksum_file)
+        .await
+        .context_io(format!("Failed to read checksum file: {}", checksum_file))?;
+    let entries = parse_checksum_file(&content)?;
+    let base_dir = Path::new(checksum_file)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    // Hashing reads whole files, so it runs off the async workers
+    tokio::task::spawn_blocking(move || verify_checksums(&entries, &base_dir))
+        .await
+        .map_err(|e| ArchtreeError::other(format!("Checksum verification failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_sfv_and_sums_files() {
+        let sfv = "; Generated by QuickSFV\r\nmovie part 1.mkv 1A2B3C4D\r\nreadme.txt deadbeef\r\n";
+        let entries = parse_checksum_file(sfv).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ChecksumEntry {
+                    path: "movie part 1.mkv".to_string(),
+                    expected: "1a2b3c4d".to_string(),
+                    algorithm: ChecksumAlgorithm::Crc32,
+                },
+                ChecksumEntry {
+                    path: "readme.txt".to_string(),
+                    expected: "deadbeef".to_string(),
+                    algorithm: ChecksumAlgorithm::Crc32,
+                },
+            ]
+        );
+
+        let md5sums = format!(
+            "{}  docs/a b.txt\n{} *image.iso\n\n",
+            "d41d8cd98f00b204e9800998ecf8427e",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        let entries = parse_checksum_file(&md5sums).unwrap();
+        assert_eq!(entries[0].path, "docs/a b.txt");
+        assert_eq!(entries[0].algorithm, ChecksumAlgorithm::Md5);
+        assert_eq!(entries[1].path, "image.iso");
+        assert_eq!(entries[1].algorithm, ChecksumAlgorithm::Sha256);
+
+        assert!(parse_checksum_file("not a checksum line").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_checksum_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("hello.txt"), "hello\n").unwrap();
+        fs::write(temp_dir.path().join("changed.txt"), "changed\n").unwrap();
+
+        // CRC32, MD5 and SHA-1 of "hello\n"
+        let sfv = temp_dir.path().join("files.sfv");
+        fs::write(&sfv, "hello.txt 363a3020\nchanged.txt 363a3020\n").unwrap();
+        let md5sums = temp_dir.path().join("MD5SUMS");
+        fs::write(
+            &md5sums,
+            "b1946ac92492d2347c6235b4d2611184  hello.txt\nb1946ac92492d2347c6235b4d2611184  missing.txt\n",
+        )
+        .unwrap();
+        let sha1sums = temp_dir.path().join("SHA1SUMS");
+        fs::write(
+            &sha1sums,
+            "f572d396fae9206628714fb2ce00f72e94f2258f  hello.txt\n",
+        )
+        .unwrap();
+
+        let result = verify_checksum_file(&sfv.to_string_lossy()).await.unwrap();
+        assert_eq!(result.matched, vec!["hello.txt".to_string()]);
+        assert_eq!(result.mismatched.len(), 1);
+        assert_eq!(result.mismatched[0].path, "changed.txt");
+        assert_eq!(result.mismatched[0].expected, "363a3020");
+        assert!(!result.is_ok());
+
+        let result = verify_checksum_file(&md5sums.to_string_lossy())
+            .await
+            .unwrap();
+        assert_eq!(result.matched, vec!["hello.txt".to_string()]);
+        assert_eq!(result.unreadable.len(), 1);
+        assert_eq!(result.unreadable[0].0, "missing.txt");
+
+        let result = verify_checksum_file(&sha1sums.to_string_lossy())
+            .await
+            .unwrap();
+        assert!(result.is_ok());
+        assert_eq!(result.total(), 1);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::core::Result;
+use crate::verification::verifier::{ArchiveEntry, ArchiveVerifier};
+use std::collections::BTreeMap;
+
+/// A file whose size differs between two archives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeChange {
+    pub path: String,
+    pub previous_size: u64,
+    pub current_size: u64,
+}
+
+/// Differences between the files of two archives, each list sorted by path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveDiff {
+    /// Files only in the current archive
+    pub added: Vec<String>,
+    /// Files only in the previous archive
+    pub removed: Vec<String>,
+    /// Files in both archives with a different size
+    pub size_changed: Vec<SizeChange>,
+}
+
+impl ArchiveDiff {
+    /// Compare the file entries of two listings; directories are ignored
+    pub fn between(previous: &[ArchiveEntry], current: &[ArchiveEntry]) -> Self {
+        let files = |entries: &[ArchiveEntry]| -> BTreeMap<String, u64> {
+            entries
+                .iter()
+                .filter(|entry| !entry.is_directory)
+                .map(|entry| (entry.path.clone(), entry.size))
+                .collect()
+        };
+        let previous = files(previous);
+        let current = files(current);
+
+        let mut diff = Self::default();
+        for (path, &current_size) in &current {
+            match previous.get(path) {
+                None => diff.added.push(path.clone()),
+                Some(&previous_size) if previous_size != current_size => {
+                    diff.size_changed.push(SizeChange {
+                        path: path.clone(),
+                        previous_size,
+                        current_size,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = previous
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Check if both archives hold the same files with the same sizes
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.size_changed.is_empty()
+    }
+
+    /// Print a one-line summary, followed by every change when `detailed` is set
+    pub fn print_summary(&self, previous_archive: &str, detailed: bool) {
+        eprintln!(
+            "\n🔀 Changes since {}: {} added, {} removed, {} size changed",
+            previous_archive,
+            self.added.len(),
+            self.removed.len(),
+            self.size_changed.len()
+        );
+        if !detailed {
+            return;
+        }
+
+        for path in &self.added {
+            eprintln!("  + {}", path);
+        }
+        for path in &self.removed {
+            eprintln!("  - {}", path);
+        }
+        for change in &self.size_changed {
+            eprintln!(
+                "  ~ {} ({} -> {} bytes)",
+                change.path, change.previous_size, change.current_size
+            );
+        }
+    }
+}
+
+/// List two archives and compare their files
+pub async fn diff_archives<V: ArchiveVerifier + ?Sized>(
+    verifier: &V,
+    previous_archive: &str,
+    current_archive: &str,
+) -> Result<ArchiveDiff> {
+    let previous = verifier.list_archive_entries(previous_archive).await?;
+    let current = verifier.list_archive_entries(current_archive).await?;
+    Ok(ArchiveDiff::between(&previous, &current))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: u64) -> ArchiveEntry {
+        ArchiveEntry {
+            path: path.to_string(),
+            is_directory: false,
+            size,
+            modified: None,
+            packed_size: None,
+            crc: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_between_listings() {
+        let mut previous = vec![
+            file("data/kept.txt", 10),
+            file("data/grown.txt", 10),
+            file("data/deleted.txt", 5),
+        ];
+        previous.push(ArchiveEntry {
+            is_directory: true,
+            ..file("data", 0)
+        });
+        let current = vec![
+            file("data/kept.txt", 10),
+            file("data/grown.txt", 25),
+            file("data/new.txt", 1),
+        ];
+
+        let diff = ArchiveDiff::between(&previous, &current);
+        assert_eq!(diff.added, vec!["data/new.txt"]);
+        // The directory entry is ignored, only the deleted file counts as removed
+        assert_eq!(diff.removed, vec!["data/deleted.txt"]);
+        assert_eq!(
+            diff.size_changed,
+            vec![SizeChange {
+                path: "data/grown.txt".to_string(),
+                previous_size: 10,
+                current_size: 25,
+            }]
+        );
+        assert!(!diff.is_empty());
+        assert!(ArchiveDiff::between(&current, &current).is_empty());
+    }
+}
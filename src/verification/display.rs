@@ -2,9 +2,9 @@
 use crate::verification::verifier::VerificationResult;
 
 /// Trait for different missing file display strategies
-pub trait MissingFileDisplayStrategy {
-    /// Display missing files according to the strategy
-    fn display_missing_files(&self, result: &VerificationResult);
+pub trait MissingFileDisplayStrategy: Send + Sync {
+    /// Missing files to list according to the strategy
+    fn format_missing_files(&self, result: &VerificationResult) -> Vec<String>;
 
     /// Get the name of the strategy for identification
     fn name(&self) -> &'static str;
@@ -14,10 +14,8 @@ pub trait MissingFileDisplayStrategy {
 pub struct DetailedDisplayStrategy;
 
 impl MissingFileDisplayStrategy for DetailedDisplayStrategy {
-    fn display_missing_files(&self, result: &VerificationResult) {
-        for missing in &result.missing_files {
-            eprintln!("    - {}", missing);
-        }
+    fn format_missing_files(&self, result: &VerificationResult) -> Vec<String> {
+        result.missing_files.clone()
     }
 
     fn name(&self) -> &'static str {
@@ -29,11 +27,8 @@ impl MissingFileDisplayStrategy for DetailedDisplayStrategy {
 pub struct ConsolidatedDisplayStrategy;
 
 impl MissingFileDisplayStrategy for ConsolidatedDisplayStrategy {
-    fn display_missing_files(&self, result: &VerificationResult) {
-        let consolidated_missing = result.get_consolidated_missing_files();
-        for missing in &consolidated_missing {
-            eprintln!("    - {}", missing);
-        }
+    fn format_missing_files(&self, result: &VerificationResult) -> Vec<String> {
+        result.get_consolidated_missing_files()
     }
 
     fn name(&self) -> &'static str {
@@ -62,9 +57,9 @@ impl MissingFileDisplayContext {
         Self::new(Box::new(ConsolidatedDisplayStrategy))
     }
 
-    /// Display missing files using the configured strategy
-    pub fn display_missing_files(&self, result: &VerificationResult) {
-        self.strategy.display_missing_files(result);
+    /// Missing files to list using the configured strategy
+    pub fn format_missing_files(&self, result: &VerificationResult) -> Vec<String> {
+        self.strategy.format_missing_files(result)
     }
 
     /// Get the name of the current strategy
@@ -0,0 +1,236 @@
+use crate::core::{ErrorContext, Result};
+use crate::verification::verifier::{FileEnumerator, expand_input_paths_with};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// First line of a cache file, changed whenever its format changes
+const HEADER: &str = "archtree-expand-cache 2";
+
+/// Numbers the temporary files of concurrent writes within this process
+static WRITE_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Cache of the files that a set of input paths expands to, reused while the tree
+/// appears unchanged.
+///
+/// Besides the files, the cache records the modification time of every directory the
+/// expansion depends on: every directory below the input directories, symlinked ones
+/// aside, and the directories holding the expanded files. Adding, removing or renaming a
+/// file changes its directory's time, so the cache is rebuilt then, as it is when a
+/// missing input path appears. It is a heuristic: an edit that keeps a file's name goes
+/// unnoticed, as does a change below a symlinked directory that held no expanded file.
+/// The cache file itself must live outside those directories, since writing it changes
+/// the time of the directory it is in.
+///
+/// The file holds a header, a `key<TAB>hash` line identifying the input paths and
+/// settings, then `dir<TAB>nanoseconds<TAB>path`, `missing<TAB>path` and `file<TAB>path`
+/// lines.
+pub struct ExpandCache {
+    path: PathBuf,
+}
+
+impl ExpandCache {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Expand `input_paths` like [`crate::verification::verifier::expand_input_paths`],
+    /// reading the files from the cache when it matches and writing a new cache otherwise
+    pub async fn expand(
+        &self,
+        input_paths: &[String],
+        enumerator: &dyn FileEnumerator,
+    ) -> Result<Vec<String>> {
+        self.expand_with(input_paths, "", || {
+            expand_input_paths_with(input_paths, enumerator)
+        })
+        .await
+    }
+
+    /// Files `input_paths` expand to, read from the cache when it matches and otherwise
+    /// produced by `expand` and cached.
+    ///
+    /// `settings` describes everything else that decides which files are expanded to,
+    /// such as exclusion patterns; a cache written with other settings isn't used.
+    pub async fn expand_with<F, Fut>(
+        &self,
+        input_paths: &[String],
+        settings: &str,
+        expand: F,
+    ) -> Result<Vec<String>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<String>>>,
+    {
+        let key = cache_key(input_paths, settings);
+        if let Some(files) = self.load(&key) {
+            return Ok(files);
+        }
+
+        let files = expand().await?;
+        self.store(&key, input_paths, &files).await?;
+        Ok(files)
+    }
+
+    /// Cached files, if the cache is for `key` and none of its directories changed
+    fn load(&self, key: &str) -> Option<Vec<String>> {
+        let content = std::fs::read_to_string(&self.path).ok()?;
+        let mut lines = content.lines();
+        if lines.next() != Some(HEADER) || lines.next()? != format!("key\t{}", key) {
+            return None;
+        }
+
+        let mut files = Vec::new();
+        for line in lines {
+            match line.split_once('\t')? {
+                ("dir", rest) => {
+                    let (modified, dir) = rest.split_once('\t')?;
+                    if modified_nanos(Path::new(dir)) != modified.parse().ok() {
+                        return None;
+                    }
+                }
+                ("missing", path) if Path::new(path).exists() => return None,
+                ("missing", _) => {}
+                ("file", file) => files.push(file.to_string()),
+                _ => return None,
+            }
+        }
+        Some(files)
+    }
+
+    async fn store(&self, key: &str, input_paths: &[String], files: &[String]) -> Result<()> {
+        // Sorted, so the same tree always gives the same file
+        let mut dirs = BTreeMap::new();
+        let input_dirs = input_paths
+            .iter()
+            .map(Path::new)
+            .filter(|path| path.is_dir())
+            .flat_map(|input| {
+                WalkDir::new(input)
+                    .into_iter()
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.file_type().is_dir())
+                    .map(|entry| entry.into_path())
+            });
+        let parents = files
+            .iter()
+            .filter_map(|file| Path::new(file).parent())
+            .map(Path::to_path_buf);
+        for dir in input_dirs.chain(parents) {
+            if !dir.as_os_str().is_empty() && !dirs.contains_key(&dir) {
+                let modified = modified_nanos(&dir);
+                dirs.insert(dir, modified);
+            }
+        }
+
+        let mut content = format!("{}\nkey\t{}\n", HEADER, key);
+        for (dir, modified) in dirs {
+            // A directory without a readable time can't be checked, so it isn't cached
+            let Some(modified) = modified else {
+                return Ok(());
+            };
+            content.push_str(&format!("dir\t{}\t{}\n", modified, dir.display()));
+        }
+        for input in input_paths {
+            if !Path::new(input).exists() {
+                content.push_str(&format!("missing\t{}\n", input));
+            }
+        }
+        for file in files {
+            content.push_str(&format!("file\t{}\n", file));
+        }
+
+        // Written aside and renamed, so concurrent verifications never read a torn cache
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(format!(
+            ".{}_{}.tmp",
+            std::process::id(),
+            WRITE_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+        ));
+        tokio::fs::write(&temp_path, content)
+            .await
+            .context_io(format!(
+                "Failed to write expand cache: {}",
+                self.path.display()
+            ))?;
+        tokio::fs::rename(&temp_path, &self.path)
+            .await
+            .context_io(format!(
+                "Failed to write expand cache: {}",
+                self.path.display()
+            ))
+    }
+}
+
+/// Hash identifying a list of input paths, order included, and the settings expanding them
+fn cache_key(input_paths: &[String], settings: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(settings.as_bytes());
+    hasher.update([0]);
+    for path in input_paths {
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn modified_nanos(path: &Path) -> Option<u128> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::SpyEnumerator;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_unchanged_tree_is_loaded_from_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::write(source.join("a.txt"), "a").unwrap();
+        fs::write(source.join("sub").join("b.txt"), "b").unwrap();
+        let inputs = vec![source.to_string_lossy().to_string()];
+        let cache = ExpandCache::new(temp_dir.path().join("expand.cache"));
+        let spy = SpyEnumerator::default();
+
+        let mut first = cache.expand(&inputs, &spy).await.unwrap();
+        assert_eq!(spy.calls(), 1);
+        let mut second = cache.expand(&inputs, &spy).await.unwrap();
+        assert_eq!(spy.calls(), 1);
+        first.sort();
+        second.sort();
+        assert_eq!(first, second);
+        assert_eq!(second.len(), 2);
+
+        // A new file changes its directory's time, so the tree is walked again
+        fs::write(source.join("sub").join("c.txt"), "c").unwrap();
+        let third = cache.expand(&inputs, &spy).await.unwrap();
+        assert_eq!(spy.calls(), 2);
+        assert_eq!(third.len(), 3);
+
+        // So does one in a directory that held no files so far
+        fs::create_dir(source.join("empty")).unwrap();
+        assert_eq!(cache.expand(&inputs, &spy).await.unwrap().len(), 3);
+        assert_eq!(spy.calls(), 3);
+        fs::write(source.join("empty").join("d.txt"), "d").unwrap();
+        assert_eq!(cache.expand(&inputs, &spy).await.unwrap().len(), 4);
+        assert_eq!(spy.calls(), 4);
+
+        // Other input paths don't reuse the cache
+        let other = vec![source.join("sub").to_string_lossy().to_string()];
+        assert_eq!(cache.expand(&other, &spy).await.unwrap().len(), 2);
+        assert_eq!(spy.calls(), 5);
+    }
+}
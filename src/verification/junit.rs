@@ -0,0 +1,212 @@
+use crate::core::{ErrorContext, Result};
+use crate::services::RunReport;
+use crate::verification::service::ArchiveVerificationReport;
+use crate::verification::verifier::CorruptedFile;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Render verification results as a JUnit XML report, for CI systems showing test results.
+///
+/// Each archive is a test suite and each expected file a test case, whose class name is the
+/// file's directory so reports group files by folder. Missing and corrupted files are
+/// failures and files an allow-missing pattern accepts are skipped. An archive that
+/// couldn't be verified is a suite with a single erroring test case.
+pub fn junit_xml(reports: &[ArchiveVerificationReport]) -> String {
+    let mut suites = String::new();
+    let (mut tests, mut failures, mut errors, mut skipped) = (0, 0, 0, 0);
+
+    for report in reports {
+        let archive = escape(&report.archive);
+        let result = match &report.result {
+            Ok(RunReport {
+                verification: Some(result),
+                ..
+            }) => result,
+            // An archive whose files weren't compared has no test cases
+            Ok(_) => continue,
+            Err(e) => {
+                tests += 1;
+                errors += 1;
+                let _ = writeln!(
+                    suites,
+                    "  <testsuite name=\"{0}\" tests=\"1\" failures=\"0\" errors=\"1\" skipped=\"0\">\n    <testcase name=\"verify\" classname=\"{0}\">\n      <error message=\"{1}\"/>\n    </testcase>\n  </testsuite>",
+                    archive,
+                    escape(&e.to_string())
+                );
+                continue;
+            }
+        };
+
+        let corrupted: HashMap<&str, &CorruptedFile> = result
+            .corrupted_files
+            .iter()
+            .map(|file| (file.path.as_str(), file))
+            .collect();
+        let mut cases: Vec<(&str, Outcome)> = result
+            .archived_files
+            .iter()
+            .map(|file| match corrupted.get(file.as_str()) {
+                Some(corrupted) => (file.as_str(), Outcome::Corrupted(corrupted)),
+                None => (file.as_str(), Outcome::Passed),
+            })
+            .chain(
+                result
+                    .missing_files
+                    .iter()
+                    .map(|file| (file.as_str(), Outcome::Missing)),
+            )
+            .chain(
+                result
+                    .expected_missing
+                    .iter()
+                    .map(|file| (file.as_str(), Outcome::ExpectedMissing)),
+            )
+            .collect();
+        cases.sort_by_key(|(file, _)| *file);
+
+        let suite_failures = result.missing_files.len() + result.corrupted_files.len();
+        tests += cases.len();
+        failures += suite_failures;
+        skipped += result.expected_missing.len();
+        let _ = writeln!(
+            suites,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">",
+            archive,
+            cases.len(),
+            suite_failures,
+            result.expected_missing.len()
+        );
+        for (file, outcome) in cases {
+            let classname = Path::new(file)
+                .parent()
+                .map(|parent| parent.to_string_lossy())
+                .unwrap_or_default();
+            let _ = write!(
+                suites,
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                escape(file),
+                escape(&classname)
+            );
+            match outcome {
+                Outcome::Passed => suites.push_str("/>\n"),
+                Outcome::Missing => suites.push_str(
+                    ">\n      <failure message=\"not found in archive\"/>\n    </testcase>\n",
+                ),
+                Outcome::ExpectedMissing => suites.push_str(
+                    ">\n      <skipped message=\"missing, accepted by --allow-missing\"/>\n    </testcase>\n",
+                ),
+                Outcome::Corrupted(file) => {
+                    let _ = writeln!(
+                        suites,
+                        ">\n      <failure message=\"content differs from disk (archive CRC {:08X}, disk CRC {:08X})\"/>\n    </testcase>",
+                        file.archive_crc, file.filesystem_crc
+                    );
+                }
+            }
+        }
+        if !result.listing_warnings.is_empty() {
+            let _ = writeln!(
+                suites,
+                "    <system-err>{}</system-err>",
+                escape(&result.listing_warnings.join("\n"))
+            );
+        }
+        suites.push_str("  </testsuite>\n");
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"archtree verify\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\">\n{}</testsuites>\n",
+        tests, failures, errors, skipped, suites
+    )
+}
+
+/// Write the JUnit XML report of `reports` to `path`
+pub async fn write_junit_report<P: AsRef<Path>>(
+    path: P,
+    reports: &[ArchiveVerificationReport],
+) -> Result<()> {
+    let path = path.as_ref();
+    tokio::fs::write(path, junit_xml(reports))
+        .await
+        .context_io(format!("Failed to write JUnit report: {}", path.display()))
+}
+
+/// How an expected file fared
+enum Outcome<'a> {
+    Passed,
+    Missing,
+    ExpectedMissing,
+    Corrupted(&'a CorruptedFile),
+}
+
+/// Escape text for XML attributes and content. Control characters XML 1.0 can't hold,
+/// even as references, become U+FFFD.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() && c < '\u{80}' => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ArchtreeError, Config};
+    use crate::verification::verifier::VerificationResult;
+
+    #[test]
+    fn test_junit_counts_missing_files_as_failures() {
+        let config = Config::builder()
+            .output_path(Some("week.7z"), false)
+            .build()
+            .unwrap();
+        let result = VerificationResult {
+            missing_files: vec!["/data/b.txt".to_string(), "/data/sub/c&d.txt".to_string()],
+            expected_missing: vec!["/data/app.lock".to_string()],
+            archived_files: vec!["/data/a.txt".to_string()],
+            all_expected_files: Vec::new(),
+            total_expected: 4,
+            total_archived: 1,
+            listing_warnings: Vec::new(),
+            corrupted_files: Vec::new(),
+            unchecked_files: Vec::new(),
+        };
+        let reports = vec![
+            ArchiveVerificationReport {
+                archive: "week.7z".to_string(),
+                result: Ok(RunReport::for_archive(config, "week.7z").with_verification(result)),
+            },
+            ArchiveVerificationReport {
+                archive: "broken.7z".to_string(),
+                result: Err(ArchtreeError::verification("Can't open", None::<String>)),
+            },
+        ];
+
+        let xml = junit_xml(&reports);
+        assert!(xml.contains(
+            "<testsuites name=\"archtree verify\" tests=\"5\" failures=\"2\" errors=\"1\" skipped=\"1\">"
+        ));
+        assert!(xml.contains(
+            "<testsuite name=\"week.7z\" tests=\"4\" failures=\"2\" errors=\"0\" skipped=\"1\">"
+        ));
+        assert_eq!(xml.matches("<testcase ").count(), 5);
+        assert_eq!(xml.matches("<failure ").count(), 2);
+        assert_eq!(xml.matches("<skipped ").count(), 1);
+        assert_eq!(xml.matches("<error ").count(), 1);
+        assert!(xml.contains(
+            "<testcase name=\"/data/sub/c&amp;d.txt\" classname=\"/data/sub\">\n      <failure "
+        ));
+        assert!(xml.contains("<testcase name=\"/data/a.txt\" classname=\"/data\"/>"));
+    }
+}
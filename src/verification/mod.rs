@@ -1,6 +1,15 @@
+pub mod checkpoint;
+pub mod checksum;
+pub mod diff;
 pub mod display;
+pub mod expand_cache;
+pub mod junit;
 pub mod service;
 pub mod verifier;
 
-pub use service::{ConsoleCallback, VerificationAndRetryService, VerificationMode};
+pub use diff::ArchiveDiff;
+pub use service::{
+    ArchiveVerificationReport, ConsoleCallback, JsonCallback, ReportFormat,
+    VerificationAndRetryService, VerificationCallback, VerificationMode,
+};
 pub use verifier::SevenZipVerifier;
@@ -1,12 +1,19 @@
 use crate::{
-    core::Result,
-    io::Archiver,
+    core::{ArchtreeError, Config, Result},
+    io::{Archiver, SummaryTable, without_emoji},
     processing::validation::PathValidator,
+    services::RunReport,
     verification::{
         display,
-        verifier::{ArchiveVerifier, VerificationResult},
+        verifier::{ArchiveVerifier, CorruptedFile, OutdatedFile, VerificationResult},
     },
 };
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 
 /// Events that occur during verification process
 #[derive(Debug, Clone)]
@@ -21,8 +28,18 @@ pub enum VerificationEvent {
         found: usize,
         total_expected: usize,
     },
-    /// Displaying missing files to user
-    DisplayingMissingFiles { count: usize },
+    /// The archiver reported errors or warnings while listing the archive
+    ListingWarnings { warnings: Vec<String> },
+    /// Files are missing, but an allow-missing pattern accepts them
+    ExpectedMissing { files: Vec<String> },
+    /// Checksums were compared, and some archived files differ from the disk or couldn't
+    /// be compared
+    ChecksumProblems {
+        corrupted: Vec<CorruptedFile>,
+        unchecked: Vec<String>,
+    },
+    /// Displaying missing files to user, consolidated into directories where appropriate
+    DisplayingMissingFiles { count: usize, files: Vec<String> },
     /// Freshness checking is starting
     FreshnessCheckStarting,
     /// Freshness checking completed
@@ -30,16 +47,26 @@ pub enum VerificationEvent {
         outdated: usize,
         up_to_date: usize,
         unverifiable: usize,
+        newer_in_archive: usize,
         total_checked: usize,
     },
     /// Displaying outdated files to user
-    DisplayingOutdatedFiles { count: usize },
+    DisplayingOutdatedFiles {
+        count: usize,
+        files: Vec<OutdatedFile>,
+    },
+    /// Archived files are newer than their files on disk
+    NewerInArchiveFiles { files: Vec<String> },
+    /// Files whose freshness couldn't be checked
+    UnverifiableFiles { files: Vec<String> },
     /// Starting update of outdated files
     UpdatingOutdatedFiles { files_to_update: usize },
     /// Update of outdated files completed
     UpdateOutdatedComplete { files_updated: usize },
     /// Starting retry process
     RetryStarting { files_to_retry: usize },
+    /// None of the missing files exist anymore, so there is nothing to retry
+    NothingToRetry,
     /// Retry operation completed
     RetryComplete { files_added: usize },
     /// Final verification after retry completed
@@ -56,65 +83,246 @@ pub enum VerificationEvent {
 pub trait VerificationCallback: Send + Sync {
     /// Called when a verification event occurs
     fn on_event(&self, event: VerificationEvent);
+
+    /// Called once with the final result of an archive's verification, after any retry
+    fn on_result(&self, _result: &VerificationResult) {}
+}
+
+impl<C: VerificationCallback + ?Sized> VerificationCallback for Box<C> {
+    fn on_event(&self, event: VerificationEvent) {
+        (**self).on_event(event);
+    }
+
+    fn on_result(&self, result: &VerificationResult) {
+        (**self).on_result(result);
+    }
 }
 
 /// Console-based callback implementation for CLI output
 pub struct ConsoleCallback {
     show_progress: bool,
+    table: bool,
+    /// Whether lines are printed without their leading emoji
+    no_emoji: bool,
+    output: Mutex<Box<dyn Write + Send>>,
+    /// Heading and output held back until the callback is dropped, when buffered
+    buffer: Option<(String, Mutex<Vec<u8>>)>,
 }
 
 impl ConsoleCallback {
     pub fn new(show_progress: bool) -> Self {
-        Self { show_progress }
+        Self {
+            show_progress,
+            table: false,
+            no_emoji: false,
+            output: Mutex::new(Box::new(std::io::stderr())),
+            buffer: None,
+        }
+    }
+
+    /// Render result summaries as aligned tables
+    pub fn with_table(mut self, table: bool) -> Self {
+        self.table = table;
+        self
+    }
+
+    /// Print lines without their leading emoji
+    pub fn with_no_emoji(mut self, no_emoji: bool) -> Self {
+        self.no_emoji = no_emoji;
+        self
+    }
+
+    /// Write to `output` instead of standard error
+    pub fn with_output<W: Write + Send + 'static>(mut self, output: W) -> Self {
+        self.output = Mutex::new(Box::new(output));
+        self
+    }
+
+    /// Hold everything back until the callback is dropped and then write it in one piece
+    /// under a heading naming `archive`, so archives verified at the same time don't
+    /// interleave their output
+    pub fn buffered(mut self, archive: &str) -> Self {
+        self.buffer = Some((format!("🔍 {}:", archive), Mutex::new(Vec::new())));
+        self
+    }
+
+    /// `line` as printed, without its emoji with --no-emoji
+    fn line(&self, line: impl std::fmt::Display) -> String {
+        if self.no_emoji {
+            without_emoji(&line.to_string())
+        } else {
+            line.to_string()
+        }
+    }
+
+    /// Write a line, or hold it back when buffered
+    fn print(&self, line: impl std::fmt::Display) {
+        let line = self.line(line);
+        match &self.buffer {
+            Some((_, buffer)) => {
+                let mut buffer = buffer.lock().unwrap_or_else(|e| e.into_inner());
+                let _ = writeln!(buffer, "{}", line);
+            }
+            None => {
+                let mut output = self.output.lock().unwrap_or_else(|e| e.into_inner());
+                let _ = writeln!(output, "{}", line);
+            }
+        }
+    }
+
+    fn print_table(&self, table: SummaryTable) {
+        let mut rendered = Vec::new();
+        let _ = table.render(&mut rendered);
+        self.print(String::from_utf8_lossy(&rendered).trim_end());
+    }
+}
+
+impl Drop for ConsoleCallback {
+    fn drop(&mut self) {
+        let Some((heading, buffer)) = self.buffer.take() else {
+            return;
+        };
+        let buffer = buffer.into_inner().unwrap_or_else(|e| e.into_inner());
+        if buffer.is_empty() {
+            return;
+        }
+        // One write, which standard error makes whole against other threads
+        let mut block = format!("{}\n", self.line(heading)).into_bytes();
+        block.extend(buffer);
+        let output = self.output.get_mut().unwrap_or_else(|e| e.into_inner());
+        let _ = output.write_all(&block);
+        let _ = output.flush();
     }
 }
 
 impl VerificationCallback for ConsoleCallback {
     fn on_event(&self, event: VerificationEvent) {
-        if !self.show_progress {
+        // Lists of problem files are shown even without progress output
+        let lists_files = matches!(
+            event,
+            VerificationEvent::DisplayingMissingFiles { .. }
+                | VerificationEvent::DisplayingOutdatedFiles { .. }
+                | VerificationEvent::NewerInArchiveFiles { .. }
+                | VerificationEvent::UnverifiableFiles { .. }
+                | VerificationEvent::NothingToRetry
+        );
+        if !self.show_progress && !lists_files {
             return;
         }
 
         match event {
             VerificationEvent::Starting => {
-                eprintln!("🔍 Verifying archive contents...");
+                self.print("🔍 Verifying archive contents...");
             }
             VerificationEvent::ArchiveListingComplete { entries_found: _ } => {
                 // Could add debug info here if needed
             }
+            VerificationEvent::ComparisonComplete {
+                missing,
+                found,
+                total_expected,
+            } if self.table => {
+                self.print_table(
+                    SummaryTable::new("Verification Results")
+                        .row("Expected", total_expected)
+                        .row("Archived", found)
+                        .row("Missing", missing),
+                );
+            }
             VerificationEvent::ComparisonComplete {
                 missing,
                 found,
                 total_expected,
             } => {
-                eprintln!("📊 Verification Results:");
+                self.print("📊 Verification Results:");
                 let success_rate = if total_expected > 0 {
                     found as f64 / total_expected as f64 * 100.0
                 } else {
                     100.0
                 };
-                eprintln!(
+                self.print(format_args!(
                     "  ✅ Successfully archived: {}/{} files ({:.1}%)",
                     found, total_expected, success_rate
-                );
+                ));
                 if missing > 0 {
-                    eprintln!("  ❌ Missing files: {}", missing);
+                    self.print(format_args!("  ❌ Missing files: {}", missing));
                 }
             }
-            VerificationEvent::DisplayingMissingFiles { count: _ } => {
-                // Missing files are displayed by the display strategy
+            VerificationEvent::ListingWarnings { warnings } => {
+                self.print("⚠️  7-Zip reported problems while listing the archive:");
+                for warning in warnings {
+                    self.print(format_args!("    - {}", warning));
+                }
+            }
+            VerificationEvent::ExpectedMissing { files } => {
+                self.print(format_args!(
+                    "ℹ️  Expected missing (allowed): {} files",
+                    files.len()
+                ));
+                for file in files {
+                    self.print(format_args!("    - {}", file));
+                }
+            }
+            VerificationEvent::ChecksumProblems {
+                corrupted,
+                unchecked,
+            } => {
+                if !corrupted.is_empty() {
+                    self.print(format_args!(
+                        "💥 Corrupted files (content differs from disk): {}",
+                        corrupted.len()
+                    ));
+                    for file in corrupted {
+                        self.print(format_args!(
+                            "    - {} (archive CRC {:08X}, disk CRC {:08X})",
+                            file.path, file.archive_crc, file.filesystem_crc
+                        ));
+                    }
+                }
+                if !unchecked.is_empty() {
+                    self.print(format_args!(
+                        "❓ Checksums not compared: {} files",
+                        unchecked.len()
+                    ));
+                    for file in unchecked {
+                        self.print(format_args!("    - {}", file));
+                    }
+                }
+            }
+            VerificationEvent::DisplayingMissingFiles { count: _, files } => {
+                for file in files {
+                    self.print(format_args!("    - {}", file));
+                }
             }
             VerificationEvent::FreshnessCheckStarting => {
-                eprintln!("🕒 Checking file freshness...");
+                self.print("🕒 Checking file freshness...");
+            }
+            VerificationEvent::FreshnessCheckComplete {
+                outdated,
+                up_to_date,
+                unverifiable,
+                newer_in_archive,
+                total_checked,
+            } if self.table => {
+                let mut table = SummaryTable::new("Freshness Check Results")
+                    .row("Checked", total_checked)
+                    .row("Up-to-date", up_to_date)
+                    .row("Outdated", outdated)
+                    .row("Unverifiable", unverifiable);
+                if newer_in_archive > 0 {
+                    table = table.row("Newer in archive", newer_in_archive);
+                }
+                self.print_table(table);
             }
             VerificationEvent::FreshnessCheckComplete {
                 outdated,
                 up_to_date,
                 unverifiable,
+                newer_in_archive,
                 total_checked,
             } => {
-                eprintln!("📊 Freshness Check Results:");
-                eprintln!(
+                self.print("📊 Freshness Check Results:");
+                self.print(format_args!(
                     "  ✅ Up-to-date files: {}/{} ({:.1}%)",
                     up_to_date,
                     total_checked,
@@ -123,34 +331,90 @@ impl VerificationCallback for ConsoleCallback {
                     } else {
                         100.0
                     }
-                );
+                ));
                 if outdated > 0 {
-                    eprintln!("  ⚠️  Outdated files: {}", outdated);
+                    self.print(format_args!("  ⚠️  Outdated files: {}", outdated));
                 }
                 if unverifiable > 0 {
-                    eprintln!("  ❓ Unverifiable files: {}", unverifiable);
+                    self.print(format_args!("  ❓ Unverifiable files: {}", unverifiable));
+                }
+                if newer_in_archive > 0 {
+                    self.print(format_args!(
+                        "  🕰️  Newer in archive than on disk: {}",
+                        newer_in_archive
+                    ));
                 }
             }
-            VerificationEvent::DisplayingOutdatedFiles { count: _ } => {
-                // Outdated files are displayed by the display strategy
+            VerificationEvent::DisplayingOutdatedFiles { count: _, files } => {
+                self.print("⚠️  Outdated files found in archive:");
+                for outdated in files {
+                    self.print(format_args!("  📄 {}", outdated.path));
+                    if let (Some(archive_time), Some(fs_time)) =
+                        (outdated.archive_modified, outdated.filesystem_modified)
+                    {
+                        let time_diff = fs_time
+                            .duration_since(archive_time)
+                            .unwrap_or_default()
+                            .as_secs();
+                        if time_diff > 3600 {
+                            self.print(format_args!(
+                                "    📅 Archive is {:.1} hours older than filesystem",
+                                time_diff as f64 / 3600.0
+                            ));
+                        } else if time_diff > 60 {
+                            self.print(format_args!(
+                                "    📅 Archive is {} minutes older than filesystem",
+                                time_diff / 60
+                            ));
+                        } else {
+                            self.print(format_args!(
+                                "    📅 Archive is {} seconds older than filesystem",
+                                time_diff
+                            ));
+                        }
+                    }
+                }
+            }
+            VerificationEvent::NewerInArchiveFiles { files } => {
+                self.print(
+                    "🕰️  Archived files newer than the filesystem (clock skew or rolled-back disk?):",
+                );
+                for file in files {
+                    self.print(format_args!("  📄 {}", file));
+                }
+            }
+            VerificationEvent::UnverifiableFiles { files } => {
+                self.print("❓ Files that could not be verified for freshness:");
+                for file in files {
+                    self.print(format_args!("  📄 {}", file));
+                }
             }
             VerificationEvent::UpdatingOutdatedFiles { files_to_update } => {
-                eprintln!("🔄 Updating outdated files... ({} files)", files_to_update);
+                self.print(format_args!(
+                    "🔄 Updating outdated files... ({} files)",
+                    files_to_update
+                ));
             }
             VerificationEvent::UpdateOutdatedComplete { files_updated } => {
-                eprintln!(
+                self.print(format_args!(
                     "✅ Update completed. {} files updated in archive.",
                     files_updated
-                );
+                ));
             }
             VerificationEvent::RetryStarting { files_to_retry } => {
-                eprintln!("🔄 Retrying missing files... ({} files)", files_to_retry);
+                self.print(format_args!(
+                    "🔄 Retrying missing files... ({} files)",
+                    files_to_retry
+                ));
+            }
+            VerificationEvent::NothingToRetry => {
+                self.print("⚠️  No valid missing files found to retry.");
             }
             VerificationEvent::RetryComplete { files_added } => {
-                eprintln!(
+                self.print(format_args!(
                     "✅ Retry completed. {} files added to archive.",
                     files_added
-                );
+                ));
             }
             VerificationEvent::RetryVerificationComplete {
                 final_missing: _,
@@ -162,19 +426,17 @@ impl VerificationCallback for ConsoleCallback {
                 } else {
                     100.0
                 };
-                eprintln!(
+                self.print(format_args!(
                     "📊 Final Results: {}/{} files ({:.1}%)",
                     final_found, final_total, final_success_rate
-                );
+                ));
             }
             VerificationEvent::Complete { mode } => {
-                eprintln!("🎉 All files successfully archived!");
+                self.print("🎉 All files successfully archived!");
                 match mode {
-                    VerificationMode::VerifyOnly => {
-                        eprintln!(
-                            "💡 Use --retry flag to automatically attempt adding missing files."
-                        )
-                    }
+                    VerificationMode::VerifyOnly => self.print(
+                        "💡 Use --retry flag to automatically attempt adding missing files.",
+                    ),
                     VerificationMode::VerifyWithRetry => {}
                 }
             }
@@ -182,6 +444,84 @@ impl VerificationCallback for ConsoleCallback {
     }
 }
 
+/// Callback writing the final result as a single JSON object for scripts.
+///
+/// Progress still goes to standard error through the wrapped [`ConsoleCallback`], so the
+/// output holds nothing but one JSON line per verified archive.
+pub struct JsonCallback {
+    archive: String,
+    progress: ConsoleCallback,
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonCallback {
+    /// Write the result of verifying `archive` to standard output, reporting progress through `progress`
+    pub fn new<S: Into<String>>(archive: S, progress: ConsoleCallback) -> Self {
+        Self {
+            archive: archive.into(),
+            progress,
+            output: Mutex::new(Box::new(std::io::stdout())),
+        }
+    }
+
+    /// Write the JSON to `output` instead of standard output
+    pub fn with_output<W: Write + Send + 'static>(mut self, output: W) -> Self {
+        self.output = Mutex::new(Box::new(output));
+        self
+    }
+}
+
+impl VerificationCallback for JsonCallback {
+    fn on_event(&self, event: VerificationEvent) {
+        self.progress.on_event(event);
+    }
+
+    fn on_result(&self, result: &VerificationResult) {
+        let json = serde_json::json!({
+            "archive": self.archive,
+            "missing_files": result.missing_files,
+            "expected_missing": result.expected_missing,
+            "archived_files": result.archived_files,
+            "total_expected": result.total_expected,
+            "total_archived": result.total_archived,
+            "success_rate": result.success_rate(),
+            "listing_warnings": result.listing_warnings,
+            "corrupted_files": result.corrupted_files,
+            "unchecked_files": result.unchecked_files,
+            "complete": result.is_complete(),
+        });
+        // A single write per object keeps the lines of concurrent verifications whole
+        let mut output = self.output.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = output.write_all(format!("{}\n", json).as_bytes());
+        let _ = output.flush();
+    }
+}
+
+/// How the result of a verification is reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Human-readable progress and summaries on standard error
+    #[default]
+    Text,
+    /// The final result as JSON on standard output, see [`JsonCallback`]
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unknown report format '{}' (expected text or json)",
+                value
+            )),
+        }
+    }
+}
+
 /// Verification mode enumeration
 #[derive(Debug, Clone, Copy)]
 pub enum VerificationMode {
@@ -191,12 +531,39 @@ pub enum VerificationMode {
     VerifyWithRetry,
 }
 
+/// Verification outcome of a single archive when checking several at once
+#[derive(Debug)]
+pub struct ArchiveVerificationReport {
+    /// Path of the verified archive
+    pub archive: String,
+    /// Report of the verification, or the error that stopped it
+    pub result: Result<RunReport>,
+}
+
+impl ArchiveVerificationReport {
+    /// Result of comparing the archive with the expected files, if it got that far
+    pub fn verification(&self) -> Option<&VerificationResult> {
+        self.result.as_ref().ok()?.verification.as_ref()
+    }
+
+    /// Whether the archive was verified and contains every expected file
+    pub fn is_complete(&self) -> bool {
+        self.verification()
+            .is_some_and(|result| result.is_complete())
+    }
+}
+
 /// Service for handling verification and retry operations with callback support
 pub struct VerificationAndRetryService;
 
 impl VerificationAndRetryService {
-    /// Verify archive contents with optional retry and progress callbacks
+    /// Verify archive contents with optional retry and progress callbacks.
+    ///
+    /// Triggering `cancellation` stops the verification promptly with a cancellation
+    /// error; a retry that already started adding files is finished first.
+    #[allow(clippy::too_many_arguments)]
     pub async fn verify<A, V, R, C>(
+        config: &Config,
         archive_path: &str,
         input_paths: &[String],
         archiver: &A,
@@ -204,59 +571,32 @@ impl VerificationAndRetryService {
         verifier: &R,
         mode: VerificationMode,
         callback: C,
-    ) -> Result<VerificationResult>
+        cancellation: &CancellationToken,
+    ) -> Result<RunReport>
     where
         A: Archiver,
         V: PathValidator,
         R: ArchiveVerifier + Clone,
         C: VerificationCallback,
     {
-        callback.on_event(VerificationEvent::Starting);
-
-        // Verify archive directly with the verifier
-        let result = verifier.verify_archive(archive_path, input_paths).await?;
-
-        // Notify completion of comparison
-        callback.on_event(VerificationEvent::ComparisonComplete {
-            missing: result.missing_files.len(),
-            found: result.archived_files.len(),
-            total_expected: result.total_expected,
-        });
-
-        if !result.missing_files.is_empty() {
-            // Display missing files using the strategy pattern
-            callback.on_event(VerificationEvent::DisplayingMissingFiles {
-                count: result.missing_files.len(),
-            });
-            let display_context = display::MissingFileDisplayContext::with_consolidated_strategy();
-            display_context.display_missing_files(&result);
-
-            // Handle retry if requested
-            match mode {
-                VerificationMode::VerifyWithRetry => {
-                    return Self::retry_missing_files(
-                        archive_path,
-                        input_paths,
-                        &result,
-                        archiver,
-                        validator,
-                        verifier,
-                        callback,
-                    )
-                    .await;
-                }
-                VerificationMode::VerifyOnly => {
-                    // No action needed
-                }
-            }
-        } else {
-            callback.on_event(VerificationEvent::Complete { mode });
-        }
-
-        Ok(result)
+        Self::verify_with_freshness(
+            config,
+            archive_path,
+            input_paths,
+            archiver,
+            validator,
+            verifier,
+            mode,
+            false,
+            false,
+            callback,
+            cancellation,
+        )
+        .await
     }
 
     /// Retry adding missing files to the archive
+    #[allow(clippy::too_many_arguments)]
     async fn retry_missing_files<A, V, R, C>(
         archive_path: &str,
         input_paths: &[String],
@@ -264,7 +604,8 @@ impl VerificationAndRetryService {
         archiver: &A,
         validator: &V,
         verifier: &R,
-        callback: C,
+        callback: &C,
+        cancellation: &CancellationToken,
     ) -> Result<VerificationResult>
     where
         A: Archiver,
@@ -277,6 +618,10 @@ impl VerificationAndRetryService {
             .validate_paths(&verification_result.missing_files)
             .await?;
 
+        if cancellation.is_cancelled() {
+            return Err(ArchtreeError::cancelled("Verification"));
+        }
+
         if !valid_missing.is_empty() {
             callback.on_event(VerificationEvent::RetryStarting {
                 files_to_retry: valid_missing.len(),
@@ -292,7 +637,9 @@ impl VerificationAndRetryService {
             });
 
             // Verify again after retry
-            let retry_result = verifier.verify_archive(archive_path, input_paths).await?;
+            let retry_result = verifier
+                .verify_archive_cancellable(archive_path, input_paths, cancellation)
+                .await?;
 
             callback.on_event(VerificationEvent::RetryVerificationComplete {
                 final_missing: retry_result.missing_files.len(),
@@ -308,13 +655,106 @@ impl VerificationAndRetryService {
 
             Ok(retry_result)
         } else {
-            eprintln!("⚠️  No valid missing files found to retry.");
+            callback.on_event(VerificationEvent::NothingToRetry);
             Ok(verification_result.clone())
         }
     }
 
-    /// Verify archive contents with optional freshness checking
+    /// Verify several archives against the same input paths, checking freshness too if
+    /// `check_freshness` is set (see [`Self::verify_with_freshness`]).
+    ///
+    /// At most `max_concurrent` archives are verified at the same time. Reports are
+    /// returned in the order of `archive_paths`; a failure of one archive doesn't stop
+    /// the others, but `cancellation` stops all of them. Give each archive a buffered
+    /// callback to keep their output apart.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn verify_archives<A, V, R, C, F>(
+        config: &Config,
+        archive_paths: &[String],
+        input_paths: &[String],
+        archiver: Arc<A>,
+        validator: Arc<V>,
+        verifier: Arc<R>,
+        mode: VerificationMode,
+        check_freshness: bool,
+        update_outdated: bool,
+        max_concurrent: usize,
+        make_callback: F,
+        cancellation: &CancellationToken,
+    ) -> Vec<ArchiveVerificationReport>
+    where
+        A: Archiver + 'static,
+        V: PathValidator + 'static,
+        R: ArchiveVerifier + Clone + 'static,
+        C: VerificationCallback + 'static,
+        F: Fn(&str) -> C,
+    {
+        let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+        let config = Arc::new(config.clone());
+        let input_paths: Arc<[String]> = input_paths.into();
+        let mut tasks = JoinSet::new();
+
+        for (index, archive_path) in archive_paths.iter().enumerate() {
+            let archive_path = archive_path.clone();
+            let config = Arc::clone(&config);
+            let input_paths = Arc::clone(&input_paths);
+            let archiver = Arc::clone(&archiver);
+            let validator = Arc::clone(&validator);
+            let verifier = Arc::clone(&verifier);
+            let semaphore = Arc::clone(&semaphore);
+            let callback = make_callback(&archive_path);
+            let cancellation = cancellation.clone();
+
+            tasks.spawn(async move {
+                // The semaphore is never closed, so acquiring only waits for a free slot
+                let _permit = semaphore.acquire_owned().await;
+                let result = Self::verify_with_freshness(
+                    &config,
+                    &archive_path,
+                    &input_paths,
+                    archiver.as_ref(),
+                    validator.as_ref(),
+                    verifier.as_ref(),
+                    mode,
+                    check_freshness,
+                    update_outdated,
+                    callback,
+                    &cancellation,
+                )
+                .await;
+                (index, result)
+            });
+        }
+
+        let mut results: Vec<Option<Result<RunReport>>> =
+            archive_paths.iter().map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            // A panicking task leaves its slot empty and is reported below
+            if let Ok((index, result)) = joined {
+                results[index] = Some(result);
+            }
+        }
+
+        archive_paths
+            .iter()
+            .zip(results)
+            .map(|(archive, result)| ArchiveVerificationReport {
+                archive: archive.clone(),
+                result: result.unwrap_or_else(|| {
+                    Err(ArchtreeError::verification(
+                        "Verification task panicked",
+                        Some(archive.clone()),
+                    ))
+                }),
+            })
+            .collect()
+    }
+
+    /// Verify archive contents with optional freshness checking, which only runs when no
+    /// files are missing; see [`Self::verify`] for how `cancellation` is honoured
+    #[allow(clippy::too_many_arguments)]
     pub async fn verify_with_freshness<A, V, R, C>(
+        config: &Config,
         archive_path: &str,
         input_paths: &[String],
         archiver: &A,
@@ -324,7 +764,8 @@ impl VerificationAndRetryService {
         check_freshness: bool,
         update_outdated: bool,
         callback: C,
-    ) -> Result<VerificationResult>
+        cancellation: &CancellationToken,
+    ) -> Result<RunReport>
     where
         A: Archiver,
         V: PathValidator,
@@ -334,7 +775,9 @@ impl VerificationAndRetryService {
         callback.on_event(VerificationEvent::Starting);
 
         // Verify archive directly with the verifier
-        let result = verifier.verify_archive(archive_path, input_paths).await?;
+        let result = verifier
+            .verify_archive_cancellable(archive_path, input_paths, cancellation)
+            .await?;
 
         // Notify completion of comparison
         callback.on_event(VerificationEvent::ComparisonComplete {
@@ -343,95 +786,88 @@ impl VerificationAndRetryService {
             total_expected: result.total_expected,
         });
 
+        if !result.listing_warnings.is_empty() {
+            callback.on_event(VerificationEvent::ListingWarnings {
+                warnings: result.listing_warnings.clone(),
+            });
+        }
+
+        if !result.expected_missing.is_empty() {
+            callback.on_event(VerificationEvent::ExpectedMissing {
+                files: result.expected_missing.clone(),
+            });
+        }
+
+        if !result.corrupted_files.is_empty() || !result.unchecked_files.is_empty() {
+            callback.on_event(VerificationEvent::ChecksumProblems {
+                corrupted: result.corrupted_files.clone(),
+                unchecked: result.unchecked_files.clone(),
+            });
+        }
+
         if !result.missing_files.is_empty() {
             // Display missing files using the strategy pattern
+            let display_context = display::MissingFileDisplayContext::with_consolidated_strategy();
             callback.on_event(VerificationEvent::DisplayingMissingFiles {
                 count: result.missing_files.len(),
+                files: display_context.format_missing_files(&result),
             });
-            let display_context = display::MissingFileDisplayContext::with_consolidated_strategy();
-            display_context.display_missing_files(&result);
 
             // Handle retry if requested
             match mode {
                 VerificationMode::VerifyWithRetry => {
-                    return Self::retry_missing_files(
+                    let retry_result = Self::retry_missing_files(
                         archive_path,
                         input_paths,
                         &result,
                         archiver,
                         validator,
                         verifier,
-                        callback,
+                        &callback,
+                        cancellation,
                     )
-                    .await;
+                    .await?;
+                    callback.on_result(&retry_result);
+                    return Ok(RunReport::for_archive(config.clone(), archive_path)
+                        .with_verification(retry_result));
                 }
                 VerificationMode::VerifyOnly => {
                     // No action needed
                 }
             }
-        } else {
+        } else if result.corrupted_files.is_empty() {
             callback.on_event(VerificationEvent::Complete { mode });
         }
 
+        let mut report = RunReport::for_archive(config.clone(), archive_path);
+
         // If freshness checking is requested and there are no missing files,
         // proceed with freshness verification
         if check_freshness && result.missing_files.is_empty() {
             callback.on_event(VerificationEvent::FreshnessCheckStarting);
 
-            let freshness_result = verifier
-                .verify_archive_freshness(archive_path, input_paths)
-                .await?;
+            let freshness_result = tokio::select! {
+                biased;
+                _ = cancellation.cancelled() => return Err(ArchtreeError::cancelled("Verification")),
+                result = verifier.verify_archive_freshness(archive_path, input_paths) => result?,
+            };
 
             callback.on_event(VerificationEvent::FreshnessCheckComplete {
                 outdated: freshness_result.outdated_files.len(),
                 up_to_date: freshness_result.up_to_date_files.len(),
                 unverifiable: freshness_result.unverifiable_files.len(),
+                newer_in_archive: freshness_result.newer_in_archive_files.len(),
                 total_checked: freshness_result.total_checked,
             });
 
             if !freshness_result.outdated_files.is_empty() {
                 callback.on_event(VerificationEvent::DisplayingOutdatedFiles {
                     count: freshness_result.outdated_files.len(),
+                    files: freshness_result.outdated_files.clone(),
                 });
 
-                // Display outdated files
-                println!("⚠️  Outdated files found in archive:");
-                for outdated in &freshness_result.outdated_files {
-                    println!("  📄 {}", outdated.path);
-                    if let (Some(archive_time), Some(fs_time)) =
-                        (&outdated.archive_modified, &outdated.filesystem_modified)
-                    {
-                        // Convert SystemTime to more readable format
-                        use std::time::UNIX_EPOCH;
-                        let archive_duration =
-                            archive_time.duration_since(UNIX_EPOCH).unwrap_or_default();
-                        let fs_duration = fs_time.duration_since(UNIX_EPOCH).unwrap_or_default();
-                        let archive_secs = archive_duration.as_secs();
-                        let fs_secs = fs_duration.as_secs();
-
-                        // Simple time difference display
-                        let time_diff = fs_secs.saturating_sub(archive_secs);
-                        if time_diff > 3600 {
-                            println!(
-                                "    📅 Archive is {:.1} hours older than filesystem",
-                                time_diff as f64 / 3600.0
-                            );
-                        } else if time_diff > 60 {
-                            println!(
-                                "    📅 Archive is {} minutes older than filesystem",
-                                time_diff / 60
-                            );
-                        } else {
-                            println!(
-                                "    📅 Archive is {} seconds older than filesystem",
-                                time_diff
-                            );
-                        }
-                    }
-                }
-
                 // Handle updating outdated files if requested
-                if update_outdated && !freshness_result.outdated_files.is_empty() {
+                if update_outdated {
                     callback.on_event(VerificationEvent::UpdatingOutdatedFiles {
                         files_to_update: freshness_result.outdated_files.len(),
                     });
@@ -451,19 +887,344 @@ impl VerificationAndRetryService {
                     callback.on_event(VerificationEvent::UpdateOutdatedComplete {
                         files_updated: outdated_paths.len(),
                     });
-
-                    println!("✅ All outdated files have been updated in the archive!");
                 }
             }
 
+            if !freshness_result.newer_in_archive_files.is_empty() {
+                callback.on_event(VerificationEvent::NewerInArchiveFiles {
+                    files: freshness_result
+                        .newer_in_archive_files
+                        .iter()
+                        .map(|newer| newer.path.clone())
+                        .collect(),
+                });
+            }
+
             if !freshness_result.unverifiable_files.is_empty() {
-                println!("❓ Files that could not be verified for freshness:");
-                for file in &freshness_result.unverifiable_files {
-                    println!("  📄 {}", file);
-                }
+                callback.on_event(VerificationEvent::UnverifiableFiles {
+                    files: freshness_result.unverifiable_files.clone(),
+                });
             }
+            report = report.with_freshness(freshness_result);
+        }
+
+        callback.on_result(&result);
+        Ok(report.with_verification(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockArchiver, MockVerifier};
+    use async_trait::async_trait;
+    use std::path::Path;
+
+    struct MockValidator;
+
+    #[async_trait]
+    impl PathValidator for MockValidator {
+        async fn validate(&self, _path: &Path) -> Result<bool> {
+            Ok(true)
+        }
+
+        fn description(&self) -> &'static str {
+            "mock"
+        }
+    }
+
+    /// Configuration of a verify run of `archive`
+    fn config(archive: &str) -> Config {
+        Config::builder()
+            .output_path(Some(archive), false)
+            .show_progress(false)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_archives_reports_each_archive() {
+        let expected = vec!["a.txt".to_string(), "b.txt".to_string()];
+        let verifier = MockVerifier::new()
+            .with_files("week1.7z", &["a.txt", "b.txt"])
+            .with_files("week2.7z", &["a.txt"]);
+        let archives = vec![
+            "week1.7z".to_string(),
+            "week2.7z".to_string(),
+            "week3.7z".to_string(),
+        ];
+
+        let reports = VerificationAndRetryService::verify_archives(
+            &config("week1.7z"),
+            &archives,
+            &expected,
+            Arc::new(MockArchiver::new()),
+            Arc::new(MockValidator),
+            Arc::new(verifier),
+            VerificationMode::VerifyOnly,
+            false,
+            false,
+            2,
+            |_| ConsoleCallback::new(false),
+            &CancellationToken::new(),
+        )
+        .await;
+
+        assert_eq!(reports.len(), 3);
+
+        assert_eq!(reports[0].archive, "week1.7z");
+        assert!(reports[0].is_complete());
+
+        assert_eq!(reports[1].archive, "week2.7z");
+        assert!(!reports[1].is_complete());
+        assert_eq!(reports[1].result.as_ref().unwrap().archive_path, "week2.7z");
+        let week2 = reports[1].verification().unwrap();
+        assert_eq!(week2.missing_files, vec!["b.txt"]);
+        assert_eq!(week2.total_archived, 1);
+
+        assert_eq!(reports[2].archive, "week3.7z");
+        assert!(reports[2].result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_archives_checks_freshness_of_each_archive() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("a.txt");
+        std::fs::write(&file, "a").unwrap();
+        let expected = vec![file.to_string_lossy().to_string()];
+        let archiver = MockArchiver::new();
+        archiver.create_archive(&expected, "old.7z").await.unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        archiver.create_archive(&expected, "new.7z").await.unwrap();
+
+        let archives = vec!["old.7z".to_string(), "new.7z".to_string()];
+        let reports = VerificationAndRetryService::verify_archives(
+            &config("old.7z"),
+            &archives,
+            &expected,
+            Arc::new(archiver.clone()),
+            Arc::new(MockValidator),
+            Arc::new(archiver.verifier()),
+            VerificationMode::VerifyOnly,
+            true,
+            false,
+            2,
+            |archive| ConsoleCallback::new(false).buffered(archive),
+            &CancellationToken::new(),
+        )
+        .await;
+
+        let freshness = |index: usize| {
+            reports[index]
+                .result
+                .as_ref()
+                .unwrap()
+                .freshness
+                .clone()
+                .unwrap()
+        };
+        let outdated: Vec<String> = freshness(0)
+            .outdated_files
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+        assert_eq!(outdated, expected);
+        assert!(freshness(1).outdated_files.is_empty());
+        assert_eq!(freshness(1).up_to_date_files, expected);
+    }
+
+    #[test]
+    fn test_buffered_console_writes_each_archive_in_one_block() {
+        let output = SharedBuffer::default();
+        let first = ConsoleCallback::new(true)
+            .with_output(output.clone())
+            .buffered("week1.7z");
+        let second = ConsoleCallback::new(true)
+            .with_output(output.clone())
+            .buffered("week2.7z");
+        let quiet = ConsoleCallback::new(false)
+            .with_output(output.clone())
+            .buffered("week3.7z");
+
+        for callback in [&first, &second, &quiet] {
+            callback.on_event(VerificationEvent::Starting);
+            callback.on_event(VerificationEvent::ComparisonComplete {
+                missing: 0,
+                found: 1,
+                total_expected: 1,
+            });
         }
+        assert!(output.0.lock().unwrap().is_empty());
+
+        drop(second);
+        drop(first);
+        drop(quiet);
+        let written = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let block = |archive: &str| {
+            format!(
+                "🔍 {}:\n🔍 Verifying archive contents...\n📊 Verification Results:\n  ✅ Successfully archived: 1/1 files (100.0%)\n",
+                archive
+            )
+        };
+        // Nothing was printed for the quiet archive, so not even its heading is written
+        assert_eq!(written, block("week2.7z") + &block("week1.7z"));
+    }
+
+    #[test]
+    fn test_console_without_emoji() {
+        let output = SharedBuffer::default();
+        let callback = ConsoleCallback::new(true)
+            .with_output(output.clone())
+            .with_no_emoji(true)
+            .buffered("week1.7z");
+        callback.on_event(VerificationEvent::ComparisonComplete {
+            missing: 1,
+            found: 1,
+            total_expected: 2,
+        });
+        drop(callback);
+
+        let written = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            written,
+            "week1.7z:\nVerification Results:\n  Successfully archived: 1/2 files (50.0%)\n  Missing files: 1\n"
+        );
+    }
+
+    /// Writer collecting everything written to it
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_json_callback_writes_final_result() {
+        let expected = vec!["a.txt".to_string(), "b.txt".to_string()];
+        let verifier = MockVerifier::new().with_files("week.7z", &["a.txt"]);
+        let output = SharedBuffer::default();
+        let callback =
+            JsonCallback::new("week.7z", ConsoleCallback::new(false)).with_output(output.clone());
+
+        VerificationAndRetryService::verify(
+            &config("week.7z"),
+            "week.7z",
+            &expected,
+            &MockArchiver::new(),
+            &MockValidator,
+            &verifier,
+            VerificationMode::VerifyOnly,
+            callback,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        let written = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(written.lines().count(), 1);
+        let json: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(json["archive"], "week.7z");
+        assert_eq!(json["missing_files"], serde_json::json!(["b.txt"]));
+        assert_eq!(json["archived_files"], serde_json::json!(["a.txt"]));
+        assert_eq!(json["total_expected"], 2);
+        assert_eq!(json["total_archived"], 1);
+        assert_eq!(json["success_rate"], 50.0);
+        assert_eq!(json["complete"], false);
+        assert_eq!("JSON".parse::<ReportFormat>(), Ok(ReportFormat::Json));
+    }
+
+    #[test]
+    fn test_json_callback_reports_corrupted_and_unchecked_files() {
+        let output = SharedBuffer::default();
+        let callback =
+            JsonCallback::new("week.7z", ConsoleCallback::new(false)).with_output(output.clone());
+        callback.on_result(&VerificationResult {
+            missing_files: Vec::new(),
+            expected_missing: Vec::new(),
+            archived_files: vec!["a.txt".to_string(), "b.txt".to_string()],
+            all_expected_files: vec!["a.txt".to_string(), "b.txt".to_string()],
+            total_expected: 2,
+            total_archived: 2,
+            listing_warnings: Vec::new(),
+            corrupted_files: vec![CorruptedFile {
+                path: "a.txt".to_string(),
+                archive_crc: 0x1234,
+                filesystem_crc: 0x5678,
+            }],
+            unchecked_files: vec!["b.txt".to_string()],
+        });
+
+        let written = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(
+            json["corrupted_files"],
+            serde_json::json!([{"path": "a.txt", "archive_crc": 0x1234, "filesystem_crc": 0x5678}])
+        );
+        assert_eq!(json["unchecked_files"], serde_json::json!(["b.txt"]));
+        assert_eq!(json["complete"], false);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_verify_stops_when_cancelled() {
+        use crate::verification::SevenZipVerifier;
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, "7z").unwrap();
+        let file = temp_dir.path().join("a.txt");
+        std::fs::write(&file, "a").unwrap();
+
+        // Stand-in for 7-Zip that would take ten seconds to list the archive
+        let runner = temp_dir.path().join("slow7z");
+        std::fs::write(
+            &runner,
+            format!(
+                "#!/bin/sh\n[ \"$1\" = \"--help\" ] && exit 0\necho \"Path = {}\"\nsleep 10\n",
+                file.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let verifier = SevenZipVerifier::with_path(runner.to_string_lossy().to_string());
+
+        let cancellation = CancellationToken::new();
+        let trigger = cancellation.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            trigger.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let result = VerificationAndRetryService::verify(
+            &config(&archive.to_string_lossy()),
+            &archive.to_string_lossy(),
+            &[file.to_string_lossy().to_string()],
+            &MockArchiver::new(),
+            &MockValidator,
+            &verifier,
+            VerificationMode::VerifyWithRetry,
+            ConsoleCallback::new(false),
+            &cancellation,
+        )
+        .await;
 
-        Ok(result)
+        let error = result.unwrap_err();
+        assert_eq!(error.category(), "cancelled");
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
     }
 }
@@ -1,11 +1,32 @@
-use crate::core::{ArchtreeError, ErrorContext, Result};
+use crate::core::{ArchtreeError, Config, ErrorContext, Password, Result};
+use crate::io::log::append_invocation_log;
+use crate::io::volumes::resolve_archive_path;
+use crate::processing::path_processor::{
+    ExclusionMatcher, UNFOLLOWED_LINK_REASON, WildcardMatcher,
+};
+use crate::verification::checkpoint::VerificationCheckpoint;
+use crate::verification::checksum::crc32_file;
+use crate::verification::expand_cache::ExpandCache;
 use async_trait::async_trait;
-use chrono::{NaiveDateTime, TimeZone};
-use std::collections::{HashMap, HashSet};
-use std::path::Path;
-use std::time::SystemTime;
+use chrono::{DateTime, Local, LocalResult, NaiveDateTime, TimeZone, Utc};
+use regex::Regex;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use tokio::fs;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+/// Number of files compared between two checks of the cancellation token
+const CANCELLATION_CHECK_INTERVAL: usize = 1024;
+
+/// Locale the list command runs under unless overridden
+pub const DEFAULT_LIST_LOCALE: &str = "en_US.UTF-8";
 
 /// Represents an entry in an archive
 #[derive(Debug, Clone)]
@@ -18,6 +39,34 @@ pub struct ArchiveEntry {
     pub size: u64,
     /// Modification time of the file when it was archived (None for directories or if unavailable)
     pub modified: Option<SystemTime>,
+    /// Compressed size as reported by `Packed Size =` (None if the listing omits it)
+    pub packed_size: Option<u64>,
+    /// CRC32 of the file's content as reported by `CRC =` (None for directories, empty
+    /// files and formats that don't store one)
+    pub crc: Option<u32>,
+}
+
+impl ArchiveEntry {
+    /// Packed size as a fraction of the original size.
+    ///
+    /// Returns None when the ratio can't be known: no packed size was listed, the file is
+    /// empty, or the packed size is 0 because the file shares a solid block with the
+    /// entry that leads it.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        match self.packed_size {
+            Some(packed) if packed > 0 && self.size > 0 => Some(packed as f64 / self.size as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Entries of an archive together with diagnostics reported while listing it
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveListing {
+    /// Entries (files and directories) found in the archive
+    pub entries: Vec<ArchiveEntry>,
+    /// Error and warning lines the archiver printed while listing
+    pub warnings: Vec<String>,
 }
 
 /// Trait for archive verification strategies
@@ -26,6 +75,14 @@ pub trait ArchiveVerifier: Send + Sync {
     /// List all entries (files and directories) contained in an archive
     async fn list_archive_entries(&self, archive_path: &str) -> Result<Vec<ArchiveEntry>>;
 
+    /// List all entries along with any warnings reported while listing
+    async fn list_archive(&self, archive_path: &str) -> Result<ArchiveListing> {
+        Ok(ArchiveListing {
+            entries: self.list_archive_entries(archive_path).await?,
+            warnings: Vec::new(),
+        })
+    }
+
     /// List all files contained in an archive (backwards compatibility)
     async fn list_archive_contents(&self, archive_path: &str) -> Result<Vec<String>> {
         let entries = self.list_archive_entries(archive_path).await?;
@@ -43,6 +100,34 @@ pub trait ArchiveVerifier: Send + Sync {
         expected_paths: &[String],
     ) -> Result<VerificationResult>;
 
+    /// Verify like [`Self::verify_archive`], stopping with a cancellation error as soon
+    /// as `cancellation` is triggered
+    async fn verify_archive_cancellable(
+        &self,
+        archive_path: &str,
+        expected_paths: &[String],
+        cancellation: &CancellationToken,
+    ) -> Result<VerificationResult> {
+        tokio::select! {
+            biased;
+            _ = cancellation.cancelled() => Err(ArchtreeError::cancelled("Verification")),
+            result = self.verify_archive(archive_path, expected_paths) => result,
+        }
+    }
+
+    /// Verify like [`Self::verify_archive`] against files that are already expanded,
+    /// e.g. the processed paths of the backup that created the archive.
+    ///
+    /// Directories in `expanded_files` are not walked again. By default the files are
+    /// handed to [`Self::verify_archive`], which expands them anyway.
+    async fn verify_archive_with_expanded(
+        &self,
+        archive_path: &str,
+        expanded_files: &[String],
+    ) -> Result<VerificationResult> {
+        self.verify_archive(archive_path, expanded_files).await
+    }
+
     /// Verify that files in the archive are up to date with the filesystem
     async fn verify_archive_freshness(
         &self,
@@ -50,6 +135,17 @@ pub trait ArchiveVerifier: Send + Sync {
         expected_paths: &[String],
     ) -> Result<FreshnessVerificationResult>;
 
+    /// Run the archive format's own integrity test, which reads every entry and checks it
+    /// against its stored CRC, returning whether the archive passed.
+    ///
+    /// By default the verifier can't test archives and returns an error.
+    async fn test_integrity(&self, archive_path: &str) -> Result<bool> {
+        Err(ArchtreeError::verification(
+            format!("{} can't test archive integrity", self.name()),
+            Some(archive_path),
+        ))
+    }
+
     /// Check if the verifier is available on the system
     async fn is_available(&self) -> bool;
 
@@ -61,17 +157,331 @@ pub trait ArchiveVerifier: Send + Sync {
 #[derive(Debug, Clone)]
 pub struct SevenZipVerifier {
     executable_path: String,
+    /// Number of leading path components removed from both sides before comparison
+    strip_components: usize,
+    /// File used to persist comparison progress so an interrupted run can resume
+    checkpoint_path: Option<PathBuf>,
+    /// Whether a file that can't be read while checking it fails verification
+    strict_hash: bool,
+    /// Whether archive entries newer than the filesystem are reported separately
+    report_reverse_freshness: bool,
+    /// Window in whole seconds within which archive and filesystem times count as equal
+    freshness_tolerance: u64,
+    /// Switches replacing the default encoding switch of the list command
+    list_args: Option<Vec<String>>,
+    /// Locale the list command runs under
+    list_locale: String,
+    /// File the raw output of every list command is appended to
+    log_path: Option<PathBuf>,
+    /// Password for listing encrypted archives
+    password: Option<Password>,
+    /// Entries whose path matches one of these are left out of listings and comparisons
+    ignore_entries: Vec<Regex>,
+    /// Missing files matching this are reported as expected instead of failing verification
+    allow_missing: Option<WildcardMatcher>,
+    /// File caching the expanded expected files between runs
+    expand_cache: Option<PathBuf>,
+    /// Whether archived files are checked against the CRC32 of their file on disk
+    verify_checksums: bool,
+    /// Whether listed modification times without a zone are UTC instead of local time
+    assume_utc: bool,
+    /// Whether symlinks to directories are descended into when expanding expected paths
+    follow_symlinks: bool,
+    /// Lists expected directories instead of a [`DirectoryEnumerator`]
+    enumerator: Option<Arc<dyn FileEnumerator>>,
 }
 
 impl SevenZipVerifier {
     pub fn new() -> Self {
+        Self::with_path("7z.exe".to_string())
+    }
+
+    pub fn with_path(executable_path: String) -> Self {
         Self {
-            executable_path: "7z.exe".to_string(),
+            executable_path,
+            strip_components: 0,
+            checkpoint_path: None,
+            strict_hash: false,
+            report_reverse_freshness: false,
+            freshness_tolerance: FRESHNESS_TOLERANCE_SECONDS,
+            list_args: None,
+            list_locale: DEFAULT_LIST_LOCALE.to_string(),
+            log_path: None,
+            password: None,
+            ignore_entries: Vec::new(),
+            allow_missing: None,
+            expand_cache: None,
+            verify_checksums: false,
+            assume_utc: false,
+            follow_symlinks: false,
+            enumerator: None,
         }
     }
 
-    pub fn with_path(executable_path: String) -> Self {
-        Self { executable_path }
+    /// Create a verifier using the 7-Zip path, password and list command settings of `config`
+    pub fn from_config(config: &Config) -> Self {
+        let verifier = match &config.seven_zip_path {
+            Some(path) => Self::with_path(path.clone()),
+            None => Self::new(),
+        }
+        .with_password(config.password.clone())
+        .with_assume_utc(config.assume_utc)
+        .with_follow_symlinks(config.follow_symlinks)
+        .with_ignored_entries(
+            // Patterns were validated when the config was built
+            config
+                .ignore_entries
+                .iter()
+                .filter_map(|pattern| Regex::new(pattern).ok())
+                .collect(),
+        );
+        // Wildcards are escaped when compiled, so the patterns can't be invalid
+        let verifier = match WildcardMatcher::with_case_sensitivity(
+            &config.allow_missing,
+            config.case_sensitive,
+        ) {
+            Ok(matcher) if !config.allow_missing.is_empty() => {
+                verifier.with_allowed_missing(matcher)
+            }
+            _ => verifier,
+        };
+        let verifier = match &config.list_args {
+            Some(args) => verifier.with_list_args(args.clone()),
+            None => verifier,
+        };
+        let verifier = match &config.list_locale {
+            Some(locale) => verifier.with_list_locale(locale.clone()),
+            None => verifier,
+        };
+        match &config.seven_zip_log {
+            Some(path) => verifier.with_log(path),
+            None => verifier,
+        }
+    }
+
+    /// Append the raw output of every list command to this file
+    pub fn with_log<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.log_path = Some(path.into());
+        self
+    }
+
+    /// Pass these switches to `7z l` instead of the default `-sccUTF-8`.
+    ///
+    /// `-slt` is always passed, since the listing parser depends on its output format.
+    pub fn with_list_args(mut self, args: Vec<String>) -> Self {
+        self.list_args = Some(args);
+        self
+    }
+
+    /// List archives with this password, needed when their headers are encrypted
+    pub fn with_password(mut self, password: Option<Password>) -> Self {
+        self.password = password;
+        self
+    }
+
+    /// Run `7z l` under this locale instead of `en_US.UTF-8`
+    pub fn with_list_locale<S: Into<String>>(mut self, locale: S) -> Self {
+        self.list_locale = locale.into();
+        self
+    }
+
+    /// Read listed modification times that carry no zone as UTC instead of local time,
+    /// for archives listed by a 7-Zip set up to show UTC
+    pub fn with_assume_utc(mut self, assume_utc: bool) -> Self {
+        self.assume_utc = assume_utc;
+        self
+    }
+
+    /// Descend into symlinks to directories when expanding expected directories, like
+    /// a backup made with `--follow-symlinks`; without it they are skipped
+    pub fn with_follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// List expected directories with `enumerator` instead of walking them
+    pub fn with_enumerator(mut self, enumerator: Arc<dyn FileEnumerator>) -> Self {
+        self.enumerator = Some(enumerator);
+        self
+    }
+
+    /// Leave out archived and expected files whose path matches one of `patterns`
+    /// anywhere, with `\` turned into `/`, so they are neither reported missing nor as
+    /// extras
+    pub fn with_ignored_entries(mut self, patterns: Vec<Regex>) -> Self {
+        self.ignore_entries = patterns;
+        self
+    }
+
+    /// Report missing files matching `matcher` as expected-missing, so they don't fail
+    /// verification; meant for transient files such as locks and sockets
+    pub fn with_allowed_missing(mut self, matcher: WildcardMatcher) -> Self {
+        self.allow_missing = Some(matcher);
+        self
+    }
+
+    /// Cache the files the expected paths expand to in `path`, reusing them while the
+    /// tree's directories are unchanged (see [`ExpandCache`])
+    pub fn with_expand_cache<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.expand_cache = Some(path.into());
+        self
+    }
+
+    /// Expand the expected paths to individual files, through the cache if one is set
+    async fn expand_expected(&self, expected_paths: &[String]) -> Result<Vec<String>> {
+        let walker = DirectoryEnumerator::new(self.follow_symlinks);
+        let enumerator = self.enumerator.as_deref().unwrap_or(&walker);
+        match &self.expand_cache {
+            Some(path) => {
+                ExpandCache::new(path)
+                    .expand(expected_paths, enumerator)
+                    .await
+            }
+            None => expand_input_paths_with(expected_paths, enumerator).await,
+        }
+    }
+
+    /// Whether `path` matches an ignored-entry pattern, with backslashes turned into `/`
+    /// so the same pattern works for Windows paths
+    fn is_ignored(&self, path: &str) -> bool {
+        if self.ignore_entries.is_empty() {
+            return false;
+        }
+        let path = path.replace('\\', "/");
+        self.ignore_entries
+            .iter()
+            .any(|pattern| pattern.is_match(&path))
+    }
+
+    /// Ignore the first `count` path components of archived and expected paths when comparing
+    pub fn with_strip_components(mut self, count: usize) -> Self {
+        self.strip_components = count;
+        self
+    }
+
+    /// Persist comparison progress to `path` and skip files already checked there
+    pub fn with_checkpoint<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.checkpoint_path = Some(path.into());
+        self
+    }
+
+    /// Fail verification when a file can't be read while checking its freshness or
+    /// checksum, naming the file, instead of listing it as unverifiable or not compared
+    pub fn with_strict_hash(mut self, strict: bool) -> Self {
+        self.strict_hash = strict;
+        self
+    }
+
+    /// Report archive entries that are newer than their filesystem files in their own category
+    pub fn with_reverse_freshness(mut self, report: bool) -> Self {
+        self.report_reverse_freshness = report;
+        self
+    }
+
+    /// Count archive and filesystem times within `seconds` of each other as equal when
+    /// checking freshness, instead of [`FRESHNESS_TOLERANCE_SECONDS`]; filesystems with
+    /// coarse timestamps need more
+    pub fn with_freshness_tolerance(mut self, seconds: u64) -> Self {
+        self.freshness_tolerance = seconds;
+        self
+    }
+
+    /// Compare the CRC32 7-Zip stored for each archived file with one computed over the
+    /// file on disk, reporting files whose content differs as corrupted.
+    ///
+    /// Every archived expected file is read in full.
+    pub fn with_checksums(mut self, verify: bool) -> Self {
+        self.verify_checksums = verify;
+        self
+    }
+
+    /// Compare the archive's files with an already expanded set of expected files
+    async fn verify_expanded(
+        &self,
+        archive_path: &str,
+        expanded_expected_files: Vec<String>,
+        cancellation: &CancellationToken,
+    ) -> Result<VerificationResult> {
+        // Stream the listing, keeping only the archived files that are expected, so memory
+        // is bounded by the expected set however large the archive is. Dropping the
+        // listing kills the 7-Zip process.
+        let expanded_expected_files: Vec<String> = expanded_expected_files
+            .into_iter()
+            .filter(|file| !self.is_ignored(file))
+            .collect();
+        let expected_keys: HashSet<String> = expanded_expected_files
+            .iter()
+            .filter_map(|file| comparison_key(file, self.strip_components))
+            .collect();
+        let (archived_keys, archived_crcs, listing_warnings) = tokio::select! {
+            biased;
+            _ = cancellation.cancelled() => return Err(ArchtreeError::cancelled("Verification")),
+            listing = self.list_expected_files(archive_path, &expected_keys) => listing?,
+        };
+        drop(expected_keys);
+
+        // Compare expected vs archived files
+        let (missing_files, found_files) = match &self.checkpoint_path {
+            Some(checkpoint_path) => {
+                let mut checkpoint = VerificationCheckpoint::load(checkpoint_path, archive_path)?;
+                let lists = compare_file_lists_with_checkpoint(
+                    &expanded_expected_files,
+                    &archived_keys,
+                    self.strip_components,
+                    &mut checkpoint,
+                    cancellation,
+                )?;
+                // The comparison ran to completion, so there is nothing left to resume
+                checkpoint.finish()?;
+                lists
+            }
+            None => compare_file_lists(
+                &expanded_expected_files,
+                &archived_keys,
+                self.strip_components,
+                cancellation,
+            )?,
+        };
+
+        let (corrupted_files, unchecked_files) = if self.verify_checksums {
+            let found_files = found_files.clone();
+            let strip_components = self.strip_components;
+            let strict = self.strict_hash;
+            let cancellation = cancellation.clone();
+            tokio::task::spawn_blocking(move || {
+                compare_checksums(
+                    &found_files,
+                    &archived_crcs,
+                    strip_components,
+                    strict,
+                    &cancellation,
+                )
+            })
+            .await
+            .map_err(|e| ArchtreeError::other(format!("Failed to compare checksums: {}", e)))??
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        let total_archived = found_files.len();
+        let (expected_missing, missing_files) = match &self.allow_missing {
+            Some(matcher) => missing_files
+                .into_iter()
+                .partition(|file| matcher.matches(Path::new(file), "")),
+            None => (Vec::new(), missing_files),
+        };
+
+        Ok(VerificationResult {
+            missing_files,
+            expected_missing,
+            archived_files: found_files,
+            total_expected: expanded_expected_files.len(),
+            all_expected_files: expanded_expected_files,
+            total_archived,
+            listing_warnings,
+            corrupted_files,
+            unchecked_files,
+        })
     }
 
     /// Alternative method for listing archive entries with better Unicode support
@@ -85,159 +495,390 @@ impl SevenZipVerifier {
     async fn list_archive_entries_with_encoding(
         &self,
         archive_path: &str,
-    ) -> Result<Vec<ArchiveEntry>> {
-        // First try the standard UTF-8 approach
-        match self.list_archive_entries_utf8(archive_path).await {
-            Ok(entries) => Ok(entries),
+    ) -> Result<ArchiveListing> {
+        let mut entries = Vec::new();
+        let warnings = match self
+            .stream_listing(archive_path, true, |entry| entries.push(entry))
+            .await
+        {
+            Ok(warnings) => warnings,
             Err(_) => {
-                // Fallback to original method if UTF-8 fails
-                self.list_archive_entries_legacy(archive_path).await
+                // Fallback to lossy decoding if UTF-8 fails
+                entries.clear();
+                self.stream_listing(archive_path, false, |entry| entries.push(entry))
+                    .await?
             }
-        }
+        };
+        Ok(ArchiveListing { entries, warnings })
     }
 
-    /// Try to list archive entries using UTF-8 encoding
-    async fn list_archive_entries_utf8(&self, archive_path: &str) -> Result<Vec<ArchiveEntry>> {
-        let archive_path = tokio::fs::canonicalize(archive_path)
+    /// List the archive like [`Self::list_archive_entries_with_encoding`], but keep only
+    /// the comparison keys of archived files that are in `expected_keys`.
+    ///
+    /// Returns the matched keys, their listed CRC32 when checksums are verified, and the
+    /// listing diagnostics.
+    async fn list_expected_files(
+        &self,
+        archive_path: &str,
+        expected_keys: &HashSet<String>,
+    ) -> Result<(HashSet<String>, HashMap<String, u32>, Vec<String>)> {
+        let mut archived_keys = HashSet::new();
+        let mut archived_crcs = HashMap::new();
+        let mut keep_expected = |entry: ArchiveEntry| {
+            if entry.is_directory {
+                return;
+            }
+            let Some(key) = comparison_key(&entry.path, self.strip_components) else {
+                return;
+            };
+            if expected_keys.contains(&key) {
+                // 7-Zip lists no CRC for empty files; the CRC32 of no data is 0
+                if self.verify_checksums
+                    && let Some(crc) = entry.crc.or((entry.size == 0).then_some(0))
+                {
+                    archived_crcs.insert(key.clone(), crc);
+                }
+                archived_keys.insert(key);
+            }
+        };
+        let warnings = match self
+            .stream_listing(archive_path, true, &mut keep_expected)
             .await
-            .context_io("Failed to canonicalize archive path")?
-            .to_string_lossy()
-            .to_string();
+        {
+            Ok(warnings) => warnings,
+            // Keys matched before the UTF-8 attempt failed came from valid lines and stay
+            Err(_) => {
+                self.stream_listing(archive_path, false, &mut keep_expected)
+                    .await?
+            }
+        };
+        Ok((archived_keys, archived_crcs, warnings))
+    }
 
+    /// Build the `7z l -slt` command for an archive.
+    ///
+    /// The locale is forced to English because localized 7-Zip builds translate the
+    /// `Path =`/`Size =` keys that [`ListingParser`] relies on.
+    ///
+    /// Custom list switches (see [`Self::with_list_args`]) replace the encoding switch in
+    /// both the UTF-8 and the legacy attempt.
+    fn list_command(&self, archive_path: &str, force_utf8: bool) -> Command {
         let mut cmd = Command::new(&self.executable_path);
-        cmd.args([
-            "l",
-            "-slt",
-            "-sccUTF-8", // Force UTF-8 output
-            &archive_path,
-        ]);
-
-        let output = cmd
-            .output()
-            .await
-            .context_io("Failed to execute 7z list command")?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(ArchtreeError::external_tool(
-                "7z",
-                format!("7z list command failed: {}", stderr),
-            ));
+        cmd.args(["l", "-slt"]);
+        if let Some(password) = &self.password {
+            cmd.arg(format!("-p{}", password.expose()));
         }
+        match &self.list_args {
+            Some(args) => {
+                cmd.args(args);
+            }
+            None if force_utf8 => {
+                cmd.arg("-sccUTF-8"); // Force UTF-8 output
+            }
+            None => {}
+        }
+        cmd.arg(archive_path)
+            .env("LANG", &self.list_locale) // English unless overridden
+            .env("LC_ALL", &self.list_locale) // Override locale settings
+            .kill_on_drop(true); // Don't leave 7-Zip running when a listing is cancelled
+        cmd
+    }
 
-        // Parse as UTF-8
-        let stdout = String::from_utf8(output.stdout)
-            .map_err(|_| ArchtreeError::external_tool("7z", "Invalid UTF-8 output"))?;
-
-        self.parse_seven_zip_output(&stdout, &archive_path)
+    /// Build the `7z t` command testing an archive, under the same locale as listings so
+    /// the summary line can be recognized
+    fn test_command(&self, archive_path: &str) -> Command {
+        let mut cmd = Command::new(&self.executable_path);
+        cmd.arg("t");
+        if let Some(password) = &self.password {
+            cmd.arg(format!("-p{}", password.expose()));
+        }
+        cmd.arg(archive_path)
+            .env("LANG", &self.list_locale)
+            .env("LC_ALL", &self.list_locale)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        cmd
     }
 
-    /// Legacy method for listing archive entries (original implementation)
-    async fn list_archive_entries_legacy(&self, archive_path: &str) -> Result<Vec<ArchiveEntry>> {
-        let archive_path = tokio::fs::canonicalize(archive_path)
+    /// Run the list command and hand every entry to `on_entry` as soon as its block is read.
+    ///
+    /// With `force_utf8` the output must be valid UTF-8, otherwise it is decoded lossily.
+    /// Entries may already have been handed out when an error is returned. Returns the
+    /// error and warning lines printed while listing.
+    async fn stream_listing<F: FnMut(ArchiveEntry)>(
+        &self,
+        archive_path: &str,
+        force_utf8: bool,
+        mut on_entry: F,
+    ) -> Result<Vec<String>> {
+        // A split archive is listed through its first volume
+        let archive_path = tokio::fs::canonicalize(resolve_archive_path(archive_path))
             .await
             .context_io("Failed to canonicalize archive path")?
             .to_string_lossy()
             .to_string();
 
-        let mut cmd = Command::new(&self.executable_path);
-        cmd.args(["l", "-slt", &archive_path]);
+        let mut cmd = self.list_command(&archive_path, force_utf8);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = cmd
+            .spawn()
+            .context_io("Failed to execute 7z list command")?;
 
-        let output = cmd
-            .output()
+        // Stderr is drained alongside, so 7-Zip never blocks on a full pipe
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let stderr_task = tokio::spawn(async move {
+            let mut buffer = Vec::new();
+            stderr.read_to_end(&mut buffer).await.map(|_| buffer)
+        });
+
+        // The raw output is only kept when it has to be logged
+        let mut logged_stdout = self.log_path.as_ref().map(|_| Vec::new());
+        let mut parser = ListingParser::new(&archive_path);
+        parser.assume_utc = self.assume_utc;
+        let mut invalid_utf8 = false;
+        let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = stdout
+                .read_until(b'\n', &mut line)
+                .await
+                .context_io("Failed to read 7z list output")?;
+            if read == 0 {
+                break;
+            }
+            if let Some(logged) = &mut logged_stdout {
+                logged.extend_from_slice(&line);
+            }
+            // Keep reading after invalid output, so 7-Zip can run to completion
+            if invalid_utf8 {
+                continue;
+            }
+
+            let text = if force_utf8 {
+                match std::str::from_utf8(&line) {
+                    Ok(text) => Cow::Borrowed(text),
+                    Err(_) => {
+                        invalid_utf8 = true;
+                        continue;
+                    }
+                }
+            } else {
+                String::from_utf8_lossy(&line)
+            };
+            if let Some(entry) = parser.feed(&text)
+                && !self.is_ignored(&entry.path)
+            {
+                on_entry(entry);
+            }
+        }
+
+        let status = child
+            .wait()
             .await
             .context_io("Failed to execute 7z list command")?;
+        let stderr = stderr_task
+            .await
+            .map_err(|e| ArchtreeError::other(format!("Failed to read 7z list output: {}", e)))?
+            .context_io("Failed to read 7z list output")?;
+        if let Some(log_path) = &self.log_path {
+            let output = Output {
+                status,
+                stdout: logged_stdout.unwrap_or_default(),
+                stderr: stderr.clone(),
+            };
+            append_invocation_log(log_path, &cmd, &output).await?;
+        }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(&stderr);
             return Err(ArchtreeError::external_tool(
                 "7z",
                 format!("7z list command failed: {}", stderr),
             ));
         }
+        if invalid_utf8 {
+            return Err(ArchtreeError::external_tool("7z", "Invalid UTF-8 output"));
+        }
 
-        // Use lossy conversion for legacy compatibility
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        self.parse_seven_zip_output(&stdout, &archive_path)
+        let (last_entry, mut warnings) = parser.finish();
+        if let Some(entry) = last_entry
+            && !self.is_ignored(&entry.path)
+        {
+            on_entry(entry);
+        }
+        warnings.extend(collect_listing_diagnostics(&String::from_utf8_lossy(
+            &stderr,
+        )));
+        Ok(warnings)
     }
+}
 
-    /// Parse 7-Zip output and extract archive entries
-    fn parse_seven_zip_output(
-        &self,
-        stdout: &str,
-        archive_path: &str,
-    ) -> Result<Vec<ArchiveEntry>> {
-        let mut entries = Vec::new();
+/// Parser for 7-Zip's `-slt` listing, fed one line at a time.
+///
+/// The output consists of blocks for each entry with Path, Attributes, Size, etc.,
+/// separated by empty lines.
+struct ListingParser<'a> {
+    archive_path: &'a str,
+    current_entry: Option<ArchiveEntry>,
+    warnings: Vec<String>,
+    /// Whether modification times without a zone are UTC instead of local time
+    assume_utc: bool,
+}
 
-        // Parse 7z -slt output which provides detailed information
-        // Format includes blocks for each entry with Path, Attributes, Size, etc.
-        let mut current_entry: Option<ArchiveEntry> = None;
-
-        for line in stdout.lines() {
-            let line = line.trim();
-
-            if line.starts_with("Path = ") {
-                // Start of a new entry
-                let path = line.strip_prefix("Path = ").unwrap_or("").to_string();
-
-                // Skip the archive itself and empty paths
-                if path != archive_path && !path.is_empty() {
-                    current_entry = Some(ArchiveEntry {
-                        path,
-                        is_directory: false, // Will be set by Attributes line
-                        size: 0,             // Will be set by Size line
-                        modified: None,      // Will be set by Modified line
-                    });
-                }
-            } else if line.starts_with("Attributes = ") && current_entry.is_some() {
-                // Parse attributes to determine if it's a directory
-                let attributes = line.strip_prefix("Attributes = ").unwrap_or("");
-                // Directory entries typically have 'D' in their attributes string
-                if let Some(ref mut entry) = current_entry {
-                    entry.is_directory = attributes.contains('D');
-                }
-            } else if line.starts_with("Size = ") && current_entry.is_some() {
-                // Parse file size
-                if let Some(size_str) = line.strip_prefix("Size = ") {
-                    if let Ok(size) = size_str.parse::<u64>() {
-                        if let Some(ref mut entry) = current_entry {
-                            entry.size = size;
-                        }
-                    }
-                }
-            } else if line.starts_with("Modified = ") && current_entry.is_some() {
-                // Parse modification time from 7-Zip format "YYYY-MM-DD HH:MM:SS"
-                if let Some(modified_str) = line.strip_prefix("Modified = ") {
-                    if let Ok(naive_dt) =
-                        NaiveDateTime::parse_from_str(modified_str, "%Y-%m-%d %H:%M:%S")
-                    {
-                        // 7-Zip shows local time, so treat it as local time and convert to SystemTime
-                        // We'll assume local timezone for the archive timestamps
-                        use chrono::Local;
-                        let local_dt = Local.from_local_datetime(&naive_dt).single();
-                        if let Some(local_time) = local_dt {
-                            let system_time = SystemTime::from(local_time);
-                            if let Some(ref mut entry) = current_entry {
-                                entry.modified = Some(system_time);
-                            }
-                        }
-                    }
-                }
-            } else if line.is_empty() && current_entry.is_some() {
-                // End of entry block, save the entry
-                if let Some(entry) = current_entry.take() {
-                    entries.push(entry);
-                }
-            }
+impl<'a> ListingParser<'a> {
+    fn new(archive_path: &'a str) -> Self {
+        Self {
+            archive_path,
+            current_entry: None,
+            warnings: Vec::new(),
+            assume_utc: false,
         }
+    }
 
-        // Handle case where the last entry doesn't have a trailing empty line
-        if let Some(entry) = current_entry {
-            entries.push(entry);
+    /// Parse one line of output, returning the entry whose block it ends
+    fn feed(&mut self, line: &str) -> Option<ArchiveEntry> {
+        let line = line.trim();
+        let archive_path = self.archive_path;
+
+        if is_listing_diagnostic(line) {
+            // Error/warning lines can appear between entry blocks; keep them instead of
+            // silently dropping them with the other unrecognized lines
+            self.warnings.push(line.to_string());
+        } else if line.starts_with("Path = ") {
+            // Start of a new entry
+            let path = line.strip_prefix("Path = ").unwrap_or("").to_string();
+
+            // Skip the archive itself and empty paths; a split archive is also
+            // listed as the archive its volumes join into, `<archive>` without `.001`
+            let joined_archive = archive_path.strip_suffix(".001");
+            if path != archive_path && Some(path.as_str()) != joined_archive && !path.is_empty() {
+                self.current_entry = Some(ArchiveEntry {
+                    path,
+                    is_directory: false, // Will be set by Attributes line
+                    size: 0,             // Will be set by Size line
+                    modified: None,      // Will be set by Modified line
+                    packed_size: None,   // Will be set by Packed Size line
+                    crc: None,           // Will be set by CRC line
+                });
+            }
+        } else if line.starts_with("Attributes = ") && self.current_entry.is_some() {
+            // Parse attributes to determine if it's a directory
+            let attributes = line.strip_prefix("Attributes = ").unwrap_or("");
+            // Directory entries typically have 'D' in their attributes string
+            if let Some(ref mut entry) = self.current_entry {
+                entry.is_directory = attributes.contains('D');
+            }
+        } else if line.starts_with("Size = ") && self.current_entry.is_some() {
+            // Parse file size
+            if let Some(size_str) = line.strip_prefix("Size = ")
+                && let Ok(size) = size_str.parse::<u64>()
+                && let Some(ref mut entry) = self.current_entry
+            {
+                entry.size = size;
+            }
+        } else if line.starts_with("Packed Size = ") && self.current_entry.is_some() {
+            // Parse compressed size; the value is blank for some formats
+            if let Some(packed_str) = line.strip_prefix("Packed Size = ")
+                && let Ok(packed_size) = packed_str.parse::<u64>()
+                && let Some(ref mut entry) = self.current_entry
+            {
+                entry.packed_size = Some(packed_size);
+            }
+        } else if line.starts_with("CRC = ") && self.current_entry.is_some() {
+            // Parse the content checksum, printed as 8 hex digits; blank for empty files
+            if let Some(crc_str) = line.strip_prefix("CRC = ")
+                && let Ok(crc) = u32::from_str_radix(crc_str, 16)
+                && let Some(ref mut entry) = self.current_entry
+            {
+                entry.crc = Some(crc);
+            }
+        } else if line.starts_with("Modified = ") && self.current_entry.is_some() {
+            // Parse modification time from 7-Zip format "YYYY-MM-DD HH:MM:SS"
+            let modified_str = line.strip_prefix("Modified = ").unwrap_or("");
+            let modified = if self.assume_utc {
+                parse_listed_time(modified_str, &Utc)
+            } else {
+                parse_listed_time(modified_str, &Local)
+            };
+            if let Some(ref mut entry) = self.current_entry {
+                entry.modified = modified;
+            }
+        } else if line.is_empty() && self.current_entry.is_some() {
+            // End of entry block, save the entry
+            return self.current_entry.take();
         }
+        None
+    }
+
+    /// Entry of a last block without a trailing empty line, and the diagnostics seen
+    fn finish(self) -> (Option<ArchiveEntry>, Vec<String>) {
+        (self.current_entry, self.warnings)
+    }
+}
+
+/// Parse a modification time of a listing, `YYYY-MM-DD HH:MM:SS` with optional fractional
+/// seconds.
+///
+/// A time that names its zone, with a trailing `Z`, ` UTC` or an offset such as `+02:00`,
+/// is read in that zone. 7-Zip shows times without one in the zone it is set up for,
+/// normally local time, so those are read in `zone`. A time repeated when the clocks go
+/// back is taken as its earliest occurrence, and a time skipped when they go forward is
+/// shifted an hour later, so files saved during a clock change can still be compared.
+fn parse_listed_time<Tz: TimeZone>(value: &str, zone: &Tz) -> Option<SystemTime> {
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+    let value = value.trim();
+    if let Some(utc) = value
+        .strip_suffix('Z')
+        .or_else(|| value.strip_suffix(" UTC"))
+    {
+        let naive = NaiveDateTime::parse_from_str(utc.trim_end(), FORMAT).ok()?;
+        return Some(SystemTime::from(Utc.from_utc_datetime(&naive)));
+    }
+    if let Ok(with_offset) = DateTime::parse_from_str(value, &format!("{} %:z", FORMAT))
+        .or_else(|_| DateTime::parse_from_str(value, &format!("{}%:z", FORMAT)))
+    {
+        return Some(SystemTime::from(with_offset));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, FORMAT).ok()?;
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => Some(time.into()),
+        LocalResult::None => zone
+            .from_local_datetime(&(naive + chrono::Duration::hours(1)))
+            .earliest()
+            .map(SystemTime::from),
+    }
+}
 
-        Ok(entries)
+/// Whether the output of `7z t` reports a passed test: 7-Zip exits with an error status on
+/// CRC or data errors, and only prints `Everything is Ok` once every entry checked out
+fn integrity_test_passed(success: bool, stdout: &str) -> bool {
+    success && stdout.lines().any(|line| line.trim() == "Everything is Ok")
+}
+
+/// Check if a line of 7-Zip listing output is an error or warning message
+fn is_listing_diagnostic(line: &str) -> bool {
+    if line.contains(" = ") {
+        return false;
     }
+    let upper = line.to_ascii_uppercase();
+    ["ERROR", "WARNING", "OPEN ERROR", "OPEN WARNING"]
+        .iter()
+        .any(|prefix| upper.starts_with(prefix))
+}
+
+/// Collect error and warning lines from 7-Zip output
+fn collect_listing_diagnostics(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| is_listing_diagnostic(line))
+        .map(str::to_string)
+        .collect()
 }
 
 impl Default for SevenZipVerifier {
@@ -249,10 +890,40 @@ impl Default for SevenZipVerifier {
 #[async_trait]
 impl ArchiveVerifier for SevenZipVerifier {
     async fn list_archive_entries(&self, archive_path: &str) -> Result<Vec<ArchiveEntry>> {
+        Ok(self.list_archive(archive_path).await?.entries)
+    }
+
+    async fn list_archive(&self, archive_path: &str) -> Result<ArchiveListing> {
         // Use the new encoding-aware method
         self.list_archive_entries_with_encoding(archive_path).await
     }
 
+    async fn test_integrity(&self, archive_path: &str) -> Result<bool> {
+        if !self.is_available().await {
+            return Err(ArchtreeError::tool_not_available(self.name()));
+        }
+
+        // A split archive is tested through its first volume
+        let archive_path = tokio::fs::canonicalize(resolve_archive_path(archive_path))
+            .await
+            .context_io("Failed to canonicalize archive path")?
+            .to_string_lossy()
+            .to_string();
+        let mut cmd = self.test_command(&archive_path);
+        let output = cmd
+            .output()
+            .await
+            .context_io("Failed to execute 7z test command")?;
+        if let Some(log_path) = &self.log_path {
+            append_invocation_log(log_path, &cmd, &output).await?;
+        }
+
+        Ok(integrity_test_passed(
+            output.status.success(),
+            &String::from_utf8_lossy(&output.stdout),
+        ))
+    }
+
     async fn is_available(&self) -> bool {
         Command::new(&self.executable_path)
             .arg("--help")
@@ -270,114 +941,120 @@ impl ArchiveVerifier for SevenZipVerifier {
         &self,
         archive_path: &str,
         expected_paths: &[String],
+    ) -> Result<VerificationResult> {
+        self.verify_archive_cancellable(archive_path, expected_paths, &CancellationToken::new())
+            .await
+    }
+
+    async fn verify_archive_cancellable(
+        &self,
+        archive_path: &str,
+        expected_paths: &[String],
+        cancellation: &CancellationToken,
     ) -> Result<VerificationResult> {
         // Check if verifier is available
         if !self.is_available().await {
-            return Err(ArchtreeError::external_tool(
-                self.name(),
-                "is not available",
-            ));
+            return Err(ArchtreeError::tool_not_available(self.name()));
+        }
+
+        // Expand input paths to get all individual files; walking a huge tree can take a
+        // while, so it stops as soon as the token is triggered
+        let expanded_expected_files = tokio::select! {
+            biased;
+            _ = cancellation.cancelled() => return Err(ArchtreeError::cancelled("Verification")),
+            expanded = self.expand_expected(expected_paths) => expanded?,
+        };
+        self.verify_expanded(archive_path, expanded_expected_files, cancellation)
+            .await
+    }
+
+    async fn verify_archive_with_expanded(
+        &self,
+        archive_path: &str,
+        expanded_files: &[String],
+    ) -> Result<VerificationResult> {
+        if !self.is_available().await {
+            return Err(ArchtreeError::tool_not_available(self.name()));
+        }
+
+        self.verify_expanded(
+            archive_path,
+            expanded_files.to_vec(),
+            &CancellationToken::new(),
+        )
+        .await
+    }
+
+    async fn verify_archive_freshness(
+        &self,
+        archive_path: &str,
+        expected_paths: &[String],
+    ) -> Result<FreshnessVerificationResult> {
+        // Check if verifier is available
+        if !self.is_available().await {
+            return Err(ArchtreeError::tool_not_available(self.name()));
         }
 
         // Expand input paths to get all individual files
-        let expanded_expected_files = expand_input_paths(expected_paths).await?;
+        let expanded_expected_files = self.expand_expected(expected_paths).await?;
 
         // Get archive entries
         let archive_entries = self.list_archive_entries(archive_path).await?;
 
-        // Extract just the files from archive entries
-        let archived_files: Vec<&ArchiveEntry> = archive_entries
+        // Build a map of archive entries by path for quick lookup
+        let archive_map: HashMap<String, &ArchiveEntry> = archive_entries
             .iter()
             .filter(|entry| !entry.is_directory)
-            .collect();
-
-        let archived_file_paths: Vec<String> = archived_files
-            .iter()
-            .map(|entry| entry.path.clone())
-            .collect();
-
-        // Compare expected vs archived files
-        let (missing_files, found_files) =
-            compare_file_lists(&expanded_expected_files, &archived_file_paths);
-
-        let total_archived = found_files.len();
-
-        Ok(VerificationResult {
-            missing_files,
-            archived_files: found_files,
-            all_expected_files: expanded_expected_files.clone(),
-            total_expected: expanded_expected_files.len(),
-            total_archived,
-        })
-    }
-
-    async fn verify_archive_freshness(
-        &self,
-        archive_path: &str,
-        expected_paths: &[String],
-    ) -> Result<FreshnessVerificationResult> {
-        // Check if verifier is available
-        if !self.is_available().await {
-            return Err(ArchtreeError::external_tool(
-                self.name(),
-                "is not available",
-            ));
-        }
-
-        // Expand input paths to get all individual files
-        let expanded_expected_files = expand_input_paths(expected_paths).await?;
-
-        // Get archive entries
-        let archive_entries = self.list_archive_entries(archive_path).await?;
-
-        // Build a map of archive entries by path for quick lookup
-        let archive_map: HashMap<String, &ArchiveEntry> = archive_entries
-            .iter()
-            .filter(|entry| !entry.is_directory)
-            .map(|entry| (entry.path.clone(), entry))
+            .filter_map(|entry| Some((comparison_key(&entry.path, self.strip_components)?, entry)))
             .collect();
 
         let mut outdated_files = Vec::new();
         let mut up_to_date_files = Vec::new();
         let mut unverifiable_files = Vec::new();
+        let mut newer_in_archive_files = Vec::new();
 
         // Check each expected file for freshness
         for file_path in &expanded_expected_files {
-            if let Some(archive_entry) = archive_map.get(file_path) {
+            if let Some(archive_entry) = comparison_key(file_path, self.strip_components)
+                .and_then(|key| archive_map.get(&key))
+            {
                 // File exists in archive, check if it's up to date
                 match (archive_entry.modified, fs::metadata(file_path).await) {
                     (Some(archive_modified), Ok(fs_metadata)) => {
                         if let Ok(fs_modified) = fs_metadata.modified() {
-                            // Calculate time difference in seconds
-                            let time_diff = if fs_modified > archive_modified {
-                                fs_modified
-                                    .duration_since(archive_modified)
-                                    .unwrap_or_default()
-                                    .as_secs()
-                            } else {
-                                0
+                            let mismatch = OutdatedFile {
+                                path: file_path.clone(),
+                                archive_modified: Some(archive_modified),
+                                filesystem_modified: Some(fs_modified),
                             };
 
-                            // Consider files up to date if they're within 2 seconds
-                            // This accounts for precision differences between archive and filesystem timestamps
-                            const FRESHNESS_TOLERANCE_SECONDS: u64 = 2;
-
-                            if time_diff > FRESHNESS_TOLERANCE_SECONDS {
+                            match compare_modification_times_with_tolerance(
+                                archive_modified,
+                                fs_modified,
+                                self.freshness_tolerance,
+                            ) {
                                 // Filesystem version is significantly newer
-                                outdated_files.push(OutdatedFile {
-                                    path: file_path.clone(),
-                                    archive_modified: Some(archive_modified),
-                                    filesystem_modified: Some(fs_modified),
-                                });
-                            } else {
+                                FreshnessStatus::Outdated => outdated_files.push(mismatch),
+                                FreshnessStatus::NewerInArchive
+                                    if self.report_reverse_freshness =>
+                                {
+                                    newer_in_archive_files.push(mismatch)
+                                }
                                 // Archive version is up to date (within tolerance)
-                                up_to_date_files.push(file_path.clone());
+                                _ => up_to_date_files.push(file_path.clone()),
                             }
                         } else {
                             // Can't get filesystem modification time
                             unverifiable_files.push(file_path.clone());
                         }
                     }
+                    (_, Err(e)) if self.strict_hash => {
+                        // The file was never checked, which mustn't pass as checked
+                        return Err(ArchtreeError::verification(
+                            format!("Failed to read {} while checking it: {}", file_path, e),
+                            Some(archive_path),
+                        ));
+                    }
                     _ => {
                         // Can't compare modification times (missing data)
                         unverifiable_files.push(file_path.clone());
@@ -392,46 +1069,335 @@ impl ArchiveVerifier for SevenZipVerifier {
             outdated_files,
             up_to_date_files,
             unverifiable_files,
+            newer_in_archive_files,
             total_checked: expanded_expected_files.len(),
         })
     }
 }
 
-/// Compare two file lists and return (missing_files, found_files)
-fn compare_file_lists(expected: &[String], archived: &[String]) -> (Vec<String>, Vec<String>) {
-    let archived_set: HashSet<&String> = archived.iter().collect();
-    let _expected_set: HashSet<&String> = expected.iter().collect();
+/// Verifier whose expected paths are already expanded to individual files.
+///
+/// Hands them to [`ArchiveVerifier::verify_archive_with_expanded`] of the wrapped
+/// verifier, so callers holding a processed file set don't walk the inputs again.
+#[derive(Debug, Clone)]
+pub struct ExpandedVerifier<V> {
+    inner: V,
+}
 
-    let missing_files: Vec<String> = expected
-        .iter()
-        .filter(|&file| !archived_set.contains(file))
-        .cloned()
-        .collect();
+impl<V: ArchiveVerifier> ExpandedVerifier<V> {
+    pub fn new(inner: V) -> Self {
+        Self { inner }
+    }
+}
 
-    let found_files: Vec<String> = expected
-        .iter()
-        .filter(|&file| archived_set.contains(file))
-        .cloned()
+#[async_trait]
+impl<V: ArchiveVerifier> ArchiveVerifier for ExpandedVerifier<V> {
+    async fn list_archive_entries(&self, archive_path: &str) -> Result<Vec<ArchiveEntry>> {
+        self.inner.list_archive_entries(archive_path).await
+    }
+
+    async fn list_archive(&self, archive_path: &str) -> Result<ArchiveListing> {
+        self.inner.list_archive(archive_path).await
+    }
+
+    async fn verify_archive(
+        &self,
+        archive_path: &str,
+        expected_paths: &[String],
+    ) -> Result<VerificationResult> {
+        self.inner
+            .verify_archive_with_expanded(archive_path, expected_paths)
+            .await
+    }
+
+    async fn verify_archive_freshness(
+        &self,
+        archive_path: &str,
+        expected_paths: &[String],
+    ) -> Result<FreshnessVerificationResult> {
+        self.inner
+            .verify_archive_freshness(archive_path, expected_paths)
+            .await
+    }
+
+    async fn test_integrity(&self, archive_path: &str) -> Result<bool> {
+        self.inner.test_integrity(archive_path).await
+    }
+
+    async fn is_available(&self) -> bool {
+        self.inner.is_available().await
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+/// Outcome of comparing an archived file's modification time with the filesystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreshnessStatus {
+    /// Both timestamps agree within the tolerance
+    UpToDate,
+    /// The filesystem version is newer than the archived one
+    Outdated,
+    /// The archived version is newer than the filesystem one (clock skew or rolled-back disk)
+    NewerInArchive,
+}
+
+/// Default window in whole seconds within which archive and filesystem times count as equal
+pub const FRESHNESS_TOLERANCE_SECONDS: u64 = 2;
+
+/// Compare archive and filesystem modification times.
+///
+/// Differences under a second are always ignored, because precision differs by format:
+/// NTFS and 7z archives keep 100 ns, most Unix filesystems keep 1 ns, tar keeps whole
+/// seconds and zip's DOS timestamps only even seconds. Sub-second differences are
+/// therefore never meaningful, and the tolerance window covers zip's 2-second granularity.
+pub fn compare_modification_times(
+    archive_modified: SystemTime,
+    fs_modified: SystemTime,
+) -> FreshnessStatus {
+    compare_modification_times_with_tolerance(
+        archive_modified,
+        fs_modified,
+        FRESHNESS_TOLERANCE_SECONDS,
+    )
+}
+
+/// Compare modification times, counting them as equal while they are less than
+/// `tolerance_seconds` plus one second apart.
+///
+/// The absolute difference is compared, so times on either side of a second boundary
+/// aren't a second further apart than they really are.
+pub fn compare_modification_times_with_tolerance(
+    archive_modified: SystemTime,
+    fs_modified: SystemTime,
+    tolerance_seconds: u64,
+) -> FreshnessStatus {
+    let window = Duration::from_secs(tolerance_seconds.saturating_add(1));
+    match fs_modified.duration_since(archive_modified) {
+        Ok(newer_on_disk) if newer_on_disk >= window => FreshnessStatus::Outdated,
+        Ok(_) => FreshnessStatus::UpToDate,
+        Err(newer_in_archive) if newer_in_archive.duration() >= window => {
+            FreshnessStatus::NewerInArchive
+        }
+        Err(_) => FreshnessStatus::UpToDate,
+    }
+}
+
+/// Build the key used to match an archived path against an expected path, or `None` if
+/// nothing of the path is left to match.
+///
+/// `.` and `..` segments are resolved first, see [`normalize_dot_segments`]. The path is
+/// then split on both `/` and `\`, the first `strip_components` components (including a
+/// drive such as `C:`) are dropped and the rest is joined with `/`, like
+/// `tar --strip-components`. Every count normalizes the same way, so `/data/f.txt` (as
+/// given) and `data/f.txt` (as 7-Zip stores it, without the leading `/`) share a key.
+pub fn comparison_key(path: &str, strip_components: usize) -> Option<String> {
+    let path = normalize_dot_segments(path);
+    let components: Vec<&str> = path
+        .split(['/', '\\'])
+        .filter(|component| !component.is_empty())
+        .skip(strip_components)
         .collect();
+    (!components.is_empty()).then(|| components.join("/"))
+}
+
+/// Resolve `.` and `..` segments of a path lexically, without looking at the filesystem.
+///
+/// Paths without such segments are returned unchanged. Otherwise the remaining segments
+/// are joined with the first separator of the path. A `..` leading out of a relative path
+/// is kept, while one above the root or a drive such as `C:` is dropped.
+pub fn normalize_dot_segments(path: &str) -> Cow<'_, str> {
+    if !path
+        .split(['/', '\\'])
+        .any(|segment| segment == "." || segment == "..")
+    {
+        return Cow::Borrowed(path);
+    }
+
+    let separator = path
+        .chars()
+        .find(|c| matches!(c, '/' | '\\'))
+        .unwrap_or('/');
+    let absolute = path.starts_with(['/', '\\']);
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => match segments.last() {
+                Some(last) if *last == ".." => segments.push(".."),
+                Some(last) if segments.len() == 1 && last.ends_with(':') => {}
+                Some(_) => {
+                    segments.pop();
+                }
+                None if absolute => {}
+                None => segments.push(".."),
+            },
+            _ => segments.push(segment),
+        }
+    }
+
+    let joined = segments.join(separator.encode_utf8(&mut [0; 4]));
+    Cow::Owned(if absolute {
+        format!("{}{}", separator, joined)
+    } else {
+        joined
+    })
+}
+
+/// Compare the expected files with the [`comparison_key`]s of the archived files and
+/// return (missing_files, found_files)
+///
+/// The returned lists keep the original expected paths. The comparison stops with a
+/// cancellation error once `cancellation` is triggered.
+fn compare_file_lists(
+    expected: &[String],
+    archived_keys: &HashSet<String>,
+    strip_components: usize,
+    cancellation: &CancellationToken,
+) -> Result<(Vec<String>, Vec<String>)> {
+    let mut missing_files = Vec::new();
+    let mut found_files = Vec::new();
+
+    for (index, file) in expected.iter().enumerate() {
+        if index % CANCELLATION_CHECK_INTERVAL == 0 && cancellation.is_cancelled() {
+            return Err(ArchtreeError::cancelled("Verification"));
+        }
+
+        if comparison_key(file, strip_components).is_some_and(|key| archived_keys.contains(&key)) {
+            found_files.push(file.clone());
+        } else {
+            missing_files.push(file.clone());
+        }
+    }
+
+    Ok((missing_files, found_files))
+}
+
+/// Compare two file lists like [`compare_file_lists`], resuming from a checkpoint.
+///
+/// The current listing always decides whether a file is found. Files the checkpoint
+/// already holds with that result are not recorded again; every other file is recorded
+/// as it is processed, so a result the listing contradicts is corrected. A cancelled
+/// comparison flushes the checkpoint first, so the next run resumes where it stopped.
+fn compare_file_lists_with_checkpoint(
+    expected: &[String],
+    archived_keys: &HashSet<String>,
+    strip_components: usize,
+    checkpoint: &mut VerificationCheckpoint,
+    cancellation: &CancellationToken,
+) -> Result<(Vec<String>, Vec<String>)> {
+    let mut missing_files = Vec::new();
+    let mut found_files = Vec::new();
+
+    for (index, file) in expected.iter().enumerate() {
+        if index % CANCELLATION_CHECK_INTERVAL == 0 && cancellation.is_cancelled() {
+            checkpoint.flush()?;
+            return Err(ArchtreeError::cancelled("Verification"));
+        }
+
+        let found =
+            comparison_key(file, strip_components).is_some_and(|key| archived_keys.contains(&key));
+        if checkpoint.result_for(file) != Some(found) {
+            checkpoint.record(file, found)?;
+        }
+
+        if found {
+            found_files.push(file.clone());
+        } else {
+            missing_files.push(file.clone());
+        }
+    }
+
+    checkpoint.flush()?;
+    Ok((missing_files, found_files))
+}
 
-    (missing_files, found_files)
+/// Compare the CRC32 of each found file on disk with the CRC32 listed for it in the archive.
+///
+/// Returns the files whose content differs, and the files that couldn't be compared
+/// because the archive lists no CRC for them or they couldn't be read. With `strict`, a
+/// file that can't be read is a verification error instead.
+fn compare_checksums(
+    found_files: &[String],
+    archived_crcs: &HashMap<String, u32>,
+    strip_components: usize,
+    strict: bool,
+    cancellation: &CancellationToken,
+) -> Result<(Vec<CorruptedFile>, Vec<String>)> {
+    let mut corrupted_files = Vec::new();
+    let mut unchecked_files = Vec::new();
+    for file in found_files {
+        if cancellation.is_cancelled() {
+            return Err(ArchtreeError::cancelled("Verification"));
+        }
+        let Some(&archive_crc) =
+            comparison_key(file, strip_components).and_then(|key| archived_crcs.get(&key))
+        else {
+            unchecked_files.push(file.clone());
+            continue;
+        };
+        match crc32_file(Path::new(file)) {
+            Ok(filesystem_crc) if filesystem_crc != archive_crc => {
+                corrupted_files.push(CorruptedFile {
+                    path: file.clone(),
+                    archive_crc,
+                    filesystem_crc,
+                })
+            }
+            Ok(_) => {}
+            Err(e) if strict => {
+                return Err(ArchtreeError::verification(
+                    format!("Failed to read {} while checksumming: {}", file, e),
+                    None::<String>,
+                ));
+            }
+            Err(_) => unchecked_files.push(file.clone()),
+        }
+    }
+    Ok((corrupted_files, unchecked_files))
 }
 
 /// Recursively enumerate all files in a directory
-pub async fn enumerate_directory_files(dir_path: &str) -> Result<Vec<String>> {
+///
+/// Symlinked directories are skipped unless `follow_symlinks` is set, the same rule as
+/// the backup walk. Followed or not, each directory is only walked once, so a symlink
+/// loop can't make the walk recurse forever.
+pub async fn enumerate_directory_files(
+    dir_path: &str,
+    follow_symlinks: bool,
+) -> Result<Vec<String>> {
+    let (files, skipped) = walk_directory_files(dir_path, follow_symlinks).await?;
+    for warning in skipped {
+        eprintln!("Warning: {}", warning);
+    }
+    Ok(files)
+}
+
+/// Files below `dir_path`, and a message for every directory that wasn't walked because
+/// it had been already or is a symlink that isn't followed
+async fn walk_directory_files(
+    dir_path: &str,
+    follow_symlinks: bool,
+) -> Result<(Vec<String>, Vec<String>)> {
     let mut files = Vec::new();
+    let mut skipped = Vec::new();
 
     let path = Path::new(dir_path);
     if !path.exists() {
-        return Ok(files);
+        return Ok((files, skipped));
     }
 
     if path.is_file() {
         // If it's a file, just return it
         files.push(dir_path.to_string());
-        return Ok(files);
+        return Ok((files, skipped));
     }
 
+    // Directories are tracked by their canonical path, which symlinks all resolve to
+    let mut visited: HashSet<PathBuf> = fs::canonicalize(path).await.into_iter().collect();
+
     // Recursively walk the directory
     let mut stack = vec![path.to_path_buf()];
 
@@ -442,8 +1408,39 @@ pub async fn enumerate_directory_files(dir_path: &str) -> Result<Vec<String>> {
                     let entry_path = entry.path();
 
                     if entry_path.is_dir() {
-                        // Add directory to stack for recursive processing
-                        stack.push(entry_path);
+                        if !follow_symlinks
+                            && entry.file_type().await.is_ok_and(|kind| kind.is_symlink())
+                        {
+                            skipped.push(format!(
+                                "Not descending into {}: {}",
+                                entry_path.display(),
+                                UNFOLLOWED_LINK_REASON
+                            ));
+                            continue;
+                        }
+                        let Ok(target) = fs::canonicalize(&entry_path).await else {
+                            stack.push(entry_path);
+                            continue;
+                        };
+                        if visited.insert(target.clone()) {
+                            // Add directory to stack for recursive processing
+                            stack.push(entry_path);
+                        } else if fs::canonicalize(&current_dir)
+                            .await
+                            .is_ok_and(|current| current.starts_with(&target))
+                        {
+                            skipped.push(format!(
+                                "Not descending into {}: symlink loop back to {}",
+                                entry_path.display(),
+                                target.display()
+                            ));
+                        } else {
+                            skipped.push(format!(
+                                "Not descending into {}: {} is already listed",
+                                entry_path.display(),
+                                target.display()
+                            ));
+                        }
                     } else if entry_path.is_file() {
                         // Add file to results
                         if let Some(path_str) = entry_path.to_str() {
@@ -463,15 +1460,50 @@ pub async fn enumerate_directory_files(dir_path: &str) -> Result<Vec<String>> {
         }
     }
 
-    Ok(files)
+    Ok((files, skipped))
+}
+
+/// Lists the files an input path expands to, replaceable in tests
+#[async_trait]
+pub trait FileEnumerator: Send + Sync + std::fmt::Debug {
+    async fn enumerate(&self, path: &str) -> Result<Vec<String>>;
+}
+
+/// Enumerator walking the filesystem with [`enumerate_directory_files`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DirectoryEnumerator {
+    follow_symlinks: bool,
+}
+
+impl DirectoryEnumerator {
+    /// Enumerator descending into symlinked directories only if `follow_symlinks` is set
+    pub fn new(follow_symlinks: bool) -> Self {
+        Self { follow_symlinks }
+    }
+}
+
+#[async_trait]
+impl FileEnumerator for DirectoryEnumerator {
+    async fn enumerate(&self, path: &str) -> Result<Vec<String>> {
+        enumerate_directory_files(path, self.follow_symlinks).await
+    }
 }
 
-/// Expand input paths by recursively enumerating directory contents
+/// Expand input paths by recursively enumerating directory contents, skipping symlinked
+/// directories like a backup without `--follow-symlinks`
 pub async fn expand_input_paths(input_paths: &[String]) -> Result<Vec<String>> {
+    expand_input_paths_with(input_paths, &DirectoryEnumerator::default()).await
+}
+
+/// Expand input paths like [`expand_input_paths`], listing each with `enumerator`
+pub async fn expand_input_paths_with(
+    input_paths: &[String],
+    enumerator: &dyn FileEnumerator,
+) -> Result<Vec<String>> {
     let mut expanded_files = Vec::new();
 
     for input_path in input_paths {
-        let files = enumerate_directory_files(input_path).await?;
+        let files = enumerator.enumerate(input_path).await?;
         expanded_files.extend(files);
     }
 
@@ -488,11 +1520,247 @@ pub async fn expand_input_paths(input_paths: &[String]) -> Result<Vec<String>> {
     Ok(unique_files)
 }
 
-/// Result of archive verification
+/// Expand a glob over archive files, newest first by modification time.
+///
+/// Wildcards (`*` and `?`) are supported in the file name only, e.g.
+/// `D:\backups\backup-*.7z`. It is an error if nothing matches.
+pub async fn expand_archive_glob(pattern: &str) -> Result<Vec<String>> {
+    let pattern_path = Path::new(pattern);
+    let file_pattern = pattern_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| ArchtreeError::config(format!("Invalid archive glob: {}", pattern)))?;
+    let directory = match pattern_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let mut regex = String::from("^");
+    for c in file_pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    let matcher = regex::Regex::new(&regex)
+        .map_err(|e| ArchtreeError::config_with_source("Invalid archive glob", e))?;
+
+    let mut matches = Vec::new();
+    let mut entries = fs::read_dir(&directory).await.context_io(format!(
+        "Failed to read archive directory: {}",
+        directory.display()
+    ))?;
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .context_io("Failed to read archive directory entry")?
+    {
+        if !matcher.is_match(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        let metadata = entry
+            .metadata()
+            .await
+            .context_io("Failed to read archive metadata")?;
+        if metadata.is_file() {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            matches.push((modified, entry.path().to_string_lossy().to_string()));
+        }
+    }
+
+    if matches.is_empty() {
+        return Err(ArchtreeError::config(format!(
+            "No archives match: {}",
+            pattern
+        )));
+    }
+
+    // Newest first; ties are broken by name for a stable order
+    matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    Ok(matches.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Check that every file archived in `archive_path` still exists on disk.
+///
+/// This is the inverse of [`ArchiveVerifier::verify_archive`]: archive entries are
+/// mapped back to filesystem paths with [`entry_filesystem_path`] and stat'ed.
+pub async fn verify_reverse<V: ArchiveVerifier + ?Sized>(
+    verifier: &V,
+    archive_path: &str,
+) -> Result<ReverseVerificationResult> {
+    let entries = verifier.list_archive_entries(archive_path).await?;
+
+    let mut gone_from_disk = Vec::new();
+    let mut total_checked = 0;
+    for entry in entries.iter().filter(|entry| !entry.is_directory) {
+        total_checked += 1;
+        if fs::metadata(entry_filesystem_path(&entry.path))
+            .await
+            .is_err()
+        {
+            gone_from_disk.push(entry.path.clone());
+        }
+    }
+
+    Ok(ReverseVerificationResult {
+        gone_from_disk,
+        total_checked,
+    })
+}
+
+/// Filesystem path an archive entry was created from.
+///
+/// Archives are created with full paths, but 7-Zip drops the leading `/` of Unix paths,
+/// so relative entries are resolved against the root there. Entries that are already
+/// absolute (such as `C:\data\file.txt` on Windows) are used as they are.
+pub fn entry_filesystem_path(entry_path: &str) -> PathBuf {
+    let path = PathBuf::from(entry_path);
+    if path.is_absolute() || cfg!(windows) {
+        path
+    } else {
+        Path::new("/").join(path)
+    }
+}
+
+/// Result of checking archive entries against the filesystem
+#[derive(Debug, Clone)]
+pub struct ReverseVerificationResult {
+    /// Archived files that no longer exist on disk, as archive paths
+    pub gone_from_disk: Vec<String>,
+    /// Number of archived files checked
+    pub total_checked: usize,
+}
+
+impl ReverseVerificationResult {
+    /// Check if every archived file still exists on disk
+    pub fn is_complete(&self) -> bool {
+        self.gone_from_disk.is_empty()
+    }
+}
+
+/// Compare the archive file's own modification time against its source files.
+///
+/// This doesn't list the archive contents: it stats the archive once and every expanded
+/// source file, so it's a cheap "is this archive stale overall?" check.
+pub async fn check_archive_age(
+    archive_path: &str,
+    expected_paths: &[String],
+) -> Result<ArchiveAgeResult> {
+    let archive_modified = fs::metadata(archive_path)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .context_io(format!(
+            "Failed to read modification time of {}",
+            archive_path
+        ))?;
+
+    let expanded_files = expand_input_paths(expected_paths).await?;
+
+    let mut newer_sources = Vec::new();
+    let mut newest_source: Option<(String, SystemTime)> = None;
+
+    for file in &expanded_files {
+        let Ok(modified) = fs::metadata(file).await.and_then(|m| m.modified()) else {
+            continue;
+        };
+
+        if modified > archive_modified {
+            newer_sources.push(file.clone());
+        }
+        if newest_source
+            .as_ref()
+            .is_none_or(|(_, newest)| modified > *newest)
+        {
+            newest_source = Some((file.clone(), modified));
+        }
+    }
+
+    Ok(ArchiveAgeResult {
+        archive_modified,
+        newest_source,
+        newer_sources,
+        total_checked: expanded_files.len(),
+    })
+}
+
+/// Result of comparing an archive's modification time with its sources
 #[derive(Debug, Clone)]
+pub struct ArchiveAgeResult {
+    /// Modification time of the archive file itself
+    pub archive_modified: SystemTime,
+    /// Most recently modified source file and its modification time
+    pub newest_source: Option<(String, SystemTime)>,
+    /// Source files modified after the archive was written
+    pub newer_sources: Vec<String>,
+    /// Total number of source files checked
+    pub total_checked: usize,
+}
+
+impl ArchiveAgeResult {
+    /// Check if any source file is newer than the archive
+    pub fn is_stale(&self) -> bool {
+        !self.newer_sources.is_empty()
+    }
+}
+
+/// Compare the number of files in an archive with the number of expanded source files.
+///
+/// Only the counts are compared, no paths, so this is a quick smoke test rather than
+/// a full verification.
+pub async fn verify_file_count<V: ArchiveVerifier + ?Sized>(
+    verifier: &V,
+    archive_path: &str,
+    expected_paths: &[String],
+    tolerance: usize,
+) -> Result<FileCountResult> {
+    let archive_files = verifier
+        .list_archive_entries(archive_path)
+        .await?
+        .iter()
+        .filter(|entry| !entry.is_directory)
+        .count();
+    let source_files = expand_input_paths(expected_paths).await?.len();
+
+    Ok(FileCountResult {
+        archive_files,
+        source_files,
+        tolerance,
+    })
+}
+
+/// Result of comparing file counts between an archive and its sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileCountResult {
+    /// Number of file entries in the archive
+    pub archive_files: usize,
+    /// Number of source files after expanding directories
+    pub source_files: usize,
+    /// Largest difference still considered a match
+    pub tolerance: usize,
+}
+
+impl FileCountResult {
+    /// Archive files minus source files (negative when the archive has fewer files)
+    pub fn delta(&self) -> i64 {
+        self.archive_files as i64 - self.source_files as i64
+    }
+
+    /// Check if the counts differ by no more than the tolerance
+    pub fn is_match(&self) -> bool {
+        self.archive_files.abs_diff(self.source_files) <= self.tolerance
+    }
+}
+
+/// Result of archive verification
+#[derive(Debug, Clone, Serialize)]
 pub struct VerificationResult {
     /// Files that were expected but not found in the archive
     pub missing_files: Vec<String>,
+    /// Files not found in the archive that an allow-missing pattern accepts; they don't
+    /// make verification fail
+    pub expected_missing: Vec<String>,
     /// Files that were found in the archive
     pub archived_files: Vec<String>,
     /// All files that were expected to be archived (for consolidation)
@@ -501,12 +1769,20 @@ pub struct VerificationResult {
     pub total_expected: usize,
     /// Total number of files actually found in the archive
     pub total_archived: usize,
+    /// Error and warning lines reported by the archiver while listing the archive
+    pub listing_warnings: Vec<String>,
+    /// Archived files whose content differs from the file on disk (only when checksums
+    /// are verified)
+    pub corrupted_files: Vec<CorruptedFile>,
+    /// Archived files whose checksum couldn't be compared, because the archive lists none
+    /// for them or they couldn't be read (only when checksums are verified)
+    pub unchecked_files: Vec<String>,
 }
 
 impl VerificationResult {
-    /// Check if verification passed (no missing files)
+    /// Check if verification passed (no missing or corrupted files)
     pub fn is_complete(&self) -> bool {
-        self.missing_files.is_empty()
+        self.missing_files.is_empty() && self.corrupted_files.is_empty()
     }
 
     /// Get the success rate as a percentage
@@ -522,6 +1798,25 @@ impl VerificationResult {
     pub fn get_consolidated_missing_files(&self) -> Vec<String> {
         consolidate_missing_files(&self.missing_files, &self.all_expected_files)
     }
+
+    /// Write the missing files, one per line, as an input file for a follow-up backup
+    pub async fn write_missing_files<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let mut content = String::new();
+        for missing in &self.missing_files {
+            // Escaped, so the input reader doesn't take the path for a comment
+            if missing.starts_with('#') {
+                content.push('\\');
+            }
+            content.push_str(missing);
+            content.push('\n');
+        }
+
+        fs::write(path, content).await.context_io(format!(
+            "Failed to write missing file list: {}",
+            path.display()
+        ))
+    }
 }
 
 /// Represents a directory and its missing files for consolidation
@@ -554,7 +1849,7 @@ pub fn consolidate_missing_files(
             let dir_str = parent.to_string_lossy().to_string();
             dir_expected_files
                 .entry(dir_str)
-                .or_insert_with(HashSet::new)
+                .or_default()
                 .insert(expected_file.clone());
         }
     }
@@ -567,79 +1862,55 @@ pub fn consolidate_missing_files(
             let dir_str = parent.to_string_lossy().to_string();
             dir_missing_files
                 .entry(dir_str)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(missing_file.clone());
         }
     }
 
     // Analyze each directory to see if it's completely missing
-    let mut directory_analysis: HashMap<String, DirectoryMissingFiles> = HashMap::new();
-
-    for (dir, missing_in_dir) in &dir_missing_files {
-        let expected_in_dir = dir_expected_files.get(dir).map(|s| s.len()).unwrap_or(0);
-        let is_complete = missing_in_dir.len() == expected_in_dir && expected_in_dir > 0;
-
-        directory_analysis.insert(
-            dir.clone(),
-            DirectoryMissingFiles {
+    let directory_analysis: BTreeMap<String, DirectoryMissingFiles> = dir_missing_files
+        .into_iter()
+        .map(|(dir, missing_in_dir)| {
+            let expected_in_dir = dir_expected_files.get(&dir).map(|s| s.len()).unwrap_or(0);
+            let is_complete = missing_in_dir.len() == expected_in_dir && expected_in_dir > 0;
+            let info = DirectoryMissingFiles {
                 directory: dir.clone(),
-                missing_files: missing_in_dir.clone(),
+                missing_files: missing_in_dir,
                 total_files: expected_in_dir,
                 is_complete_directory: is_complete,
-            },
-        );
-    }
+            };
+            (dir, info)
+        })
+        .collect();
 
-    // Now build the consolidated output, handling hierarchical directories
-    let mut consolidated = Vec::new();
-    let mut processed_dirs = HashSet::new();
+    // Every completely missing directory is shown as a whole, nested ones included
+    let complete_dirs: HashSet<&str> = directory_analysis
+        .values()
+        .filter(|info| info.is_complete_directory)
+        .map(|info| info.directory.as_str())
+        .collect();
 
-    // Sort directories by depth (deeper first) to handle parent-child relationships
-    let mut sorted_dirs: Vec<_> = directory_analysis.keys().collect();
-    sorted_dirs
-        .sort_by_key(|dir| std::cmp::Reverse(dir.matches(std::path::MAIN_SEPARATOR).count()));
+    let mut consolidated: Vec<String> = directory_analysis
+        .keys()
+        .filter(|dir| complete_dirs.contains(dir.as_str()))
+        .map(|dir| format!("{}{}*", dir, std::path::MAIN_SEPARATOR))
+        .collect();
 
-    for dir in sorted_dirs {
-        if processed_dirs.contains(dir) {
+    // Partially missing directories list their files, unless an ancestor directory
+    // is already shown as completely missing. Looking up ancestors keeps this linear
+    // in the number of directories times their depth.
+    for (dir, dir_info) in &directory_analysis {
+        if dir_info.is_complete_directory {
             continue;
         }
 
-        let dir_info = &directory_analysis[dir];
-
-        if dir_info.is_complete_directory {
-            // Check if any parent directory is already completely missing
-            let mut parent_already_processed = false;
-
-            if let Some(parent) = Path::new(dir).parent() {
-                let parent_str = parent.to_string_lossy().to_string();
-                if processed_dirs.contains(&parent_str) {
-                    // Parent directory is already being shown as completely missing
-                    parent_already_processed = true;
-                }
-            }
-
-            if !parent_already_processed {
-                // Show the entire directory
-                consolidated.push(format!("{}{}*", dir, std::path::MAIN_SEPARATOR));
-                processed_dirs.insert(dir.clone());
-
-                // Mark all subdirectories as processed
-                for other_dir in directory_analysis.keys() {
-                    if other_dir.starts_with(dir) && other_dir != dir {
-                        processed_dirs.insert(other_dir.clone());
-                    }
-                }
-            }
-        }
-    }
+        let covered_by_ancestor = Path::new(dir)
+            .ancestors()
+            .skip(1)
+            .any(|ancestor| complete_dirs.contains(ancestor.to_string_lossy().as_ref()));
 
-    // Add individual files from directories that are not completely missing
-    for (dir, dir_info) in &directory_analysis {
-        if !processed_dirs.contains(dir) && !dir_info.is_complete_directory {
-            // Only show individual files for partially missing directories
-            for missing_file in &dir_info.missing_files {
-                consolidated.push(missing_file.clone());
-            }
+        if !covered_by_ancestor {
+            consolidated.extend(dir_info.missing_files.iter().cloned());
         }
     }
 
@@ -649,7 +1920,7 @@ pub fn consolidate_missing_files(
 }
 
 /// Represents the result of comparing file modification times between filesystem and archive
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FreshnessVerificationResult {
     /// Files that exist in both locations but are newer on the filesystem
     pub outdated_files: Vec<OutdatedFile>,
@@ -657,12 +1928,26 @@ pub struct FreshnessVerificationResult {
     pub up_to_date_files: Vec<String>,
     /// Files that couldn't be compared (missing modification time in archive or filesystem errors)
     pub unverifiable_files: Vec<String>,
+    /// Files whose archived version is newer than the filesystem (only when reverse freshness
+    /// reporting is enabled; otherwise they count as up to date)
+    pub newer_in_archive_files: Vec<OutdatedFile>,
     /// Total number of files checked
     pub total_checked: usize,
 }
 
+/// An archived file whose content differs from the file on disk
+#[derive(Debug, Clone, Serialize)]
+pub struct CorruptedFile {
+    /// Path of the file
+    pub path: String,
+    /// CRC32 stored in the archive
+    pub archive_crc: u32,
+    /// CRC32 of the file on disk
+    pub filesystem_crc: u32,
+}
+
 /// Represents a file that is outdated in the archive
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct OutdatedFile {
     /// Path of the file
     pub path: String,
@@ -675,6 +1960,38 @@ pub struct OutdatedFile {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{MockVerifier, SpyEnumerator, file_entry};
+
+    /// Seconds since the Unix epoch, rounded down
+    fn whole_seconds(time: SystemTime) -> i64 {
+        match time.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(error) => {
+                let before = error.duration();
+                -(before.as_secs() as i64) - i64::from(before.subsec_nanos() > 0)
+            }
+        }
+    }
+
+    /// Comparison keys of archived paths, as the streamed listing collects them
+    fn keys_of(archived: &[String], strip_components: usize) -> HashSet<String> {
+        archived
+            .iter()
+            .filter_map(|path| comparison_key(path, strip_components))
+            .collect()
+    }
+
+    /// Parse a whole listing at once
+    fn parse_listing(output: &str, archive_path: &str) -> ArchiveListing {
+        let mut parser = ListingParser::new(archive_path);
+        let mut entries: Vec<ArchiveEntry> = output
+            .lines()
+            .filter_map(|line| parser.feed(line))
+            .collect();
+        let (last_entry, warnings) = parser.finish();
+        entries.extend(last_entry);
+        ArchiveListing { entries, warnings }
+    }
 
     #[test]
     fn test_freshness_verification_result() {
@@ -688,6 +2005,7 @@ mod tests {
             }],
             up_to_date_files: vec!["current.txt".to_string()],
             unverifiable_files: vec!["unknown.txt".to_string()],
+            newer_in_archive_files: vec![],
             total_checked: 3,
         };
 
@@ -698,18 +2016,1341 @@ mod tests {
     }
 
     #[test]
-    fn test_outdated_file_structure() {
-        let outdated = OutdatedFile {
-            path: "test.txt".to_string(),
-            archive_modified: Some(SystemTime::UNIX_EPOCH),
-            filesystem_modified: Some(
-                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(3600),
-            ),
-        };
+    fn test_compare_modification_times() {
+        let base = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        let secs = std::time::Duration::from_secs;
 
-        assert_eq!(outdated.path, "test.txt");
-        assert!(outdated.archive_modified.is_some());
-        assert!(outdated.filesystem_modified.is_some());
+        assert_eq!(
+            compare_modification_times(base, base + secs(1)),
+            FreshnessStatus::UpToDate
+        );
+        assert_eq!(
+            compare_modification_times(base, base + secs(60)),
+            FreshnessStatus::Outdated
+        );
+        // The archive entry is an hour ahead of the file on disk
+        assert_eq!(
+            compare_modification_times(base + secs(3600), base),
+            FreshnessStatus::NewerInArchive
+        );
+        assert_eq!(
+            compare_modification_times(base + secs(1), base),
+            FreshnessStatus::UpToDate
+        );
+    }
+
+    #[test]
+    fn test_sub_second_difference_ignores_tolerance() {
+        let archived = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        let on_disk = archived + std::time::Duration::from_millis(500);
+
+        // A seconds-only archive timestamp against a precise filesystem one
+        assert_eq!(
+            compare_modification_times_with_tolerance(archived, on_disk, 0),
+            FreshnessStatus::UpToDate
+        );
+        assert_eq!(
+            compare_modification_times_with_tolerance(on_disk, archived, 0),
+            FreshnessStatus::UpToDate
+        );
+        assert_eq!(
+            compare_modification_times_with_tolerance(
+                archived,
+                on_disk + std::time::Duration::from_secs(1),
+                0
+            ),
+            FreshnessStatus::Outdated
+        );
+        assert_eq!(
+            whole_seconds(SystemTime::UNIX_EPOCH - std::time::Duration::from_millis(500)),
+            -1
+        );
+
+        // 2.002 s apart across three second boundaries is still within a 2-second tolerance
+        let archived = SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(10_999);
+        let on_disk = SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(13_001);
+        assert_eq!(
+            compare_modification_times_with_tolerance(archived, on_disk, 2),
+            FreshnessStatus::UpToDate
+        );
+        assert_eq!(
+            compare_modification_times_with_tolerance(on_disk, archived, 2),
+            FreshnessStatus::UpToDate
+        );
+        assert_eq!(
+            compare_modification_times_with_tolerance(archived, on_disk, 1),
+            FreshnessStatus::Outdated
+        );
+        assert_eq!(
+            compare_modification_times_with_tolerance(archived, on_disk, u64::MAX),
+            FreshnessStatus::UpToDate
+        );
+    }
+
+    #[test]
+    fn test_outdated_file_structure() {
+        let outdated = OutdatedFile {
+            path: "test.txt".to_string(),
+            archive_modified: Some(SystemTime::UNIX_EPOCH),
+            filesystem_modified: Some(
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(3600),
+            ),
+        };
+
+        assert_eq!(outdated.path, "test.txt");
+        assert!(outdated.archive_modified.is_some());
+        assert!(outdated.filesystem_modified.is_some());
+    }
+
+    #[test]
+    fn test_compare_file_lists_with_strip_components() {
+        let expected = vec![
+            "/mnt/a/data/f.txt".to_string(),
+            "/mnt/a/data/g.txt".to_string(),
+        ];
+        let archived = vec!["backup/b/data/f.txt".to_string()];
+
+        // Without stripping, the differing prefixes make every file look missing
+        let (missing, found) = compare_file_lists(
+            &expected,
+            &keys_of(&archived, 0),
+            0,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!(missing.len(), 2);
+        assert!(found.is_empty());
+
+        // After dropping two leading components both sides agree on "data/f.txt"
+        let (missing, found) = compare_file_lists(
+            &expected,
+            &keys_of(&archived, 2),
+            2,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!(found, vec!["/mnt/a/data/f.txt".to_string()]);
+        assert_eq!(missing, vec!["/mnt/a/data/g.txt".to_string()]);
+    }
+
+    /// Archive file for a checkpoint to identify, with `entries` already recorded for it
+    fn checkpoint_with(
+        temp_dir: &tempfile::TempDir,
+        entries: &[(&str, bool)],
+    ) -> (PathBuf, String) {
+        let checkpoint_path = temp_dir.path().join("verify.checkpoint");
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, b"7z").unwrap();
+        let archive = archive.to_string_lossy().to_string();
+
+        let mut checkpoint = VerificationCheckpoint::load(&checkpoint_path, &archive).unwrap();
+        for (file, found) in entries {
+            checkpoint.record(file, *found).unwrap();
+        }
+        checkpoint.flush().unwrap();
+        (checkpoint_path, archive)
+    }
+
+    /// Entries appended to a checkpoint file after its header line
+    fn checkpoint_entries(checkpoint_path: &Path) -> Vec<String> {
+        std::fs::read_to_string(checkpoint_path)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_cancelled_comparison_keeps_checkpoint() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (checkpoint_path, archive) = checkpoint_with(&temp_dir, &[("/data/a.txt", true)]);
+
+        let expected = vec!["/data/a.txt".to_string(), "/data/b.txt".to_string()];
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let error =
+            compare_file_lists(&expected, &keys_of(&expected, 0), 0, &cancellation).unwrap_err();
+        assert_eq!(error.category(), "cancelled");
+
+        let mut checkpoint = VerificationCheckpoint::load(&checkpoint_path, &archive).unwrap();
+        let error = compare_file_lists_with_checkpoint(
+            &expected,
+            &keys_of(&expected, 0),
+            0,
+            &mut checkpoint,
+            &cancellation,
+        )
+        .unwrap_err();
+        assert_eq!(error.category(), "cancelled");
+
+        // The recorded progress is still there for the next run to resume from
+        let checkpoint = VerificationCheckpoint::load(&checkpoint_path, &archive).unwrap();
+        assert_eq!(checkpoint.result_for("/data/a.txt"), Some(true));
+    }
+
+    #[test]
+    fn test_compare_file_lists_resumes_from_checkpoint() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        // A previous run already checked the first half of the set
+        let (checkpoint_path, archive) =
+            checkpoint_with(&temp_dir, &[("/data/a.txt", true), ("/data/b.txt", false)]);
+
+        let expected = vec![
+            "/data/a.txt".to_string(),
+            "/data/b.txt".to_string(),
+            "/data/c.txt".to_string(),
+            "/data/d.txt".to_string(),
+        ];
+        // The listing has b although the checkpoint recorded it missing: the listing wins
+        let archived = vec![
+            "/data/a.txt".to_string(),
+            "/data/b.txt".to_string(),
+            "/data/c.txt".to_string(),
+        ];
+
+        let mut checkpoint = VerificationCheckpoint::load(&checkpoint_path, &archive).unwrap();
+        let (missing, found) = compare_file_lists_with_checkpoint(
+            &expected,
+            &keys_of(&archived, 0),
+            0,
+            &mut checkpoint,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+
+        assert_eq!(found, vec!["/data/a.txt", "/data/b.txt", "/data/c.txt"]);
+        assert_eq!(missing, vec!["/data/d.txt"]);
+
+        // The confirmed result of a isn't recorded again; b is corrected, and the
+        // remaining half is appended
+        assert_eq!(
+            checkpoint_entries(&checkpoint_path),
+            vec![
+                "+\t/data/a.txt",
+                "-\t/data/b.txt",
+                "+\t/data/b.txt",
+                "+\t/data/c.txt",
+                "-\t/data/d.txt"
+            ]
+        );
+        let reloaded = VerificationCheckpoint::load(&checkpoint_path, &archive).unwrap();
+        assert_eq!(reloaded.result_for("/data/b.txt"), Some(true));
+    }
+
+    #[test]
+    fn test_parse_listing_captures_error_lines() {
+        let output = "\
+Listing archive: backup.7z
+
+----------
+Path = data/a.txt
+Size = 5
+Attributes = A
+
+ERROR: data/b.txt : Data Error
+Path = data/c.txt
+Size = 7
+Attributes = A
+
+WARNING: There are some data after the end of the payload data
+";
+        let listing = parse_listing(output, "backup.7z");
+
+        assert_eq!(listing.entries.len(), 2);
+        assert_eq!(listing.entries[1].path, "data/c.txt");
+        assert_eq!(
+            listing.warnings,
+            vec![
+                "ERROR: data/b.txt : Data Error".to_string(),
+                "WARNING: There are some data after the end of the payload data".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_listing_of_split_archive() {
+        let output = "\
+Listing archive: /backups/week.7z.001
+
+--
+Path = /backups/week.7z.001
+Type = Split
+Volumes = 2
+----
+Path = /backups/week.7z
+Size = 2048
+--
+Path = /backups/week.7z
+Type = 7z
+
+----------
+Path = data/a.txt
+Size = 5
+Attributes = A
+";
+        let listing = parse_listing(output, "/backups/week.7z.001");
+
+        let paths: Vec<_> = listing.entries.iter().map(|entry| &entry.path).collect();
+        assert_eq!(paths, ["data/a.txt"]);
+    }
+
+    #[test]
+    fn test_parse_listing_packed_size() {
+        let output = "\
+Path = data/a.txt
+Size = 1000
+Packed Size = 250
+Attributes = A
+
+Path = data/b.txt
+Size = 400
+Packed Size = 0
+Attributes = A
+
+Path = data/c.txt
+Size = 10
+Attributes = A
+";
+        let listing = parse_listing(output, "backup.7z");
+
+        let leading = &listing.entries[0];
+        assert_eq!(leading.packed_size, Some(250));
+        assert_eq!(leading.compression_ratio(), Some(0.25));
+
+        // Inside a solid block the packed size is attributed to the leading file
+        assert_eq!(listing.entries[1].packed_size, Some(0));
+        assert_eq!(listing.entries[1].compression_ratio(), None);
+
+        assert_eq!(listing.entries[2].packed_size, None);
+        assert_eq!(listing.entries[2].compression_ratio(), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_integrity_test_reads_seven_zip_verdict() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, "7z").unwrap();
+        let archive = archive.to_string_lossy().to_string();
+        // Stand-ins for 7-Zip answering a test of an intact and of a damaged archive
+        let fake_7z = |name: &str, script: &str| {
+            let runner = temp_dir.path().join(name);
+            std::fs::write(&runner, script).unwrap();
+            std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+            SevenZipVerifier::with_path(runner.to_string_lossy().to_string())
+        };
+
+        let intact = fake_7z(
+            "intact7z",
+            "#!/bin/sh\necho \"Testing     data/a.txt\"\necho\necho \"Everything is Ok\"\n",
+        );
+        assert!(intact.test_integrity(&archive).await.unwrap());
+
+        // 7-Zip's --help succeeds, but testing reports a CRC error and exits with 2
+        let damaged = fake_7z(
+            "damaged7z",
+            "#!/bin/sh\n[ \"$1\" = \"--help\" ] && exit 0\necho \"ERROR: CRC Failed : data/a.txt\"\necho \"Sub items Errors: 1\"\nexit 2\n",
+        );
+        assert!(!damaged.test_integrity(&archive).await.unwrap());
+
+        assert!(!integrity_test_passed(
+            true,
+            "Scanning the drive for archives:\n"
+        ));
+        let missing = temp_dir.path().join("missing.7z");
+        assert!(
+            intact
+                .test_integrity(&missing.to_string_lossy())
+                .await
+                .is_err()
+        );
+    }
+
+    /// Zone at UTC+1 that moves to UTC+2 in summer 2021: local times from 02:00 to 03:00
+    /// don't exist on 2021-03-28 and happen twice on 2021-10-31
+    #[derive(Debug, Clone)]
+    struct DstZone;
+
+    impl DstZone {
+        fn transition(month: u32, day: u32) -> NaiveDateTime {
+            chrono::NaiveDate::from_ymd_opt(2021, month, day)
+                .unwrap()
+                .and_hms_opt(2, 0, 0)
+                .unwrap()
+        }
+
+        fn hours(hours: i32) -> chrono::FixedOffset {
+            chrono::FixedOffset::east_opt(hours * 3600).unwrap()
+        }
+    }
+
+    impl TimeZone for DstZone {
+        type Offset = chrono::FixedOffset;
+
+        fn from_offset(_offset: &chrono::FixedOffset) -> Self {
+            DstZone
+        }
+
+        fn offset_from_local_date(
+            &self,
+            local: &chrono::NaiveDate,
+        ) -> chrono::LocalResult<chrono::FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(
+            &self,
+            local: &NaiveDateTime,
+        ) -> chrono::LocalResult<chrono::FixedOffset> {
+            let (spring, fall) = (Self::transition(3, 28), Self::transition(10, 31));
+            let hour = chrono::Duration::hours(1);
+            if *local < spring || *local >= fall + hour {
+                chrono::LocalResult::Single(Self::hours(1))
+            } else if *local < spring + hour {
+                chrono::LocalResult::None
+            } else if *local < fall {
+                chrono::LocalResult::Single(Self::hours(2))
+            } else {
+                chrono::LocalResult::Ambiguous(Self::hours(2), Self::hours(1))
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &chrono::NaiveDate) -> chrono::FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> chrono::FixedOffset {
+            // Both transitions happen at 01:00 UTC
+            let hour = chrono::Duration::hours(1);
+            let summer = Self::transition(3, 28) - hour..Self::transition(10, 31) - hour;
+            Self::hours(if summer.contains(utc) { 2 } else { 1 })
+        }
+    }
+
+    /// Seconds since the Unix epoch of a UTC time
+    fn utc_seconds(value: &str) -> i64 {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    #[test]
+    fn test_parse_listed_time_zones() {
+        let seconds = |time: Option<SystemTime>| whole_seconds(time.unwrap());
+
+        // Times naming their zone are read in it, whatever the listing's zone
+        let noon = utc_seconds("2021-06-01 12:00:00");
+        assert_eq!(
+            seconds(parse_listed_time("2021-06-01 12:00:00Z", &DstZone)),
+            noon
+        );
+        assert_eq!(
+            seconds(parse_listed_time("2021-06-01 12:00:00 UTC", &DstZone)),
+            noon
+        );
+        assert_eq!(
+            seconds(parse_listed_time("2021-06-01 14:00:00 +02:00", &Utc)),
+            noon
+        );
+        assert_eq!(
+            seconds(parse_listed_time("2021-06-01 12:00:00.1234567", &Utc)),
+            noon
+        );
+
+        // Others are read in the listing's zone: summer and winter time, or UTC
+        assert_eq!(
+            seconds(parse_listed_time("2021-06-01 14:00:00", &DstZone)),
+            noon
+        );
+        assert_eq!(
+            seconds(parse_listed_time("2021-12-01 13:00:00", &DstZone)),
+            utc_seconds("2021-12-01 12:00:00")
+        );
+        assert_eq!(
+            seconds(parse_listed_time("2021-06-01 12:00:00", &Utc)),
+            noon
+        );
+        assert_eq!(parse_listed_time("yesterday", &Utc), None);
+    }
+
+    #[test]
+    fn test_parse_listed_time_around_dst_transitions() {
+        // A local time skipped when the clocks go forward is shifted an hour later
+        assert_eq!(
+            whole_seconds(parse_listed_time("2021-03-28 02:30:00", &DstZone).unwrap()),
+            utc_seconds("2021-03-28 01:30:00")
+        );
+        assert_eq!(
+            whole_seconds(parse_listed_time("2021-03-28 02:00:00", &DstZone).unwrap()),
+            utc_seconds("2021-03-28 01:00:00")
+        );
+
+        // A local time repeated when they go back is its earliest occurrence, in summer time
+        assert_eq!(
+            whole_seconds(parse_listed_time("2021-10-31 02:30:00", &DstZone).unwrap()),
+            utc_seconds("2021-10-31 00:30:00")
+        );
+        assert_eq!(
+            whole_seconds(parse_listed_time("2021-10-31 02:59:59", &DstZone).unwrap()),
+            utc_seconds("2021-10-31 00:59:59")
+        );
+
+        // Read as UTC they are ordinary times
+        assert_eq!(
+            whole_seconds(parse_listed_time("2021-03-28 02:30:00", &Utc).unwrap()),
+            utc_seconds("2021-03-28 02:30:00")
+        );
+
+        // Just outside the transitions local times are unambiguous
+        assert_eq!(
+            whole_seconds(parse_listed_time("2021-03-28 01:59:59", &DstZone).unwrap()),
+            utc_seconds("2021-03-28 00:59:59")
+        );
+        assert_eq!(
+            whole_seconds(parse_listed_time("2021-03-28 03:00:00", &DstZone).unwrap()),
+            utc_seconds("2021-03-28 01:00:00")
+        );
+        assert_eq!(
+            whole_seconds(parse_listed_time("2021-10-31 03:00:00", &DstZone).unwrap()),
+            utc_seconds("2021-10-31 02:00:00")
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_freshness_tolerance_is_configurable() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, "7z").unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        std::fs::write(&file, "notes").unwrap();
+        let on_disk = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(on_disk)
+            .unwrap();
+
+        // Stand-in for 7-Zip listing the file as archived one second before its disk time
+        let archived = chrono::DateTime::<chrono::Local>::from(on_disk - Duration::from_secs(1));
+        let runner = temp_dir.path().join("fake7z");
+        std::fs::write(
+            &runner,
+            format!(
+                "#!/bin/sh\necho \"Path = {}\"\necho \"Size = 5\"\necho \"Modified = {}\"\necho \"Attributes = A\"\necho\n",
+                file.display(),
+                archived.format("%Y-%m-%d %H:%M:%S")
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let archive = archive.to_string_lossy().to_string();
+        let expected = vec![file.to_string_lossy().to_string()];
+        let check = |tolerance: u64| {
+            let verifier = SevenZipVerifier::with_path(runner.to_string_lossy().to_string())
+                .with_freshness_tolerance(tolerance);
+            let (archive, expected) = (archive.clone(), expected.clone());
+            async move {
+                verifier
+                    .verify_archive_freshness(&archive, &expected)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let strict = check(0).await;
+        assert_eq!(strict.outdated_files.len(), 1);
+        assert_eq!(strict.outdated_files[0].path, expected[0]);
+        let lenient = check(5).await;
+        assert!(lenient.outdated_files.is_empty());
+        assert_eq!(lenient.up_to_date_files, expected);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_archive_newer_than_disk_is_reported_only_when_asked() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, "7z").unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        std::fs::write(&file, "notes").unwrap();
+        let on_disk = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(on_disk)
+            .unwrap();
+
+        // Stand-in for 7-Zip listing the file as archived an hour after its disk time
+        let archived = chrono::DateTime::<chrono::Local>::from(on_disk + Duration::from_secs(3600));
+        let runner = temp_dir.path().join("fake7z");
+        std::fs::write(
+            &runner,
+            format!(
+                "#!/bin/sh\necho \"Path = {}\"\necho \"Size = 5\"\necho \"Modified = {}\"\necho \"Attributes = A\"\necho\n",
+                file.display(),
+                archived.format("%Y-%m-%d %H:%M:%S")
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let archive = archive.to_string_lossy().to_string();
+        let expected = vec![file.to_string_lossy().to_string()];
+        let check = |report: bool| {
+            let verifier = SevenZipVerifier::with_path(runner.to_string_lossy().to_string())
+                .with_reverse_freshness(report);
+            let (archive, expected) = (archive.clone(), expected.clone());
+            async move {
+                verifier
+                    .verify_archive_freshness(&archive, &expected)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let reported = check(true).await;
+        assert!(reported.outdated_files.is_empty());
+        assert!(reported.up_to_date_files.is_empty());
+        assert_eq!(reported.newer_in_archive_files.len(), 1);
+        assert_eq!(reported.newer_in_archive_files[0].path, expected[0]);
+        assert_eq!(
+            reported.newer_in_archive_files[0].archive_modified,
+            Some(on_disk + Duration::from_secs(3600))
+        );
+
+        // Without the flag the file isn't outdated either
+        let silent = check(false).await;
+        assert!(silent.outdated_files.is_empty());
+        assert!(silent.newer_in_archive_files.is_empty());
+        assert_eq!(silent.up_to_date_files, expected);
+    }
+
+    #[test]
+    fn test_compare_checksums_reports_corrupted_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let intact = temp_dir.path().join("intact.txt");
+        let changed = temp_dir.path().join("changed.txt");
+        let unlisted = temp_dir.path().join("unlisted.txt");
+        std::fs::write(&intact, "hello").unwrap();
+        std::fs::write(&changed, "hellO").unwrap();
+        std::fs::write(&unlisted, "hello").unwrap();
+        let output = format!(
+            "Path = {}\nSize = 5\nCRC = 3610A686\nAttributes = A\n\nPath = {}\nSize = 5\nCRC = 3610A686\nAttributes = A\n\nPath = {}\nSize = 5\nCRC = \nAttributes = A\n",
+            intact.display(),
+            changed.display(),
+            unlisted.display()
+        );
+        let listing = parse_listing(&output, "backup.7z");
+        assert_eq!(listing.entries[0].crc, Some(0x3610A686));
+        assert_eq!(listing.entries[2].crc, None);
+
+        let archived_crcs: HashMap<String, u32> = listing
+            .entries
+            .iter()
+            .filter_map(|entry| Some((comparison_key(&entry.path, 0)?, entry.crc?)))
+            .collect();
+        let found_files: Vec<String> = [&intact, &changed, &unlisted]
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        let (corrupted, unchecked) = compare_checksums(
+            &found_files,
+            &archived_crcs,
+            0,
+            false,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+
+        assert_eq!(corrupted.len(), 1);
+        assert_eq!(corrupted[0].path, found_files[1]);
+        assert_eq!(corrupted[0].archive_crc, 0x3610A686);
+        assert_ne!(corrupted[0].filesystem_crc, 0x3610A686);
+        assert_eq!(unchecked, [found_files[2].clone()]);
+    }
+
+    #[test]
+    fn test_strict_hash_fails_on_file_unreadable_while_checksumming() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vanished = temp_dir.path().join("vanished.txt");
+        let file = vanished.to_string_lossy().to_string();
+        // Listed in the archive, but gone from disk by the time it is hashed
+        let archived_crcs = HashMap::from([(comparison_key(&file, 0).unwrap(), 0x3610A686)]);
+        let found_files = vec![file.clone()];
+
+        let (corrupted, unchecked) = compare_checksums(
+            &found_files,
+            &archived_crcs,
+            0,
+            false,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert!(corrupted.is_empty());
+        assert_eq!(unchecked, vec![file.clone()]);
+
+        let error = compare_checksums(
+            &found_files,
+            &archived_crcs,
+            0,
+            true,
+            &CancellationToken::new(),
+        )
+        .unwrap_err();
+        assert_eq!(error.category(), "verification");
+        assert!(error.to_string().contains(&file), "{}", error);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancelled_verification_stops_expansion_and_listing() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, "7z").unwrap();
+        let archive = archive.to_string_lossy().to_string();
+        let data = temp_dir.path().join("data");
+        std::fs::create_dir(&data).unwrap();
+        std::fs::write(data.join("a.txt"), "a").unwrap();
+        let expected = vec![data.to_string_lossy().to_string()];
+
+        // Stand-in for 7-Zip that marks when it starts listing, then lists very slowly
+        let listed = temp_dir.path().join("listed");
+        let runner = temp_dir.path().join("slow7z");
+        std::fs::write(
+            &runner,
+            format!(
+                "#!/bin/sh\n[ \"$1\" = \"--help\" ] && exit 0\ntouch {}\necho \"Path = {}\"\nsleep 10\n",
+                listed.display(),
+                data.join("a.txt").display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let verifier = SevenZipVerifier::with_path(runner.to_string_lossy().to_string());
+
+        // Cancelled before expanding: 7-Zip is never asked for the listing
+        let cancelled = CancellationToken::new();
+        cancelled.cancel();
+        let error = verifier
+            .verify_archive_cancellable(&archive, &expected, &cancelled)
+            .await
+            .unwrap_err();
+        assert_eq!(error.category(), "cancelled");
+        assert!(!listed.exists());
+
+        // Cancelled while 7-Zip is listing the archive
+        let cancellation = CancellationToken::new();
+        let trigger = cancellation.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            trigger.cancel();
+        });
+        let started = std::time::Instant::now();
+        let error = verifier
+            .verify_expanded(&archive, expected.clone(), &cancellation)
+            .await
+            .unwrap_err();
+        assert_eq!(error.category(), "cancelled");
+        assert!(listed.exists());
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_write_missing_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output = temp_dir.path().join("missing.txt");
+        let result = VerificationResult {
+            missing_files: vec!["/data/b.txt".to_string(), "/data/sub/c.txt".to_string()],
+            expected_missing: Vec::new(),
+            archived_files: vec!["/data/a.txt".to_string()],
+            all_expected_files: vec![
+                "/data/a.txt".to_string(),
+                "/data/b.txt".to_string(),
+                "/data/sub/c.txt".to_string(),
+            ],
+            total_expected: 3,
+            total_archived: 1,
+            listing_warnings: Vec::new(),
+            corrupted_files: Vec::new(),
+            unchecked_files: Vec::new(),
+        };
+
+        result.write_missing_files(&output).await.unwrap();
+
+        let written = std::fs::read_to_string(&output).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines, result.missing_files);
+        assert_eq!(written, "/data/b.txt\n/data/sub/c.txt\n");
+    }
+
+    #[test]
+    fn test_list_command_forces_english_locale() {
+        let verifier = SevenZipVerifier::with_path("7z".to_string());
+
+        for force_utf8 in [true, false] {
+            let cmd = verifier.list_command("backup.7z", force_utf8);
+            let cmd = cmd.as_std();
+
+            let envs: HashMap<_, _> = cmd.get_envs().collect();
+            assert_eq!(
+                envs.get(std::ffi::OsStr::new("LANG")),
+                Some(&Some(std::ffi::OsStr::new("en_US.UTF-8")))
+            );
+            assert_eq!(
+                envs.get(std::ffi::OsStr::new("LC_ALL")),
+                Some(&Some(std::ffi::OsStr::new("en_US.UTF-8")))
+            );
+
+            let args: Vec<_> = cmd.get_args().collect();
+            assert_eq!(args.first().unwrap(), &"l");
+            assert_eq!(args.last().unwrap(), &"backup.7z");
+            assert_eq!(
+                args.contains(&std::ffi::OsStr::new("-sccUTF-8")),
+                force_utf8
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_list_args_and_locale() {
+        let verifier = SevenZipVerifier::with_path("7z".to_string())
+            .with_list_args(vec!["-scsWIN".to_string(), "-sccWIN".to_string()])
+            .with_list_locale("C.UTF-8");
+
+        for force_utf8 in [true, false] {
+            let cmd = verifier.list_command("backup.7z", force_utf8);
+            let args: Vec<_> = cmd
+                .as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect();
+            assert_eq!(args, ["l", "-slt", "-scsWIN", "-sccWIN", "backup.7z"]);
+
+            let envs: HashMap<_, _> = cmd.as_std().get_envs().collect();
+            assert_eq!(
+                envs.get(std::ffi::OsStr::new("LC_ALL")),
+                Some(&Some(std::ffi::OsStr::new("C.UTF-8")))
+            );
+        }
+
+        let config = Config::builder()
+            .output_path(Some("backup.7z"), false)
+            .list_args(Some("-sccUTF-16"))
+            .build()
+            .unwrap();
+        let cmd = SevenZipVerifier::from_config(&config).list_command("backup.7z", true);
+        let args: Vec<_> = cmd.as_std().get_args().collect();
+        assert!(args.contains(&std::ffi::OsStr::new("-sccUTF-16")));
+        assert!(!args.contains(&std::ffi::OsStr::new("-sccUTF-8")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pre_expanded_files_are_not_expanded_again() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let data = temp_dir.path().join("data");
+        std::fs::create_dir(&data).unwrap();
+        let file = data.join("a.txt").to_string_lossy().to_string();
+        std::fs::write(&file, "a").unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, "").unwrap();
+
+        // Stand-in for 7-Zip listing a.txt
+        let runner = temp_dir.path().join("fake7z");
+        std::fs::write(
+            &runner,
+            format!(
+                "#!/bin/sh\necho ----------\necho 'Path = {}'\necho 'Size = 1'\necho 'Attributes = A'\n",
+                file
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let spy = Arc::new(SpyEnumerator::default());
+        let verifier = SevenZipVerifier::with_path(runner.to_string_lossy().to_string())
+            .with_enumerator(spy.clone());
+        let archive = archive.to_string_lossy().to_string();
+        let expected = vec![data.to_string_lossy().to_string()];
+
+        // The directory is walked, finding the archived a.txt
+        let result = verifier.verify_archive(&archive, &expected).await.unwrap();
+        assert_eq!(result.all_expected_files, vec![file.clone()]);
+        assert!(result.is_complete());
+        assert_eq!(spy.calls(), 1);
+
+        // Taken as already expanded, the directory is compared as it is
+        for result in [
+            verifier
+                .verify_archive_with_expanded(&archive, &expected)
+                .await
+                .unwrap(),
+            ExpandedVerifier::new(verifier.clone())
+                .verify_archive(&archive, &expected)
+                .await
+                .unwrap(),
+        ] {
+            assert_eq!(result.all_expected_files, expected);
+            assert_eq!(result.missing_files, expected);
+        }
+
+        let result = verifier
+            .verify_archive_with_expanded(&archive, std::slice::from_ref(&file))
+            .await
+            .unwrap();
+        assert!(result.is_complete());
+
+        // Nothing but the first verification asked for a walk
+        assert_eq!(spy.calls(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_allowed_missing_files_dont_fail_verification() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, "").unwrap();
+        let runner = temp_dir.path().join("fake7z");
+        std::fs::write(
+            &runner,
+            "#!/bin/sh\necho ----------\nprintf 'Path = data/a.txt\\nSize = 1\\n\\n'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let archive = archive.to_string_lossy().to_string();
+        let verifier = SevenZipVerifier::with_path(runner.to_string_lossy().to_string())
+            .with_allowed_missing(WildcardMatcher::with_patterns(&["*.lock".to_string()]).unwrap());
+
+        let expected = vec!["data/a.txt".to_string(), "data/app.lock".to_string()];
+        let result = verifier
+            .verify_archive_with_expanded(&archive, &expected)
+            .await
+            .unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.expected_missing, vec!["data/app.lock"]);
+
+        // A missing file that isn't allowed still fails
+        let expected = vec![
+            "data/a.txt".to_string(),
+            "data/app.lock".to_string(),
+            "data/b.txt".to_string(),
+        ];
+        let result = verifier
+            .verify_archive_with_expanded(&archive, &expected)
+            .await
+            .unwrap();
+        assert!(!result.is_complete());
+        assert_eq!(result.missing_files, vec!["data/b.txt"]);
+        assert_eq!(result.expected_missing, vec!["data/app.lock"]);
+    }
+
+    #[test]
+    fn test_ignored_entries_match_windows_paths() {
+        let verifier = SevenZipVerifier::new()
+            .with_ignored_entries(vec![Regex::new(r"(^|/)Thumbs\.db$").unwrap()]);
+        assert!(verifier.is_ignored("photos/Thumbs.db"));
+        assert!(verifier.is_ignored("C:\\Users\\me\\Pictures\\Thumbs.db"));
+        assert!(!verifier.is_ignored("C:\\Users\\me\\Pictures\\MyThumbs.db"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_ignored_entries_are_left_out_of_reverse_verification() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, "").unwrap();
+        let photo = temp_dir.path().join("photo.jpg");
+        std::fs::write(&photo, "jpg").unwrap();
+        let thumbs = temp_dir.path().join("Thumbs.db");
+
+        // The archive holds a Thumbs.db that was never on this disk
+        let runner = temp_dir.path().join("fake7z");
+        std::fs::write(
+            &runner,
+            format!(
+                "#!/bin/sh\necho ----------\nprintf 'Path = {}\\nSize = 3\\n\\n'\n\
+                 printf 'Path = {}\\nSize = 1\\n\\n'\n",
+                photo.display(),
+                thumbs.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let archive = archive.to_string_lossy().to_string();
+        let verifier = SevenZipVerifier::with_path(runner.to_string_lossy().to_string());
+
+        let result = verify_reverse(&verifier, &archive).await.unwrap();
+        assert_eq!(
+            result.gone_from_disk,
+            vec![thumbs.to_string_lossy().to_string()]
+        );
+
+        let verifier =
+            verifier.with_ignored_entries(vec![Regex::new(r"(^|/)Thumbs\.db$").unwrap()]);
+        let result = verify_reverse(&verifier, &archive).await.unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.total_checked, 1);
+
+        // An ignored file isn't expected either
+        let expected = vec![
+            photo.to_string_lossy().to_string(),
+            thumbs.to_string_lossy().to_string(),
+        ];
+        let result = verifier
+            .verify_archive_with_expanded(&archive, &expected)
+            .await
+            .unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.total_expected, 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_streamed_listing_matches_buffered_listing() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        std::fs::write(&archive, "").unwrap();
+
+        // Stand-in for 7-Zip listing a directory and 5000 files
+        let runner = temp_dir.path().join("fake7z");
+        std::fs::write(
+            &runner,
+            "#!/bin/sh\necho ----------\nprintf 'Path = data\\nAttributes = D\\n\\n'\n\
+             i=0\nwhile [ $i -lt 5000 ]; do\n\
+             printf 'Path = data/f%d.txt\\nSize = 1\\nAttributes = A\\n\\n' $i\n\
+             i=$((i+1))\ndone\necho 'WARNING: headers error' >&2\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let verifier = SevenZipVerifier::with_path(runner.to_string_lossy().to_string());
+        let archive = archive.to_string_lossy().to_string();
+        let expected: Vec<String> = ["data", "data/f0.txt", "data/f4999.txt", "data/gone.txt"]
+            .iter()
+            .map(|path| path.to_string())
+            .collect();
+
+        // Buffered: every entry of the listing is held before comparing
+        let listing = verifier.list_archive(&archive).await.unwrap();
+        assert_eq!(listing.entries.len(), 5001);
+        let files: Vec<String> = listing
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_directory)
+            .map(|entry| entry.path.clone())
+            .collect();
+        let (buffered_missing, buffered_found) =
+            compare_file_lists(&expected, &keys_of(&files, 0), 0, &CancellationToken::new())
+                .unwrap();
+
+        // Streamed: only the expected files found in the listing are kept
+        let (archived_keys, _, warnings) = verifier
+            .list_expected_files(&archive, &keys_of(&expected, 0))
+            .await
+            .unwrap();
+        assert_eq!(archived_keys, keys_of(&expected[1..3], 0));
+        assert_eq!(warnings, listing.warnings);
+        assert_eq!(warnings, vec!["WARNING: headers error".to_string()]);
+
+        let result = verifier
+            .verify_archive_with_expanded(&archive, &expected)
+            .await
+            .unwrap();
+        assert_eq!(result.archived_files, buffered_found);
+        assert_eq!(result.missing_files, buffered_missing);
+        assert_eq!(result.missing_files, ["data", "data/gone.txt"]);
+        assert_eq!(result.total_archived, 2);
+        assert_eq!(result.listing_warnings, listing.warnings);
+    }
+
+    #[test]
+    fn test_password_reaches_list_command() {
+        let config = Config::builder()
+            .output_path(Some("backup.7z"), false)
+            .password(Some(Password::new("s3cret")))
+            .build()
+            .unwrap();
+        let cmd = SevenZipVerifier::from_config(&config).list_command("backup.7z", true);
+        let args: Vec<_> = cmd
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(args, ["l", "-slt", "-ps3cret", "-sccUTF-8", "backup.7z"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_loop_is_walked_once() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let nested = temp_dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join("file.txt"), "data").unwrap();
+        std::os::unix::fs::symlink(temp_dir.path().join("a"), nested.join("up")).unwrap();
+
+        let root = temp_dir.path().to_string_lossy().to_string();
+        let (files, skipped) = walk_directory_files(&root, true).await.unwrap();
+        assert_eq!(files, vec![nested.join("file.txt").to_string_lossy()]);
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].contains("symlink loop"), "{}", skipped[0]);
+        assert!(skipped[0].contains("up"), "{}", skipped[0]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlinked_directory_is_walked_only_when_followed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let shared = temp_dir.path().join("shared");
+        let input = temp_dir.path().join("input");
+        std::fs::create_dir_all(&shared).unwrap();
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(shared.join("data.txt"), "data").unwrap();
+        std::fs::write(input.join("own.txt"), "own").unwrap();
+        std::os::unix::fs::symlink(&shared, input.join("dir_link")).unwrap();
+
+        let root = input.to_string_lossy().to_string();
+        let (files, skipped) = walk_directory_files(&root, false).await.unwrap();
+        assert_eq!(files, vec![input.join("own.txt").to_string_lossy()]);
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].contains("--follow-symlinks"), "{}", skipped[0]);
+
+        let (mut files, skipped) = walk_directory_files(&root, true).await.unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                input.join("dir_link").join("data.txt").to_string_lossy(),
+                input.join("own.txt").to_string_lossy(),
+            ]
+        );
+        assert!(skipped.is_empty(), "{:?}", skipped);
+    }
+
+    #[tokio::test]
+    async fn test_expand_archive_glob_newest_first() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let now = SystemTime::now();
+        let day = std::time::Duration::from_secs(86400);
+
+        for (name, age_days) in [
+            ("backup-week1.7z", 14),
+            ("backup-week3.7z", 0),
+            ("backup-week2.7z", 7),
+            ("other.7z", 0),
+        ] {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, name).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(now - day * age_days)
+                .unwrap();
+        }
+
+        let pattern = temp_dir.path().join("backup-*.7z");
+        let archives = expand_archive_glob(&pattern.to_string_lossy())
+            .await
+            .unwrap();
+
+        let names: Vec<String> = archives
+            .iter()
+            .map(|path| {
+                Path::new(path)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec!["backup-week3.7z", "backup-week2.7z", "backup-week1.7z"]
+        );
+
+        let missing = temp_dir.path().join("nightly-*.7z");
+        assert!(
+            expand_archive_glob(&missing.to_string_lossy())
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_comparison_key() {
+        let key = |path: &str, strip_components: usize| comparison_key(path, strip_components);
+
+        // Separators and a leading `/` are normalized the same way for every count
+        assert_eq!(key("C:\\data\\f.txt", 0).as_deref(), Some("C:/data/f.txt"));
+        assert_eq!(key("/mnt/a/data/f.txt", 0), key("mnt\\a\\data\\f.txt", 0));
+        assert_eq!(key("C:\\data\\f.txt", 1).as_deref(), Some("data/f.txt"));
+        assert_eq!(key("/mnt/a/data/f.txt", 2).as_deref(), Some("data/f.txt"));
+        assert_eq!(key("/mnt/a/data/f.txt", 2), key("mnt/b/data/f.txt", 2));
+
+        // Nothing left after stripping never matches, not even another over-stripped path
+        assert_eq!(key("a/b", 2), None);
+        assert_eq!(key("a/b", 5), None);
+        let (missing, found) = compare_file_lists(
+            &["a/b".to_string()],
+            &keys_of(&["c/d".to_string()], 5),
+            5,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!(missing, vec!["a/b"]);
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_dot_segments_are_normalized() {
+        assert_eq!(normalize_dot_segments("a/./b/c"), "a/b/c");
+        assert_eq!(normalize_dot_segments("a/b/../b/c"), "a/b/c");
+        assert_eq!(normalize_dot_segments("/data/./x/../f.txt"), "/data/f.txt");
+        assert_eq!(
+            normalize_dot_segments("C:\\data\\.\\f.txt"),
+            "C:\\data\\f.txt"
+        );
+        assert_eq!(normalize_dot_segments("C:\\..\\f.txt"), "C:\\f.txt");
+        assert_eq!(normalize_dot_segments("/../f.txt"), "/f.txt");
+        assert_eq!(normalize_dot_segments("../../a/./b"), "../../a/b");
+        // Names merely starting with dots are left alone
+        assert!(matches!(
+            normalize_dot_segments("a/.hidden/..b"),
+            Cow::Borrowed("a/.hidden/..b")
+        ));
+
+        // A stored `a/./b/c` counts as the expected `a/b/c`, also with stripped components
+        let expected = vec!["a/b/c".to_string(), "a/b/d".to_string()];
+        let archived = vec!["a/./b/c".to_string(), "a/b/x/../d".to_string()];
+        for strip_components in [0, 1] {
+            let (missing, found) = compare_file_lists(
+                &expected,
+                &keys_of(&archived, strip_components),
+                strip_components,
+                &CancellationToken::new(),
+            )
+            .unwrap();
+            assert_eq!(found, expected);
+            assert!(missing.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_archive_age_reports_newer_sources() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        let old_file = temp_dir.path().join("old.txt");
+        let new_file = temp_dir.path().join("new.txt");
+        std::fs::write(&archive, "archive").unwrap();
+        std::fs::write(&old_file, "old").unwrap();
+        std::fs::write(&new_file, "new").unwrap();
+
+        let now = SystemTime::now();
+        let hour = std::time::Duration::from_secs(3600);
+        let set_mtime = |path: &Path, time: SystemTime| {
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+        };
+        set_mtime(&archive, now - hour);
+        set_mtime(&old_file, now - 2 * hour);
+        set_mtime(&new_file, now);
+
+        let sources = vec![
+            old_file.to_string_lossy().to_string(),
+            new_file.to_string_lossy().to_string(),
+        ];
+        let result = check_archive_age(&archive.to_string_lossy(), &sources)
+            .await
+            .unwrap();
+
+        assert!(result.is_stale());
+        assert_eq!(result.total_checked, 2);
+        assert_eq!(
+            result.newer_sources,
+            vec![new_file.to_string_lossy().to_string()]
+        );
+        assert_eq!(
+            result.newest_source.map(|(path, _)| path),
+            Some(new_file.to_string_lossy().to_string())
+        );
+
+        // Once the archive is rewritten after the newest source it is no longer stale
+        set_mtime(&archive, now + hour);
+        let result = check_archive_age(&archive.to_string_lossy(), &sources)
+            .await
+            .unwrap();
+        assert!(!result.is_stale());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_strict_hash_fails_on_unreadable_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive = temp_dir.path().join("backup.7z");
+        let file = temp_dir.path().join("vanishing.txt");
+        std::fs::write(&archive, "archive").unwrap();
+        std::fs::write(&file, "data").unwrap();
+
+        // Stand-in for 7-Zip listing the file, which vanishes before it is checked
+        let runner = temp_dir.path().join("fake7z");
+        std::fs::write(
+            &runner,
+            format!(
+                "#!/bin/sh\n[ \"$1\" = \"--help\" ] && exit 0\nrm -f \"{0}\"\necho \"Path = {0}\"\necho \"Size = 4\"\necho \"Modified = 2024-01-01 00:00:00\"\necho\n",
+                file.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&runner, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let archive = archive.to_string_lossy().to_string();
+        let file = file.to_string_lossy().to_string();
+        let verify = |strict: bool| {
+            std::fs::write(&file, "data").unwrap();
+            let verifier = SevenZipVerifier::with_path(runner.to_string_lossy().to_string())
+                .with_strict_hash(strict);
+            let (archive, expected) = (archive.clone(), vec![file.clone()]);
+            async move { verifier.verify_archive_freshness(&archive, &expected).await }
+        };
+
+        // By default the file is only listed as unverifiable
+        let result = verify(false).await.unwrap();
+        assert_eq!(result.unverifiable_files, vec![file.clone()]);
+        assert!(result.outdated_files.is_empty());
+
+        let error = verify(true).await.unwrap_err();
+        assert!(matches!(error, ArchtreeError::Verification { .. }));
+        assert!(error.to_string().contains(&file), "{}", error);
     }
 
     #[tokio::test]
@@ -731,6 +3372,7 @@ mod tests {
     async fn test_verification_result() {
         let result = VerificationResult {
             missing_files: vec!["missing.txt".to_string()],
+            expected_missing: Vec::new(),
             archived_files: vec!["found1.txt".to_string(), "found2.txt".to_string()],
             all_expected_files: vec![
                 "missing.txt".to_string(),
@@ -739,6 +3381,9 @@ mod tests {
             ],
             total_expected: 3,
             total_archived: 2,
+            listing_warnings: vec![],
+            corrupted_files: Vec::new(),
+            unchecked_files: Vec::new(),
         };
 
         assert!(!result.is_complete());
@@ -746,13 +3391,188 @@ mod tests {
 
         let complete_result = VerificationResult {
             missing_files: vec![],
+            expected_missing: Vec::new(),
             archived_files: vec!["file1.txt".to_string(), "file2.txt".to_string()],
             all_expected_files: vec!["file1.txt".to_string(), "file2.txt".to_string()],
             total_expected: 2,
             total_archived: 2,
+            listing_warnings: vec![],
+            corrupted_files: Vec::new(),
+            unchecked_files: Vec::new(),
         };
 
         assert!(complete_result.is_complete());
         assert_eq!(complete_result.success_rate(), 100.0);
     }
+
+    fn path_of(parts: &[&str]) -> String {
+        parts
+            .iter()
+            .collect::<PathBuf>()
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn test_consolidate_missing_files() {
+        let sep = std::path::MAIN_SEPARATOR;
+        let expected = vec![
+            path_of(&["root", "gone", "a.txt"]),
+            path_of(&["root", "gone", "nested", "b.txt"]),
+            path_of(&["root", "gone", "partial", "c.txt"]),
+            path_of(&["root", "gone", "partial", "d.txt"]),
+            path_of(&["root", "half", "e.txt"]),
+            path_of(&["root", "half", "f.txt"]),
+            path_of(&["root", "gonex", "g.txt"]),
+            path_of(&["root", "gonex", "h.txt"]),
+        ];
+        let missing = vec![
+            path_of(&["root", "gone", "a.txt"]),
+            path_of(&["root", "gone", "nested", "b.txt"]),
+            path_of(&["root", "gone", "partial", "c.txt"]),
+            path_of(&["root", "half", "e.txt"]),
+            path_of(&["root", "gonex", "g.txt"]),
+        ];
+
+        let consolidated = consolidate_missing_files(&missing, &expected);
+
+        // Nested complete directories are listed too; files of partial directories
+        // below a complete one are folded into it. A sibling that merely shares the
+        // name prefix is not.
+        assert_eq!(
+            consolidated,
+            vec![
+                format!("{}{}*", path_of(&["root", "gone"]), sep),
+                format!("{}{}*", path_of(&["root", "gone", "nested"]), sep),
+                path_of(&["root", "gonex", "g.txt"]),
+                path_of(&["root", "half", "e.txt"]),
+            ]
+        );
+        assert!(consolidate_missing_files(&[], &expected).is_empty());
+    }
+
+    #[test]
+    fn test_consolidate_many_missing_files() {
+        let sep = std::path::MAIN_SEPARATOR;
+        let mut expected = Vec::new();
+        let mut missing = Vec::new();
+        let mut want = Vec::new();
+
+        // 400 directories of 25 files each; every other one is completely missing,
+        // the rest are missing a single file
+        for d in 0..400 {
+            let dir = format!("dir{:03}", d);
+            for f in 0..25 {
+                let file = path_of(&["data", &dir, "sub", &format!("file{:02}.txt", f)]);
+                expected.push(file.clone());
+                if d % 2 == 0 || f == 0 {
+                    missing.push(file.clone());
+                }
+                if d % 2 == 1 && f == 0 {
+                    want.push(file);
+                }
+            }
+            if d % 2 == 0 {
+                want.push(format!("{}{}*", path_of(&["data", &dir, "sub"]), sep));
+            }
+        }
+        want.sort();
+
+        let start = std::time::Instant::now();
+        let consolidated = consolidate_missing_files(&missing, &expected);
+        let elapsed = start.elapsed();
+
+        assert_eq!(missing.len(), 5200);
+        assert_eq!(consolidated, want);
+        assert!(
+            elapsed < std::time::Duration::from_secs(2),
+            "consolidation took {:?}",
+            elapsed
+        );
+    }
+
+    /// Verifier listing `files` files and one directory in `a.7z`
+    fn counting_verifier(files: usize) -> MockVerifier {
+        let mut entries: Vec<ArchiveEntry> = (0..files)
+            .map(|i| file_entry(&format!("data/file{}.txt", i)))
+            .collect();
+        entries.push(ArchiveEntry {
+            is_directory: true,
+            ..file_entry("data")
+        });
+        MockVerifier::new().with_archive("a.7z", entries)
+    }
+
+    #[tokio::test]
+    async fn test_verify_file_count() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for i in 0..3 {
+            std::fs::write(temp_dir.path().join(format!("file{}.txt", i)), "x").unwrap();
+        }
+        let inputs = vec![temp_dir.path().to_string_lossy().to_string()];
+
+        let result = verify_file_count(&counting_verifier(3), "a.7z", &inputs, 0)
+            .await
+            .unwrap();
+        assert!(result.is_match());
+        assert_eq!(result.delta(), 0);
+
+        let result = verify_file_count(&counting_verifier(1), "a.7z", &inputs, 0)
+            .await
+            .unwrap();
+        assert!(!result.is_match());
+        assert_eq!(result.delta(), -2);
+
+        let result = verify_file_count(&counting_verifier(4), "a.7z", &inputs, 1)
+            .await
+            .unwrap();
+        assert!(result.is_match());
+        assert_eq!(result.delta(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reverse_verification_reports_deleted_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let kept = temp_dir.path().join("kept.txt");
+        let deleted = temp_dir.path().join("deleted.txt");
+        std::fs::write(&kept, "kept").unwrap();
+
+        let entry = |path: &Path, is_directory: bool| ArchiveEntry {
+            path: path.to_string_lossy().to_string(),
+            is_directory,
+            size: 0,
+            modified: None,
+            packed_size: None,
+            crc: None,
+        };
+        let verifier = MockVerifier::new().with_archive(
+            "backup.zip",
+            vec![
+                entry(temp_dir.path(), true),
+                entry(&kept, false),
+                entry(&deleted, false),
+            ],
+        );
+
+        let result = verify_reverse(&verifier, "backup.zip").await.unwrap();
+        assert!(!result.is_complete());
+        assert_eq!(result.total_checked, 2);
+        assert_eq!(
+            result.gone_from_disk,
+            vec![deleted.to_string_lossy().to_string()]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_entry_filesystem_path_restores_root() {
+        assert_eq!(
+            entry_filesystem_path("home/user/file.txt"),
+            PathBuf::from("/home/user/file.txt")
+        );
+        assert_eq!(
+            entry_filesystem_path("/home/user/file.txt"),
+            PathBuf::from("/home/user/file.txt")
+        );
+    }
 }